@@ -0,0 +1,119 @@
+use std::hint::black_box;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use shepherd::pty_widget::PtyWidget;
+use shepherd::session::TerminalCallbacks;
+use vt100::Parser;
+
+const ROWS: u16 = 50;
+const COLS: u16 = 200;
+const SCROLLBACK: usize = 1000;
+const STREAM_SIZE: usize = 200 * 1024;
+
+/// Build a PTY stream resembling agent/build output: colored log lines,
+/// progress bars redrawn with carriage returns, cursor movement and line clears.
+fn synthetic_stream(size: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(size + 256);
+    let mut i = 0usize;
+    while out.len() < size {
+        match i % 4 {
+            0 => write!(
+                out,
+                "\x1b[1;32m   Compiling\x1b[0m crate-{} v0.{}.{} (/home/user/src/crate-{})\r\n",
+                i,
+                i % 10,
+                i % 7,
+                i
+            ),
+            1 => write!(
+                out,
+                "\x1b[38;5;{}m● \x1b[0mRead(src/module_{}.rs) \x1b[2m— {} lines\x1b[0m\r\n",
+                i % 256,
+                i,
+                i * 3
+            ),
+            2 => {
+                let filled = i % 40;
+                write!(
+                    out,
+                    "\r\x1b[K\x1b[36m[{}{}]\x1b[0m {}/40",
+                    "=".repeat(filled),
+                    " ".repeat(40 - filled),
+                    filled
+                )
+            }
+            _ => write!(
+                out,
+                "\r\n\x1b[A\x1b[2K\x1b[38;2;{};{};{}mstatus:\x1b[0m working on item {} ✓\r\n",
+                i % 255,
+                (i * 7) % 255,
+                (i * 13) % 255,
+                i
+            ),
+        }
+        .unwrap();
+        i += 1;
+    }
+    out
+}
+
+fn new_parser() -> Parser<TerminalCallbacks> {
+    let sink: Box<dyn Write + Send> = Box::new(std::io::sink());
+    let callbacks = TerminalCallbacks::new(Arc::new(Mutex::new(sink)), Arc::default());
+    Parser::new_with_callbacks(ROWS, COLS, SCROLLBACK, callbacks)
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let stream = synthetic_stream(STREAM_SIZE);
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(stream.len() as u64));
+    group.bench_function("200kb_stream", |b| {
+        b.iter(|| {
+            let mut parser = new_parser();
+            // Feed in reader-sized chunks, as the session reader does
+            for chunk in stream.chunks(8 * 1024) {
+                parser.process(black_box(chunk));
+            }
+            black_box(parser.screen().cursor_position());
+        })
+    });
+    group.finish();
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut parser = new_parser();
+    parser.process(&synthetic_stream(STREAM_SIZE));
+    let area = Rect::new(0, 0, COLS, ROWS);
+    let mut buf = Buffer::empty(area);
+
+    let mut group = c.benchmark_group("render");
+    group.bench_function("in_place", |b| {
+        b.iter(|| {
+            PtyWidget::new(parser.screen()).render(area, &mut buf);
+            black_box(&buf);
+        })
+    });
+    // The previous render path cloned the whole Screen every dirty frame
+    group.bench_function("clone_then_render", |b| {
+        b.iter(|| {
+            let screen = parser.screen().clone();
+            PtyWidget::new(&screen).render(area, &mut buf);
+            black_box(&buf);
+        })
+    });
+    group.bench_function("scrollback_in_place", |b| {
+        b.iter(|| {
+            parser.screen_mut().set_scrollback(100);
+            PtyWidget::new(parser.screen()).render(area, &mut buf);
+            parser.screen_mut().set_scrollback(0);
+            black_box(&buf);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Files written when a worktree was archived. Either is None when there was nothing
+/// to save.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Archive {
+    /// Uncommitted changes, including untracked files, as a binary diff against HEAD
+    pub patch: Option<PathBuf>,
+    /// Commits on the branch that are not in the base branch
+    pub bundle: Option<PathBuf>,
+}
+
+/// Archive directory (~/.shepherd/archives) where worktrees are exported before deletion
+pub struct Archives {
+    dir: PathBuf,
+}
+
+impl Archives {
+    pub fn open() -> anyhow::Result<Self> {
+        let home =
+            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+        Ok(Self::at(home.join(".shepherd").join("archives")))
+    }
+
+    fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Export the worktree's uncommitted changes as `<timestamp>-<name>.patch` and its
+    /// unmerged commits as `<timestamp>-<name>.bundle`. `base_ref` limits the bundle to
+    /// commits not yet in the base branch; without it the whole branch is bundled.
+    pub fn archive(&self, worktree: &Path, base_ref: Option<&str>) -> anyhow::Result<Archive> {
+        std::fs::create_dir_all(&self.dir)?;
+        let name = worktree
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?;
+        let stem = format!("{}-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), name);
+
+        Ok(Archive {
+            patch: self.write_patch(worktree, &stem)?,
+            bundle: self.write_bundle(worktree, &stem, base_ref)?,
+        })
+    }
+
+    /// Diff everything in the worktree against HEAD. Untracked files are staged into a
+    /// throwaway index so the worktree's own index is left alone.
+    fn write_patch(&self, worktree: &Path, stem: &str) -> anyhow::Result<Option<PathBuf>> {
+        let index = self.dir.join(format!("{}.index", stem));
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .env("GIT_INDEX_FILE", &index)
+                .current_dir(worktree)
+                .output()
+        };
+        let diff = git(&["read-tree", "HEAD"])
+            .and_then(|_| git(&["add", "-A"]))
+            .and_then(|_| git(&["diff", "--cached", "--binary", "HEAD"]));
+        let _ = std::fs::remove_file(&index);
+        let diff = checked(diff?, "git diff")?;
+
+        if diff.stdout.is_empty() {
+            return Ok(None);
+        }
+        let path = self.dir.join(format!("{}.patch", stem));
+        std::fs::write(&path, diff.stdout)?;
+        Ok(Some(path))
+    }
+
+    fn write_bundle(
+        &self,
+        worktree: &Path,
+        stem: &str,
+        base_ref: Option<&str>,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let range = match base_ref {
+            Some(base) => format!("{}..HEAD", base),
+            None => "HEAD".to_string(),
+        };
+        let count = checked(
+            Command::new("git")
+                .args(["rev-list", "--count", &range])
+                .current_dir(worktree)
+                .output()?,
+            "git rev-list",
+        )?;
+        if String::from_utf8_lossy(&count.stdout).trim() == "0" {
+            return Ok(None);
+        }
+
+        // Bundle the branch by name so `git fetch <bundle>` recreates it
+        let head = Command::new("git")
+            .args(["symbolic-ref", "--quiet", "HEAD"])
+            .current_dir(worktree)
+            .output()?;
+        let tip = if head.status.success() {
+            String::from_utf8_lossy(&head.stdout).trim().to_string()
+        } else {
+            "HEAD".to_string()
+        };
+
+        let path = self.dir.join(format!("{}.bundle", stem));
+        let mut args = vec!["bundle".to_string(), "create".to_string()];
+        args.push(path.to_string_lossy().into_owned());
+        args.push(tip);
+        if let Some(base) = base_ref {
+            args.push(format!("^{}", base));
+        }
+        checked(
+            Command::new("git")
+                .args(&args)
+                .current_dir(worktree)
+                .output()?,
+            "git bundle",
+        )?;
+        Ok(Some(path))
+    }
+}
+
+fn checked(output: Output, what: &str) -> anyhow::Result<Output> {
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{} failed: {}", what, stderr.trim());
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{TempDir, git};
+
+    #[test]
+    fn test_archive_worktree() {
+        let root = TempDir::new("archive");
+        let repo = root.repo("feature");
+        git(&repo, &["checkout", "-q", "-b", "feature"]);
+        std::fs::write(repo.join("committed.txt"), "work").unwrap();
+        git(&repo, &["add", "committed.txt"]);
+        git(&repo, &["commit", "-q", "-m", "work"]);
+        std::fs::write(repo.join("untracked.txt"), "draft").unwrap();
+
+        let archives = Archives::at(root.join("archives"));
+        let archive = archives.archive(&repo, Some("main")).unwrap();
+
+        let patch = std::fs::read_to_string(archive.patch.unwrap()).unwrap();
+        assert!(patch.contains("untracked.txt"));
+        assert!(!patch.contains("committed.txt"));
+        assert!(archive.bundle.unwrap().exists());
+        // The worktree's own index is untouched
+        assert_eq!(git(&repo, &["status", "--porcelain"]), "?? untracked.txt\n");
+
+        // Nothing to save on the base branch itself
+        std::fs::remove_file(repo.join("untracked.txt")).unwrap();
+        git(&repo, &["checkout", "-q", "main"]);
+        assert_eq!(
+            archives.archive(&repo, Some("main")).unwrap(),
+            Archive::default()
+        );
+    }
+}
@@ -1,10 +1,638 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::ShepardError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub claude_args: Vec<String>,
+    /// Extra claude arguments per repo, keyed by repository name, placed before `claude_args`
+    pub repo_claude_args: HashMap<String, Vec<String>>,
     pub workflows_path: PathBuf,
+    /// Branch new worktrees start from. Defaults to main/master, or HEAD if neither exists.
+    /// Taken from origin when the repo has that remote.
+    pub base_branch: Option<String>,
+    /// `cli` runs git for every status and worktree query, `libgit2` reads repositories
+    /// in-process. Changes to repositories always go through the CLI.
+    pub git_backend: GitBackendKind,
+    /// Run `git fetch` in the background this often (seconds) so session ahead/behind
+    /// counts notice upstream moving. Off when unset. A fetch that would prompt for
+    /// credentials fails instead, and one running over a minute is killed.
+    pub fetch_interval_secs: Option<u64>,
+    /// Extra setup run in each new worktree (submodules, LFS)
+    pub worktree_setup: WorktreeSetup,
+    /// Executables that provision sessions in place of git worktrees
+    pub script_workflow: Option<ScriptWorkflowConfig>,
+    /// Workflow the new session dialog starts on
+    pub workflow: WorkflowConfig,
+    /// Build caches shared between a repo's worktrees
+    pub build_cache: BuildCache,
+    /// Enable Alt+1..Alt+9 to jump directly to the Nth live session
+    pub quick_jump_hotkeys: bool,
+    /// When the new session name is left blank, ask for the task prompt and derive the name from it
+    pub name_from_prompt: bool,
+    /// Resource limits applied to every spawned session (claude and shell panes)
+    pub session_limits: SessionLimits,
+    /// Most claude sessions allowed to run at once. Unset means no limit.
+    pub max_live_sessions: Option<usize>,
+    /// What starting a session past `max_live_sessions` does
+    pub session_limit_policy: SessionLimitPolicy,
+    /// Overrides for the terminal env vars given to spawned sessions
+    pub terminal_env: TerminalEnv,
+    /// Force a redraw (fresh screen plus a resize jiggle) whenever a session is switched to
+    pub redraw_on_attach: bool,
+    /// Ask before killing a session
+    pub confirm_kill: bool,
+    /// Ask before quitting
+    pub confirm_quit: bool,
+    /// Ask before deleting worktrees
+    pub confirm_worktree_delete: bool,
+    /// Show what creating a session will do and wait for confirmation (Ctrl+P in the new
+    /// session dialog shows it either way)
+    pub confirm_new_session: bool,
+    /// Named payloads that can be sent to the active session by key chord or from the picker
+    pub macros: Vec<Macro>,
+    /// Regexes matched against every session's output, with what to do on a match
+    pub watch_rules: Vec<WatchRule>,
+    /// Test command run against a session's worktree with Alt+c
+    pub checks: ChecksConfig,
+    /// What the shell view runs; $SHELL with no arguments by default
+    pub shell: ShellConfig,
+    /// Opens a file in your editor (Alt+o, `shepherd open`), run through the shell in the
+    /// background with `{path}` and `{line}` filled in, e.g. `code --goto {path}:{line}`.
+    /// Unset runs $VISUAL/$EDITOR in shepherd's terminal.
+    pub editor_command: Option<String>,
+    /// Follow-up sessions started when a session stops, for pipelines like
+    /// implement -> review -> tests
+    pub chains: Vec<ChainStep>,
+    /// Sessions the task dispatcher (Alt+q) may create
+    pub dispatch: DispatchConfig,
+    /// How long status bar messages stay up, per level
+    pub status_timeouts: StatusTimeouts,
+    /// Size-based rotation of ~/.shepherd/events.log
+    pub event_log: EventLogConfig,
+    /// Deleted worktrees go to ~/.shepherd/trash instead of being removed outright
+    pub trash: TrashConfig,
+    /// How many recent sessions history keeps per repo, and for how long
+    pub history: HistoryConfig,
+    /// Copies of each session's raw output in ~/.shepherd/logs/<repo>/<session>.raw
+    pub raw_output_log: RawOutputLog,
+}
+
+/// Optional per-session resource limits. Unset fields mean "no limit".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionLimits {
+    /// Lines of scrollback kept by each session's parser (bounds parser memory)
+    pub max_scrollback_lines: Option<usize>,
+    /// Background sessions unused for this long (seconds) have their scrollback moved to
+    /// disk until they are attached again. Off when unset.
+    pub trim_scrollback_after_secs: Option<u64>,
+    /// Maximum bytes per second read from the PTY; excess output stays buffered in the kernel
+    pub max_output_rate: Option<usize>,
+    /// While a session floods output, stop reading it for this long (milliseconds) after
+    /// each keystroke sent to it. Off when unset.
+    pub pause_output_while_typing_ms: Option<u64>,
+    /// Sessions in the background parse their output at most this often (milliseconds),
+    /// leaving the CPU to the one on screen. Unset or 0 parses as it arrives.
+    pub background_parse_interval_ms: Option<u64>,
+    /// CPU niceness applied to the child process on spawn (-20..=19)
+    pub nice: Option<i32>,
+    /// On shutdown, also kill everything the session started (e.g. a dev server run from
+    /// a shell pane), not just its direct child
+    pub kill_process_tree: bool,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_scrollback_lines: None,
+            trim_scrollback_after_secs: None,
+            max_output_rate: None,
+            pause_output_while_typing_ms: None,
+            background_parse_interval_ms: Some(250),
+            nice: None,
+            kill_process_tree: true,
+        }
+    }
+}
+
+/// What answers git queries like status, branch state and worktree listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitBackendKind {
+    /// Run the git CLI
+    #[default]
+    Cli,
+    /// Read the repository in-process with libgit2
+    Libgit2,
+}
+
+/// Handling of a session started while `max_live_sessions` are already running
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionLimitPolicy {
+    /// Hold it until another session exits
+    #[default]
+    Queue,
+    /// Offer to kill the least recently used session to make room
+    KillLru,
+}
+
+impl SessionLimitPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionLimitPolicy::Queue => "queue",
+            SessionLimitPolicy::KillLru => "kill LRU",
+        }
+    }
+}
+
+/// A workflow made of external executables, for provisioning shepherd doesn't do itself
+/// (terraform, database seeds, ...). Relative paths with a directory part, like
+/// `./scripts/provision`, are taken from the repository root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptWorkflowConfig {
+    /// Run before a session starts; replies with the directory the session runs in
+    pub pre_session: PathBuf,
+    /// Run once a session's claude process has started
+    #[serde(default)]
+    pub post_session_start: Option<PathBuf>,
+    /// Run when a session's claude process exits or is killed
+    #[serde(default)]
+    pub on_session_exit: Option<PathBuf>,
+    /// Run before a session's worktree is deleted; failing keeps the worktree
+    #[serde(default)]
+    pub pre_worktree_delete: Option<PathBuf>,
+    /// Run after a session's worktree is deleted
+    #[serde(default)]
+    pub post_worktree_delete: Option<PathBuf>,
+}
+
+/// Which workflow new sessions go through: `"worktree"`, `"directory"` (the repo's own
+/// checkout) or `"script"`. Unset means `script` when `script_workflow` is configured,
+/// otherwise `worktree` (`directory` outside git).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowConfig {
+    pub default: Option<String>,
+    /// Per-repo choices, keyed by repository name
+    pub repos: HashMap<String, String>,
+}
+
+impl WorkflowConfig {
+    /// Workflow configured for `repo`: its own, else the global one
+    pub fn name_for(&self, repo: &str) -> Option<&str> {
+        self.repos
+            .get(repo)
+            .or(self.default.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Post-checkout steps for new worktrees. Unset fields are auto-detected from the checkout.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorktreeSetup {
+    /// Run `git submodule update --init --recursive` (detected from `.gitmodules`)
+    pub submodules: Option<bool>,
+    /// Run `git lfs pull` (detected from LFS filters in `.gitattributes`)
+    pub lfs: Option<bool>,
+    /// Per-repo steps, keyed by repository name
+    pub repos: HashMap<String, RepoSetup>,
+}
+
+/// Setup for one repo's new worktrees, run after the git steps
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoSetup {
+    /// Shell commands run in the worktree, in order
+    pub commands: Vec<String>,
+    /// Files copied from the main checkout, e.g. untracked `.env` files. `*` matches
+    /// within one path component.
+    pub copy_files: Vec<String>,
+}
+
+/// Shared build caches for sessions. Unset tools are enabled when the worktree uses them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildCache {
+    /// Share one cargo target dir per repo via CARGO_TARGET_DIR
+    pub cargo: Option<bool>,
+    /// Share a pnpm package store between worktrees
+    pub pnpm: Option<bool>,
+    /// Where shared caches live. Defaults to `<workflows_path>/.cache`.
+    pub dir: Option<PathBuf>,
+    /// Per-repo overrides, keyed by repository name
+    pub repos: HashMap<String, BuildCacheTools>,
+}
+
+/// Which tools get a shared cache
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildCacheTools {
+    pub cargo: Option<bool>,
+    pub pnpm: Option<bool>,
+}
+
+impl BuildCache {
+    /// Settings for `repo`, with its overrides applied over the global ones
+    pub fn for_repo(&self, repo: &str) -> BuildCacheTools {
+        let overrides = self.repos.get(repo).cloned().unwrap_or_default();
+        BuildCacheTools {
+            cargo: overrides.cargo.or(self.cargo),
+            pnpm: overrides.pnpm.or(self.pnpm),
+        }
+    }
+}
+
+/// How serious a status message is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusLevel {
+    Info,
+    Success,
+    Warn,
+    Err,
+}
+
+impl StatusLevel {
+    /// Tag written to the event log
+    pub fn log_tag(self) -> &'static str {
+        match self {
+            StatusLevel::Info => "INFO",
+            StatusLevel::Success => "OK",
+            StatusLevel::Warn => "WARN",
+            StatusLevel::Err => "ERR",
+        }
+    }
+}
+
+/// Seconds a status message stays up for each level, unless the message sets its own
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusTimeouts {
+    pub info_secs: u64,
+    pub success_secs: u64,
+    pub warn_secs: u64,
+    pub err_secs: u64,
+}
+
+impl Default for StatusTimeouts {
+    fn default() -> Self {
+        Self {
+            info_secs: 15,
+            success_secs: 10,
+            warn_secs: 30,
+            err_secs: 60,
+        }
+    }
+}
+
+impl StatusTimeouts {
+    pub fn for_level(&self, level: StatusLevel) -> Duration {
+        let secs = match level {
+            StatusLevel::Info => self.info_secs,
+            StatusLevel::Success => self.success_secs,
+            StatusLevel::Warn => self.warn_secs,
+            StatusLevel::Err => self.err_secs,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+/// When the event log rotates and how much rotated history is kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventLogConfig {
+    /// Size at which events.log is compressed into events.log.1.gz
+    pub max_size_kb: u64,
+    /// Rotated logs kept (events.log.1.gz ..= events.log.N.gz); 0 discards old entries
+    pub keep_rotated: usize,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: 1024,
+            keep_rotated: 5,
+        }
+    }
+}
+
+/// Mirroring of everything a session's child prints, kept past the parser's scrollback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RawOutputLog {
+    pub enabled: bool,
+    /// Size at which `<session>.raw` is rotated to `<session>.raw.1`
+    pub max_size_mb: u64,
+    /// Rotated files kept per session; 0 discards old output
+    pub keep_rotated: usize,
+}
+
+impl Default for RawOutputLog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size_mb: 64,
+            keep_rotated: 1,
+        }
+    }
+}
+
+/// Whether deleted worktrees are kept in the trash, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    pub enabled: bool,
+    /// Trashed worktrees older than this are purged at startup
+    pub keep_days: u64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_days: 7,
+        }
+    }
+}
+
+/// Retention of recent sessions and the time log in ~/.shepherd/history.json. Pinned
+/// sessions are kept regardless.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Recent sessions kept per repo
+    pub max_recent_per_repo: usize,
+    /// Recent sessions not active for this long are dropped at startup. Unset keeps them.
+    pub max_age_days: Option<u64>,
+    /// Finished session runs kept for time reports; the oldest go first
+    pub max_time_entries: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_recent_per_repo: 5,
+            max_age_days: None,
+            max_time_entries: 2000,
+        }
+    }
+}
+
+/// Terminal identification passed to child PTYs. Unset fields are derived from the
+/// host terminal detected at startup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalEnv {
+    /// Value of TERM inside sessions, e.g. "xterm-256color"
+    pub term: Option<String>,
+    /// Value of COLORTERM inside sessions, e.g. "truecolor"
+    pub colorterm: Option<String>,
+}
+
+/// A payload written verbatim to the active session, e.g. `"continue\r"`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Macro {
+    pub name: String,
+    /// Key chord that fires the macro, e.g. "alt+c" or "ctrl+g". Built-in hotkeys win on conflict.
+    pub key: Option<String>,
+    pub payload: String,
+}
+
+impl Macro {
+    /// Raw bytes the terminal sends for this macro's key chord, if it has a valid one.
+    /// Supports `ctrl+<letter>`, `alt+<char>` and `ctrl+alt+<letter>`.
+    pub fn key_bytes(&self) -> Option<Vec<u8>> {
+        let key = self.key.as_ref()?.to_lowercase();
+        let mut parts: Vec<&str> = key.split('+').map(str::trim).collect();
+        let base = parts.pop()?;
+        let mut chars = base.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        if !c.is_ascii_graphic() {
+            return None;
+        }
+
+        let (mut ctrl, mut alt) = (false, false);
+        for modifier in parts {
+            match modifier {
+                "ctrl" => ctrl = true,
+                "alt" => alt = true,
+                _ => return None,
+            }
+        }
+
+        let byte = if ctrl {
+            if !c.is_ascii_lowercase() {
+                return None;
+            }
+            c as u8 & 0x1f
+        } else {
+            c as u8
+        };
+        match (ctrl, alt) {
+            (_, true) => Some(vec![0x1b, byte]),
+            (true, false) => Some(vec![byte]),
+            // A bare key would swallow ordinary typing
+            (false, false) => None,
+        }
+    }
+}
+
+/// Shell command that checks a worktree (tests, lints), run from its root
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChecksConfig {
+    pub command: Option<String>,
+    /// Per-repo commands, keyed by repository name
+    pub repos: HashMap<String, String>,
+}
+
+impl ChecksConfig {
+    /// Command for `repo`: its own, else the global one
+    pub fn command_for(&self, repo: &str) -> Option<&str> {
+        self.repos
+            .get(repo)
+            .or(self.command.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Shell started in shell view panes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellConfig {
+    /// Program to run, e.g. `fish` or `nu`. Unset uses $SHELL.
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    /// File of `KEY=value` lines (or any sh script) sourced into the shell's environment;
+    /// relative paths are taken from the worktree and `~/` from your home directory
+    pub env_file: Option<String>,
+    /// Per-repo overrides, keyed by repository name
+    pub repos: HashMap<String, ShellOverride>,
+}
+
+/// Per-repo shell settings; unset fields fall back to the global ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellOverride {
+    pub command: Option<String>,
+    /// Defaults to the global args only when `command` is not overridden
+    pub args: Option<Vec<String>>,
+    pub env_file: Option<String>,
+}
+
+impl ShellConfig {
+    /// Program and arguments for a shell in `repo`, with `default_shell` standing in for
+    /// an unset command. An env file wraps the shell in `sh`, which sources the file and
+    /// execs it.
+    pub fn argv_for(&self, repo: &str, default_shell: &str) -> Vec<String> {
+        let overrides = self.repos.get(repo).cloned().unwrap_or_default();
+        let args = match overrides.command {
+            Some(_) => overrides.args.unwrap_or_default(),
+            None => overrides.args.unwrap_or_else(|| self.args.clone()),
+        };
+        let command = overrides
+            .command
+            .or_else(|| self.command.clone())
+            .unwrap_or_else(|| default_shell.to_string());
+        let mut argv = vec![command];
+        argv.extend(args);
+
+        let Some(env_file) = overrides.env_file.or_else(|| self.env_file.clone()) else {
+            return argv;
+        };
+        let env_file = match (env_file.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+            _ => env_file,
+        };
+        let mut wrapped = vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            r#"if [ -r "$1" ]; then set -a; . "$1"; set +a; fi; shift; exec "$@""#.to_string(),
+            "sh".to_string(),
+            env_file,
+        ];
+        wrapped.extend(argv);
+        wrapped
+    }
+}
+
+/// Limits for the task dispatcher, which hands queued tasks to idle sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DispatchConfig {
+    /// New sessions are only created for queued tasks while fewer than this many are live
+    pub max_sessions: usize,
+    /// Created sessions are named `<prefix>-1`, `<prefix>-2`, ...
+    pub name_prefix: String,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: 4,
+            name_prefix: "task".to_string(),
+        }
+    }
+}
+
+/// Starts (or prompts) a session when a matching session first stops
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainStep {
+    /// Name of the session whose stop triggers this step; `*` matches any characters
+    pub after: String,
+    /// Session to start, e.g. "{session}-review". A live session with that name is sent
+    /// the prompt instead.
+    pub start: String,
+    /// Prompt for the started session. `{session}` and `{path}` are replaced with the
+    /// stopped session's name and worktree.
+    pub prompt: String,
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+impl ChainStep {
+    /// Whether a stop of session `name` triggers this step
+    pub fn matches(&self, name: &str) -> bool {
+        wildcard_match(&self.after, name)
+    }
+
+    /// `template` with the stopped session's details filled in
+    pub fn fill(template: &str, session: &str, path: &Path) -> String {
+        template
+            .replace("{session}", session)
+            .replace("{path}", &path.to_string_lossy())
+    }
+}
+
+/// Fires when a line of any session's output (claude or shell panes) matches `pattern`,
+/// e.g. "BUILD FAILED" or "All tests passed"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchRule {
+    /// Shown in messages about the match; defaults to the pattern
+    pub name: Option<String>,
+    pub pattern: String,
+    /// Level of the status bar message posted on a match, or none for no message
+    pub status: Option<StatusLevel>,
+    /// Raise a desktop notification through the host terminal (OSC 9)
+    pub notify: bool,
+    /// Flag the session as needing attention until it is next typed into
+    pub mark: bool,
+    /// Shell command run in the session's worktree, with SHEPHERD_SESSION and
+    /// SHEPHERD_MATCH (the matching line) set
+    pub command: Option<String>,
+    /// Seconds before the rule fires again for the same session, since screen redraws
+    /// repeat output
+    pub cooldown_secs: u64,
+}
+
+impl Default for WatchRule {
+    fn default() -> Self {
+        Self {
+            name: None,
+            pattern: String::new(),
+            status: Some(StatusLevel::Info),
+            notify: false,
+            mark: false,
+            command: None,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+impl WatchRule {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.pattern)
+    }
 }
 
 impl Default for Config {
@@ -15,28 +643,67 @@ impl Default for Config {
 
         Self {
             claude_args: vec!["--dangerously-skip-permissions".to_string()],
+            repo_claude_args: HashMap::new(),
             workflows_path,
+            base_branch: None,
+            fetch_interval_secs: None,
+            worktree_setup: WorktreeSetup::default(),
+            script_workflow: None,
+            workflow: WorkflowConfig::default(),
+            build_cache: BuildCache::default(),
+            quick_jump_hotkeys: true,
+            name_from_prompt: true,
+            session_limits: SessionLimits::default(),
+            max_live_sessions: None,
+            session_limit_policy: SessionLimitPolicy::default(),
+            git_backend: GitBackendKind::default(),
+            terminal_env: TerminalEnv::default(),
+            redraw_on_attach: false,
+            confirm_kill: true,
+            confirm_quit: true,
+            confirm_worktree_delete: true,
+            confirm_new_session: false,
+            macros: Vec::new(),
+            watch_rules: Vec::new(),
+            checks: ChecksConfig::default(),
+            shell: ShellConfig::default(),
+            editor_command: None,
+            chains: Vec::new(),
+            dispatch: DispatchConfig::default(),
+            status_timeouts: StatusTimeouts::default(),
+            event_log: EventLogConfig::default(),
+            trash: TrashConfig::default(),
+            history: HistoryConfig::default(),
+            raw_output_log: RawOutputLog::default(),
         }
     }
 }
 
 impl Config {
-    fn config_path() -> anyhow::Result<PathBuf> {
-        let home =
-            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+    /// Arguments claude gets in `repo`: the repo's own, then `claude_args`
+    pub fn claude_args_for(&self, repo: &str) -> Vec<String> {
+        let mut args = self.repo_claude_args.get(repo).cloned().unwrap_or_default();
+        args.extend(self.claude_args.iter().cloned());
+        args
+    }
+
+    fn config_path() -> Result<PathBuf, ShepardError> {
+        let home = dirs::home_dir().ok_or_else(|| {
+            ShepardError::config("~/.shepherd/config.json", "could not find home directory")
+        })?;
         Ok(home.join(".shepherd").join("config.json"))
     }
 
-    pub fn load() -> anyhow::Result<Self> {
+    pub fn load() -> Result<Self, ShepardError> {
         let path = Self::config_path()?;
 
         if path.exists() {
-            let contents = std::fs::read_to_string(&path)?;
-            let config: Config = serde_json::from_str(&contents)?;
-            Ok(config)
+            let contents =
+                std::fs::read_to_string(&path).map_err(|e| ShepardError::config(&path, e))?;
+            serde_json::from_str(&contents).map_err(|e| ShepardError::config(&path, e))
         } else {
             let config = Config::default();
-            config.save()?;
+            config.save().map_err(|e| ShepardError::config(&path, e))?;
             Ok(config)
         }
     }
@@ -53,3 +720,65 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(key: &str) -> Option<Vec<u8>> {
+        Macro {
+            key: Some(key.to_string()),
+            ..Macro::default()
+        }
+        .key_bytes()
+    }
+
+    #[test]
+    fn test_macro_key_chords() {
+        assert_eq!(chord("ctrl+g"), Some(vec![0x07]));
+        assert_eq!(chord("Alt+C"), Some(vec![0x1b, b'c']));
+        assert_eq!(chord("ctrl+alt+r"), Some(vec![0x1b, 0x12]));
+        assert_eq!(chord("c"), None);
+        assert_eq!(chord("ctrl+1"), None);
+        assert_eq!(chord("super+x"), None);
+    }
+
+    #[test]
+    fn test_shell_argv() {
+        let mut shell = ShellConfig {
+            command: Some("fish".to_string()),
+            args: vec!["-l".to_string()],
+            ..ShellConfig::default()
+        };
+        shell.repos.insert(
+            "data".to_string(),
+            ShellOverride {
+                command: Some("nu".to_string()),
+                env_file: Some(".env".to_string()),
+                ..ShellOverride::default()
+            },
+        );
+        assert_eq!(shell.argv_for("web", "/bin/zsh"), vec!["fish", "-l"]);
+        let data = shell.argv_for("data", "/bin/zsh");
+        assert_eq!(data[0], "/bin/sh");
+        assert_eq!(data[3..], ["sh", ".env", "nu"]);
+        assert_eq!(
+            ShellConfig::default().argv_for("web", "/bin/zsh"),
+            vec!["/bin/zsh"]
+        );
+    }
+
+    #[test]
+    fn test_chain_step_matches() {
+        let step = |after: &str| ChainStep {
+            after: after.to_string(),
+            ..ChainStep::default()
+        };
+        assert!(step("impl").matches("impl"));
+        assert!(!step("impl").matches("impl-review"));
+        assert!(step("*-impl").matches("auth-impl"));
+        assert!(!step("*-impl").matches("auth-impl-review"));
+        assert!(step("feat-*-impl").matches("feat-x-impl"));
+        assert!(step("*").matches("anything"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors from shepherd's library code, by the part of the system they came from. The UI
+/// turns them into status messages; everything else can match on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShepardError {
+    /// A git command couldn't run or reported failure
+    Git { context: String, message: String },
+    /// A session's pseudo-terminal or process couldn't be set up
+    Pty { context: String, message: String },
+    /// A config file couldn't be read, written or parsed
+    Config { path: PathBuf, message: String },
+    /// A workflow couldn't prepare or clean up after a session
+    Workflow {
+        workflow: &'static str,
+        message: String,
+    },
+}
+
+impl ShepardError {
+    /// `context` is what was being done, e.g. "git worktree add"
+    pub fn git(context: impl Into<String>, message: impl fmt::Display) -> Self {
+        Self::Git {
+            context: context.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn pty(context: impl Into<String>, message: impl fmt::Display) -> Self {
+        Self::Pty {
+            context: context.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn config(path: impl Into<PathBuf>, message: impl fmt::Display) -> Self {
+        Self::Config {
+            path: path.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn workflow(workflow: &'static str, message: impl fmt::Display) -> Self {
+        Self::Workflow {
+            workflow,
+            message: message.to_string(),
+        }
+    }
+
+    /// A few words for the status bar; the full error is its `Display`
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Git { context, .. } => format!("{} failed", context),
+            Self::Pty { context, .. } => format!("Could not {}", context),
+            Self::Config { path, .. } => format!(
+                "Invalid {}",
+                path.file_name().map_or_else(
+                    || path.display().to_string(),
+                    |n| n.to_string_lossy().into()
+                )
+            ),
+            Self::Workflow { workflow, .. } => format!("Workflow {} failed", workflow),
+        }
+    }
+}
+
+impl fmt::Display for ShepardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Git { context, message } | Self::Pty { context, message } => {
+                write!(f, "{}: {}", context, message)
+            }
+            Self::Config { path, message } => write!(f, "{}: {}", path.display(), message),
+            Self::Workflow { workflow, message } => {
+                write!(f, "workflow {}: {}", workflow, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShepardError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_and_display() {
+        let error = ShepardError::git("git worktree add", "fatal: 'x' already exists");
+        assert_eq!(error.summary(), "git worktree add failed");
+        assert_eq!(
+            error.to_string(),
+            "git worktree add: fatal: 'x' already exists"
+        );
+
+        let error = ShepardError::config("/home/me/.shepherd/config.json", "expected `,`");
+        assert_eq!(error.summary(), "Invalid config.json");
+
+        // Kept intact through anyhow, for callers that need the variant back
+        let any: anyhow::Error = ShepardError::workflow("script", "no capacity").into();
+        assert!(matches!(
+            any.downcast_ref::<ShepardError>(),
+            Some(ShepardError::Workflow {
+                workflow: "script",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use git2::{BranchType, Repository, StatusOptions};
+
+use crate::config::GitBackendKind;
+
+static BACKEND: OnceLock<Box<dyn GitBackend>> = OnceLock::new();
+
+/// Branch and working tree state of a session's checkout
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitStatus {
+    /// Branch name, or None when HEAD is detached
+    pub branch: Option<String>,
+    /// Abbreviated HEAD commit, or None before the first commit
+    pub head: Option<String>,
+    /// Uncommitted changes or untracked files present
+    pub dirty: bool,
+    /// Commits ahead of / behind the branch's upstream, if it has one
+    pub ahead_behind: Option<(u32, u32)>,
+    /// Files that would conflict if the branch were merged with the base branch
+    pub conflicts: Vec<String>,
+}
+
+impl GitStatus {
+    /// Compact "↑3 ↓1" label, or None when in sync with (or without) an upstream
+    pub fn ahead_behind_label(&self) -> Option<String> {
+        match self.ahead_behind? {
+            (0, 0) => None,
+            (ahead, 0) => Some(format!("↑{}", ahead)),
+            (0, behind) => Some(format!("↓{}", behind)),
+            (ahead, behind) => Some(format!("↑{} ↓{}", ahead, behind)),
+        }
+    }
+}
+
+/// The read-only git queries shepherd makes all the time: finding repositories, listing
+/// worktrees and checking branch state. Commands that change a repository still run the
+/// git CLI.
+pub trait GitBackend: Send + Sync {
+    /// Common git dir (the main checkout's `.git`) of the repository containing `dir`
+    fn common_dir(&self, dir: &Path) -> Option<PathBuf>;
+
+    /// Linked worktrees of the repository at `root`, without the main checkout
+    fn worktrees(&self, root: &Path) -> Vec<PathBuf>;
+
+    /// Branch, HEAD and working tree state of the checkout at `path`, without conflicts
+    fn status(&self, path: &Path) -> Option<GitStatus>;
+
+    /// Whether `rev` names a commit in the checkout at `path`
+    fn has_commit(&self, path: &Path, rev: &str) -> bool;
+
+    /// Commits in `local` that aren't in `upstream`, and the other way round
+    fn ahead_behind(&self, path: &Path, local: &str, upstream: &str) -> Option<(u32, u32)>;
+
+    /// Unix time of HEAD's commit
+    fn last_commit_time(&self, path: &Path) -> Option<i64>;
+}
+
+/// Use `kind` for every query from now on. Only the first call takes effect.
+pub fn install(kind: GitBackendKind) {
+    let _ = BACKEND.set(match kind {
+        GitBackendKind::Cli => Box::new(CliBackend),
+        GitBackendKind::Libgit2 => Box::new(Libgit2Backend),
+    });
+}
+
+/// The installed backend, or the git CLI if none was installed
+pub fn get() -> &'static dyn GitBackend {
+    BACKEND.get_or_init(|| Box::new(CliBackend)).as_ref()
+}
+
+/// Runs `git` and parses its output
+pub struct CliBackend;
+
+impl CliBackend {
+    /// Stdout of a successful git command run in `dir`
+    fn stdout(dir: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Parse `git status --porcelain=v2 --branch` output
+    fn parse_status(output: &str) -> GitStatus {
+        let mut status = GitStatus::default();
+        for line in output.lines() {
+            if let Some(oid) = line.strip_prefix("# branch.oid ") {
+                if oid != "(initial)" {
+                    status.head = Some(oid.chars().take(7).collect());
+                }
+            } else if let Some(head) = line.strip_prefix("# branch.head ") {
+                if head != "(detached)" {
+                    status.branch = Some(head.to_string());
+                }
+            } else if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                // Format: +<ahead> -<behind>
+                let mut counts = ab
+                    .split_whitespace()
+                    .map(|n| n.trim_start_matches(['+', '-']).parse().ok());
+                if let (Some(Some(ahead)), Some(Some(behind))) = (counts.next(), counts.next()) {
+                    status.ahead_behind = Some((ahead, behind));
+                }
+            } else if !line.starts_with('#') && !line.is_empty() {
+                status.dirty = true;
+            }
+        }
+        status
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn common_dir(&self, dir: &Path) -> Option<PathBuf> {
+        let common_dir = Self::stdout(dir, &["rev-parse", "--git-common-dir"])?;
+        // Either ".git" (relative) or "/path/to/repo/.git" (absolute)
+        Some(dir.join(common_dir.trim()))
+    }
+
+    fn worktrees(&self, root: &Path) -> Vec<PathBuf> {
+        let Some(output) = Self::stdout(root, &["worktree", "list", "--porcelain"]) else {
+            return Vec::new();
+        };
+        output
+            .lines()
+            .filter_map(|line| line.strip_prefix("worktree "))
+            .skip(1)
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    fn status(&self, path: &Path) -> Option<GitStatus> {
+        Self::stdout(path, &["status", "--porcelain=v2", "--branch"])
+            .map(|output| Self::parse_status(&output))
+    }
+
+    fn has_commit(&self, path: &Path, rev: &str) -> bool {
+        let rev = format!("{}^{{commit}}", rev);
+        Self::stdout(path, &["rev-parse", "--verify", "--quiet", &rev]).is_some()
+    }
+
+    fn ahead_behind(&self, path: &Path, local: &str, upstream: &str) -> Option<(u32, u32)> {
+        let range = format!("{}...{}", local, upstream);
+        let output = Self::stdout(path, &["rev-list", "--left-right", "--count", &range])?;
+        let mut counts = output.split_whitespace().map(|n| n.parse().ok());
+        Some((counts.next()??, counts.next()??))
+    }
+
+    fn last_commit_time(&self, path: &Path) -> Option<i64> {
+        Self::stdout(path, &["log", "-1", "--format=%ct"]).and_then(|t| t.trim().parse().ok())
+    }
+}
+
+/// Reads repositories in-process with libgit2, saving a git process per query
+pub struct Libgit2Backend;
+
+impl Libgit2Backend {
+    /// Commits in `local` that aren't in `upstream`, and the other way round
+    fn count(repo: &Repository, local: &str, upstream: &str) -> Option<(u32, u32)> {
+        let local = repo.revparse_single(local).ok()?.peel_to_commit().ok()?;
+        let upstream = repo.revparse_single(upstream).ok()?.peel_to_commit().ok()?;
+        let (ahead, behind) = repo.graph_ahead_behind(local.id(), upstream.id()).ok()?;
+        Some((ahead as u32, behind as u32))
+    }
+}
+
+impl GitBackend for Libgit2Backend {
+    fn common_dir(&self, dir: &Path) -> Option<PathBuf> {
+        let repo = Repository::discover(dir).ok()?;
+        Some(repo.commondir().to_path_buf())
+    }
+
+    fn worktrees(&self, root: &Path) -> Vec<PathBuf> {
+        let Ok(repo) = Repository::open(root) else {
+            return Vec::new();
+        };
+        let Ok(names) = repo.worktrees() else {
+            return Vec::new();
+        };
+        names
+            .iter()
+            .flatten()
+            .filter_map(|name| repo.find_worktree(name).ok())
+            .map(|worktree| worktree.path().to_path_buf())
+            .collect()
+    }
+
+    fn status(&self, path: &Path) -> Option<GitStatus> {
+        let repo = Repository::open(path).ok()?;
+        let mut status = GitStatus::default();
+        // HEAD of a branch without commits yet doesn't resolve, but still names the branch
+        let head = repo.find_reference("HEAD").ok()?;
+        if let Some(target) = head.symbolic_target() {
+            status.branch = target.strip_prefix("refs/heads/").map(str::to_string);
+        }
+        status.head = head
+            .resolve()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string().chars().take(7).collect());
+        status.dirty = repo
+            .statuses(Some(
+                StatusOptions::new()
+                    .include_untracked(true)
+                    .recurse_untracked_dirs(false),
+            ))
+            .ok()?
+            .iter()
+            .next()
+            .is_some();
+        if let Some(ref branch) = status.branch
+            && let Ok(upstream) = repo
+                .find_branch(branch, BranchType::Local)
+                .and_then(|b| b.upstream())
+            && let Ok(Some(upstream)) = upstream.name()
+        {
+            status.ahead_behind = Self::count(&repo, "HEAD", upstream);
+        }
+        Some(status)
+    }
+
+    fn has_commit(&self, path: &Path, rev: &str) -> bool {
+        Repository::open(path).is_ok_and(|repo| {
+            repo.revparse_single(rev)
+                .and_then(|o| o.peel_to_commit())
+                .is_ok()
+        })
+    }
+
+    fn ahead_behind(&self, path: &Path, local: &str, upstream: &str) -> Option<(u32, u32)> {
+        Self::count(&Repository::open(path).ok()?, local, upstream)
+    }
+
+    fn last_commit_time(&self, path: &Path) -> Option<i64> {
+        let repo = Repository::open(path).ok()?;
+        let commit = repo.head().ok()?.peel_to_commit().ok()?;
+        Some(commit.time().seconds())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{TempDir, git};
+
+    #[test]
+    fn test_parse_porcelain_v2() {
+        let status = CliBackend::parse_status(
+            "# branch.oid 1234567890abcdef\n\
+             # branch.head feat/login\n\
+             # branch.upstream origin/feat/login\n\
+             # branch.ab +3 -1\n\
+             1 .M N... 100644 100644 100644 abc abc src/main.rs\n",
+        );
+        assert_eq!(status.branch.as_deref(), Some("feat/login"));
+        assert_eq!(status.head.as_deref(), Some("1234567"));
+        assert!(status.dirty);
+        assert_eq!(status.ahead_behind_label().as_deref(), Some("↑3 ↓1"));
+
+        let clean = CliBackend::parse_status("# branch.oid (initial)\n# branch.head (detached)\n");
+        assert_eq!(clean, GitStatus::default());
+    }
+
+    /// Both backends see the same repository the same way
+    #[test]
+    fn test_backends_agree() {
+        let root = TempDir::new("git-backend");
+        let repo = root.repo("repo");
+        git(
+            &repo,
+            &["worktree", "add", "-q", "-b", "feature", "../feature"],
+        );
+        let feature = root.join("feature");
+        git(&feature, &["commit", "-q", "--allow-empty", "-m", "two"]);
+        std::fs::write(feature.join("new.txt"), "").unwrap();
+
+        let canonical = |p: PathBuf| p.canonicalize().unwrap();
+        for backend in [&CliBackend as &dyn GitBackend, &Libgit2Backend] {
+            assert_eq!(
+                backend.common_dir(&feature).map(canonical),
+                Some(canonical(repo.join(".git")))
+            );
+            assert_eq!(
+                backend
+                    .worktrees(&repo)
+                    .into_iter()
+                    .map(canonical)
+                    .collect::<Vec<_>>(),
+                vec![canonical(feature.clone())]
+            );
+            let status = backend.status(&feature).unwrap();
+            assert_eq!(status.branch.as_deref(), Some("feature"));
+            assert!(status.dirty);
+            assert!(backend.has_commit(&feature, "main"));
+            assert!(!backend.has_commit(&feature, "origin/main"));
+            assert_eq!(backend.ahead_behind(&feature, "HEAD", "main"), Some((1, 0)));
+            assert!(backend.last_commit_time(&feature).is_some());
+        }
+    }
+}
@@ -1,20 +1,79 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::path::PathBuf;
+use std::time::Duration;
 
-const MAX_RECENT_PER_WORKSPACE: usize = 5;
+/// Recent sessions kept per repo unless `set_retention` says otherwise
+const DEFAULT_MAX_RECENT: usize = 5;
+/// Session runs kept in the time log unless `set_retention` says otherwise
+const DEFAULT_MAX_TIME_ENTRIES: usize = 2000;
+/// Longest prompt excerpt kept per recent session
+const MAX_PROMPT_EXCERPT: usize = 200;
+/// Sent prompts remembered per repository
+const MAX_PROMPTS_PER_REPO: usize = 200;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RecentSession {
     pub name: String,
     /// The original project path (git repo root) where the session was created from
     pub project_path: PathBuf,
+    /// When the session was first recorded
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the session was created or last ended
+    #[serde(default)]
+    pub last_active: Option<DateTime<Utc>>,
+    /// First line of the prompt the session was created with
+    #[serde(default)]
+    pub prompt: Option<String>,
+}
+
+/// Time spent in one run of a session, recorded when the session ends
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeEntry {
+    pub repo: String,
+    pub session: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Wall-clock time the session was alive
+    pub running_secs: u64,
+    /// Time the session was the one on screen
+    pub focused_secs: u64,
+}
+
+/// A prompt sent to a session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptEntry {
+    pub text: String,
+    /// Session it was last sent to
+    pub session: String,
+    pub sent_at: DateTime<Utc>,
 }
 
 /// Stores recent sessions per repository name.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SessionHistory {
     recent_sessions: HashMap<String, VecDeque<RecentSession>>,
+    /// Every finished session run, oldest first
+    #[serde(default)]
+    time_log: Vec<TimeEntry>,
+    /// Prompts sent per repository, most recent first
+    #[serde(default)]
+    prompts: HashMap<String, VecDeque<PromptEntry>>,
+    /// Session names pinned per repository; pinned sessions are listed first and never
+    /// trimmed from the recent list
+    #[serde(default)]
+    pinned: HashMap<String, BTreeSet<String>>,
+    /// Recent sessions kept per repo
+    #[serde(skip)]
+    max_recent: Option<usize>,
+    /// Session runs kept in the time log
+    #[serde(skip)]
+    max_time_entries: Option<usize>,
+    /// Never written to disk
+    #[serde(skip)]
+    in_memory: bool,
 }
 
 impl SessionHistory {
@@ -36,7 +95,18 @@ impl SessionHistory {
         }
     }
 
+    /// An empty history that `save` keeps off disk, for tests
+    pub fn in_memory() -> Self {
+        Self {
+            in_memory: true,
+            ..Self::default()
+        }
+    }
+
     pub fn save(&self) -> anyhow::Result<()> {
+        if self.in_memory {
+            return Ok(());
+        }
         let path = Self::history_path()?;
 
         if let Some(parent) = path.parent() {
@@ -48,33 +118,87 @@ impl SessionHistory {
         Ok(())
     }
 
+    /// Record a session as the most recent one for `repo_name`. A session created without
+    /// a prompt keeps the prompt recorded for an earlier run with the same name.
     pub fn set_recent_session(
         &mut self,
         repo_name: String,
         session_name: String,
         project_path: PathBuf,
+        prompt: Option<&str>,
     ) -> anyhow::Result<()> {
+        let pinned = self.pinned.get(&repo_name).cloned().unwrap_or_default();
+        let sessions = self.recent_sessions.entry(repo_name).or_default();
+        let same_session =
+            |s: &RecentSession| s.name == session_name && s.project_path == project_path;
+
+        let earlier = sessions.iter().find(|s| same_session(s));
+        let created_at = earlier.and_then(|s| s.created_at).or(Some(Utc::now()));
+        let prompt = match prompt {
+            Some(prompt) => prompt_excerpt(prompt),
+            None => earlier.and_then(|s| s.prompt.clone()),
+        };
+
+        // Remove existing entry if present (will be re-added at front)
+        sessions.retain(|s| !same_session(s));
         let entry = RecentSession {
             name: session_name,
             project_path,
+            created_at,
+            last_active: Some(Utc::now()),
+            prompt,
         };
 
-        let sessions = self.recent_sessions.entry(repo_name).or_default();
-
-        // Remove existing entry if present (will be re-added at front)
-        sessions.retain(|s| s != &entry);
-
         // Add to front
         sessions.push_front(entry);
 
-        // Trim to max size
-        while sessions.len() > MAX_RECENT_PER_WORKSPACE {
-            sessions.pop_back();
-        }
+        trim_unpinned(
+            sessions,
+            &pinned,
+            self.max_recent.unwrap_or(DEFAULT_MAX_RECENT),
+        );
 
         self.save()
     }
 
+    /// Keep at most `max_recent` recent sessions per repo and `max_time_entries` session
+    /// runs from now on, and drop the sessions not active for `max_age` right away. Pinned
+    /// sessions stay either way. Returns how many entries were dropped.
+    pub fn set_retention(
+        &mut self,
+        max_recent: usize,
+        max_age: Option<Duration>,
+        max_time_entries: usize,
+    ) -> usize {
+        self.max_recent = Some(max_recent);
+        self.max_time_entries = Some(max_time_entries);
+        let cutoff = max_age
+            .and_then(|age| chrono::Duration::from_std(age).ok())
+            .map(|age| Utc::now() - age);
+        let mut dropped = 0;
+        for (repo, sessions) in &mut self.recent_sessions {
+            let pinned = self.pinned.get(repo).cloned().unwrap_or_default();
+            let before = sessions.len();
+            if let Some(cutoff) = cutoff {
+                // Entries from before activity was recorded have no time; keep those
+                sessions.retain(|s| {
+                    pinned.contains(&s.name) || s.last_active.is_none_or(|at| at >= cutoff)
+                });
+            }
+            trim_unpinned(sessions, &pinned, max_recent);
+            dropped += before - sessions.len();
+        }
+        dropped + self.trim_time_log()
+    }
+
+    /// Drop the oldest runs past `max_time_entries`, returning how many went
+    fn trim_time_log(&mut self) -> usize {
+        let max = self.max_time_entries.unwrap_or(DEFAULT_MAX_TIME_ENTRIES);
+        let excess = self.time_log.len().saturating_sub(max);
+        self.time_log.drain(..excess);
+        excess
+    }
+
     /// Get the most recent session for a repository
     pub fn get_recent_session(&self, repo_name: &str) -> Option<&RecentSession> {
         self.recent_sessions
@@ -90,10 +214,179 @@ impl SessionHistory {
             .flat_map(|sessions| sessions.iter())
     }
 
+    /// Recent sessions of every repository except `repo_name`, as (repo, session), most
+    /// recently active first
+    pub fn recent_elsewhere(&self, repo_name: &str) -> Vec<(&str, &RecentSession)> {
+        let mut sessions: Vec<(&str, &RecentSession)> = self
+            .recent_sessions
+            .iter()
+            .filter(|(repo, _)| repo.as_str() != repo_name)
+            .flat_map(|(repo, sessions)| sessions.iter().map(move |s| (repo.as_str(), s)))
+            .collect();
+        sessions.sort_by_key(|(_, s)| std::cmp::Reverse(s.last_active));
+        sessions
+    }
+
     /// Remove a session by name from a specific repository
     pub fn remove_by_name(&mut self, repo_name: &str, session_name: &str) {
         if let Some(sessions) = self.recent_sessions.get_mut(repo_name) {
             sessions.retain(|s| s.name != session_name);
         }
+        if let Some(pinned) = self.pinned.get_mut(repo_name) {
+            pinned.remove(session_name);
+        }
+    }
+
+    pub fn is_pinned(&self, repo_name: &str, session_name: &str) -> bool {
+        self.pinned
+            .get(repo_name)
+            .is_some_and(|pinned| pinned.contains(session_name))
+    }
+
+    /// Pin or unpin a session, returning whether it is pinned now
+    pub fn toggle_pin(&mut self, repo_name: &str, session_name: &str) -> anyhow::Result<bool> {
+        let pinned = self.pinned.entry(repo_name.to_string()).or_default();
+        let now_pinned = !pinned.remove(session_name);
+        if now_pinned {
+            pinned.insert(session_name.to_string());
+        }
+        self.save()?;
+        Ok(now_pinned)
+    }
+
+    /// Append a finished session run to the time log
+    pub fn record_time(&mut self, entry: TimeEntry) -> anyhow::Result<()> {
+        if let Some(recent) = self
+            .recent_sessions
+            .get_mut(&entry.repo)
+            .and_then(|sessions| sessions.iter_mut().find(|s| s.name == entry.session))
+        {
+            recent.last_active = Some(entry.ended_at);
+        }
+        self.time_log.push(entry);
+        self.trim_time_log();
+        self.save()
+    }
+
+    /// Remember a prompt sent in `repo_name`. Sending the same text again moves it to the front.
+    pub fn record_prompt(
+        &mut self,
+        repo_name: String,
+        session_name: String,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let prompts = self.prompts.entry(repo_name).or_default();
+        prompts.retain(|p| p.text != text);
+        prompts.push_front(PromptEntry {
+            text: text.to_string(),
+            session: session_name,
+            sent_at: Utc::now(),
+        });
+        prompts.truncate(MAX_PROMPTS_PER_REPO);
+        self.save()
+    }
+
+    /// Prompts sent in a repository (most recent first)
+    pub fn prompts(&self, repo_name: &str) -> impl Iterator<Item = &PromptEntry> {
+        self.prompts
+            .get(repo_name)
+            .into_iter()
+            .flat_map(|prompts| prompts.iter())
+    }
+
+    /// All recorded session runs, oldest first
+    pub fn time_entries(&self) -> &[TimeEntry] {
+        &self.time_log
+    }
+}
+
+/// Drop the oldest unpinned sessions until at most `max` are left (or only pinned ones)
+fn trim_unpinned(sessions: &mut VecDeque<RecentSession>, pinned: &BTreeSet<String>, max: usize) {
+    while sessions.len() > max {
+        let Some(oldest) = sessions.iter().rposition(|s| !pinned.contains(&s.name)) else {
+            break;
+        };
+        sessions.remove(oldest);
+    }
+}
+
+/// First non-empty line of `prompt`, shortened for display
+fn prompt_excerpt(prompt: &str) -> Option<String> {
+    let line = prompt.lines().map(str::trim).find(|l| !l.is_empty())?;
+    Some(line.chars().take(MAX_PROMPT_EXCERPT).collect())
+}
+
+/// Compact human duration: "45s", "42m", "3h05m", "2d4h"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+        _ => format!("{}d{}h", secs / 86400, (secs % 86400) / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, days_ago: i64) -> RecentSession {
+        RecentSession {
+            name: name.to_string(),
+            project_path: PathBuf::from("/repo"),
+            created_at: None,
+            last_active: Some(Utc::now() - chrono::Duration::days(days_ago)),
+            prompt: None,
+        }
+    }
+
+    #[test]
+    fn test_retention() {
+        let mut history = SessionHistory::default();
+        history.recent_sessions.insert(
+            "repo".to_string(),
+            ["a", "b", "c", "d"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| session(name, i as i64 * 10))
+                .collect(),
+        );
+        history
+            .pinned
+            .insert("repo".to_string(), BTreeSet::from(["d".to_string()]));
+
+        // "c" is too old; of the rest, "b" is the oldest unpinned past the limit of two
+        let dropped = history.set_retention(2, Some(Duration::from_secs(15 * 86400)), 100);
+        assert_eq!(dropped, 2);
+        let names: Vec<&str> = history
+            .get_recent_sessions("repo")
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "d"]);
+    }
+
+    #[test]
+    fn test_time_log_cap() {
+        let mut history = SessionHistory::in_memory();
+        history.set_retention(5, None, 3);
+        for i in 0..5 {
+            history
+                .record_time(TimeEntry {
+                    repo: "repo".to_string(),
+                    session: format!("s{}", i),
+                    started_at: Utc::now(),
+                    ended_at: Utc::now(),
+                    running_secs: 0,
+                    focused_secs: 0,
+                })
+                .unwrap();
+        }
+        let sessions: Vec<&str> = history
+            .time_entries()
+            .iter()
+            .map(|e| e.session.as_str())
+            .collect();
+        assert_eq!(sessions, vec!["s2", "s3", "s4"]);
     }
 }
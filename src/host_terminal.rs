@@ -0,0 +1,245 @@
+use std::io::{IsTerminal, Write};
+use std::os::fd::AsFd;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+
+use crate::config::TerminalEnv;
+
+/// How long to wait for the host terminal to answer startup queries
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// TERM_PROGRAM values of terminals known to render 24-bit color
+const TRUECOLOR_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"];
+/// TERM values of terminals known to render 24-bit color
+const TRUECOLOR_TERMS: &[&str] = &[
+    "xterm-kitty",
+    "xterm-ghostty",
+    "alacritty",
+    "wezterm",
+    "foot",
+];
+
+/// Colors reported to child applications when the host terminal doesn't answer
+const DEFAULT_FOREGROUND: &str = "rgb:ffff/ffff/ffff";
+const DEFAULT_BACKGROUND: &str = "rgb:0000/0000/0000";
+
+static HOST: OnceLock<HostTerminal> = OnceLock::new();
+
+/// What we learned about the real terminal shepherd is running in.
+/// Used to answer queries from child applications on its behalf.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostTerminal {
+    /// OSC 10 foreground color spec, e.g. `rgb:ffff/ffff/ffff`
+    pub foreground: Option<String>,
+    /// OSC 11 background color spec
+    pub background: Option<String>,
+    /// TERM of the host terminal
+    pub term: Option<String>,
+    /// COLORTERM of the host terminal
+    pub colorterm: Option<String>,
+    /// TERM_PROGRAM of the host terminal, e.g. `iTerm.app`
+    pub term_program: Option<String>,
+}
+
+impl HostTerminal {
+    /// Identify the host terminal from the environment and query it for its default
+    /// colors. Must be called in raw mode, before anything else starts reading stdin.
+    /// Colors are left unset if stdin/stdout aren't terminals or the terminal doesn't
+    /// answer in time.
+    pub fn detect() -> Self {
+        let env = |key| std::env::var(key).ok().filter(|v: &String| !v.is_empty());
+        Self {
+            term: env("TERM"),
+            colorterm: env("COLORTERM"),
+            term_program: env("TERM_PROGRAM"),
+            ..Self::query_colors()
+        }
+    }
+
+    fn query_colors() -> Self {
+        if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+            return Self::default();
+        }
+
+        // DA1 goes last: every terminal answers it, so its reply marks the end of
+        // the answers we're going to get
+        let mut stdout = std::io::stdout();
+        if stdout
+            .write_all(b"\x1b]10;?\x1b\\\x1b]11;?\x1b\\\x1b[c")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return Self::default();
+        }
+
+        let stdin = std::io::stdin();
+        let deadline = Instant::now() + QUERY_TIMEOUT;
+        let mut reply = Vec::new();
+        let mut buf = [0u8; 256];
+        while !has_da1_reply(&reply) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            let timeout = PollTimeout::try_from(remaining).unwrap_or(PollTimeout::ZERO);
+            let mut fds = [PollFd::new(stdin.as_fd(), PollFlags::POLLIN)];
+            match poll(&mut fds, timeout) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(nix::errno::Errno::EINTR) => continue,
+                Err(_) => break,
+            }
+            match nix::unistd::read(&stdin, &mut buf) {
+                Ok(0) => break,
+                Ok(n) => reply.extend_from_slice(&buf[..n]),
+                Err(nix::errno::Errno::EINTR | nix::errno::Errno::EAGAIN) => {}
+                Err(_) => break,
+            }
+        }
+
+        Self::parse(&reply)
+    }
+
+    /// Extract OSC 10/11 color replies from raw terminal output
+    fn parse(reply: &[u8]) -> Self {
+        Self {
+            foreground: parse_color_reply(reply, b"10"),
+            background: parse_color_reply(reply, b"11"),
+            ..Self::default()
+        }
+    }
+
+    /// Record the detected terminal for the rest of the process
+    pub fn install(self) {
+        let _ = HOST.set(self);
+    }
+
+    /// The installed host terminal, or defaults if detection never ran
+    pub fn get() -> &'static HostTerminal {
+        HOST.get_or_init(HostTerminal::default)
+    }
+
+    pub fn foreground(&self) -> &str {
+        self.foreground.as_deref().unwrap_or(DEFAULT_FOREGROUND)
+    }
+
+    pub fn background(&self) -> &str {
+        self.background.as_deref().unwrap_or(DEFAULT_BACKGROUND)
+    }
+
+    /// Whether the host renders 24-bit color, so truecolor output can pass through
+    pub fn truecolor(&self) -> bool {
+        let term = self.term.as_deref().unwrap_or_default();
+        matches!(self.colorterm.as_deref(), Some("truecolor" | "24bit"))
+            || term.ends_with("-direct")
+            || TRUECOLOR_TERMS.contains(&term)
+            || self
+                .term_program
+                .as_deref()
+                .is_some_and(|p| TRUECOLOR_PROGRAMS.contains(&p))
+    }
+
+    /// TERM/COLORTERM for child sessions. Children talk to shepherd's xterm-compatible
+    /// emulator rather than the host, so TERM always names an xterm entry; only the
+    /// color depth follows the host. Config values take precedence.
+    pub fn session_env(&self, overrides: &TerminalEnv) -> Vec<(&'static str, String)> {
+        let truecolor = self.truecolor();
+        let term = overrides.term.clone().unwrap_or_else(|| {
+            let host_term = self.term.as_deref().unwrap_or_default();
+            if truecolor || host_term.contains("256color") {
+                "xterm-256color".to_string()
+            } else {
+                "xterm".to_string()
+            }
+        });
+
+        let mut env = vec![("TERM", term)];
+        let colorterm = overrides
+            .colorterm
+            .clone()
+            .or_else(|| truecolor.then(|| "truecolor".to_string()));
+        if let Some(colorterm) = colorterm {
+            env.push(("COLORTERM", colorterm));
+        }
+        env
+    }
+}
+
+/// True once `reply` contains a DA1 response (`ESC [ ? ... c`)
+fn has_da1_reply(reply: &[u8]) -> bool {
+    reply.windows(3).enumerate().any(|(i, w)| {
+        w == b"\x1b[?"
+            && reply[i + 3..]
+                .iter()
+                .find(|b| !b.is_ascii_digit() && **b != b';')
+                == Some(&b'c')
+    })
+}
+
+/// Find `ESC ] <code> ; <spec> (BEL | ESC \)` and return the spec
+fn parse_color_reply(reply: &[u8], code: &[u8]) -> Option<String> {
+    let prefix = [b"\x1b]".as_slice(), code, b";"].concat();
+    let start = reply.windows(prefix.len()).position(|w| w == prefix)? + prefix.len();
+    let rest = &reply[start..];
+    let end = rest.iter().position(|&b| b == 0x07 || b == 0x1b)?;
+    let spec = std::str::from_utf8(&rest[..end]).ok()?;
+    spec.starts_with("rgb:").then(|| spec.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_replies() {
+        let reply = b"\x1b]10;rgb:d0d0/d0d0/d0d0\x1b\\\x1b]11;rgb:1c1c/1c1c/1c1c\x07\x1b[?62;22c";
+        assert!(has_da1_reply(reply));
+        let host = HostTerminal::parse(reply);
+        assert_eq!(host.foreground(), "rgb:d0d0/d0d0/d0d0");
+        assert_eq!(host.background(), "rgb:1c1c/1c1c/1c1c");
+
+        // Terminals that don't support OSC 10/11 only answer DA1
+        let host = HostTerminal::parse(b"\x1b[?1;2c");
+        assert_eq!(host, HostTerminal::default());
+        assert_eq!(host.background(), DEFAULT_BACKGROUND);
+        assert!(!has_da1_reply(b"\x1b]10;rgb:0/0/0"));
+    }
+
+    #[test]
+    fn test_session_env() {
+        let host = HostTerminal {
+            term: Some("xterm-kitty".to_string()),
+            ..HostTerminal::default()
+        };
+        assert_eq!(
+            host.session_env(&TerminalEnv::default()),
+            vec![
+                ("TERM", "xterm-256color".to_string()),
+                ("COLORTERM", "truecolor".to_string())
+            ]
+        );
+
+        let host = HostTerminal {
+            term: Some("screen".to_string()),
+            ..HostTerminal::default()
+        };
+        assert_eq!(
+            host.session_env(&TerminalEnv::default()),
+            vec![("TERM", "xterm".to_string())]
+        );
+
+        let overrides = TerminalEnv {
+            term: Some("tmux-256color".to_string()),
+            colorterm: Some("24bit".to_string()),
+        };
+        assert_eq!(
+            host.session_env(&overrides),
+            vec![
+                ("TERM", "tmux-256color".to_string()),
+                ("COLORTERM", "24bit".to_string())
+            ]
+        );
+    }
+}
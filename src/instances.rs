@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use nix::sys::signal::kill;
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::workflows::RepoLayout;
+
+/// A running shepherd
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Instance {
+    pub pid: u32,
+    /// Repository or workspace the instance was started in
+    pub repo: PathBuf,
+    pub started_at: DateTime<Utc>,
+    /// Status socket, for forwarding requests to the instance
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+}
+
+/// What an instance started in `dir` registers as: the repository root, or `dir` itself
+/// outside a repository
+pub fn instance_root(dir: &Path) -> PathBuf {
+    RepoLayout::detect(dir).map_or_else(|| dir.to_path_buf(), |repo| repo.root().to_path_buf())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    instances: Vec<Instance>,
+    /// Worktree path -> pid of the instance running a session in it
+    worktrees: BTreeMap<PathBuf, u32>,
+}
+
+/// Registry of running shepherd instances (~/.shepherd/instances.json) and the worktrees
+/// each one has sessions in, so two instances in one repo don't work in or delete the same
+/// worktree. Every access holds an exclusive lock on `instances.lock`; entries of processes
+/// that are gone are dropped on the way.
+pub struct Registry {
+    dir: PathBuf,
+    pid: u32,
+}
+
+impl Registry {
+    pub fn open() -> anyhow::Result<Self> {
+        let home =
+            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+        Ok(Self::at(home.join(".shepherd"), std::process::id()))
+    }
+
+    /// The registry in `dir`, seen from the instance with `pid`
+    pub(crate) fn at(dir: PathBuf, pid: u32) -> Self {
+        Self { dir, pid }
+    }
+
+    /// Run `f` on the registry with the lock held, saving what it leaves behind
+    fn update<T>(&self, f: impl FnOnce(&mut RegistryFile) -> T) -> anyhow::Result<T> {
+        std::fs::create_dir_all(&self.dir)?;
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.join("instances.lock"))?;
+        let _lock = Flock::lock(lock, FlockArg::LockExclusive)
+            .map_err(|(_, errno): (File, Errno)| anyhow::anyhow!("lock failed: {}", errno))?;
+
+        let path = self.dir.join("instances.json");
+        let mut registry: RegistryFile = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        registry.instances.retain(|i| is_running(i.pid));
+        let live: Vec<u32> = registry.instances.iter().map(|i| i.pid).collect();
+        registry.worktrees.retain(|_, pid| live.contains(pid));
+
+        let result = f(&mut registry);
+        std::fs::write(&path, serde_json::to_string_pretty(&registry)?)?;
+        Ok(result)
+    }
+
+    /// Record this instance as running in `repo`, returning the other instances running
+    /// in the same place
+    pub fn register(&self, repo: &Path, socket: Option<&Path>) -> anyhow::Result<Vec<Instance>> {
+        self.update(|registry| {
+            registry.instances.retain(|i| i.pid != self.pid);
+            let others = registry
+                .instances
+                .iter()
+                .filter(|i| i.repo == repo)
+                .cloned()
+                .collect();
+            registry.instances.push(Instance {
+                pid: self.pid,
+                repo: repo.to_path_buf(),
+                started_at: Utc::now(),
+                socket: socket.map(Path::to_path_buf),
+            });
+            others
+        })
+    }
+
+    /// Other instances running in `repo`, oldest first
+    pub fn running_in(&self, repo: &Path) -> anyhow::Result<Vec<Instance>> {
+        self.update(|registry| {
+            let mut others: Vec<Instance> = registry
+                .instances
+                .iter()
+                .filter(|i| i.pid != self.pid && i.repo == repo)
+                .cloned()
+                .collect();
+            others.sort_by_key(|i| i.started_at);
+            others
+        })
+    }
+
+    /// Forget this instance and every worktree it held
+    pub fn unregister(&self) -> anyhow::Result<()> {
+        self.update(|registry| {
+            registry.instances.retain(|i| i.pid != self.pid);
+            registry.worktrees.retain(|_, pid| *pid != self.pid);
+        })
+    }
+
+    /// Take `worktree` for this instance. Returns the pid of the other instance that holds
+    /// it instead, if any.
+    pub fn claim(&self, worktree: &Path) -> anyhow::Result<Option<u32>> {
+        self.update(|registry| match registry.worktrees.get(worktree) {
+            Some(&pid) if pid != self.pid => Some(pid),
+            _ => {
+                registry.worktrees.insert(worktree.to_path_buf(), self.pid);
+                None
+            }
+        })
+    }
+
+    /// Give `worktree` up, if this instance holds it
+    pub fn release(&self, worktree: &Path) -> anyhow::Result<()> {
+        self.update(|registry| {
+            if registry.worktrees.get(worktree) == Some(&self.pid) {
+                registry.worktrees.remove(worktree);
+            }
+        })
+    }
+
+    /// Worktrees held by other running instances, with their pids
+    pub fn foreign_worktrees(&self) -> anyhow::Result<BTreeMap<PathBuf, u32>> {
+        self.update(|registry| {
+            registry
+                .worktrees
+                .iter()
+                .filter(|(_, pid)| **pid != self.pid)
+                .map(|(path, pid)| (path.clone(), *pid))
+                .collect()
+        })
+    }
+}
+
+/// Whether a process with `pid` exists (EPERM means it does, owned by someone else)
+fn is_running(pid: u32) -> bool {
+    !matches!(kill(Pid::from_raw(pid as i32), None), Err(Errno::ESRCH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_worktree_claims() {
+        let dir = TempDir::new("instances");
+        let repo = Path::new("/home/me/repo");
+        let worktree = Path::new("/home/me/repo/.worktrees/feature");
+        // The test process and its parent stand in for two running instances
+        let ours = Registry::at(dir.to_path_buf(), std::process::id());
+        let theirs = Registry::at(dir.to_path_buf(), std::os::unix::process::parent_id());
+
+        assert!(theirs.register(repo, None).unwrap().is_empty());
+        assert_eq!(ours.register(repo, None).unwrap().len(), 1);
+        assert_eq!(ours.running_in(repo).unwrap()[0].pid, theirs.pid);
+        assert_eq!(theirs.claim(worktree).unwrap(), None);
+        assert_eq!(
+            ours.claim(worktree).unwrap(),
+            Some(std::os::unix::process::parent_id())
+        );
+        assert!(ours.foreign_worktrees().unwrap().contains_key(worktree));
+
+        // Releasing someone else's worktree does nothing; exiting frees it
+        ours.release(worktree).unwrap();
+        assert!(ours.claim(worktree).unwrap().is_some());
+        theirs.unregister().unwrap();
+        assert_eq!(ours.claim(worktree).unwrap(), None);
+        assert!(ours.foreign_worktrees().unwrap().is_empty());
+    }
+}
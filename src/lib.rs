@@ -0,0 +1,25 @@
+pub mod archive;
+pub mod config;
+pub mod error;
+pub mod git_backend;
+pub mod history;
+pub mod host_terminal;
+pub mod instances;
+pub mod output_watch;
+pub mod pty_widget;
+pub mod raw_log;
+pub mod reader_pool;
+pub mod report;
+pub mod rotating_file;
+pub mod screens;
+pub mod session;
+pub mod session_manager;
+pub mod status_socket;
+#[cfg(test)]
+pub mod test_fixtures;
+#[cfg(test)]
+pub mod test_session;
+pub mod trace;
+pub mod transcript;
+pub mod trash;
+pub mod workflows;
@@ -1,22 +1,261 @@
-mod config;
-mod history;
-mod pty_widget;
-mod session;
-mod session_manager;
-mod status_socket;
-mod workflows;
+use chrono::{TimeDelta, Utc};
+use shepherd::history::SessionHistory;
+use shepherd::instances::{Registry, instance_root};
+use shepherd::report::{Report, ReportFormat, parse_since};
+use shepherd::session_manager::{
+    NESTED_ENV, Script, TuiSessionManager, event_log_path, install_panic_hook,
+};
+use shepherd::status_socket::{Command, send_command};
+use shepherd::trash::Trash;
+use shepherd::workflows::resolve_branch_name;
+use std::io::{BufRead, IsTerminal, Write};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 
-use session_manager::TuiSessionManager;
+const REPORT_USAGE: &str = "usage: shepherd report [--since <N>(m|h|d|w)] [--json]";
+const TRASH_USAGE: &str = "usage: shepherd trash [list | empty | restore [<name>]]";
+const OPEN_USAGE: &str = "usage: shepherd open <path>[:<line>]";
+const SCRIPT_USAGE: &str = "usage: shepherd --script <file>";
+const CTL_USAGE: &str = "usage: shepherd ctl [list | create <name> [<prompt>] | kill <name> | send-prompt <name> <prompt>]";
 
 fn main() -> anyhow::Result<()> {
-    let mut manager = TuiSessionManager::new()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("report") {
+        return report(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("trash") {
+        return trash(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("ctl") {
+        return ctl(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("open") {
+        return open(&args[1..]);
+    }
+    let script = match args.as_slice() {
+        [flag, path] if flag == "--script" => Some(Script::load(Path::new(path))?),
+        [flag, ..] if flag == "--script" => anyhow::bail!(SCRIPT_USAGE),
+        _ => None,
+    };
+
+    // Inside one of our own sessions the two instances would fight over raw mode and input
+    if let Ok(pid) = std::env::var(NESTED_ENV) {
+        eprintln!(
+            "shepherd: already running inside a shepherd session (shepherd pid {}).\n\
+             Switch sessions with ctrl+l in the outer shepherd, or unset {} to run nested anyway.",
+            pid, NESTED_ENV
+        );
+        std::process::exit(1);
+    }
+
+    if script.is_none() && forward_to_running_instance()? {
+        return Ok(());
+    }
 
-    // Try to resume a previous session, otherwise open new session dialog
-    if !manager.try_resume()? {
-        manager.open_new_session();
+    // Kept until exit so buffered traces are flushed
+    let _trace = shepherd::trace::init()?;
+    install_panic_hook();
+    // The hook has already restored the terminal and reported the panic; dropping the
+    // manager while unwinding shuts the sessions down
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut manager = match script {
+            Some(script) => TuiSessionManager::scripted(script)?,
+            None => {
+                let mut manager = TuiSessionManager::new()?;
+                // Try to resume a previous session, otherwise let the user pick or create one
+                if !manager.try_resume()? {
+                    manager.open_startup_picker();
+                }
+                manager
+            }
+        };
+
+        manager.run()
+    }));
+    // The sessions hung up as the manager dropped get their grace period together
+    shepherd::session::wait_for_hangups();
+    result.unwrap_or_else(|_| std::process::exit(101))
+}
+
+/// When another shepherd already manages this repo, offer to have it create a session
+/// instead of starting a second UI. Returns true if the request was forwarded.
+fn forward_to_running_instance() -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    let Ok(registry) = Registry::open() else {
+        return Ok(false);
+    };
+    let place = instance_root(&std::env::current_dir()?);
+    let Some((pid, socket)) = registry
+        .running_in(&place)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|i| Some((i.pid, i.socket?)))
+    else {
+        return Ok(false);
+    };
+
+    eprintln!(
+        "shepherd (pid {}) is already running in {}.",
+        pid,
+        place.display()
+    );
+    eprint!("Session to create there (empty starts another shepherd): ");
+    std::io::stderr().flush()?;
+    let mut input = String::new();
+    std::io::stdin().lock().read_line(&mut input)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(false);
     }
 
-    manager.run()?;
+    let name = resolve_branch_name(input)
+        .map_err(|reason| anyhow::anyhow!("invalid session name: {}", reason))?;
+    let command = Command::Create {
+        session: name.clone(),
+        prompt: None,
+    };
+    send_command(&socket, &command)
+        .map_err(|e| anyhow::anyhow!("shepherd (pid {}): {}", pid, e))?;
+    println!("Asked shepherd (pid {}) to create '{}'", pid, name);
+    Ok(true)
+}
 
+/// `shepherd ctl`: drive the shepherd running in this repo (or the one whose session this
+/// is) from scripts and editors, printing its JSON reply
+fn ctl(args: &[String]) -> anyhow::Result<()> {
+    let command = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["list"] => Command::List,
+        ["create", name, prompt @ ..] => Command::Create {
+            session: name.to_string(),
+            prompt: (!prompt.is_empty()).then(|| prompt.join(" ")),
+        },
+        ["kill", name] => Command::Kill {
+            session: name.to_string(),
+        },
+        ["send-prompt", name, prompt @ ..] if !prompt.is_empty() => Command::SendPrompt {
+            session: name.to_string(),
+            prompt: prompt.join(" "),
+        },
+        _ => anyhow::bail!(CTL_USAGE),
+    };
+    let reply = send_command(&ctl_socket()?, &command)?;
+    println!("{}", reply);
+    Ok(())
+}
+
+/// `shepherd open`: have the running shepherd open a file in its configured editor
+fn open(args: &[String]) -> anyhow::Result<()> {
+    let [target] = args else {
+        anyhow::bail!(OPEN_USAGE);
+    };
+    let (path, line) = match target.split_once(':') {
+        Some((path, rest)) => (path, rest.split(':').next().and_then(|l| l.parse().ok())),
+        None => (target.as_str(), None),
+    };
+    // Paths that exist from here are sent whole; others are taken from the active
+    // session's worktree
+    let local = std::env::current_dir()?.join(path);
+    let path = if local.is_file() {
+        local.to_string_lossy().to_string()
+    } else {
+        path.to_string()
+    };
+    send_command(&ctl_socket()?, &Command::Open { path, line })?;
+    Ok(())
+}
+
+/// Socket of the shepherd to control: the one this session runs in, else the oldest one
+/// running in the current repo
+fn ctl_socket() -> anyhow::Result<PathBuf> {
+    if let Ok(socket) = std::env::var("SHEPHERD_SOCKET") {
+        return Ok(PathBuf::from(socket));
+    }
+    let place = instance_root(&std::env::current_dir()?);
+    Registry::open()?
+        .running_in(&place)?
+        .into_iter()
+        .find_map(|i| i.socket)
+        .ok_or_else(|| anyhow::anyhow!("no shepherd is running in {}", place.display()))
+}
+
+/// `shepherd report`: print an activity summary for the last day (or `--since` window)
+fn report(args: &[String]) -> anyhow::Result<()> {
+    let mut window = TimeDelta::days(1);
+    let mut format = ReportFormat::Markdown;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--since" => {
+                window = args
+                    .next()
+                    .and_then(|v| parse_since(v))
+                    .ok_or_else(|| anyhow::anyhow!(REPORT_USAGE))?;
+            }
+            "--json" => format = ReportFormat::Json,
+            _ => anyhow::bail!(REPORT_USAGE),
+        }
+    }
+
+    let log_path =
+        event_log_path().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+    let history = SessionHistory::load()?;
+    let report = Report::build(Utc::now() - window, &log_path, &history);
+    println!("{}", report.render(format)?);
+    Ok(())
+}
+
+/// `shepherd trash`: list, purge or restore worktrees deleted from shepherd
+fn trash(args: &[String]) -> anyhow::Result<()> {
+    let trash = Trash::open()?;
+    let entries = trash.list();
+
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] | ["list"] => {
+            if entries.is_empty() {
+                println!("Trash is empty");
+            }
+            for entry in &entries {
+                println!(
+                    "{}  {}  (from {})",
+                    entry
+                        .trashed_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    entry.name(),
+                    entry.original_path.display()
+                );
+            }
+        }
+        ["empty"] => {
+            for entry in &entries {
+                trash.purge(entry)?;
+            }
+            println!("Purged {} worktree(s)", entries.len());
+        }
+        ["restore", rest @ ..] if rest.len() <= 1 => {
+            // Without a name, restore the most recently trashed worktree
+            let entry = match rest.first() {
+                Some(name) => entries.iter().find(|e| e.name() == *name || e.id == *name),
+                None => entries.first(),
+            }
+            .ok_or_else(|| anyhow::anyhow!("no matching worktree in the trash"))?;
+            trash.restore(entry)?;
+            println!("Restored {}", entry.original_path.display());
+        }
+        _ => anyhow::bail!(TRASH_USAGE),
+    }
     Ok(())
 }
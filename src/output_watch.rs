@@ -0,0 +1,181 @@
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::config::WatchRule;
+
+/// Completed lines waiting to be taken; older ones are dropped past this
+const MAX_PENDING_LINES: usize = 500;
+/// Longer lines are cut off here
+const MAX_LINE_LEN: usize = 1024;
+
+#[derive(Debug, Default, PartialEq)]
+enum Escape {
+    #[default]
+    None,
+    Esc,
+    Csi,
+    /// OSC and other string sequences, ended by BEL or ESC \
+    String,
+    StringEsc,
+}
+
+/// Splits raw PTY output into plain text lines: escape sequences are dropped and a line
+/// ends at CR or LF. Cursor-forward moves count as a space, since TUIs use them for gaps.
+#[derive(Debug, Default)]
+pub struct OutputLines {
+    line: String,
+    escape: Escape,
+    lines: VecDeque<String>,
+}
+
+impl OutputLines {
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for c in String::from_utf8_lossy(bytes).chars() {
+            match self.escape {
+                Escape::None => match c {
+                    '\r' | '\n' => self.end_line(),
+                    '\x1b' => self.escape = Escape::Esc,
+                    c if (!c.is_control() || c == '\t') && self.line.len() < MAX_LINE_LEN => {
+                        self.line.push(c)
+                    }
+                    _ => {}
+                },
+                Escape::Esc => {
+                    self.escape = match c {
+                        '[' => Escape::Csi,
+                        ']' | 'P' | '_' | '^' => Escape::String,
+                        _ => Escape::None,
+                    }
+                }
+                Escape::Csi => {
+                    if ('@'..='~').contains(&c) {
+                        if c == 'C' {
+                            self.line.push(' ');
+                        }
+                        self.escape = Escape::None;
+                    }
+                }
+                Escape::String => match c {
+                    '\x07' => self.escape = Escape::None,
+                    '\x1b' => self.escape = Escape::StringEsc,
+                    _ => {}
+                },
+                Escape::StringEsc => {
+                    self.escape = if c == '\\' {
+                        Escape::None
+                    } else {
+                        Escape::String
+                    }
+                }
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.lines.len() == MAX_PENDING_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.to_string());
+    }
+
+    /// Completed lines since the last call, oldest first
+    pub fn take(&mut self) -> Vec<String> {
+        self.lines.drain(..).collect()
+    }
+}
+
+/// Compiled watch rules, tracking when each last fired per session
+pub struct WatchRules {
+    rules: Vec<(Regex, WatchRule)>,
+    last_fired: HashMap<(usize, String), Instant>,
+}
+
+impl WatchRules {
+    /// Compile `rules`, returning the error for each pattern that isn't a valid regex
+    pub fn compile(rules: &[WatchRule]) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let rules = rules
+            .iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some((regex, rule.clone())),
+                Err(e) => {
+                    errors.push(format!("watch rule '{}': {}", rule.label(), e));
+                    None
+                }
+            })
+            .collect();
+        let watch = Self {
+            rules,
+            last_fired: HashMap::new(),
+        };
+        (watch, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules matching a line of `session`'s output that are not cooling down from an
+    /// earlier match in that session
+    pub fn check(&mut self, session: &str, line: &str) -> Vec<&WatchRule> {
+        let now = Instant::now();
+        let mut fired = Vec::new();
+        for (i, (regex, rule)) in self.rules.iter().enumerate() {
+            if !regex.is_match(line) {
+                continue;
+            }
+            let key = (i, session.to_string());
+            let cooldown = Duration::from_secs(rule.cooldown_secs);
+            if self
+                .last_fired
+                .get(&key)
+                .is_some_and(|at| now.duration_since(*at) < cooldown)
+            {
+                continue;
+            }
+            self.last_fired.insert(key, now);
+            fired.push(rule);
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_lines() {
+        let mut lines = OutputLines::default();
+        lines.feed(b"\x1b[1;31mBUILD\x1b[1CFAILED\x1b[0m\r\n\x1b]0;title\x07  \r\nhalf");
+        assert_eq!(lines.take(), vec!["BUILD FAILED"]);
+        lines.feed(b" done\n");
+        assert_eq!(lines.take(), vec!["half done"]);
+        assert!(lines.take().is_empty());
+    }
+
+    #[test]
+    fn test_watch_rules_cooldown() {
+        let rule = WatchRule {
+            pattern: "tests? passed".to_string(),
+            ..WatchRule::default()
+        };
+        let bad = WatchRule {
+            pattern: "(".to_string(),
+            ..WatchRule::default()
+        };
+        let (mut watch, errors) = WatchRules::compile(&[rule, bad]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(watch.check("a", "All tests passed").len(), 1);
+        // Redraws of the same output don't fire again, but other sessions do
+        assert!(watch.check("a", "All tests passed").is_empty());
+        assert_eq!(watch.check("b", "1 test passed").len(), 1);
+        assert!(watch.check("b", "BUILD FAILED").is_empty());
+    }
+}
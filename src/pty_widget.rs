@@ -6,11 +6,13 @@ use ratatui::{
 };
 use vt100::Screen;
 
-/// A widget that renders a vt100 terminal screen
+/// A widget that renders a vt100 terminal screen.
+/// Scrollback is applied by the caller (see `Session::with_screen`) so rendering never clones.
 pub struct PtyWidget<'a> {
     screen: &'a Screen,
     dimmed: bool,
-    scroll_offset: usize,
+    /// Show the bottom of the screen when the area is shorter than it
+    tail: bool,
 }
 
 impl<'a> PtyWidget<'a> {
@@ -18,7 +20,7 @@ impl<'a> PtyWidget<'a> {
         Self {
             screen,
             dimmed: false,
-            scroll_offset: 0,
+            tail: false,
         }
     }
 
@@ -27,8 +29,8 @@ impl<'a> PtyWidget<'a> {
         self
     }
 
-    pub fn scroll_offset(mut self, offset: usize) -> Self {
-        self.scroll_offset = offset;
+    pub fn tail(mut self, tail: bool) -> Self {
+        self.tail = tail;
         self
     }
 }
@@ -38,19 +40,13 @@ impl Widget for PtyWidget<'_> {
         let (screen_rows, screen_cols) = self.screen.size();
         let display_rows = area.height.min(screen_rows);
         let cols = area.width.min(screen_cols);
-
-        if self.scroll_offset == 0 {
-            // No scrollback - render current screen directly
-            self.render_screen(self.screen, area, buf, display_rows, cols);
+        let first_row = if self.tail {
+            screen_rows - display_rows
         } else {
-            // Scrollback mode - clone screen and set scrollback position
-            // vt100's set_scrollback(n) shows the view starting n rows from the top of scrollback
-            // We want scroll_offset to mean "how many lines up from bottom"
-            // So we need to convert: scrollback_position = scroll_offset
-            let mut scrolled_screen = self.screen.clone();
-            scrolled_screen.set_scrollback(self.scroll_offset);
-            self.render_screen(&scrolled_screen, area, buf, display_rows, cols);
-        }
+            0
+        };
+
+        self.render_screen(self.screen, area, buf, first_row, display_rows, cols);
     }
 }
 
@@ -60,12 +56,13 @@ impl PtyWidget<'_> {
         screen: &Screen,
         area: Rect,
         buf: &mut Buffer,
+        first_row: u16,
         display_rows: u16,
         cols: u16,
     ) {
         for row in 0..display_rows {
             for col in 0..cols {
-                if let Some(cell) = screen.cell(row, col) {
+                if let Some(cell) = screen.cell(first_row + row, col) {
                     let mut style = vt100_to_ratatui_style(cell);
                     if self.dimmed {
                         style = style.add_modifier(Modifier::DIM);
@@ -74,12 +71,26 @@ impl PtyWidget<'_> {
                     let y = area.y + row;
 
                     if x < buf.area.width && y < buf.area.height {
-                        let contents = cell.contents();
-                        if contents.is_empty() {
-                            buf[(x, y)].set_char(' ').set_style(style);
+                        let target = &mut buf[(x, y)];
+                        // The right half of a double-width character: the character
+                        // already covers it, so leave it blank like ratatui's own text
+                        // rendering does, otherwise the diff would draw over the glyph
+                        if cell.is_wide_continuation() {
+                            target.reset();
+                            continue;
+                        }
+                        // vt100 gives each emoji of a ZWJ sequence its own cell. Drop the
+                        // joiner, or the host terminal fuses neighbouring cells into one
+                        // glyph and shifts the rest of the row left.
+                        let contents = cell.contents().trim_end_matches('\u{200D}');
+                        // A wide character cut off by the right edge would spill past
+                        // the area, so show a blank instead
+                        if contents.is_empty() || (cell.is_wide() && col + 1 >= cols) {
+                            target.set_char(' ');
                         } else {
-                            buf.set_string(x, y, contents, style);
+                            target.set_symbol(contents);
                         }
+                        target.set_style(style);
                     }
                 }
             }
@@ -116,3 +127,45 @@ fn vt100_color_to_ratatui(color: vt100::Color) -> Color {
         vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render `output` on a `cols` wide screen into a buffer of `width` columns
+    fn render(output: &str, cols: u16, width: u16) -> Buffer {
+        let mut parser = vt100::Parser::new(2, cols, 0);
+        parser.process(output.as_bytes());
+        let area = Rect::new(0, 0, width, 2);
+        let mut buf = Buffer::empty(area);
+        PtyWidget::new(parser.screen()).render(area, &mut buf);
+        buf
+    }
+
+    fn symbols(buf: &Buffer, y: u16) -> Vec<&str> {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        // CJK: each character takes two columns and the text after it stays aligned
+        let buf = render("日本ab", 10, 10);
+        assert_eq!(symbols(&buf, 0)[..6], ["日", " ", "本", " ", "a", "b"]);
+        assert_eq!(buf[(1, 0)], ratatui::buffer::Cell::default());
+
+        // Emoji ZWJ sequence: one cell pair per emoji, without the joiners
+        let buf = render("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}x", 10, 10);
+        assert_eq!(
+            symbols(&buf, 0)[..7],
+            ["\u{1F468}", " ", "\u{1F469}", " ", "\u{1F467}", " ", "x"]
+        );
+
+        // Combining marks stay with their base character in a single column
+        let buf = render("e\u{301}x", 10, 10);
+        assert_eq!(symbols(&buf, 0)[..2], ["e\u{301}", "x"]);
+
+        // A wide character split by the right edge of the area is blanked
+        let buf = render("abc日", 10, 4);
+        assert_eq!(symbols(&buf, 0), ["a", "b", "c", " "]);
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use crate::config::RawOutputLog;
+use crate::rotating_file::RotatingFile;
+use crate::workflows::RepoLayout;
+
+/// `~/.shepherd/logs/<reponame>-<hash>/<session>.raw`, where a session's raw output is
+/// mirrored. Keyed by repository like worktrees are, so same-named sessions of different
+/// repos never share a file, and the instance registry keeps two shepherds in one repo
+/// off the same session. Sessions outside a repository log to `~/.shepherd/logs`.
+pub fn raw_log_path(repo: Option<&RepoLayout>, session: &str) -> Option<PathBuf> {
+    let name = session.replace(['/', '\\'], "-");
+    let mut dir = crate::trace::log_dir()?;
+    if let Some(repo) = repo {
+        dir.push(repo.dir_name());
+    }
+    Some(dir.join(format!("{}.raw", name)))
+}
+
+/// Every byte a session's child wrote to its terminal, escape sequences and all, appended
+/// to a file. Past `max_size_mb` the file is rotated to `<session>.raw.1`, and so on.
+pub struct RawLog {
+    file: RotatingFile,
+}
+
+impl RawLog {
+    pub fn open(path: PathBuf, config: &RawOutputLog) -> std::io::Result<Self> {
+        let max_bytes = config.max_size_mb * 1024 * 1024;
+        Ok(Self {
+            file: RotatingFile::open(path, max_bytes, config.keep_rotated)?,
+        })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.file.write(bytes)
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::{Read, Write};
+use std::os::fd::{AsFd, BorrowedFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError, unbounded};
+use nix::poll::{PollFd, PollFlags, poll};
+
+/// Longest the pool sleeps before giving every reader a tick, e.g. to notice shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Largest single read from a PTY
+const BUF_SIZE: usize = 8 * 1024;
+
+/// A PTY read on the pool thread. Nothing here may block: every session shares the thread.
+pub trait PtyReader: Send {
+    /// Descriptor polled for output. Must stay open while the reader is registered.
+    fn fd(&self) -> RawFd;
+    /// While output is being held back, when the reader wants to read again
+    fn held_until(&self) -> Option<Instant>;
+    /// Read output the fd has ready. Returns false once the reader is done.
+    fn read_ready(&mut self, buf: &mut [u8]) -> bool;
+    /// Called on every pass of the loop, whether or not there was output, and whenever
+    /// [`wake`] is called. Returns false once the reader is done.
+    fn tick(&mut self) -> bool;
+}
+
+/// The thread reading every session's PTY, multiplexing their fds with poll(2) so the
+/// thread count stays flat however many sessions are open
+struct Pool {
+    readers: Sender<Box<dyn PtyReader>>,
+    /// Written to interrupt the thread's poll
+    waker: UnixStream,
+}
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+impl Pool {
+    fn start() -> std::io::Result<Self> {
+        let (readers, rx) = unbounded();
+        let (waker, wake_rx) = UnixStream::pair()?;
+        waker.set_nonblocking(true)?;
+        wake_rx.set_nonblocking(true)?;
+        std::thread::Builder::new()
+            .name("pty-readers".to_string())
+            .spawn(move || run(rx, wake_rx))?;
+        Ok(Self { readers, waker })
+    }
+}
+
+/// Start reading `reader` on the pool thread, starting the thread on first use
+pub fn register(reader: Box<dyn PtyReader>) -> std::io::Result<()> {
+    let pool = match POOL.get() {
+        Some(pool) => pool,
+        None => {
+            // If another thread wins the race, this pool's thread exits when it is dropped
+            let pool = Pool::start()?;
+            POOL.get_or_init(|| pool)
+        }
+    };
+    pool.readers
+        .send(reader)
+        .map_err(|_| std::io::Error::other("pty reader thread has stopped"))?;
+    wake();
+    Ok(())
+}
+
+/// Have the pool tick every reader now rather than at its next wakeup, e.g. after a
+/// resize or shutdown
+pub fn wake() {
+    if let Some(pool) = POOL.get() {
+        // A full socket already has a wakeup pending
+        let _ = (&pool.waker).write(&[1]);
+    }
+}
+
+fn run(rx: Receiver<Box<dyn PtyReader>>, mut wake_rx: UnixStream) {
+    let mut buf = [0u8; BUF_SIZE];
+    let mut readers: Vec<Box<dyn PtyReader>> = Vec::new();
+    loop {
+        loop {
+            match rx.try_recv() {
+                Ok(reader) => readers.push(reader),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) if readers.is_empty() => return,
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        readers.retain_mut(|reader| reader.tick());
+
+        // Readers holding output back sit out the poll until they want to read again
+        let now = Instant::now();
+        let mut timeout = POLL_INTERVAL;
+        let polled: Vec<usize> = (0..readers.len())
+            .filter(|&i| match readers[i].held_until() {
+                Some(until) if until > now => {
+                    timeout = timeout.min(until - now);
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+
+        let mut fds = vec![PollFd::new(wake_rx.as_fd(), PollFlags::POLLIN)];
+        for &i in &polled {
+            // SAFETY: registered readers keep their fd open until they are dropped below
+            let fd = unsafe { BorrowedFd::borrow_raw(readers[i].fd()) };
+            fds.push(PollFd::new(fd, PollFlags::POLLIN));
+        }
+        let timeout_ms = (timeout.as_millis() as u16).max(1);
+        match poll(&mut fds, timeout_ms) {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(nix::errno::Errno::EINTR) => continue,
+            Err(e) => {
+                tracing::error!(error = %e, "pty poll failed");
+                continue;
+            }
+        }
+        let ready: Vec<bool> = fds
+            .iter()
+            .map(|fd| fd.revents().is_some_and(|r| !r.is_empty()))
+            .collect();
+        drop(fds);
+
+        if ready[0] {
+            let mut drain = [0u8; 64];
+            while matches!(wake_rx.read(&mut drain), Ok(n) if n > 0) {}
+        }
+        let mut done = vec![false; readers.len()];
+        for (&i, _) in polled.iter().zip(&ready[1..]).filter(|(_, ready)| **ready) {
+            done[i] = !readers[i].read_ready(&mut buf);
+        }
+        let mut done = done.into_iter();
+        readers.retain(|_| !done.next().unwrap_or(false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Forwards everything read from one end of a socket pair
+    struct Forward {
+        stream: UnixStream,
+        out: Sender<Vec<u8>>,
+    }
+
+    impl PtyReader for Forward {
+        fn fd(&self) -> RawFd {
+            std::os::fd::AsRawFd::as_raw_fd(&self.stream)
+        }
+
+        fn held_until(&self) -> Option<Instant> {
+            None
+        }
+
+        fn read_ready(&mut self, buf: &mut [u8]) -> bool {
+            match self.stream.read(buf) {
+                Ok(n) if n > 0 => self.out.send(buf[..n].to_vec()).is_ok(),
+                _ => false,
+            }
+        }
+
+        fn tick(&mut self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_readers_share_the_pool() {
+        let mut children = Vec::new();
+        let mut outputs = Vec::new();
+        for _ in 0..3 {
+            let (stream, child) = UnixStream::pair().unwrap();
+            let (out, output) = unbounded();
+            register(Box::new(Forward { stream, out })).unwrap();
+            children.push(child);
+            outputs.push(output);
+        }
+        for (i, child) in children.iter_mut().enumerate() {
+            child.write_all(format!("from {}", i).as_bytes()).unwrap();
+        }
+        for (i, output) in outputs.iter().enumerate() {
+            let read = output.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(read, format!("from {}", i).as_bytes());
+        }
+
+        // A reader that hits EOF is dropped, closing its channel
+        drop(children.remove(0));
+        let closed = outputs[0].recv_timeout(Duration::from_secs(5));
+        assert!(matches!(
+            closed,
+            Err(crossbeam_channel::RecvTimeoutError::Disconnected)
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use crate::history::{SessionHistory, format_duration};
+use crate::session_manager::read_event_log;
+
+/// Output format for `shepherd report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Json,
+}
+
+/// Activity summary over a time window, built from the event log and history
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub since: DateTime<Utc>,
+    pub sessions_created: Vec<String>,
+    pub sessions_killed: Vec<String>,
+    pub worktrees_deleted: usize,
+    pub sessions: Vec<SessionTime>,
+    pub errors: Vec<String>,
+}
+
+/// Time spent in one session across all of its runs in the window
+#[derive(Debug, Serialize)]
+pub struct SessionTime {
+    pub repo: String,
+    pub session: String,
+    pub runs: usize,
+    pub running_secs: u64,
+    pub focused_secs: u64,
+}
+
+impl Report {
+    /// Build a report covering `[since, now]` from the event log at `log_path` and `history`
+    pub fn build(since: DateTime<Utc>, log_path: &Path, history: &SessionHistory) -> Self {
+        let mut report = Report {
+            since,
+            ..Report::default()
+        };
+
+        let log = read_event_log(log_path);
+        for (timestamp, level, message) in log.lines().filter_map(parse_log_line) {
+            if timestamp < since {
+                continue;
+            }
+            if level == "ERR" {
+                report.errors.push(message.to_string());
+            }
+            if let Some(name) = quoted_name(message, "Created session '")
+                .or_else(|| quoted_name(message, "Started session '"))
+            {
+                report.sessions_created.push(name.to_string());
+            } else if let Some(name) = quoted_name(message, "Killed session '") {
+                report.sessions_killed.push(name.to_string());
+            } else if let Some(count) = deleted_worktree_count(message) {
+                report.worktrees_deleted += count;
+            }
+        }
+
+        let mut totals: BTreeMap<(&str, &str), SessionTime> = BTreeMap::new();
+        for entry in history
+            .time_entries()
+            .iter()
+            .filter(|e| e.ended_at >= since)
+        {
+            let total = totals
+                .entry((&entry.repo, &entry.session))
+                .or_insert_with(|| SessionTime {
+                    repo: entry.repo.clone(),
+                    session: entry.session.clone(),
+                    runs: 0,
+                    running_secs: 0,
+                    focused_secs: 0,
+                });
+            total.runs += 1;
+            total.running_secs += entry.running_secs;
+            total.focused_secs += entry.focused_secs;
+        }
+        report.sessions = totals.into_values().collect();
+        report
+            .sessions
+            .sort_by_key(|s| std::cmp::Reverse(s.focused_secs));
+
+        report
+    }
+
+    pub fn render(&self, format: ReportFormat) -> anyhow::Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let since = self.since.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+        let _ = writeln!(out, "# Shepherd activity since {}\n", since);
+
+        let _ = writeln!(out, "- Sessions created: {}", self.sessions_created.len());
+        let _ = writeln!(out, "- Sessions killed: {}", self.sessions_killed.len());
+        let _ = writeln!(out, "- Worktrees deleted: {}", self.worktrees_deleted);
+        let _ = writeln!(out, "- Errors: {}", self.errors.len());
+
+        if !self.sessions.is_empty() {
+            let _ = writeln!(out, "\n## Time per session\n");
+            let _ = writeln!(out, "| Repo | Session | Runs | Running | Focused |");
+            let _ = writeln!(out, "|---|---|---|---|---|");
+            for s in &self.sessions {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    s.repo,
+                    s.session,
+                    s.runs,
+                    format_duration(std::time::Duration::from_secs(s.running_secs)),
+                    format_duration(std::time::Duration::from_secs(s.focused_secs)),
+                );
+            }
+        }
+
+        if !self.errors.is_empty() {
+            let _ = writeln!(out, "\n## Errors\n");
+            for error in &self.errors {
+                let _ = writeln!(out, "- {}", error);
+            }
+        }
+
+        out
+    }
+}
+
+/// Parse a window like "7d", "12h", "30m" or "2w"
+pub fn parse_since(value: &str) -> Option<TimeDelta> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        'm' => TimeDelta::try_minutes(amount),
+        'h' => TimeDelta::try_hours(amount),
+        'd' => TimeDelta::try_days(amount),
+        'w' => TimeDelta::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// Split `[YYYY-mm-dd HH:MM:SS] [LEVEL] message` (local time) into its parts
+fn parse_log_line(line: &str) -> Option<(DateTime<Utc>, &str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (timestamp, rest) = rest.split_once("] [")?;
+    let (level, message) = rest.split_once("] ")?;
+    let naive = NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+    let local = naive.and_local_timezone(Local).earliest()?;
+    Some((local.with_timezone(&Utc), level, message))
+}
+
+/// Extract `name` from messages like `Killed session 'name'...`
+fn quoted_name<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = message.strip_prefix(prefix)?;
+    rest.split_once('\'').map(|(name, _)| name)
+}
+
+/// Worktree count from "Successfully deleted N worktree(s)" / "Deleted N of M worktree(s)"
+fn deleted_worktree_count(message: &str) -> Option<usize> {
+    let rest = message
+        .strip_prefix("Successfully deleted ")
+        .or_else(|| message.strip_prefix("Deleted "))?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("7d"), TimeDelta::try_days(7));
+        assert_eq!(parse_since("12h"), TimeDelta::try_hours(12));
+        assert_eq!(parse_since("2w"), TimeDelta::try_weeks(2));
+        assert_eq!(parse_since("d"), None);
+        assert_eq!(parse_since("7y"), None);
+    }
+
+    #[test]
+    fn test_build_from_event_log() {
+        let dir = TempDir::new("report");
+        let log_path = dir.join("events.log");
+        let now = Local::now().format("%Y-%m-%d %H:%M:%S");
+        std::fs::write(
+            &log_path,
+            format!(
+                "[2000-01-01 00:00:00] [INFO] Created session 'ancient' in /tmp\n\
+                 [{now}] [INFO] Created session 'feat' in /tmp/feat\n\
+                 [{now}] [INFO] Killed session 'feat'\n\
+                 [{now}] [INFO] Successfully deleted 2 worktree(s)\n\
+                 [{now}] [ERR] Deleted 1 of 3 worktree(s): /tmp/x: busy\n"
+            ),
+        )
+        .unwrap();
+
+        let since = Utc::now() - TimeDelta::try_days(1).unwrap();
+        let report = Report::build(since, &log_path, &SessionHistory::default());
+
+        assert_eq!(report.sessions_created, vec!["feat"]);
+        assert_eq!(report.sessions_killed, vec!["feat"]);
+        assert_eq!(report.worktrees_deleted, 3);
+        assert_eq!(report.errors.len(), 1);
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+/// Path of the `n`th rotation of `path` (1 is the most recent), e.g. `events.log.1.gz`
+pub fn rotated_path(path: &Path, n: usize, compressed: bool) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    if compressed {
+        name.push(".gz");
+    }
+    PathBuf::from(name)
+}
+
+/// A file appended to until it passes `max_bytes`, then rotated: `<path>.N` shift up by
+/// one, the live file becomes `<path>.1` (gzipped to `<path>.1.gz` with `compressed`) and
+/// a fresh one is started. Rotations past `keep_rotated` are deleted.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep_rotated: usize,
+    compressed: bool,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, keep_rotated: usize) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            keep_rotated,
+            compressed: false,
+        })
+    }
+
+    /// Gzip rotated files
+    pub fn compressed(mut self) -> Self {
+        self.compressed = true;
+        self
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.size > 0 && self.size + bytes.len() as u64 > self.max_bytes {
+            // On failure keep appending to the live file rather than lose output
+            if let Err(error) = self.rotate() {
+                tracing::warn!(path = %self.path.display(), %error, "failed to rotate");
+            }
+        }
+        self.file.write_all(bytes)?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let keep = self.keep_rotated;
+        if keep > 0 {
+            let rotated = |n| rotated_path(&self.path, n, self.compressed);
+            let _ = std::fs::remove_file(rotated(keep));
+            for n in (1..keep).rev() {
+                let _ = std::fs::rename(rotated(n), rotated(n + 1));
+            }
+            if self.compressed {
+                compress(&self.path, &rotated(1))?;
+                std::fs::remove_file(&self.path)?;
+            } else {
+                std::fs::rename(&self.path, rotated(1))?;
+            }
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn compress(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut input = File::open(from)?;
+    let mut encoder = GzEncoder::new(File::create(to)?, Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_rotation() {
+        let dir = TempDir::new("rotate");
+        let path = dir.join("fix-ci.raw");
+        let mut file = RotatingFile::open(path.clone(), 1024 * 1024, 1).unwrap();
+        let chunk = vec![b'x'; 400 * 1024];
+        for _ in 0..6 {
+            file.write(&chunk).unwrap();
+        }
+
+        // Two chunks in each of the live file and the one rotation kept; the rest dropped
+        let len = |path: &Path| std::fs::metadata(path).map(|m| m.len()).ok();
+        assert_eq!(len(&path), Some(800 * 1024));
+        assert_eq!(len(&rotated_path(&path, 1, false)), Some(800 * 1024));
+        assert_eq!(len(&rotated_path(&path, 2, false)), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+/// Last known screen of each claude session (~/.shepherd/screens), keyed by the session's
+/// working directory. Shown while a resumed session starts, instead of a blank screen.
+#[derive(Clone)]
+pub struct Screens {
+    dir: PathBuf,
+}
+
+impl Screens {
+    pub fn open() -> anyhow::Result<Self> {
+        let home =
+            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+        Ok(Self::at(home.join(".shepherd").join("screens")))
+    }
+
+    fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Snapshot file for the session in `cwd`; every character of the path that isn't
+    /// alphanumeric becomes '-'
+    fn path(&self, cwd: &Path) -> PathBuf {
+        let encoded: String = cwd
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        self.dir.join(format!("{}.ansi", encoded))
+    }
+
+    /// Store `screen`, escape sequences that redraw it, for the session in `cwd`
+    pub fn save(&self, cwd: &Path, screen: &[u8]) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(cwd);
+        // Written aside and renamed so a crash never leaves half a screen
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, screen)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, cwd: &Path) -> Option<Vec<u8>> {
+        std::fs::read(self.path(cwd)).ok()
+    }
+
+    pub fn remove(&self, cwd: &Path) {
+        let _ = std::fs::remove_file(self.path(cwd));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_save_and_load_screen() {
+        let root = TempDir::new("screens");
+        let screens = Screens::at(root.to_path_buf());
+        let cwd = Path::new("/home/me/repo/.worktrees/feature");
+
+        assert!(screens.load(cwd).is_none());
+        screens.save(cwd, b"\x1b[Hhello").unwrap();
+        screens.save(cwd, b"\x1b[Hbye").unwrap();
+        assert_eq!(screens.load(cwd).as_deref(), Some(&b"\x1b[Hbye"[..]));
+        assert!(screens.load(Path::new("/home/me/repo")).is_none());
+
+        screens.remove(cwd);
+        assert!(screens.load(cwd).is_none());
+    }
+}
@@ -1,28 +1,50 @@
 use arc_swap::ArcSwap;
-use crossbeam_channel::{Receiver, Sender, bounded};
-use portable_pty::{Child, CommandBuilder, PtySize, native_pty_system};
+use crossbeam_channel::{Receiver, Sender, TryRecvError, bounded};
+use portable_pty::{Child, CommandBuilder, ExitStatus, MasterPty, PtySize, native_pty_system};
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::os::fd::RawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use vt100::{Callbacks, Parser, Screen};
 
+use crate::config::SessionLimits;
+use crate::error::ShepardError;
+use crate::host_terminal::HostTerminal;
+use crate::output_watch::OutputLines;
+use crate::raw_log::RawLog;
+use crate::reader_pool::{self, PtyReader};
+
 /// Type alias for parser with terminal callbacks
 type CallbackParser = Parser<TerminalCallbacks>;
 
 /// Shared writer for sending responses back to the PTY
 type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
 
+/// State the child sets that vt100 does not track, kept outside the parser so it
+/// survives the parser being rebuilt
+#[derive(Debug, Default)]
+pub struct ChildModes {
+    /// Focus reporting (`CSI ? 1004 h`): the child wants `CSI I` / `CSI O` on focus changes
+    focus_reporting: AtomicBool,
+    /// Cursor shape requested with DECSCUSR (`CSI Ps SP q`), 0 = terminal default
+    cursor_style: AtomicU8,
+    /// Latest OSC 52 clipboard write (`<selection>;<base64>`) not yet passed to the host
+    clipboard: Mutex<Option<String>>,
+}
+
 /// Terminal callbacks that respond to escape sequence queries
 pub struct TerminalCallbacks {
     writer: SharedWriter,
+    modes: Arc<ChildModes>,
 }
 
 impl TerminalCallbacks {
-    pub fn new(writer: SharedWriter) -> Self {
-        Self { writer }
+    pub fn new(writer: SharedWriter, modes: Arc<ChildModes>) -> Self {
+        Self { writer, modes }
     }
 
     fn write_response(&mut self, response: &[u8]) {
@@ -33,13 +55,12 @@ impl TerminalCallbacks {
     }
 }
 
-// TODO: this is incomplete + likely wrong
 impl Callbacks for TerminalCallbacks {
     fn unhandled_csi(
         &mut self,
         screen: &mut Screen,
         i1: Option<u8>,
-        _i2: Option<u8>,
+        i2: Option<u8>,
         params: &[&[u16]],
         c: char,
     ) {
@@ -73,13 +94,537 @@ impl Callbacks for TerminalCallbacks {
                 let response = format!("\x1b[?{};{}R", row + 1, col + 1);
                 self.write_response(response.as_bytes());
             }
+            // CSI > q - XTVERSION
+            // Response: DCS > | name(version) ST
+            (Some(b'>'), 'q') if params.is_empty() || params == [[0]] => {
+                let response = format!(
+                    "\x1bP>|{}({})\x1b\\",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                );
+                self.write_response(response.as_bytes());
+            }
+            // CSI ? Ps $ p - DECRQM (DEC private mode state)
+            // Response: CSI ? Ps ; Pm $ y
+            (Some(b'?'), 'p') if i2 == Some(b'$') => {
+                let Some(&&[mode]) = params.first() else {
+                    return;
+                };
+                let state = match mode {
+                    1004 if self.modes.focus_reporting.load(Ordering::Relaxed) => 1,
+                    1004 => 2,
+                    _ => private_mode_state(screen, mode),
+                };
+                let response = format!("\x1b[?{};{}$y", mode, state);
+                self.write_response(response.as_bytes());
+            }
+            // CSI Ps $ p - DECRQM (ANSI mode state) - none are tracked
+            (Some(b'$'), 'p') => {
+                let Some(&&[mode]) = params.first() else {
+                    return;
+                };
+                let response = format!("\x1b[{};0$y", mode);
+                self.write_response(response.as_bytes());
+            }
+            // CSI ? 1004 h / l - focus reporting on / off
+            (Some(b'?'), 'h' | 'l') if params.contains(&&[1004][..]) => {
+                self.modes
+                    .focus_reporting
+                    .store(c == 'h', Ordering::Relaxed);
+            }
+            // CSI Ps SP q - DECSCUSR (cursor style)
+            (Some(b' '), 'q') => {
+                let style = params.first().and_then(|p| p.first()).copied().unwrap_or(0);
+                if let Ok(style @ 0..=6) = u8::try_from(style) {
+                    self.modes.cursor_style.store(style, Ordering::Relaxed);
+                }
+            }
             _ => {}
         }
     }
+
+    // OSC 52 ; selection ; base64 - set the clipboard. Kept for the UI thread to re-emit
+    // to the host terminal; clipboard reads are not answered.
+    fn copy_to_clipboard(&mut self, _screen: &mut Screen, ty: &[u8], data: &[u8]) {
+        if let Ok(mut clipboard) = self.modes.clipboard.lock() {
+            *clipboard = Some(format!(
+                "{};{}",
+                String::from_utf8_lossy(ty),
+                String::from_utf8_lossy(data)
+            ));
+        }
+    }
+
+    fn unhandled_osc(&mut self, _screen: &mut Screen, params: &[&[u8]]) {
+        // OSC 10/11 ; ? - query default foreground/background color.
+        // Answer with the host terminal's colors so apps pick a matching theme.
+        let host = HostTerminal::get();
+        let response = match params {
+            [b"10", b"?"] => format!("\x1b]10;{}\x1b\\", host.foreground()),
+            [b"11", b"?"] => format!("\x1b]11;{}\x1b\\", host.background()),
+            _ => return,
+        };
+        self.write_response(response.as_bytes());
+    }
+}
+
+/// DECRQM state for a DEC private mode: 1 = set, 2 = reset, 0 = not recognized
+fn private_mode_state(screen: &Screen, mode: u16) -> u8 {
+    use vt100::{MouseProtocolEncoding, MouseProtocolMode};
+
+    let set = match mode {
+        1 => screen.application_cursor(),
+        25 => !screen.hide_cursor(),
+        66 => screen.application_keypad(),
+        47 | 1047 | 1049 => screen.alternate_screen(),
+        2004 => screen.bracketed_paste(),
+        9 => screen.mouse_protocol_mode() == MouseProtocolMode::Press,
+        1000 => screen.mouse_protocol_mode() == MouseProtocolMode::PressRelease,
+        1002 => screen.mouse_protocol_mode() == MouseProtocolMode::ButtonMotion,
+        1003 => screen.mouse_protocol_mode() == MouseProtocolMode::AnyMotion,
+        1005 => screen.mouse_protocol_encoding() == MouseProtocolEncoding::Utf8,
+        1006 => screen.mouse_protocol_encoding() == MouseProtocolEncoding::Sgr,
+        _ => return 0,
+    };
+    if set { 1 } else { 2 }
 }
 
 const SCROLLBACK: usize = 1000;
-const BUF_SIZE: usize = 8 * 1024;
+/// Numbers the files trimmed scrollback is stashed in, so sessions never share one
+static STASH_SEQ: AtomicUsize = AtomicUsize::new(0);
+/// How long the reader waits for the child to be reapable after the PTY closes
+const EXIT_WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a session shows as throttled after its reader last held back output
+const THROTTLE_SHOWN_FOR: Duration = Duration::from_secs(1);
+
+type SharedChild = Arc<Mutex<Box<dyn Child + Send + Sync>>>;
+type SharedMaster = Arc<Mutex<Box<dyn MasterPty + Send>>>;
+
+/// Callback invoked once the child process has exited and been reaped
+pub type ExitNotifier = Box<dyn FnOnce() + Send>;
+
+/// Starts the child behind a new session, taking the arguments of
+/// [`AttachedSession::new_with_env`]. Tests swap in fake children.
+pub type Spawner = Box<
+    dyn Fn(
+        &str,
+        &[&str],
+        Option<ExitNotifier>,
+        SharedSize,
+        Option<&Path>,
+        &[(&str, &str)],
+        &SessionLimits,
+    ) -> Result<AttachedSession, ShepardError>,
+>;
+
+/// Wait (briefly) for the child to exit, without holding the lock across sleeps
+/// so `shutdown()` can still kill it.
+fn wait_for_exit(child: &SharedChild) -> Option<ExitStatus> {
+    let deadline = Instant::now() + EXIT_WAIT_TIMEOUT;
+    loop {
+        if let Ok(mut child) = child.lock()
+            && let Ok(Some(status)) = child.try_wait()
+        {
+            return Some(status);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Bring the PTY and parser in line with the shared size.
+/// Returns Ok(true) if a resize was applied.
+fn sync_pty_size(
+    master: &SharedMaster,
+    parser: &Mutex<CallbackParser>,
+    size: &SharedSize,
+) -> Result<bool, String> {
+    let (rows, cols) = size.get();
+    let master = master
+        .lock()
+        .map_err(|_| "PTY error: lock poisoned".to_string())?;
+    let current = master
+        .get_size()
+        .map_err(|e| format!("PTY error: failed to get size: {}", e))?;
+
+    if current.rows == rows && current.cols == cols {
+        return Ok(false);
+    }
+
+    master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("PTY error: failed to resize: {}", e))?;
+    if let Ok(mut parser) = parser.lock() {
+        parser.screen_mut().set_size(rows, cols);
+    }
+    Ok(true)
+}
+
+/// Process groups in the session led by `leader`. Children are spawned as session
+/// leaders, and job-control shells put each job in its own group within that session,
+/// so this covers everything the child started that hasn't detached with `setsid`.
+fn session_process_groups(leader: u32) -> Vec<i32> {
+    let mut groups = vec![leader as i32];
+    #[cfg(target_os = "linux")]
+    {
+        let leader = leader.to_string();
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return groups;
+        };
+        for stat in entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| std::fs::read_to_string(e.path().join("stat")).ok())
+        {
+            // Format: pid (comm) state ppid pgrp session ... - comm may contain spaces
+            let Some((_, rest)) = stat.rsplit_once(')') else {
+                continue;
+            };
+            let fields: Vec<&str> = rest.split_whitespace().take(4).collect();
+            if let [_, _, pgrp, session] = fields[..]
+                && session == leader
+                && let Ok(pgrp) = pgrp.parse()
+                && !groups.contains(&pgrp)
+            {
+                groups.push(pgrp);
+            }
+        }
+    }
+    groups
+}
+
+/// How long a session's processes get to exit after the hangup before they are killed
+const HANGUP_GRACE: Duration = Duration::from_millis(500);
+
+/// Threads giving hung up sessions their grace period, so sessions shut down together
+/// don't wait one after another
+static HANGUPS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Wait for every hung up session to exit or be killed. Called on the way out, since
+/// leaving first would skip the kill for processes that ignore the hangup.
+pub fn wait_for_hangups() {
+    let hangups = std::mem::take(&mut *HANGUPS.lock().unwrap_or_else(|e| e.into_inner()));
+    for hangup in hangups {
+        let _ = hangup.join();
+    }
+}
+
+/// Whether any of the process groups still has a process in it
+fn process_groups_alive(groups: &[i32]) -> bool {
+    // SAFETY: killpg has no memory safety preconditions; signal 0 only checks existence
+    groups
+        .iter()
+        .any(|&group| unsafe { libc::killpg(group, 0) } == 0)
+}
+
+/// Send `signal` to each process group
+fn signal_process_groups(groups: &[i32], signal: i32) {
+    for &group in groups {
+        // SAFETY: killpg has no memory safety preconditions
+        unsafe {
+            libc::killpg(group, signal);
+        }
+    }
+}
+
+/// Lower the scheduling priority of a spawned child
+fn apply_niceness(pid: u32, nice: i32) {
+    // SAFETY: setpriority has no memory safety preconditions
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice);
+    }
+}
+
+/// Throttles PTY reads to a maximum number of bytes per second
+struct RateLimiter {
+    max_per_sec: usize,
+    window_start: Instant,
+    window_bytes: usize,
+}
+
+impl RateLimiter {
+    fn new(max_per_sec: usize) -> Self {
+        Self {
+            max_per_sec,
+            window_start: Instant::now(),
+            window_bytes: 0,
+        }
+    }
+
+    /// Record `n` bytes read. Returns when reading may resume if that used up the budget.
+    fn consume(&mut self, n: usize) -> Option<Instant> {
+        if self.window_start.elapsed() >= Duration::from_secs(1) {
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+        self.window_bytes += n;
+        (self.window_bytes >= self.max_per_sec).then(|| self.window_start + Duration::from_secs(1))
+    }
+}
+
+/// Most output a background session holds back before parsing it anyway
+const MAX_DEFERRED: usize = 1024 * 1024;
+
+/// Output a background session has read but not parsed yet. Background sessions parse at
+/// most once per `interval`, so a busy one costs less while it isn't being watched.
+struct DeferredOutput {
+    interval: Option<Duration>,
+    bytes: Vec<u8>,
+    /// When the oldest queued output was read
+    since: Option<Instant>,
+}
+
+impl DeferredOutput {
+    fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            bytes: Vec::new(),
+            since: None,
+        }
+    }
+
+    /// Queue `bytes` instead of parsing them now. Returns false when they should be
+    /// parsed right away: the session is active, or nothing is deferred.
+    fn defer(&mut self, bytes: &[u8], active: bool) -> bool {
+        if active || self.interval.is_none() {
+            return false;
+        }
+        self.bytes.extend_from_slice(bytes);
+        self.since.get_or_insert_with(Instant::now);
+        true
+    }
+
+    /// The queued output, once the session is active again or it has waited long enough
+    fn take_due(&mut self, active: bool) -> Option<Vec<u8>> {
+        let since = self.since?;
+        let due = active
+            || self.bytes.len() >= MAX_DEFERRED
+            || self
+                .interval
+                .is_none_or(|interval| since.elapsed() >= interval);
+        due.then(|| self.take())?
+    }
+
+    fn take(&mut self) -> Option<Vec<u8>> {
+        self.since = None;
+        let bytes = std::mem::take(&mut self.bytes);
+        (!bytes.is_empty()).then_some(bytes)
+    }
+}
+
+/// A session's end of the PTY, read on the reader pool: output is parsed onto the
+/// session's screen, and the child is reaped once the PTY closes
+struct SessionReader {
+    fd: RawFd,
+    reader: Box<dyn Read + Send>,
+    /// Kept so `fd` stays open while the pool polls it
+    master: SharedMaster,
+    child: SharedChild,
+    size: SharedSize,
+    /// Size last applied to the PTY and parser, as (rows, cols)
+    applied: (u16, u16),
+    parser: Arc<Mutex<CallbackParser>>,
+    passthrough: Arc<AtomicBool>,
+    active: Arc<AtomicBool>,
+    dirty: Arc<AtomicBool>,
+    parsed: Arc<AtomicU64>,
+    output_lines: Arc<Mutex<Option<OutputLines>>>,
+    raw_log: Arc<Mutex<Option<RawLog>>>,
+    throttled_at: Arc<ArcSwap<Option<Instant>>>,
+    last_input: Arc<ArcSwap<Option<Instant>>>,
+    session_error: Arc<ArcSwap<Option<String>>>,
+    exit_status: Arc<ArcSwap<Option<ExitStatus>>>,
+    on_exit: Option<ExitNotifier>,
+    shutdown_rx: Receiver<()>,
+    rate_limiter: Option<RateLimiter>,
+    /// When the rate limiter lets reads resume
+    throttled_until: Option<Instant>,
+    typing_pause: Option<Duration>,
+    deferred: DeferredOutput,
+    /// Whether the last read filled the buffer, i.e. the child is flooding output
+    flooding: bool,
+    span: tracing::Span,
+}
+
+impl SessionReader {
+    /// Lock parser, process data, set dirty flag.
+    /// No screen cloning here - rendering reads the parser in place
+    fn parse(&self, bytes: &[u8]) {
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.process(bytes);
+            if self.passthrough.load(Ordering::Acquire) {
+                let mut out = std::io::stdout().lock();
+                let _ = out.write_all(bytes);
+                let _ = out.flush();
+            }
+        }
+        self.dirty.store(true, Ordering::Release);
+        self.parsed.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        if let Ok(mut output) = self.output_lines.lock()
+            && let Some(lines) = output.as_mut()
+        {
+            lines.feed(bytes);
+        }
+    }
+
+    /// Append output to the raw log, if there is one. A log that fails is dropped.
+    fn mirror(&self, bytes: &[u8]) {
+        if let Ok(mut raw_log) = self.raw_log.lock()
+            && let Some(log) = raw_log.as_mut()
+            && let Err(e) = log.write(bytes)
+        {
+            tracing::warn!(error = %e, "raw output log failed");
+            *raw_log = None;
+        }
+    }
+
+    /// Reap the child, record how it exited and notify the owner. Reaping can take a
+    /// moment, so it happens off the pool thread.
+    fn report_exit(&mut self) {
+        let child = self.child.clone();
+        let session_error = self.session_error.clone();
+        let exit_status = self.exit_status.clone();
+        let on_exit = self.on_exit.take();
+        let span = self.span.clone();
+        std::thread::spawn(move || {
+            let _span = span.entered();
+            let status = wait_for_exit(&child);
+            tracing::debug!(?status, "child exited");
+            exit_status.store(Arc::new(status.clone()));
+            let message = format!("Process {}", exit_message(status.as_ref()));
+            session_error.store(Arc::new(Some(message)));
+            if let Some(notify) = on_exit {
+                notify();
+            }
+        });
+    }
+}
+
+impl PtyReader for SessionReader {
+    fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    fn held_until(&self) -> Option<Instant> {
+        // Leave a flood in the PTY's buffer while the user types, so keystrokes aren't
+        // stuck behind parsing it
+        let typing = self
+            .typing_pause
+            .filter(|_| self.flooding)
+            .and_then(|pause| self.last_input.load().map(|typed| typed + pause));
+        self.throttled_until
+            .max(typing)
+            .filter(|&until| until > Instant::now())
+    }
+
+    fn read_ready(&mut self, buf: &mut [u8]) -> bool {
+        let _span = self.span.clone().entered();
+        let read = self.reader.read(buf);
+        // Whatever the child wrote last still belongs on its screen
+        if !matches!(read, Ok(n) if n > 0)
+            && let Some(bytes) = self.deferred.take()
+        {
+            self.parse(&bytes);
+        }
+        match read {
+            Ok(0) => {
+                // EOF - child process exited
+                self.report_exit();
+                false
+            }
+            Ok(n) => {
+                tracing::trace!(bytes = n, "read");
+                self.flooding = n == buf.len();
+                self.mirror(&buf[..n]);
+
+                // Queued output goes first so the screen sees it in order
+                let active = self.active.load(Ordering::Acquire);
+                if let Some(bytes) = self.deferred.take_due(active) {
+                    self.parse(&bytes);
+                }
+                if !self.deferred.defer(&buf[..n], active) {
+                    self.parse(&buf[..n]);
+                }
+
+                if let Some(ref mut limiter) = self.rate_limiter
+                    && let Some(until) = limiter.consume(n)
+                {
+                    self.throttled_until = Some(until);
+                    self.throttled_at.store(Arc::new(Some(Instant::now())));
+                }
+                true
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => true,
+            Err(e) if is_pty_closed(&e) => {
+                self.report_exit();
+                false
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "pty read failed");
+                self.session_error
+                    .store(Arc::new(Some(format!("PTY read error: {}", e))));
+                false
+            }
+        }
+    }
+
+    fn tick(&mut self) -> bool {
+        let _span = self.span.clone().entered();
+        // Stop once shut down, or once the session is gone
+        if !matches!(self.shutdown_rx.try_recv(), Err(TryRecvError::Empty)) {
+            return false;
+        }
+
+        // Resizes are applied here as soon as the shared size changes, so the PTY and
+        // parser agree before any more output is read
+        let size = self.size.get();
+        if size != self.applied {
+            match sync_pty_size(&self.master, &self.parser, &self.size) {
+                Ok(resized) => {
+                    self.applied = size;
+                    if resized {
+                        self.dirty.store(true, Ordering::Release);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(error = e, "pty resize failed");
+                    self.session_error.store(Arc::new(Some(e)));
+                    return false;
+                }
+            }
+        }
+
+        if let Some(bytes) = self.deferred.take_due(self.active.load(Ordering::Acquire)) {
+            self.parse(&bytes);
+        }
+        true
+    }
+}
+
+/// Whether a failed read of the master means the child's side of the PTY closed. Linux
+/// and macOS both report that as EIO once the last process holding the slave exits;
+/// `ErrorKind` alone can't tell it apart from real failures.
+fn is_pty_closed(e: &std::io::Error) -> bool {
+    e.raw_os_error() == Some(libc::EIO)
+}
+
+/// How the child process ended, e.g. "exited with code 3" or "died: Hangup"
+pub fn exit_message(status: Option<&ExitStatus>) -> String {
+    match status {
+        Some(status) if status.success() => "exited".to_string(),
+        Some(status) => match status.signal() {
+            Some(signal) => format!("died: {}", signal),
+            None => format!("exited with code {}", status.exit_code()),
+        },
+        None => "died".to_string(),
+    }
+}
 
 /// (rows, cols) ordered size stored in AtomicU32
 #[derive(Clone, Debug)]
@@ -102,30 +647,83 @@ impl SharedSize {
     }
 
     pub fn set(&self, rows: u16, cols: u16) {
-        self.0.store(Self::pack_size(rows, cols), Ordering::Relaxed);
+        let size = Self::pack_size(rows, cols);
+        // The reader pool applies the new size to every PTY sharing it
+        if self.0.swap(size, Ordering::Relaxed) != size {
+            reader_pool::wake();
+        }
     }
 }
 
 pub struct Session {
     active: Arc<AtomicBool>,
     writer: SharedWriter,
-    _reader_thread: JoinHandle<()>,
-    /// Shared parser - owned by reader thread but accessible for on-demand screen cloning
+    /// Shared parser - fed by the reader pool but readable in place for rendering
     parser: Arc<Mutex<CallbackParser>>,
-    /// Cached screen clone for rendering (only updated when dirty)
-    cached_screen: ArcSwap<Screen>,
-    /// Dirty flag - set by reader thread, cleared when screen is cloned
+    /// Dirty flag - set by the reader, cleared when the screen is rendered
     dirty: Arc<AtomicBool>,
-    /// Channel to signal the reader thread to shut down
+    /// Bytes of output parsed so far
+    parsed: Arc<AtomicU64>,
+    /// When the reader last held output back to stay under `max_output_rate`
+    throttled_at: Arc<ArcSwap<Option<Instant>>>,
+    /// When input was last written, for `pause_output_while_typing_ms`
+    last_input: Arc<ArcSwap<Option<Instant>>>,
+    /// Channel to signal the reader to stop
     shutdown_tx: Sender<()>,
     /// Error message if the session died unexpectedly
     session_error: Arc<ArcSwap<Option<String>>>,
+    /// Exit status of the child process, once it has been reaped
+    exit_status: Arc<ArcSwap<Option<ExitStatus>>>,
     /// Child process handle for killing
-    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    child: SharedChild,
+    /// Master side of the PTY, shared with the reader for resizing
+    master: SharedMaster,
+    /// Target size for the PTY (shared between sessions)
+    size: SharedSize,
+    /// Kill the child's whole process session on shutdown, not just the child
+    kill_process_tree: bool,
+    /// Set while the child's process tree is stopped with SIGSTOP
+    paused: AtomicBool,
+    /// Scrollback lines kept by the parser, for rebuilding it on a forced redraw
+    scrollback: usize,
+    /// Set while output is also copied verbatim to shepherd's own stdout (pass-through mode)
+    passthrough: Arc<AtomicBool>,
+    /// Focus reporting and cursor style requested by the child
+    modes: Arc<ChildModes>,
+    /// Plain text output lines for watch rules, once something has asked for them
+    output_lines: Arc<Mutex<Option<OutputLines>>>,
+    /// File the child's raw output is mirrored to, once `mirror_output` is called
+    raw_log: Arc<Mutex<Option<RawLog>>>,
+    /// Scrollback moved to disk by `trim_scrollback`, with its line count
+    stash: Mutex<Option<(PathBuf, usize)>>,
+}
+
+/// Approximate memory held by a session's parser
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScreenMemory {
+    /// Scrollback lines held by the parser
+    pub scrollback_lines: usize,
+    /// Scrollback lines trimmed to disk, restored on attach
+    pub stashed_lines: usize,
+    /// Grid cells of the screen and scrollback, in bytes
+    pub bytes: usize,
 }
 
 impl Session {
-    /// Check if the session has died (reader thread encountered an error)
+    /// Write input to the child. Works for detached sessions too, so background
+    /// sessions can be answered without attaching them.
+    pub fn write_input(&self, data: &[u8]) -> anyhow::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow::anyhow!("lock poisoned"))?;
+        writer.write_all(data)?;
+        writer.flush()?;
+        self.last_input.store(Arc::new(Some(Instant::now())));
+        Ok(())
+    }
+
+    /// Check if the session has died (the reader hit an error or the child exited)
     pub fn is_dead(&self) -> bool {
         self.session_error.load().is_some()
     }
@@ -135,26 +733,397 @@ impl Session {
         self.session_error.load().as_ref().clone()
     }
 
-    /// Signal the reader thread to shut down gracefully and kill the child process
+    /// Get the exit status of the child process, if it has exited
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status.load().as_ref().clone()
+    }
+
+    /// Process id of the child
+    pub fn process_id(&self) -> Option<u32> {
+        self.child.lock().ok()?.process_id()
+    }
+
+    /// Stop (SIGSTOP) or continue (SIGCONT) the child and everything it started, e.g. to
+    /// freeze a looping agent while deciding what to do with it
+    pub fn set_paused(&self, paused: bool) -> anyhow::Result<()> {
+        let child = self
+            .child
+            .lock()
+            .map_err(|_| anyhow::anyhow!("lock poisoned"))?;
+        let pid = child
+            .process_id()
+            .ok_or_else(|| anyhow::anyhow!("session has no process"))?;
+        let signal = if paused { libc::SIGSTOP } else { libc::SIGCONT };
+        signal_process_groups(&session_process_groups(pid), signal);
+        self.paused.store(paused, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Whether output was recently held back by `max_output_rate`
+    pub fn is_throttled(&self) -> bool {
+        self.throttled_at
+            .load()
+            .is_some_and(|at| at.elapsed() < THROTTLE_SHOWN_FOR)
+    }
+
+    /// Send `signal` to the PTY's foreground process group (what Ctrl+C would reach),
+    /// falling back to the child itself. Works even when the program has turned off the
+    /// terminal's own signal keys, as full-screen apps do.
+    pub fn send_signal(&self, signal: i32) -> anyhow::Result<()> {
+        let foreground = self
+            .master
+            .lock()
+            .ok()
+            .and_then(|master| master.process_group_leader())
+            .filter(|&pgrp| pgrp > 0);
+        // SAFETY: kill/killpg have no memory safety preconditions
+        let result = match foreground {
+            Some(pgrp) => unsafe { libc::killpg(pgrp, signal) },
+            None => {
+                let child = self
+                    .child
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("lock poisoned"))?;
+                let pid = child
+                    .process_id()
+                    .ok_or_else(|| anyhow::anyhow!("session has no process"))?;
+                unsafe { libc::kill(pid as libc::pid_t, signal) }
+            }
+        };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+
+    /// Signal the reader to stop and kill the child process
     pub fn shutdown(&self) {
         let _ = self.shutdown_tx.try_send(());
-        if let Ok(mut child) = self.child.lock() {
+        reader_pool::wake();
+        let Ok(mut child) = self.child.lock() else {
+            return;
+        };
+        // Don't signal a pid that has already been reaped (it may have been reused)
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
+        }
+        tracing::debug!(pid = child.process_id(), "shutting down session");
+        // Snapshot the process groups while the child is alive: once it is reaped
+        // its pid (the session id) may be reused
+        let groups = match child.process_id() {
+            Some(pid) if self.kill_process_tree => session_process_groups(pid),
+            _ => Vec::new(),
+        };
+        if groups.is_empty() {
             let _ = child.kill();
+            let _ = child.wait();
+            return;
+        }
+        signal_process_groups(&groups, libc::SIGHUP);
+        if self.is_paused() {
+            // Stopped processes only act on the hangup once continued
+            signal_process_groups(&groups, libc::SIGCONT);
+        }
+        drop(child);
+        // Give them a moment to exit cleanly before anything is killed, off the caller's
+        // thread so the UI doesn't stall and other sessions' grace periods overlap
+        let child = Arc::clone(&self.child);
+        let hangup = std::thread::spawn(move || {
+            let deadline = Instant::now() + HANGUP_GRACE;
+            while Instant::now() < deadline {
+                let reaped = child
+                    .lock()
+                    .is_ok_and(|mut child| matches!(child.try_wait(), Ok(Some(_))));
+                if reaped && !process_groups_alive(&groups) {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            if let Ok(mut child) = child.lock() {
+                let _ = child.kill();
+                // Anything that ignored the hangup
+                signal_process_groups(&groups, libc::SIGKILL);
+                // Reap the child so it doesn't linger as a zombie
+                let _ = child.wait();
+            }
+        });
+        let mut hangups = HANGUPS.lock().unwrap_or_else(|e| e.into_inner());
+        hangups.retain(|hangup| !hangup.is_finished());
+        hangups.push(hangup);
+    }
+
+    /// Resize the PTY and parser to the current shared size right away,
+    /// rather than waiting for the reader pool's next pass
+    pub fn sync_size(&self) {
+        if let Ok(true) = sync_pty_size(&self.master, &self.parser, &self.size) {
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    /// Start copying the child's raw output straight to stdout, after drawing the current
+    /// screen there so the host terminal starts out in sync. The caller must stop
+    /// rendering until [`Session::stop_passthrough`].
+    pub fn start_passthrough(&self) {
+        // Holding the parser lock keeps the reader from emitting output mid-replay
+        let parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = std::io::stdout().lock();
+        let _ = out.write_all(b"\x1b[H\x1b[2J");
+        let _ = out.write_all(&parser.screen().state_formatted());
+        let _ = out.flush();
+        self.passthrough.store(true, Ordering::Release);
+    }
+
+    pub fn stop_passthrough(&self) {
+        let _parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        self.passthrough.store(false, Ordering::Release);
+    }
+
+    /// Tell the child its terminal gained or lost focus, if it asked for focus reports
+    pub fn report_focus(&self, focused: bool) {
+        if self.modes.focus_reporting.load(Ordering::Relaxed) {
+            let _ = self.write_input(if focused { b"\x1b[I" } else { b"\x1b[O" });
+        }
+    }
+
+    /// Cursor shape last requested by the child with DECSCUSR, 0 = terminal default
+    pub fn cursor_style(&self) -> u8 {
+        self.modes.cursor_style.load(Ordering::Relaxed)
+    }
+
+    /// Take the child's latest OSC 52 clipboard write, as `<selection>;<base64>`
+    pub fn take_clipboard(&self) -> Option<String> {
+        self.modes.clipboard.lock().ok()?.take()
+    }
+
+    /// Output lines completed since the last call. Capturing starts with the first call.
+    pub fn take_output_lines(&self) -> Vec<String> {
+        let Ok(mut output) = self.output_lines.lock() else {
+            return Vec::new();
+        };
+        match output.as_mut() {
+            Some(lines) => lines.take(),
+            None => {
+                *output = Some(OutputLines::default());
+                Vec::new()
+            }
+        }
+    }
+
+    /// Append everything the child prints from now on to `log`
+    pub fn mirror_output(&self, log: RawLog) {
+        if let Ok(mut raw_log) = self.raw_log.lock() {
+            *raw_log = Some(log);
         }
     }
 
-    /// Get the current screen state (clones only if dirty)
-    pub fn get_screen(&self) -> Arc<Screen> {
-        // Only clone the screen if it's been modified since last read
-        if self.dirty.swap(false, Ordering::AcqRel)
-            && let Ok(parser) = self.parser.lock()
+    /// Recover from a screen that drifted out of sync with the child: restart the parser
+    /// blank at the current size, then resize the PTY one column narrower and back so the
+    /// child gets SIGWINCH and redraws everything
+    pub fn force_redraw(&self) -> anyhow::Result<()> {
+        let (rows, cols) = self.size.get();
         {
-            self.cached_screen.store(Arc::new(parser.screen().clone()));
+            let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+            *parser = Parser::new_with_callbacks(
+                rows,
+                cols,
+                self.scrollback,
+                TerminalCallbacks::new(self.writer.clone(), self.modes.clone()),
+            );
+        }
+        let master = self
+            .master
+            .lock()
+            .map_err(|_| anyhow::anyhow!("PTY error: lock poisoned"))?;
+        for cols in [cols.saturating_sub(1).max(1), cols] {
+            master.resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })?;
+        }
+        self.dirty.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Escape sequences that redraw the visible screen, for keeping it across restarts
+    pub fn screen_snapshot(&self) -> Vec<u8> {
+        let parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        parser.screen().contents_formatted()
+    }
+
+    /// Show a screen saved with `screen_snapshot` until the child draws over it
+    pub fn seed_screen(&self, screen: &[u8]) {
+        let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        parser.process(screen);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Bytes of output parsed since the session started
+    pub fn bytes_parsed(&self) -> u64 {
+        self.parsed.load(Ordering::Relaxed)
+    }
+
+    /// Output waiting in the PTY to be read, None when the PTY can't say
+    pub fn pending_output(&self) -> Option<usize> {
+        let fd = self.master.lock().ok()?.as_raw_fd()?;
+        let mut pending: libc::c_int = 0;
+        // SAFETY: FIONREAD writes one c_int to the pointer it is given
+        let result = unsafe { libc::ioctl(fd, libc::FIONREAD, &mut pending) };
+        (result == 0).then_some(pending.max(0) as usize)
+    }
+
+    /// What the parser holds right now
+    pub fn memory(&self) -> ScreenMemory {
+        let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        let screen = parser.screen_mut();
+        let offset = screen.scrollback();
+        screen.set_scrollback(usize::MAX);
+        let scrollback_lines = screen.scrollback();
+        screen.set_scrollback(offset);
+        let (rows, cols) = screen.size();
+        let stashed_lines = self
+            .stash
+            .lock()
+            .ok()
+            .and_then(|stash| stash.as_ref().map(|(_, lines)| *lines))
+            .unwrap_or(0);
+        ScreenMemory {
+            scrollback_lines,
+            stashed_lines,
+            bytes: (scrollback_lines + rows as usize)
+                * cols as usize
+                * std::mem::size_of::<vt100::Cell>(),
+        }
+    }
+
+    /// Move the scrollback to a file and rebuild the parser from the visible screen alone.
+    /// Returns how many lines were moved; `restore_scrollback` brings them back.
+    pub fn trim_scrollback(&self) -> anyhow::Result<usize> {
+        let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        // Full-screen apps keep nothing in scrollback, and their screen can't be replayed
+        if parser.screen().alternate_screen() {
+            return Ok(0);
+        }
+        let rows = scrollback_rows(parser.screen_mut());
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let mut stash = self.stash.lock().unwrap_or_else(|e| e.into_inner());
+        let (path, lines) = stash.get_or_insert_with(|| {
+            let seq = STASH_SEQ.fetch_add(1, Ordering::Relaxed);
+            let name = format!("shepherd-scrollback-{}-{}", std::process::id(), seq);
+            (std::env::temp_dir().join(name), 0)
+        });
+        // Appended, so lines trimmed earlier stay ahead of these
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&*path)?;
+        for row in &rows {
+            file.write_all(row)?;
+            file.write_all(b"\x1b[m\r\n")?;
+        }
+        *lines += rows.len();
+
+        let (height, width) = parser.screen().size();
+        let mut rebuilt = Parser::new_with_callbacks(
+            height,
+            width,
+            self.scrollback,
+            TerminalCallbacks::new(self.writer.clone(), self.modes.clone()),
+        );
+        rebuilt.process(&parser.screen().state_formatted());
+        *parser = rebuilt;
+        Ok(rows.len())
+    }
+
+    /// Put back scrollback moved to disk by `trim_scrollback`, ahead of anything that
+    /// scrolled off since
+    pub fn restore_scrollback(&self) {
+        let Some((path, _)) = self.stash.lock().ok().and_then(|mut stash| stash.take()) else {
+            return;
+        };
+        let stashed = std::fs::read(&path);
+        let _ = std::fs::remove_file(&path);
+        let Ok(stashed) = stashed else {
+            return;
+        };
+
+        let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        let (height, width) = parser.screen().size();
+        // With a single row, every line fed scrolls straight into the scrollback
+        let mut rebuilt = Parser::new_with_callbacks(
+            1,
+            width,
+            self.scrollback,
+            TerminalCallbacks::new(self.writer.clone(), self.modes.clone()),
+        );
+        rebuilt.process(&stashed);
+        for row in scrollback_rows(parser.screen_mut()) {
+            rebuilt.process(&row);
+            rebuilt.process(b"\x1b[m\r\n");
+        }
+        rebuilt.screen_mut().set_size(height, width);
+        rebuilt.process(&parser.screen().state_formatted());
+        *parser = rebuilt;
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Whether the screen has changed since it was last rendered
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Run `f` against the live screen without cloning it.
+    /// The view is scrolled back `scroll_offset` lines for the duration of the call.
+    pub fn with_screen<R>(&self, scroll_offset: usize, f: impl FnOnce(&Screen) -> R) -> R {
+        if scroll_offset > 0 {
+            self.restore_scrollback();
+        }
+        let mut parser = self.parser.lock().unwrap_or_else(|e| e.into_inner());
+        self.dirty.store(false, Ordering::Release);
+        if scroll_offset == 0 {
+            return f(parser.screen());
+        }
+        parser.screen_mut().set_scrollback(scroll_offset);
+        let result = f(parser.screen());
+        parser.screen_mut().set_scrollback(0);
+        result
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.shutdown();
+        if let Ok(Some((path, _))) = self.stash.get_mut().map(Option::take) {
+            let _ = std::fs::remove_file(path);
         }
-        self.cached_screen.load_full()
     }
 }
 
+/// The scrollback of `screen` as formatted rows, oldest first
+fn scrollback_rows(screen: &mut Screen) -> Vec<Vec<u8>> {
+    screen.set_scrollback(usize::MAX);
+    let total = screen.scrollback();
+    let (_, cols) = screen.size();
+    // At offset n the top visible row is the nth line from the end of the scrollback
+    let rows = (1..=total)
+        .rev()
+        .filter_map(|offset| {
+            screen.set_scrollback(offset);
+            screen.rows_formatted(0, cols).next()
+        })
+        .collect();
+    screen.set_scrollback(0);
+    rows
+}
+
 pub struct DetachedSession(Session);
 
 impl Deref for DetachedSession {
@@ -167,6 +1136,11 @@ impl Deref for DetachedSession {
 impl DetachedSession {
     pub fn attach(self) -> anyhow::Result<AttachedSession> {
         self.0.active.store(true, Ordering::Release);
+        self.0.restore_scrollback();
+        // The terminal may have been resized while this session was in the background
+        self.0.sync_size();
+        // Parse anything held back while in the background straight away
+        reader_pool::wake();
         Ok(AttachedSession(self.0))
     }
 }
@@ -192,21 +1166,23 @@ impl AttachedSession {
     pub fn new(
         command: &str,
         args: &[&str],
-        _tx: std::sync::mpsc::Sender<Screen>,
+        on_exit: Option<ExitNotifier>,
         size: SharedSize,
         cwd: Option<&Path>,
-    ) -> anyhow::Result<Self> {
-        Self::new_with_env(command, args, _tx, size, cwd, &[])
+        limits: &SessionLimits,
+    ) -> Result<Self, ShepardError> {
+        Self::new_with_env(command, args, on_exit, size, cwd, &[], limits)
     }
 
     pub fn new_with_env(
         command: &str,
         args: &[&str],
-        _tx: std::sync::mpsc::Sender<Screen>,
+        on_exit: Option<ExitNotifier>,
         size: SharedSize,
         cwd: Option<&Path>,
         env_vars: &[(&str, &str)],
-    ) -> anyhow::Result<Self> {
+        limits: &SessionLimits,
+    ) -> Result<Self, ShepardError> {
         let pty_system = native_pty_system();
 
         let (rows, cols) = size.get();
@@ -217,7 +1193,9 @@ impl AttachedSession {
             pixel_height: 0,
         };
 
-        let pair = pty_system.openpty(pty_size)?;
+        let pair = pty_system
+            .openpty(pty_size)
+            .map_err(|e| ShepardError::pty("open a pty", e))?;
 
         let mut cmd = CommandBuilder::new(command);
         cmd.args(args);
@@ -228,31 +1206,72 @@ impl AttachedSession {
             cmd.env(key, value);
         }
 
-        let child = pair.slave.spawn_command(cmd)?;
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| ShepardError::pty(format!("start {}", command), e))?;
         drop(pair.slave);
-        let child = Arc::new(Mutex::new(child));
+        tracing::debug!(command, pid = child.process_id(), rows, cols, "spawned");
+        if let (Some(nice), Some(pid)) = (limits.nice, child.process_id()) {
+            apply_niceness(pid, nice);
+        }
+        Self::start(pair.master, child, on_exit, size, limits)
+    }
 
-        let mut reader = pair.master.try_clone_reader()?;
-        let writer: SharedWriter = Arc::new(Mutex::new(pair.master.take_writer()?));
+    /// Start reading and parsing the output of a child already running on `master`
+    pub(crate) fn start(
+        master: Box<dyn MasterPty + Send>,
+        child: Box<dyn Child + Send + Sync>,
+        on_exit: Option<ExitNotifier>,
+        size: SharedSize,
+        limits: &SessionLimits,
+    ) -> Result<Self, ShepardError> {
+        let pty_error = |e: anyhow::Error| ShepardError::pty("open a pty", e);
+        let (rows, cols) = size.get();
+        let child: SharedChild = Arc::new(Mutex::new(child));
+        let reader_child = child.clone();
+
+        let reader = master.try_clone_reader().map_err(pty_error)?;
+        let writer: SharedWriter = Arc::new(Mutex::new(master.take_writer().map_err(pty_error)?));
         let callback_writer = writer.clone();
 
         let active = Arc::new(AtomicBool::new(true));
         let shared_active = active.clone();
 
-        // Create parser with callbacks - shared between reader thread and main thread
-        let callbacks = TerminalCallbacks::new(callback_writer);
+        // Create parser with callbacks - shared between the reader and main thread
+        let modes = Arc::new(ChildModes::default());
+        let callbacks = TerminalCallbacks::new(callback_writer, modes.clone());
+        let scrollback = limits.max_scrollback_lines.unwrap_or(SCROLLBACK);
         let parser = Arc::new(Mutex::new(Parser::new_with_callbacks(
-            rows, cols, SCROLLBACK, callbacks,
+            rows, cols, scrollback, callbacks,
         )));
+        let rate_limiter = limits.max_output_rate.map(RateLimiter::new);
         let shared_parser = parser.clone();
+        let passthrough = Arc::new(AtomicBool::new(false));
+        let shared_passthrough = passthrough.clone();
+        let output_lines: Arc<Mutex<Option<OutputLines>>> = Arc::default();
+        let shared_output_lines = output_lines.clone();
+        let raw_log: Arc<Mutex<Option<RawLog>>> = Arc::default();
+        let shared_raw_log = raw_log.clone();
 
-        // Create initial cached screen
-        let initial_screen = parser.lock().unwrap().screen().clone();
-        let cached_screen = ArcSwap::from_pointee(initial_screen);
-
-        // Dirty flag - starts false since cached_screen is in sync
+        // Dirty flag - starts false since nothing has been output yet
         let dirty = Arc::new(AtomicBool::new(false));
         let shared_dirty = dirty.clone();
+        let parsed = Arc::new(AtomicU64::new(0));
+        let shared_parsed = parsed.clone();
+        let throttled_at: Arc<ArcSwap<Option<Instant>>> = Arc::new(ArcSwap::from_pointee(None));
+        let shared_throttled_at = throttled_at.clone();
+        let last_input: Arc<ArcSwap<Option<Instant>>> = Arc::new(ArcSwap::from_pointee(None));
+        let shared_last_input = last_input.clone();
+        let typing_pause = limits
+            .pause_output_while_typing_ms
+            .map(Duration::from_millis);
+        let deferred = DeferredOutput::new(
+            limits
+                .background_parse_interval_ms
+                .filter(|&ms| ms > 0)
+                .map(Duration::from_millis),
+        );
 
         // Create shutdown channel - bounded(1) for non-blocking send
         let (shutdown_tx, shutdown_rx): (Sender<()>, Receiver<()>) = bounded(1);
@@ -261,92 +1280,69 @@ impl AttachedSession {
         let session_error: Arc<ArcSwap<Option<String>>> = Arc::new(ArcSwap::from_pointee(None));
         let shared_error = session_error.clone();
 
-        let reader_thread = std::thread::spawn(move || {
-            let master = pair.master;
-            let mut buf = [0u8; BUF_SIZE];
-            loop {
-                // Check for shutdown signal (non-blocking)
-                if shutdown_rx.try_recv().is_ok() {
-                    break;
-                }
+        let exit_status: Arc<ArcSwap<Option<ExitStatus>>> = Arc::new(ArcSwap::from_pointee(None));
+        let shared_exit_status = exit_status.clone();
 
-                match reader.read(&mut buf) {
-                    Ok(0) => {
-                        // EOF - child process exited
-                        shared_error.store(Arc::new(Some("Process exited".to_string())));
-                        break;
-                    }
-                    Ok(n) => {
-                        // Check if size changed and update both PTY and parser
-                        let (rows, cols) = size.get();
-
-                        // Handle PTY size query gracefully
-                        let current = match master.get_size() {
-                            Ok(size) => size,
-                            Err(e) => {
-                                shared_error.store(Arc::new(Some(format!(
-                                    "PTY error: failed to get size: {}",
-                                    e
-                                ))));
-                                break;
-                            }
-                        };
-
-                        if current.rows != rows || current.cols != cols {
-                            // Handle PTY resize gracefully
-                            if let Err(e) = master.resize(PtySize {
-                                rows,
-                                cols,
-                                pixel_width: 0,
-                                pixel_height: 0,
-                            }) {
-                                shared_error.store(Arc::new(Some(format!(
-                                    "PTY error: failed to resize: {}",
-                                    e
-                                ))));
-                                break;
-                            }
-                        }
-
-                        // Lock parser, process data, set dirty flag
-                        // No screen cloning here - that happens on-demand in get_screen()
-                        if let Ok(mut parser) = shared_parser.lock() {
-                            parser.screen_mut().set_size(rows, cols);
-                            parser.process(&buf[..n]);
-                        }
-                        shared_dirty.store(true, Ordering::Release);
-
-                        let is_active = shared_active.load(Ordering::Acquire);
-                        if !is_active {
-                            continue;
-                        }
-                    }
-                    Err(e) => {
-                        // Read error - PTY closed or child died
-                        let kind = e.kind();
-                        // EIO is expected when child process exits
-                        if kind != std::io::ErrorKind::Other {
-                            shared_error.store(Arc::new(Some(format!("PTY read error: {}", e))));
-                        } else {
-                            // EIO means process exited
-                            shared_error.store(Arc::new(Some("Process exited".to_string())));
-                        }
-                        break;
-                    }
-                }
-            }
-        });
+        let fd = master
+            .as_raw_fd()
+            .ok_or_else(|| ShepardError::pty("read the pty", "no file descriptor to poll"))?;
+        let master: SharedMaster = Arc::new(Mutex::new(master));
+        let span = tracing::debug_span!(
+            "pty_reader",
+            pid = child.lock().ok().and_then(|c| c.process_id())
+        );
+
+        reader_pool::register(Box::new(SessionReader {
+            fd,
+            reader,
+            master: master.clone(),
+            child: reader_child,
+            applied: size.get(),
+            size: size.clone(),
+            parser: shared_parser,
+            passthrough: shared_passthrough,
+            active: shared_active,
+            dirty: shared_dirty,
+            parsed: shared_parsed,
+            output_lines: shared_output_lines,
+            raw_log: shared_raw_log,
+            throttled_at: shared_throttled_at,
+            last_input: shared_last_input,
+            session_error: shared_error,
+            exit_status: shared_exit_status,
+            on_exit,
+            shutdown_rx,
+            rate_limiter,
+            throttled_until: None,
+            typing_pause,
+            deferred,
+            flooding: false,
+            span,
+        }))
+        .map_err(|e| ShepardError::pty("start reading the pty", e))?;
 
         Ok(Self(Session {
             active,
             writer,
-            _reader_thread: reader_thread,
             parser,
-            cached_screen,
             dirty,
+            parsed,
+            throttled_at,
+            last_input,
             shutdown_tx,
             session_error,
+            exit_status,
             child,
+            master,
+            size,
+            kill_process_tree: limits.kill_process_tree,
+            paused: AtomicBool::new(false),
+            scrollback,
+            passthrough,
+            modes,
+            output_lines,
+            raw_log,
+            stash: Mutex::new(None),
         }))
     }
 
@@ -354,14 +1350,324 @@ impl AttachedSession {
         self.0.active.store(false, Ordering::Release);
         DetachedSession(self.0)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Count zombie processes whose parent is this test process
+    #[cfg(target_os = "linux")]
+    fn zombie_children() -> usize {
+        let me = std::process::id().to_string();
+        std::fs::read_dir("/proc")
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| std::fs::read_to_string(e.path().join("stat")).ok())
+            .filter(|stat| {
+                // Format: pid (comm) state ppid ... - comm may contain spaces
+                let Some((_, rest)) = stat.rsplit_once(')') else {
+                    return false;
+                };
+                let mut fields = rest.split_whitespace();
+                let state = fields.next();
+                let ppid = fields.next();
+                state == Some("Z") && ppid == Some(me.as_str())
+            })
+            .count()
+    }
 
-    pub fn write_input(&mut self, data: &[u8]) -> anyhow::Result<()> {
-        let mut writer = self
-            .writer
-            .lock()
-            .map_err(|_| anyhow::anyhow!("lock poisoned"))?;
-        writer.write_all(data)?;
-        writer.flush()?;
-        Ok(())
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_no_zombies_after_killing_sessions() {
+        let sessions: Vec<AttachedSession> = (0..20)
+            .map(|_| {
+                AttachedSession::new(
+                    "sleep",
+                    &["60"],
+                    None,
+                    SharedSize::new(24, 80),
+                    None,
+                    &SessionLimits::default(),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        for session in &sessions {
+            session.shutdown();
+        }
+        drop(sessions);
+        wait_for_hangups();
+
+        assert_eq!(zombie_children(), 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_hangup_grace_periods_overlap() {
+        // Each ignores the hangup, so sits out the whole grace period before the kill
+        let sessions: Vec<AttachedSession> = (0..5)
+            .map(|_| {
+                AttachedSession::new(
+                    "sh",
+                    &["-c", "trap '' HUP; sleep 60"],
+                    None,
+                    SharedSize::new(24, 80),
+                    None,
+                    &SessionLimits::default(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let pids: Vec<u32> = sessions
+            .iter()
+            .map(|s| s.child.lock().unwrap().process_id().unwrap())
+            .collect();
+        // Let the shells install the trap
+        std::thread::sleep(Duration::from_millis(200));
+
+        let started = Instant::now();
+        for session in &sessions {
+            session.shutdown();
+        }
+        assert!(started.elapsed() < HANGUP_GRACE);
+        wait_for_hangups();
+        assert!(started.elapsed() < HANGUP_GRACE * 2);
+        // Killed and reaped
+        // SAFETY: kill has no memory safety preconditions; signal 0 only checks existence
+        assert!(
+            pids.iter()
+                .all(|&pid| unsafe { libc::kill(pid as libc::pid_t, 0) } != 0)
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_shutdown_kills_background_jobs() {
+        let session = AttachedSession::new(
+            "sh",
+            &["-c", "sleep 60 & sleep 60 & wait"],
+            None,
+            SharedSize::new(24, 80),
+            None,
+            &SessionLimits::default(),
+        )
+        .unwrap();
+        let pid = session.child.lock().unwrap().process_id().unwrap();
+
+        // Live processes in the child's session (the shell and both sleeps)
+        let alive = |pid: u32| {
+            std::fs::read_dir("/proc")
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .filter_map(|e| std::fs::read_to_string(e.path().join("stat")).ok())
+                .filter(|stat| {
+                    let fields: Vec<&str> = stat
+                        .rsplit_once(')')
+                        .map(|(_, rest)| rest.split_whitespace().take(4).collect())
+                        .unwrap_or_default();
+                    fields.len() == 4 && fields[0] != "Z" && fields[3] == pid.to_string()
+                })
+                .count()
+        };
+        // Wait for both sleeps to start
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while alive(pid) < 3 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(alive(pid), 3);
+
+        session.shutdown();
+        wait_for_hangups();
+        assert_eq!(alive(pid), 0);
+    }
+
+    /// Poll the session's screen until `predicate` holds or the timeout expires
+    fn wait_for_screen(session: &Session, predicate: impl Fn(&Screen) -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if session.with_screen(0, &predicate) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_resize_applied_on_attach() {
+        let size = SharedSize::new(24, 80);
+        // A quiet child that only reports its size when it receives SIGWINCH
+        let session = AttachedSession::new(
+            "sh",
+            &[
+                "-c",
+                "trap 'stty size' WINCH; echo ready; while :; do sleep 0.05; done",
+            ],
+            None,
+            size.clone(),
+            None,
+            &SessionLimits::default(),
+        )
+        .unwrap();
+        assert!(wait_for_screen(&session, |s| s
+            .contents()
+            .contains("ready")));
+
+        let detached = session.detach();
+        size.set(30, 100);
+        let attached = detached.attach().unwrap();
+
+        // The parser reflows immediately, without waiting for output
+        assert_eq!(attached.with_screen(0, |s| s.size()), (30, 100));
+        // ...and the child saw the new PTY size
+        assert!(wait_for_screen(&attached, |s| s
+            .contents()
+            .contains("30 100")));
+    }
+
+    #[test]
+    fn test_trim_and_restore_scrollback() {
+        let session = AttachedSession::new(
+            "sh",
+            &["-c", "seq 1 100; echo done; sleep 5"],
+            None,
+            SharedSize::new(10, 40),
+            None,
+            &SessionLimits::default(),
+        )
+        .unwrap();
+        assert!(wait_for_screen(&session, |s| s.contents().contains("done")));
+        let screen = session.with_screen(0, |s| s.contents());
+
+        let kept = session.memory().scrollback_lines;
+        assert_eq!(session.trim_scrollback().unwrap(), kept);
+        assert_eq!(session.memory().scrollback_lines, 0);
+        assert_eq!(session.memory().stashed_lines, kept);
+        assert_eq!(session.with_screen(0, |s| s.contents()), screen);
+
+        let detached = session.detach();
+        let session = detached.attach().unwrap();
+        assert_eq!(session.memory().scrollback_lines, kept);
+        assert_eq!(session.memory().stashed_lines, 0);
+        assert_eq!(session.with_screen(0, |s| s.contents()), screen);
+        let oldest = session.with_screen(kept, |s| s.contents());
+        assert!(oldest.starts_with("1\n2\n"), "{:?}", oldest);
+    }
+
+    #[test]
+    fn test_pty_closed_errors() {
+        assert!(is_pty_closed(&std::io::Error::from_raw_os_error(libc::EIO)));
+        assert!(!is_pty_closed(&std::io::Error::from_raw_os_error(
+            libc::EBADF
+        )));
+        assert!(!is_pty_closed(&std::io::Error::other("EIO")));
+    }
+
+    #[test]
+    fn test_exit_reported_with_status() {
+        let session = AttachedSession::new(
+            "sh",
+            &["-c", "echo bye; exit 3"],
+            None,
+            SharedSize::new(24, 80),
+            None,
+            &SessionLimits::default(),
+        )
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !session.is_dead() {
+            assert!(Instant::now() < deadline, "exit never reported");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(
+            session.get_error().as_deref(),
+            Some("Process exited with code 3")
+        );
+        assert_eq!(session.exit_status().map(|s| s.exit_code()), Some(3));
+        assert!(session.with_screen(0, |s| s.contents().contains("bye")));
+    }
+
+    #[test]
+    fn test_deferred_output() {
+        let mut deferred = DeferredOutput::new(Some(Duration::from_millis(50)));
+        assert!(!deferred.defer(b"a", true));
+        assert!(deferred.defer(b"b", false));
+        assert!(deferred.defer(b"c", false));
+        assert_eq!(deferred.take_due(false), None);
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(deferred.take_due(false), Some(b"bc".to_vec()));
+        assert_eq!(deferred.take_due(true), None);
+
+        // Attaching parses what was held back straight away
+        assert!(deferred.defer(b"d", false));
+        assert_eq!(deferred.take_due(true), Some(b"d".to_vec()));
+        assert!(!DeferredOutput::new(None).defer(b"e", false));
+    }
+
+    #[test]
+    fn test_output_throttled() {
+        let limits = SessionLimits {
+            max_output_rate: Some(1024),
+            ..SessionLimits::default()
+        };
+        let (child, session) =
+            crate::test_session::TestSession::spawn(None, SharedSize::new(24, 80), &limits)
+                .unwrap();
+        assert!(!session.is_throttled());
+        child.feed(vec![b'x'; 4096]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !session.is_throttled() {
+            assert!(Instant::now() < deadline, "never throttled");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        child.exit(0);
+    }
+
+    #[test]
+    fn test_terminal_queries_answered() {
+        let sink = Arc::new(Mutex::new(Vec::new()));
+        let writer: Box<dyn Write + Send> = Box::new(SinkWriter(sink.clone()));
+        let modes = Arc::new(ChildModes::default());
+        let mut parser = Parser::new_with_callbacks(
+            24,
+            80,
+            0,
+            TerminalCallbacks::new(Arc::new(Mutex::new(writer)), modes.clone()),
+        );
+
+        let mut query = |input: &[u8]| {
+            parser.process(input);
+            String::from_utf8(std::mem::take(&mut *sink.lock().unwrap())).unwrap()
+        };
+
+        assert!(query(b"\x1b[>q").starts_with("\x1bP>|shepherd("));
+        assert_eq!(query(b"\x1b[?2004$p"), "\x1b[?2004;2$y");
+        assert_eq!(query(b"\x1b[?2004h\x1b[?2004$p"), "\x1b[?2004;1$y");
+        assert_eq!(query(b"\x1b[?2026$p"), "\x1b[?2026;0$y");
+        assert!(query(b"\x1b]11;?\x1b\\").starts_with("\x1b]11;rgb:"));
+
+        // Modes vt100 ignores are tracked for the host side
+        assert_eq!(query(b"\x1b[?1004h\x1b[?1004$p"), "\x1b[?1004;1$y");
+        assert!(modes.focus_reporting.load(Ordering::Relaxed));
+        query(b"\x1b[6 q");
+        assert_eq!(modes.cursor_style.load(Ordering::Relaxed), 6);
+        assert_eq!(query(b"\x1b]52;c;aGk=\x07"), "");
+        assert_eq!(modes.clipboard.lock().unwrap().as_deref(), Some("c;aGk="));
+    }
+
+    /// Writer that appends into a shared buffer the test can inspect
+    struct SinkWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SinkWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,426 @@
+/// Something the user can trigger, from its hotkey or from the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Help,
+    CommandPalette,
+    NewSession,
+    ListSessions,
+    ToggleShell,
+    Compose,
+    PromptHistory,
+    Transcript,
+    OpenFile,
+    GitHistory,
+    Checkpoint,
+    Checkpoints,
+    UndoChanges,
+    Dispatch,
+    SplitPane,
+    ClosePane,
+    CyclePane,
+    Macros,
+    Approve,
+    Deny,
+    Inbox,
+    TogglePause,
+    SendSignal,
+    Passthrough,
+    Rebase,
+    RunChecks,
+    RunCommand,
+    MemoryStats,
+    ToggleEventLog,
+    ForceRedraw,
+    DebugDump,
+    RenderStats,
+    DismissStatus,
+    CleanupWorktrees,
+    UndoDelete,
+    KillSession,
+    Quit,
+}
+
+/// Where a key binding applies, in the order help lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    Global,
+    ShellView,
+    Selector,
+    Dashboard,
+}
+
+impl Context {
+    pub const ALL: [Context; 4] = [
+        Context::Global,
+        Context::ShellView,
+        Context::Selector,
+        Context::Dashboard,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Context::Global => "Anywhere",
+            Context::ShellView => "Shell view",
+            Context::Selector => "Session list",
+            Context::Dashboard => "Dashboard",
+        }
+    }
+}
+
+/// An action with its hotkey and a short description
+pub struct ActionInfo {
+    pub action: Action,
+    /// Empty for actions only run from the command palette
+    pub key: &'static str,
+    pub description: &'static str,
+}
+
+impl ActionInfo {
+    /// Only works inside a git repository
+    pub fn git_only(&self) -> bool {
+        matches!(
+            self.action,
+            Action::Rebase
+                | Action::UndoDelete
+                | Action::CleanupWorktrees
+                | Action::GitHistory
+                | Action::Checkpoint
+                | Action::Checkpoints
+                | Action::UndoChanges
+        )
+    }
+
+    /// Only works in shell view
+    pub fn shell_only(&self) -> bool {
+        self.context() == Context::ShellView
+    }
+
+    pub fn context(&self) -> Context {
+        match self.action {
+            Action::SplitPane | Action::ClosePane | Action::CyclePane => Context::ShellView,
+            _ => Context::Global,
+        }
+    }
+}
+
+/// A key that is not a palette action: view-specific keys, or ones taking an argument
+pub struct KeyHint {
+    pub context: Context,
+    pub key: &'static str,
+    pub description: &'static str,
+    /// Only works inside a git repository
+    pub git_only: bool,
+}
+
+const fn hint(context: Context, key: &'static str, description: &'static str) -> KeyHint {
+    KeyHint {
+        context,
+        key,
+        description,
+        git_only: false,
+    }
+}
+
+pub const KEY_HINTS: &[KeyHint] = &[
+    hint(Context::Global, "alt+1-9", "Jump to session N"),
+    hint(Context::Selector, "type", "Filter sessions"),
+    hint(
+        Context::Selector,
+        "up/down",
+        "Move (previews live sessions)",
+    ),
+    hint(Context::Selector, "enter", "Open, resume or create"),
+    hint(Context::Selector, "esc", "Back to the previous session"),
+    hint(Context::Selector, "tab", "Mark entry"),
+    hint(Context::Selector, "ctrl+x", "Kill marked sessions"),
+    hint(Context::Selector, "ctrl+b", "Resume marked in background"),
+    hint(Context::Selector, "ctrl+o", "Dashboard of marked sessions"),
+    hint(Context::Selector, "ctrl+f", "Pin/unpin session"),
+    hint(
+        Context::Selector,
+        "ctrl+a",
+        "Show other repos' recent sessions",
+    ),
+    KeyHint {
+        git_only: true,
+        ..hint(Context::Selector, "ctrl+d", "Delete worktree")
+    },
+    hint(Context::Selector, "alt+p", "Pause/resume session"),
+    hint(Context::Dashboard, "arrows/tab", "Move focus"),
+    hint(Context::Dashboard, "1-9", "Open tile N"),
+    hint(Context::Dashboard, "enter", "Open focused session"),
+    hint(Context::Dashboard, "esc", "Close"),
+];
+
+/// Every binding as (key, description), grouped by context in help order. `git`: include
+/// the ones that only work inside a git repository.
+pub fn help_sections(git: bool) -> Vec<(Context, Vec<(&'static str, &'static str)>)> {
+    Context::ALL
+        .into_iter()
+        .map(|context| {
+            let actions = ACTIONS
+                .iter()
+                .filter(|info| info.context() == context && !info.key.is_empty())
+                .filter(|info| git || !info.git_only())
+                .map(|info| (info.key, info.description));
+            let hints = KEY_HINTS
+                .iter()
+                .filter(|hint| hint.context == context && (git || !hint.git_only))
+                .map(|hint| (hint.key, hint.description));
+            (context, actions.chain(hints).collect::<Vec<_>>())
+        })
+        .filter(|(_, bindings)| !bindings.is_empty())
+        .collect()
+}
+
+/// The bytes a terminal sends for a key like "alt+b" or "ctrl+]"
+pub fn key_bytes(key: &str) -> Option<Vec<u8>> {
+    if let Some(rest) = key.strip_prefix("alt+")
+        && let [c] = rest.as_bytes()
+    {
+        return Some(vec![0x1b, *c]);
+    }
+    if let Some(rest) = key.strip_prefix("ctrl+")
+        && let [c @ (b'a'..=b'z' | b'[' | b'\\' | b']' | b'^' | b'_')] = rest.as_bytes()
+    {
+        return Some(vec![c & 0x1f]);
+    }
+    None
+}
+
+/// The action whose hotkey sends `bytes`
+pub fn action_for_key(bytes: &[u8]) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|info| key_bytes(info.key).as_deref() == Some(bytes))
+        .map(|info| info.action)
+}
+
+pub const ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::Help,
+        key: "ctrl+h",
+        description: "Help",
+    },
+    ActionInfo {
+        action: Action::CommandPalette,
+        key: "ctrl+p",
+        description: "Command palette",
+    },
+    ActionInfo {
+        action: Action::NewSession,
+        key: "ctrl+n",
+        description: "New session",
+    },
+    ActionInfo {
+        action: Action::ListSessions,
+        key: "ctrl+l",
+        description: "List sessions",
+    },
+    ActionInfo {
+        action: Action::ToggleShell,
+        key: "ctrl+t",
+        description: "Toggle shell",
+    },
+    ActionInfo {
+        action: Action::Compose,
+        key: "ctrl+e",
+        description: "Compose prompt ($EDITOR)",
+    },
+    ActionInfo {
+        action: Action::PromptHistory,
+        key: "alt+h",
+        description: "Prompt history",
+    },
+    ActionInfo {
+        action: Action::Transcript,
+        key: "alt+v",
+        description: "View Claude transcript",
+    },
+    ActionInfo {
+        action: Action::OpenFile,
+        key: "alt+o",
+        description: "Open file from output in editor",
+    },
+    ActionInfo {
+        action: Action::GitHistory,
+        key: "alt+g",
+        description: "Git log/blame of file on screen",
+    },
+    ActionInfo {
+        action: Action::Checkpoint,
+        key: "alt+k",
+        description: "Checkpoint worktree",
+    },
+    ActionInfo {
+        action: Action::Checkpoints,
+        key: "alt+a",
+        description: "Browse/restore checkpoints",
+    },
+    ActionInfo {
+        action: Action::UndoChanges,
+        key: "alt+e",
+        description: "Undo changes since checkpoint",
+    },
+    ActionInfo {
+        action: Action::Dispatch,
+        key: "alt+q",
+        description: "Dispatch tasks to sessions",
+    },
+    ActionInfo {
+        action: Action::SplitPane,
+        key: "ctrl+\\",
+        description: "Split shell pane",
+    },
+    ActionInfo {
+        action: Action::ClosePane,
+        key: "ctrl+w",
+        description: "Close shell pane",
+    },
+    ActionInfo {
+        action: Action::CyclePane,
+        key: "ctrl+y",
+        description: "Next shell pane",
+    },
+    ActionInfo {
+        action: Action::Macros,
+        key: "ctrl+]",
+        description: "Macros",
+    },
+    ActionInfo {
+        action: Action::Approve,
+        key: "alt+y",
+        description: "Approve waiting session",
+    },
+    ActionInfo {
+        action: Action::Deny,
+        key: "alt+n",
+        description: "Deny waiting session",
+    },
+    ActionInfo {
+        action: Action::Inbox,
+        key: "alt+i",
+        description: "Attention inbox",
+    },
+    ActionInfo {
+        action: Action::TogglePause,
+        key: "alt+p",
+        description: "Pause/resume session",
+    },
+    ActionInfo {
+        action: Action::SendSignal,
+        key: "alt+s",
+        description: "Send signal",
+    },
+    ActionInfo {
+        action: Action::Passthrough,
+        key: "alt+t",
+        description: "Pass-through mode (alt+t to leave)",
+    },
+    ActionInfo {
+        action: Action::Rebase,
+        key: "alt+r",
+        description: "Rebase onto base branch",
+    },
+    ActionInfo {
+        action: Action::RunChecks,
+        key: "alt+c",
+        description: "Run checks",
+    },
+    ActionInfo {
+        action: Action::RunCommand,
+        key: "alt+j",
+        description: "Run a one-off command",
+    },
+    ActionInfo {
+        action: Action::MemoryStats,
+        key: "alt+m",
+        description: "Memory per session",
+    },
+    ActionInfo {
+        action: Action::ToggleEventLog,
+        key: "alt+l",
+        description: "Toggle event log",
+    },
+    ActionInfo {
+        action: Action::ForceRedraw,
+        key: "alt+z",
+        description: "Force redraw",
+    },
+    ActionInfo {
+        action: Action::DebugDump,
+        key: "alt+w",
+        description: "Write debug dump",
+    },
+    ActionInfo {
+        action: Action::RenderStats,
+        key: "",
+        description: "Render stats overlay",
+    },
+    ActionInfo {
+        action: Action::DismissStatus,
+        key: "alt+x",
+        description: "Dismiss status message",
+    },
+    ActionInfo {
+        action: Action::CleanupWorktrees,
+        key: "ctrl+k",
+        description: "Cleanup worktrees",
+    },
+    ActionInfo {
+        action: Action::UndoDelete,
+        key: "alt+u",
+        description: "Undo worktree delete",
+    },
+    ActionInfo {
+        action: Action::KillSession,
+        key: "ctrl+x",
+        description: "Kill session",
+    },
+    ActionInfo {
+        action: Action::Quit,
+        key: "ctrl+d",
+        description: "Quit",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_sections() {
+        let keys = |git| -> Vec<&str> {
+            help_sections(git)
+                .into_iter()
+                .flat_map(|(_, bindings)| bindings.into_iter().map(|(key, _)| key))
+                .collect()
+        };
+        assert!(keys(true).contains(&"ctrl+k"));
+        assert!(!keys(false).contains(&"ctrl+k"));
+        let sections = help_sections(true);
+        assert_eq!(sections[0].0, Context::Global);
+        assert!(sections.iter().any(|(context, bindings)| {
+            *context == Context::ShellView && bindings.contains(&("ctrl+w", "Close shell pane"))
+        }));
+    }
+
+    #[test]
+    fn test_action_keys() {
+        assert_eq!(key_bytes("alt+k"), Some(vec![0x1b, b'k']));
+        assert_eq!(key_bytes("ctrl+h"), Some(vec![0x08]));
+        assert_eq!(key_bytes("ctrl+\\"), Some(vec![0x1c]));
+        assert_eq!(key_bytes("ctrl+]"), Some(vec![0x1d]));
+        assert_eq!(key_bytes("alt+1-9"), None);
+        assert_eq!(action_for_key(&[0x10]), Some(Action::CommandPalette));
+        assert_eq!(action_for_key(b"x"), None);
+
+        // Every bound action has a key of its own
+        let bound: Vec<_> = ACTIONS.iter().filter(|info| !info.key.is_empty()).collect();
+        let mut keys: Vec<_> = bound.iter().map(|info| key_bytes(info.key)).collect();
+        assert!(keys.iter().all(Option::is_some));
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), bound.len());
+    }
+}
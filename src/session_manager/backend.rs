@@ -0,0 +1,83 @@
+use std::io::{self, Stdout};
+
+use ratatui::backend::{Backend, ClearType, CrosstermBackend, TestBackend, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::layout::{Position, Size};
+
+/// Where the UI is drawn: the host terminal, or an in-memory buffer nobody sees when a
+/// script or test drives the manager without one
+pub enum UiBackend {
+    Host(CrosstermBackend<Stdout>),
+    Headless(TestBackend),
+}
+
+impl UiBackend {
+    /// Whether this draws to the host terminal, which must be restored on exit
+    pub fn is_host(&self) -> bool {
+        matches!(self, Self::Host(_))
+    }
+}
+
+/// Run `$call` on whichever backend `$self` holds, as `$backend`
+macro_rules! delegate {
+    ($self:expr, $backend:ident => $call:expr) => {
+        match $self {
+            UiBackend::Host($backend) => $call,
+            UiBackend::Headless($backend) => {
+                let Ok(result) = $call;
+                Ok(result)
+            }
+        }
+    };
+}
+
+impl Backend for UiBackend {
+    type Error = io::Error;
+
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        delegate!(self, backend => backend.draw(content))
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        delegate!(self, backend => backend.append_lines(n))
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        delegate!(self, backend => backend.hide_cursor())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        delegate!(self, backend => backend.show_cursor())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        delegate!(self, backend => backend.get_cursor_position())
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        delegate!(self, backend => backend.set_cursor_position(position))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        delegate!(self, backend => backend.clear())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        delegate!(self, backend => backend.clear_region(clear_type))
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        delegate!(self, backend => backend.size())
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        delegate!(self, backend => backend.window_size())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        delegate!(self, backend => backend.flush())
+    }
+}
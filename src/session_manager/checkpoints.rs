@@ -0,0 +1,331 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::ShepardError;
+
+/// Checkpoints are commits of a worktree's full state (tracked and untracked files, minus
+/// ignored ones) kept under `refs/shepherd/checkpoints/<worktree>/<n>`. They never touch
+/// the branch, the index or the stash.
+const REF_PREFIX: &str = "refs/shepherd/checkpoints";
+
+/// A saved state of a worktree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Sequence number within the worktree, starting at 1
+    pub number: u32,
+    pub commit: String,
+    pub message: String,
+    /// Unix time the checkpoint was taken
+    pub time: i64,
+}
+
+impl Checkpoint {
+    pub fn short(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+}
+
+/// Ref namespace for `worktree`'s checkpoints; refs are shared by all of a repo's worktrees
+fn prefix(worktree: &Path) -> anyhow::Result<String> {
+    let name = worktree
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid path"))?;
+    Ok(format!("{}/{}", REF_PREFIX, name))
+}
+
+/// Save the current state of `worktree` as a new checkpoint
+pub fn create(worktree: &Path, label: &str) -> anyhow::Result<Checkpoint> {
+    let tree = snapshot_tree(worktree)?;
+    let stat = git(worktree, &["diff", "--shortstat", "HEAD", &tree])
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    let number = list(worktree).first().map_or(1, |c| c.number + 1);
+    let message = if stat.is_empty() {
+        format!("{} #{}: no changes", label, number)
+    } else {
+        format!("{} #{}: {}", label, number, stat)
+    };
+
+    let mut commit_tree = Command::new("git");
+    commit_tree
+        .args(["commit-tree", &tree, "-m", &message])
+        // Checkpoints are shepherd's, and must work where no git identity is set up
+        .env("GIT_AUTHOR_NAME", "shepherd")
+        .env("GIT_AUTHOR_EMAIL", "shepherd@localhost")
+        .env("GIT_COMMITTER_NAME", "shepherd")
+        .env("GIT_COMMITTER_EMAIL", "shepherd@localhost")
+        .current_dir(worktree);
+    if let Ok(head) = git(worktree, &["rev-parse", "--verify", "-q", "HEAD"]) {
+        commit_tree.args(["-p", head.trim()]);
+    }
+    let output = commit_tree.output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git commit-tree failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let refname = format!("{}/{}", prefix(worktree)?, number);
+    // The empty old value makes this fail rather than overwrite an existing checkpoint
+    git(worktree, &["update-ref", &refname, &commit, ""])?;
+
+    Ok(Checkpoint {
+        number,
+        commit,
+        message,
+        time: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Checkpoints of `worktree`, newest first
+pub fn list(worktree: &Path) -> Vec<Checkpoint> {
+    let Ok(prefix) = prefix(worktree) else {
+        return Vec::new();
+    };
+    let Ok(output) = git(
+        worktree,
+        &[
+            "for-each-ref",
+            "--format=%(refname)%00%(objectname)%00%(committerdate:unix)%00%(subject)",
+            &format!("{}/", prefix),
+        ],
+    ) else {
+        return Vec::new();
+    };
+    let mut checkpoints: Vec<Checkpoint> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let number = fields.next()?.rsplit('/').next()?.parse().ok()?;
+            Some(Checkpoint {
+                number,
+                commit: fields.next()?.to_string(),
+                time: fields.next()?.parse().ok()?,
+                message: fields.next()?.to_string(),
+            })
+        })
+        .collect();
+    checkpoints.sort_by_key(|c| std::cmp::Reverse(c.number));
+    checkpoints
+}
+
+/// `git diff --stat` from `checkpoint` to the worktree as it is now
+pub fn diff_stat(worktree: &Path, checkpoint: &Checkpoint) -> anyhow::Result<String> {
+    let tree = snapshot_tree(worktree)?;
+    Ok(git(
+        worktree,
+        &["diff", "--stat", &checkpoint.commit, &tree],
+    )?)
+}
+
+/// Full diff from `checkpoint` to the worktree as it is now
+pub fn diff(worktree: &Path, checkpoint: &Checkpoint) -> anyhow::Result<String> {
+    let tree = snapshot_tree(worktree)?;
+    Ok(git(worktree, &["diff", &checkpoint.commit, &tree])?)
+}
+
+/// A file that differs between a checkpoint and the worktree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// Path relative to the worktree
+    pub path: String,
+    /// `git diff --name-status` letter: A(dded), M(odified) or D(eleted) since the checkpoint
+    pub status: char,
+}
+
+/// The latest checkpoint of `worktree`, if any
+pub fn latest(worktree: &Path) -> Option<Checkpoint> {
+    list(worktree).into_iter().next()
+}
+
+/// Files changed in the worktree since `checkpoint`
+pub fn changed_files(worktree: &Path, checkpoint: &Checkpoint) -> anyhow::Result<Vec<ChangedFile>> {
+    let tree = snapshot_tree(worktree)?;
+    let output = git(
+        worktree,
+        &[
+            "diff",
+            "--name-status",
+            "--no-renames",
+            "-z",
+            &checkpoint.commit,
+            &tree,
+        ],
+    )?;
+    // -z gives `status NUL path NUL` pairs, with paths unquoted
+    let fields: Vec<&str> = output.split('\0').collect();
+    Ok(fields
+        .chunks_exact(2)
+        .filter_map(|pair| {
+            Some(ChangedFile {
+                path: pair[1].to_string(),
+                status: pair[0].chars().next()?,
+            })
+        })
+        .collect())
+}
+
+/// Put `files` back the way they were at `checkpoint`, deleting the ones created since
+pub fn revert_files(
+    worktree: &Path,
+    checkpoint: &Checkpoint,
+    files: &[ChangedFile],
+) -> anyhow::Result<()> {
+    let mut paths = Vec::new();
+    for file in files {
+        if file.status == 'A' {
+            std::fs::remove_file(worktree.join(&file.path))?;
+        } else {
+            paths.push(file.path.as_str());
+        }
+    }
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let source = format!("--source={}", checkpoint.commit);
+    let mut args = vec!["restore", &source, "--worktree", "--"];
+    args.extend(paths);
+    git(worktree, &args)?;
+    Ok(())
+}
+
+/// Put the worktree's files back the way they were at `checkpoint`, deleting files created
+/// since. HEAD and the index stay where they are, so the result shows up as changes.
+pub fn restore(worktree: &Path, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+    let files = changed_files(worktree, checkpoint)?;
+    revert_files(worktree, checkpoint, &files)
+}
+
+/// Drop every checkpoint of `worktree`, e.g. when it is deleted
+pub fn remove_all(repo: &Path, worktree: &Path) {
+    let Ok(prefix) = prefix(worktree) else {
+        return;
+    };
+    let Ok(refs) = git(
+        repo,
+        &[
+            "for-each-ref",
+            "--format=%(refname)",
+            &format!("{}/", prefix),
+        ],
+    ) else {
+        return;
+    };
+    for refname in refs.lines() {
+        let _ = git(repo, &["update-ref", "-d", refname]);
+    }
+}
+
+/// Tree of everything in the worktree that isn't ignored, written through a scratch index
+/// so the real one is left alone
+fn snapshot_tree(worktree: &Path) -> anyhow::Result<String> {
+    let index = std::env::temp_dir().join(format!(
+        "shepherd-checkpoint-{}-{}.index",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let run = |args: &[&str]| -> anyhow::Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .env("GIT_INDEX_FILE", &index)
+            .current_dir(worktree)
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    // Starting from a copy of the real index lets git skip rehashing unchanged files
+    if let Ok(real) = git(worktree, &["rev-parse", "--git-path", "index"]) {
+        let _ = std::fs::copy(worktree.join(real.trim()), &index);
+    }
+    let tree = run(&["add", "-A", "."]).and_then(|_| run(&["write-tree"]));
+    let _ = std::fs::remove_file(&index);
+    tree
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, ShepardError> {
+    let context = format!("git {}", args.first().unwrap_or(&""));
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| ShepardError::git(&context, e))?;
+    if !output.status.success() {
+        return Err(ShepardError::git(
+            context,
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{TempDir, git};
+
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let dir = TempDir::new("checkpoints");
+        let repo = dir.repo("feature");
+        std::fs::write(repo.join("a.txt"), "one\n").unwrap();
+        git(&repo, &["add", "a.txt"]);
+        git(&repo, &["commit", "-qm", "a"]);
+
+        std::fs::write(repo.join("a.txt"), "two\n").unwrap();
+        std::fs::write(repo.join("notes.md"), "kept\n").unwrap();
+        let first = create(&repo, "feature").unwrap();
+        assert_eq!(first.number, 1);
+        assert!(
+            first.message.contains("2 files changed"),
+            "{}",
+            first.message
+        );
+        // Taking a checkpoint leaves the index alone
+        assert_eq!(git(&repo, &["diff", "--cached", "--name-only"]), "");
+
+        std::fs::write(repo.join("a.txt"), "three\n").unwrap();
+        std::fs::remove_file(repo.join("notes.md")).unwrap();
+        std::fs::write(repo.join("scratch.rs"), "junk\n").unwrap();
+        assert!(diff_stat(&repo, &first).unwrap().contains("scratch.rs"));
+        assert_eq!(create(&repo, "feature").unwrap().number, 2);
+        assert_eq!(
+            list(&repo).iter().map(|c| c.number).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        // Undo just the edit to a.txt since the latest checkpoint
+        std::fs::write(repo.join("a.txt"), "four\n").unwrap();
+        let latest = latest(&repo).unwrap();
+        let changed = changed_files(&repo, &latest).unwrap();
+        assert_eq!(
+            changed,
+            vec![ChangedFile {
+                path: "a.txt".to_string(),
+                status: 'M'
+            }]
+        );
+        revert_files(&repo, &latest, &changed).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("a.txt")).unwrap(),
+            "three\n"
+        );
+
+        restore(&repo, &first).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(repo.join("a.txt")).unwrap(),
+            "two\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("notes.md")).unwrap(),
+            "kept\n"
+        );
+        assert!(!repo.join("scratch.rs").exists());
+
+        remove_all(&repo, &repo);
+        assert!(list(&repo).is_empty());
+    }
+}
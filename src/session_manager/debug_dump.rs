@@ -0,0 +1,154 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use ratatui::buffer::Buffer;
+
+/// `~/.shepherd/debug`, where Alt+w writes its dumps
+pub fn debug_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".shepherd").join("debug"))
+}
+
+/// A live session as listed in a dump
+pub struct SessionRow {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether it is the session on screen
+    pub active: bool,
+    pub view: String,
+    pub activity: String,
+    pub pid: Option<u32>,
+    pub dead: bool,
+    pub scroll_offset: usize,
+}
+
+/// A focused session's screen as its program drew it
+pub struct ScreenDump {
+    pub title: String,
+    pub contents: String,
+    /// Cursor as (row, col)
+    pub cursor: (u16, u16),
+    pub alternate_screen: bool,
+}
+
+/// What shepherd was showing and doing at one moment, for bug reports about rendering
+/// glitches
+pub struct DebugDump {
+    pub mode: String,
+    /// Area sessions are drawn in, as (rows, cols)
+    pub session_size: (u16, u16),
+    pub sessions: Vec<SessionRow>,
+    /// The frame as last drawn
+    pub frame: Buffer,
+    pub screen: Option<ScreenDump>,
+}
+
+impl DebugDump {
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "shepherd {} debug dump, {}",
+            env!("CARGO_PKG_VERSION"),
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        let _ = writeln!(out, "mode: {}", self.mode);
+        let (rows, cols) = self.session_size;
+        let _ = writeln!(out, "session area: {} rows x {} cols", rows, cols);
+
+        let _ = writeln!(out, "\n== sessions ==");
+        if self.sessions.is_empty() {
+            let _ = writeln!(out, "(none)");
+        }
+        for session in &self.sessions {
+            let pid = session
+                .pid
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string());
+            let _ = writeln!(
+                out,
+                "{} {} pid={} view={} activity={} scroll={}{} {}",
+                if session.active { '*' } else { ' ' },
+                session.name,
+                pid,
+                session.view,
+                session.activity,
+                session.scroll_offset,
+                if session.dead { " dead" } else { "" },
+                session.path.display()
+            );
+        }
+
+        let area = self.frame.area;
+        let _ = writeln!(out, "\n== frame ({}x{}) ==", area.width, area.height);
+        for y in area.top()..area.bottom() {
+            let line: String = (area.left()..area.right())
+                .map(|x| self.frame[(x, y)].symbol())
+                .collect();
+            let _ = writeln!(out, "{}", line);
+        }
+
+        if let Some(ref screen) = self.screen {
+            let _ = writeln!(
+                out,
+                "\n== screen of {} (cursor {},{}{}) ==",
+                screen.title,
+                screen.cursor.0,
+                screen.cursor.1,
+                if screen.alternate_screen {
+                    ", alternate screen"
+                } else {
+                    ""
+                }
+            );
+            let _ = writeln!(out, "{}", screen.contents);
+        }
+        out
+    }
+
+    /// Write the dump to a new file in `dir`, returning its path
+    pub fn write(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "dump-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")
+        ));
+        std::fs::write(&path, self.render())?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_render() {
+        let mut frame = Buffer::empty(Rect::new(0, 0, 6, 2));
+        frame.set_string(0, 1, "hi", ratatui::style::Style::default());
+        let dump = DebugDump {
+            mode: "Normal".to_string(),
+            session_size: (20, 80),
+            sessions: vec![SessionRow {
+                name: "fix-ci".to_string(),
+                path: PathBuf::from("/w/fix-ci"),
+                active: true,
+                view: "Claude".to_string(),
+                activity: "Stopped".to_string(),
+                pid: Some(42),
+                dead: false,
+                scroll_offset: 0,
+            }],
+            frame,
+            screen: Some(ScreenDump {
+                title: "fix-ci".to_string(),
+                contents: "$ cargo build".to_string(),
+                cursor: (0, 13),
+                alternate_screen: false,
+            }),
+        };
+        let text = dump.render();
+        assert!(text.contains("* fix-ci pid=42 view=Claude activity=Stopped scroll=0 /w/fix-ci"));
+        assert!(text.contains("== frame (6x2) ==\n      \nhi    \n"));
+        assert!(text.contains("== screen of fix-ci (cursor 0,13) ==\n$ cargo build\n"));
+    }
+}
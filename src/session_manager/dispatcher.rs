@@ -0,0 +1,162 @@
+/// Where a dispatched task is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskState {
+    Queued,
+    /// Sent to this session, waiting for it to stop
+    Running(String),
+    /// Finished in this session
+    Done(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub text: String,
+    pub state: TaskState,
+}
+
+/// Tasks queued in the dispatcher, handed out in order to idle sessions. Sessions take
+/// turns: the next task goes to the first idle session after the one served last.
+#[derive(Debug, Default)]
+pub struct Dispatcher {
+    tasks: Vec<Task>,
+    /// Session that was given the latest task
+    last_assigned: Option<String>,
+}
+
+impl Dispatcher {
+    /// Queue each non-empty line of `text` as a task, returning how many were added
+    pub fn queue(&mut self, text: &str) -> usize {
+        let before = self.tasks.len();
+        self.tasks.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| Task {
+                    text: line.to_string(),
+                    state: TaskState::Queued,
+                }),
+        );
+        self.tasks.len() - before
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn has_queued(&self) -> bool {
+        self.tasks.iter().any(|t| t.state == TaskState::Queued)
+    }
+
+    /// Whether no task is queued or running
+    pub fn is_finished(&self) -> bool {
+        self.tasks
+            .iter()
+            .all(|t| matches!(t.state, TaskState::Done(_)))
+    }
+
+    /// Whether `session` is working on a task
+    pub fn is_busy(&self, session: &str) -> bool {
+        self.running(session).is_some()
+    }
+
+    fn running(&self, session: &str) -> Option<usize> {
+        self.tasks
+            .iter()
+            .position(|t| matches!(&t.state, TaskState::Running(s) if s == session))
+    }
+
+    /// The idle session whose turn it is, given all live sessions in slot order
+    pub fn next_session<'a>(
+        &self,
+        live: &'a [String],
+        is_idle: impl Fn(&str) -> bool,
+    ) -> Option<&'a str> {
+        let start = self
+            .last_assigned
+            .as_ref()
+            .and_then(|last| live.iter().position(|name| name == last))
+            .map_or(0, |i| i + 1);
+        live[start..]
+            .iter()
+            .chain(&live[..start])
+            .map(String::as_str)
+            .find(|name| is_idle(name))
+    }
+
+    /// Hand the oldest queued task to `session`, returning its text
+    pub fn assign(&mut self, session: &str) -> Option<String> {
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|t| t.state == TaskState::Queued)?;
+        task.state = TaskState::Running(session.to_string());
+        self.last_assigned = Some(session.to_string());
+        Some(task.text.clone())
+    }
+
+    /// Mark `session`'s task finished, returning its text
+    pub fn complete(&mut self, session: &str) -> Option<String> {
+        let index = self.running(session)?;
+        let task = &mut self.tasks[index];
+        task.state = TaskState::Done(session.to_string());
+        Some(task.text.clone())
+    }
+
+    /// Put `session`'s task back in the queue, keeping its place
+    pub fn requeue(&mut self, session: &str) {
+        if let Some(index) = self.running(session) {
+            self.tasks[index].state = TaskState::Queued;
+        }
+    }
+
+    /// Requeue the tasks of sessions that are no longer in `live`, returning how many
+    pub fn requeue_missing(&mut self, live: &[String]) -> usize {
+        let mut requeued = 0;
+        for task in &mut self.tasks {
+            if let TaskState::Running(session) = &task.state
+                && !live.contains(session)
+            {
+                task.state = TaskState::Queued;
+                requeued += 1;
+            }
+        }
+        requeued
+    }
+
+    /// Drop finished tasks
+    pub fn clear_done(&mut self) {
+        self.tasks
+            .retain(|t| !matches!(t.state, TaskState::Done(_)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispatcher_round_robin() {
+        let mut dispatcher = Dispatcher::default();
+        assert_eq!(dispatcher.queue("fix lint\n\n  add docs \nbump deps"), 3);
+        let live = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let idle = |name: &str| name != "c";
+
+        assert_eq!(dispatcher.next_session(&live, idle), Some("a"));
+        assert_eq!(dispatcher.assign("a").as_deref(), Some("fix lint"));
+        // "a" was served last, so "b" is next even though both are idle
+        assert_eq!(dispatcher.next_session(&live, idle), Some("b"));
+        assert_eq!(dispatcher.assign("b").as_deref(), Some("add docs"));
+        assert_eq!(dispatcher.next_session(&live, idle), Some("a"));
+
+        assert_eq!(dispatcher.complete("a").as_deref(), Some("fix lint"));
+        assert!(dispatcher.complete("a").is_none());
+        assert_eq!(dispatcher.requeue_missing(&["a".to_string()]), 1);
+        assert_eq!(dispatcher.assign("a").as_deref(), Some("add docs"));
+        assert!(dispatcher.is_busy("a"));
+        assert!(dispatcher.has_queued());
+        assert!(!dispatcher.is_finished());
+
+        dispatcher.clear_done();
+        assert_eq!(dispatcher.tasks().len(), 2);
+    }
+}
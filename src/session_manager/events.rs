@@ -0,0 +1,185 @@
+use std::io::Read;
+use std::os::fd::AsFd;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use nix::poll::{PollFd, PollFlags, poll};
+
+use crate::status_socket::{CommandRequest, SocketMessage, StatusEvent, StatusSocket};
+
+use super::git_status::GitStatus;
+use super::tasks::DeletedWorktrees;
+use super::ui::WorktreeEntry;
+use crate::error::ShepardError;
+use crate::workflows::SessionMetadata;
+
+const BUF_SIZE: usize = 1024;
+/// How often the event thread wakes to check for shutdown
+const POLL_INTERVAL_MS: u16 = 100;
+/// Interval between Tick events (frame cadence for rendering session output)
+pub const TICK_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Everything the run loop reacts to, delivered over a single channel.
+/// New subsystems plug in by sending their own variant on a clone of the sender.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// Raw bytes read from stdin
+    Input(Vec<u8>),
+    /// The host terminal was resized (SIGWINCH)
+    Resize,
+    /// A hook event arrived on the status socket
+    StatusSocket(StatusEvent),
+    /// A script or editor plugin sent a command on the status socket
+    Command(CommandRequest),
+    /// The child process of the named session exited
+    SessionDied(String),
+    /// Fresh git status for the checkout at the given path
+    GitStatus(PathBuf, GitStatus),
+    /// The workflow creating a session on a worker thread started a step
+    CreationStep(String),
+    /// The workflow creating the named session on a worker thread finished
+    SessionPrepared(String, Result<SessionMetadata, ShepardError>),
+    /// The worktrees of the repo at the given root were read for the cleanup dialog
+    WorktreesLoaded(PathBuf, Vec<WorktreeEntry>),
+    /// A worktree deletion on the task runner finished
+    WorktreesDeleted(DeletedWorktrees),
+    /// Periodic wakeup for rendering output and expiring status messages
+    Tick,
+}
+
+/// Background threads producing `AppEvent`s: one multiplexing stdin, SIGWINCH and the
+/// status socket with poll(2), and one emitting ticks. Both are stopped and joined on drop.
+pub struct EventSource {
+    shutdown: Arc<AtomicBool>,
+    /// Set while another program (e.g. an editor) owns stdin
+    stdin_paused: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl EventSource {
+    pub fn spawn(
+        tx: Sender<AppEvent>,
+        status_socket: Option<StatusSocket>,
+        read_stdin: bool,
+    ) -> anyhow::Result<Self> {
+        let (mut winch_rx, winch_tx) = UnixStream::pair()?;
+        winch_rx.set_nonblocking(true)?;
+        signal_hook::low_level::pipe::register(signal_hook::consts::SIGWINCH, winch_tx)?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let tick_shutdown = shutdown.clone();
+        let tick_tx = tx.clone();
+        let ticker = std::thread::spawn(move || {
+            while !tick_shutdown.load(Ordering::Acquire) {
+                std::thread::sleep(TICK_INTERVAL);
+                if tick_tx.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stdin_paused = Arc::new(AtomicBool::new(!read_stdin));
+        let thread_stdin_paused = stdin_paused.clone();
+        let thread_shutdown = shutdown.clone();
+        let thread = std::thread::spawn(move || {
+            let stdin = std::io::stdin();
+            let mut buf = [0u8; BUF_SIZE];
+
+            while !thread_shutdown.load(Ordering::Acquire) {
+                let stdin_flags = if thread_stdin_paused.load(Ordering::Acquire) {
+                    PollFlags::empty()
+                } else {
+                    PollFlags::POLLIN
+                };
+                let mut fds = vec![
+                    PollFd::new(stdin.as_fd(), stdin_flags),
+                    PollFd::new(winch_rx.as_fd(), PollFlags::POLLIN),
+                ];
+                if let Some(ref socket) = status_socket {
+                    fds.push(PollFd::new(socket.as_fd(), PollFlags::POLLIN));
+                }
+
+                match poll(&mut fds, POLL_INTERVAL_MS) {
+                    Ok(0) => continue,
+                    Ok(_) => {}
+                    Err(nix::errno::Errno::EINTR) => continue,
+                    Err(_) => break,
+                }
+
+                let ready: Vec<bool> = fds
+                    .iter()
+                    .map(|fd| fd.revents().is_some_and(|r| !r.is_empty()))
+                    .collect();
+                drop(fds);
+
+                if ready[0] && !thread_stdin_paused.load(Ordering::Acquire) {
+                    // Read the raw fd directly - std's Stdin buffers internally, which
+                    // would hide pending bytes from poll()
+                    match nix::unistd::read(&stdin, &mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            if tx.send(AppEvent::Input(buf[..n].to_vec())).is_err() {
+                                break;
+                            }
+                        }
+                        Err(nix::errno::Errno::EINTR | nix::errno::Errno::EAGAIN) => {}
+                        Err(_) => break,
+                    }
+                }
+
+                if ready[1] {
+                    // Drain the signal pipe; multiple resizes collapse into one event
+                    let mut drain = [0u8; 64];
+                    while matches!(winch_rx.read(&mut drain), Ok(n) if n > 0) {}
+                    if tx.send(AppEvent::Resize).is_err() {
+                        break;
+                    }
+                }
+
+                if ready.get(2).copied().unwrap_or(false)
+                    && let Some(ref socket) = status_socket
+                {
+                    for message in socket.poll() {
+                        let event = match message {
+                            SocketMessage::Status(event) => AppEvent::StatusSocket(event),
+                            SocketMessage::Command(request) => AppEvent::Command(request),
+                        };
+                        if tx.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            shutdown,
+            stdin_paused,
+            threads: vec![thread, ticker],
+        })
+    }
+
+    /// Stop (or resume) reading stdin, so a child program run in the foreground gets
+    /// all of its input. Pausing waits out a poll already in progress.
+    pub fn pause_stdin(&self, paused: bool) {
+        self.stdin_paused.store(paused, Ordering::Release);
+        if paused {
+            std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS as u64 * 2));
+        }
+    }
+}
+
+impl Drop for EventSource {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
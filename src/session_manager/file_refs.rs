@@ -0,0 +1,67 @@
+use std::path::{Path, PathBuf};
+
+/// A file mentioned in session output or asked for with `shepherd open`, e.g.
+/// `src/main.rs:42:7` (the column is ignored)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRef {
+    pub path: PathBuf,
+    pub line: Option<u32>,
+}
+
+impl FileRef {
+    /// Parse `path[:line[:column]]`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split(':');
+        let path = parts.next().filter(|p| !p.is_empty())?;
+        let line = parts.next().and_then(|l| l.parse().ok());
+        Some(Self {
+            path: PathBuf::from(path),
+            line,
+        })
+    }
+
+    /// The path, with relative paths taken from `root`
+    pub fn resolve(&self, root: &Path) -> PathBuf {
+        root.join(&self.path)
+    }
+}
+
+/// The file reference nearest the bottom of `text` (rightmost on its line) naming a file
+/// that exists under `root`
+pub fn last_file_ref(text: &str, root: &Path) -> Option<FileRef> {
+    text.lines().rev().find_map(|line| {
+        line.split(|c: char| c.is_whitespace() || "'\"`()[]<>,".contains(c))
+            .rev()
+            .map(|token| token.trim_end_matches(['.', ':', ';']))
+            .filter_map(FileRef::parse)
+            .find(|file| file.resolve(root).is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_file_ref() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let output = "Edited src/main.rs:12 and Cargo.toml\n\
+                      ● Update(`src/lib.rs:3:5`), see notes.md:4\n\
+                      Done.";
+        assert_eq!(
+            last_file_ref(output, root),
+            Some(FileRef {
+                path: PathBuf::from("src/lib.rs"),
+                line: Some(3),
+            })
+        );
+        assert_eq!(
+            last_file_ref("Cargo.toml: updated.", root),
+            Some(FileRef {
+                path: PathBuf::from("Cargo.toml"),
+                line: None,
+            })
+        );
+        assert!(last_file_ref("nothing to see: here", root).is_none());
+    }
+}
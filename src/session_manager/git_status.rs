@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Sender, unbounded};
+
+use super::events::AppEvent;
+use crate::error::ShepardError;
+use crate::git_backend;
+pub use crate::git_backend::GitStatus;
+
+/// Test-merges session branches against the base branch with `git merge-tree`, caching
+/// the result per checkout until either side moves
+struct ConflictChecker {
+    /// Configured base branch; main/master when unset
+    base_branch: Option<String>,
+    /// Per checkout: "<head>..<base>" commits last checked, and the conflicting files
+    cache: HashMap<PathBuf, (String, Vec<String>)>,
+}
+
+impl ConflictChecker {
+    fn new(base_branch: Option<String>) -> Self {
+        Self {
+            base_branch,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Conflicting files between the checkout's HEAD and the base branch. Empty when they
+    /// merge cleanly, HEAD is detached, or the checkout is on the base branch itself.
+    fn check(&mut self, path: &Path, status: &GitStatus) -> Vec<String> {
+        let Some(ref branch) = status.branch else {
+            return Vec::new();
+        };
+        let Some((base_branch, base_ref)) = base_ref(path, self.base_branch.as_deref()) else {
+            return Vec::new();
+        };
+        if *branch == base_branch {
+            return Vec::new();
+        }
+
+        let Some(key) = git_stdout(path, &["rev-parse", "HEAD", &base_ref])
+            .map(|revs| revs.split_whitespace().collect::<Vec<_>>().join(".."))
+        else {
+            return Vec::new();
+        };
+        if let Some((cached_key, conflicts)) = self.cache.get(path)
+            && *cached_key == key
+        {
+            return conflicts.clone();
+        }
+
+        let Ok(output) = Command::new("git")
+            .args(["merge-tree", "--write-tree", "--name-only", "--no-messages"])
+            .args([base_ref.as_str(), "HEAD"])
+            .current_dir(path)
+            .output()
+        else {
+            return Vec::new();
+        };
+        // Exit status 1 means the merge has conflicts; anything else non-zero is an error
+        let conflicts = match output.status.code() {
+            Some(1) => parse_conflicts(&String::from_utf8_lossy(&output.stdout)),
+            _ => Vec::new(),
+        };
+        self.cache
+            .insert(path.to_path_buf(), (key, conflicts.clone()));
+        conflicts
+    }
+}
+
+/// The base branch sessions are compared with (`base_branch`, else main/master) and the
+/// ref to use for it, preferring origin's copy
+pub fn base_ref(path: &Path, base_branch: Option<&str>) -> Option<(String, String)> {
+    let branches = match base_branch {
+        Some(branch) => vec![branch.to_string()],
+        None => vec!["main".to_string(), "master".to_string()],
+    };
+    for branch in branches {
+        for candidate in [format!("origin/{}", branch), branch.clone()] {
+            if git_backend::get().has_commit(path, &candidate) {
+                return Some((branch, candidate));
+            }
+        }
+    }
+    None
+}
+
+/// What a worktree's branch holds, to judge whether the worktree is safe to delete
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BranchInfo {
+    /// Checked-out branch, or None when HEAD is detached
+    pub branch: Option<String>,
+    /// HEAD is already contained in the base branch
+    pub merged: bool,
+    /// Uncommitted changes or untracked files present
+    pub dirty: bool,
+    /// Unix time of the last commit
+    pub last_commit: Option<i64>,
+}
+
+impl BranchInfo {
+    /// Inspect the checkout at `path`, comparing it with `base_ref` (see [`base_ref`])
+    pub fn load(path: &Path, base_ref: Option<&str>) -> Self {
+        let backend = git_backend::get();
+        let status = backend.status(path).unwrap_or_default();
+        // Merged when HEAD has nothing the base branch lacks
+        let merged = base_ref.is_some_and(|base| {
+            backend
+                .ahead_behind(path, "HEAD", base)
+                .is_some_and(|(ahead, _)| ahead == 0)
+        });
+        Self {
+            branch: status.branch,
+            merged,
+            dirty: status.dirty,
+            last_commit: backend.last_commit_time(path),
+        }
+    }
+}
+
+/// Whether a rebase is stopped part way in the checkout at `path`
+pub fn rebase_in_progress(path: &Path) -> bool {
+    ["rebase-merge", "rebase-apply"].iter().any(|dir| {
+        git_stdout(path, &["rev-parse", "--git-path", dir])
+            .is_some_and(|git_path| path.join(git_path.trim()).exists())
+    })
+}
+
+/// One line per commit touching `file` in the checkout at `path`, following renames
+pub fn file_log(path: &Path, file: &Path) -> Result<Vec<String>, ShepardError> {
+    git_lines(
+        path,
+        &[
+            "log",
+            "--follow",
+            "--date=short",
+            "--format=%h %ad %an  %s",
+            "--",
+        ],
+        file,
+    )
+}
+
+/// `git blame` of `file` in the checkout at `path`, one line per line of the file
+pub fn file_blame(path: &Path, file: &Path) -> Result<Vec<String>, ShepardError> {
+    git_lines(path, &["blame", "--date=short", "--"], file)
+}
+
+fn git_lines(dir: &Path, args: &[&str], file: &Path) -> Result<Vec<String>, ShepardError> {
+    let context = format!("git {}", args[0]);
+    let output = Command::new("git")
+        .args(args)
+        .arg(file)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| ShepardError::git(&context, e))?;
+    if !output.status.success() {
+        return Err(ShepardError::git(
+            context,
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// File names listed before the first blank line of `git merge-tree --name-only`, after
+/// the tree id
+fn parse_conflicts(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Stdout of a successful git command run in `dir`
+fn git_stdout(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Longest a background fetch may run before it is killed
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// `git fetch` in `repo`, never stopping to ask for a password or passphrase. Killed after
+/// `FETCH_TIMEOUT`, or as soon as `closed` is set.
+fn fetch(repo: &Path, closed: &AtomicBool) {
+    let mut command = Command::new("git");
+    command
+        .args(["fetch", "--quiet"])
+        .current_dir(repo)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // A user's own ssh command is left alone
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    let Ok(mut child) = command.spawn() else {
+        return;
+    };
+    let deadline = Instant::now() + FETCH_TIMEOUT;
+    while let Ok(None) = child.try_wait() {
+        if closed.load(Ordering::Acquire) || Instant::now() >= deadline {
+            tracing::warn!(repo = %repo.display(), "background git fetch killed");
+            let _ = child.kill();
+            let _ = child.wait();
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Runs git status queries and conflict checks off the UI thread, and background fetches
+/// on a thread of their own so a slow remote doesn't hold up status. Results arrive as
+/// `AppEvent::GitStatus`.
+pub struct GitStatusWorker {
+    /// Checkouts to refresh
+    requests: Option<Sender<PathBuf>>,
+    thread: Option<JoinHandle<()>>,
+    /// A fetch is running; further ones are skipped until it finishes
+    fetching: Arc<AtomicBool>,
+    /// Set on drop to cut a running fetch short
+    closed: Arc<AtomicBool>,
+}
+
+impl GitStatusWorker {
+    pub fn spawn(events: Sender<AppEvent>, base_branch: Option<String>) -> Self {
+        let (requests, rx) = unbounded::<PathBuf>();
+        let thread = std::thread::spawn(move || {
+            let mut conflicts = ConflictChecker::new(base_branch);
+            while let Ok(path) = rx.recv() {
+                // Collapse requests that piled up while git was running
+                let paths: HashSet<PathBuf> = std::iter::once(path).chain(rx.try_iter()).collect();
+                for path in paths {
+                    let Some(mut status) = git_backend::get().status(&path) else {
+                        continue;
+                    };
+                    status.conflicts = conflicts.check(&path, &status);
+                    if events.send(AppEvent::GitStatus(path, status)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Self {
+            requests: Some(requests),
+            thread: Some(thread),
+            fetching: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Queue a refresh of the checkout at `path`
+    pub fn refresh(&self, path: &Path) {
+        if let Some(ref requests) = self.requests {
+            let _ = requests.send(path.to_path_buf());
+        }
+    }
+
+    /// Start a `git fetch` in `repo`, followed by a refresh of `checkouts`. Does nothing
+    /// while the previous fetch is still running.
+    pub fn fetch(&self, repo: &Path, checkouts: Vec<PathBuf>) {
+        let Some(requests) = self.requests.clone() else {
+            return;
+        };
+        if self.fetching.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let repo = repo.to_path_buf();
+        let fetching = self.fetching.clone();
+        let closed = self.closed.clone();
+        std::thread::spawn(move || {
+            fetch(&repo, &closed);
+            fetching.store(false, Ordering::Release);
+            if !closed.load(Ordering::Acquire) {
+                for path in checkouts {
+                    let _ = requests.send(path);
+                }
+            }
+        });
+    }
+}
+
+impl Drop for GitStatusWorker {
+    fn drop(&mut self) {
+        // Closing the channel ends the worker loop, once a running fetch lets go of it
+        self.closed.store(true, Ordering::Release);
+        self.requests = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let output = "c7e931882e6b9c4b5121ca110ad7bad9ebfa0d67\nsrc/lib.rs\nREADME.md\n\nAuto-merging README.md\n";
+        assert_eq!(parse_conflicts(output), vec!["src/lib.rs", "README.md"]);
+        assert!(parse_conflicts("dd66ef9fdea08def26319a06300390c9506c0bce\n").is_empty());
+    }
+}
@@ -0,0 +1,479 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+use crate::config::Config;
+use crate::history::SessionHistory;
+use crate::instances::Registry;
+use crate::test_fixtures::TempDir;
+use crate::test_session::TestSession;
+
+use super::backend::UiBackend;
+use super::events::AppEvent;
+use super::{Host, TuiSessionManager};
+
+/// Size of the in-memory terminal
+const COLS: u16 = 100;
+const ROWS: u16 = 30;
+/// How long `settle` waits for more events before taking the manager as idle
+const QUIET: Duration = Duration::from_millis(50);
+/// Longest `wait_for` waits for text to show up
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const CTRL_N: u8 = 0x0E;
+const CTRL_L: u8 = 0x0C;
+
+/// A session the manager started, with the fake child behind it
+#[derive(Clone)]
+pub struct Spawned {
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub child: TestSession,
+}
+
+/// Runs a `TuiSessionManager` in a scratch git repository, drawing to an in-memory
+/// terminal. Input is fed in as raw bytes, the events the manager posts are handled as
+/// the run loop would, and every session gets a [`TestSession`] instead of a process.
+pub struct Harness {
+    pub manager: TuiSessionManager,
+    /// Scratch directory holding the repository and its worktrees
+    pub root: TempDir,
+    spawned: Arc<Mutex<Vec<Spawned>>>,
+}
+
+impl Harness {
+    /// A manager started in a new repository named `name`, with one commit on main
+    pub fn new(name: &str) -> Self {
+        let root = TempDir::new(&format!("harness-{}", name));
+        let repo = root.repo(name);
+
+        let config = Config {
+            workflows_path: root.join("worktrees"),
+            ..Config::default()
+        };
+        let spawned = Arc::new(Mutex::new(Vec::new()));
+        let recorded = spawned.clone();
+        let manager = TuiSessionManager::with_host(Host {
+            terminal: Terminal::new(UiBackend::Headless(TestBackend::new(COLS, ROWS))).unwrap(),
+            config,
+            startup_path: repo,
+            history: SessionHistory::in_memory(),
+            event_log: Some(root.join("events.log")),
+            status_socket: None,
+            instances: Some(Registry::at(root.join("instances"), std::process::id())),
+            screens: None,
+            spawner: Box::new(move |command, args, on_exit, size, cwd, _env, limits| {
+                let (child, session) = TestSession::spawn(on_exit, size, limits)?;
+                recorded.lock().unwrap().push(Spawned {
+                    command: command.to_string(),
+                    args: args.iter().map(|a| a.to_string()).collect(),
+                    cwd: cwd.map(Path::to_path_buf),
+                    child,
+                });
+                Ok(session)
+            }),
+            listen: false,
+            script: None,
+        })
+        .unwrap();
+        Self {
+            manager,
+            root,
+            spawned,
+        }
+    }
+
+    /// Feed `bytes` as one read from stdin, then handle what it set off
+    pub fn keys(&mut self, bytes: &[u8]) {
+        self.manager
+            .dispatch(AppEvent::Input(bytes.to_vec()))
+            .unwrap();
+        self.settle();
+    }
+
+    /// Type `text` a character at a time
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.keys(c.to_string().as_bytes());
+        }
+    }
+
+    /// Handle the events the manager's workers post until they stop coming, then tick
+    pub fn settle(&mut self) {
+        while let Ok(event) = self.manager.event_rx.recv_timeout(QUIET) {
+            self.manager.dispatch(event).unwrap();
+        }
+        self.manager.dispatch(AppEvent::Tick).unwrap();
+    }
+
+    /// Draw a frame and return the terminal's contents, one line per row
+    pub fn screen(&mut self) -> String {
+        self.manager.draw().unwrap();
+        let UiBackend::Headless(backend) = self.manager.terminal.backend() else {
+            unreachable!("the harness always draws to a test backend");
+        };
+        let buffer = backend.buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Keep handling events until `text` is on screen, returning the screen. Panics with
+    /// the last screen on timeout.
+    pub fn wait_for(&mut self, text: &str) -> String {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            self.settle();
+            let screen = self.screen();
+            if screen.contains(text) {
+                return screen;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "{:?} never showed up; screen was:\n{}",
+                text,
+                screen
+            );
+        }
+    }
+
+    /// Keep handling events until `done` holds for the manager. Panics on timeout.
+    pub fn wait_until(&mut self, what: &str, done: impl Fn(&TuiSessionManager) -> bool) {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            self.settle();
+            if done(&self.manager) {
+                return;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "never {}; screen was:\n{}",
+                what,
+                self.screen()
+            );
+        }
+    }
+
+    /// Keep handling events until `n` sessions have been started, returning them all
+    pub fn wait_for_spawned(&mut self, n: usize) -> Vec<Spawned> {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            self.settle();
+            let spawned = self.spawned.lock().unwrap().clone();
+            if spawned.len() >= n {
+                return spawned;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "{} session(s) started, expected {}; screen was:\n{}",
+                spawned.len(),
+                n,
+                self.screen()
+            );
+        }
+    }
+
+    /// Create a session named `name` through the new session dialog and wait for its
+    /// child to start
+    pub fn create_session(&mut self, name: &str) -> Spawned {
+        let started = self.spawned.lock().unwrap().len();
+        self.keys(&[CTRL_N]);
+        self.type_text(name);
+        self.keys(b"\r");
+        self.wait_for_spawned(started + 1).remove(started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_manager::dispatcher::TaskState;
+    use crate::session_manager::ui::NameCollision;
+    use crate::session_manager::{Script, UiMode};
+    use crate::status_socket::{Command, CommandRequest, EventKind, StatusEvent, StatusSocket};
+    use crate::workflows::RepoLayout;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_session_lifecycle() {
+        let mut harness = Harness::new("lifecycle");
+        harness.manager.open_startup_picker();
+        harness.wait_for("New Session");
+
+        let session = harness.create_session("fix-login");
+        assert_eq!(session.command, "claude");
+        assert_eq!(session.args, ["--dangerously-skip-permissions"]);
+        let cwd = session.cwd.clone().unwrap();
+        assert!(cwd.ends_with("fix-login"), "{}", cwd.display());
+        assert!(cwd.starts_with(harness.root.join("worktrees")));
+
+        // Output goes through the PTY reader and parser onto the screen
+        session.child.feed("\x1b[1mhello\x1b[0m from claude\r\n");
+        harness.wait_for("hello from claude");
+        assert_eq!(session.child.size(), harness.manager.size.get());
+        // Keys in the claude view reach the child
+        harness.keys(b"yes\r");
+        assert!(session.child.input().ends_with(b"yes\r"));
+
+        session.child.exit(3);
+        harness.wait_for("exited with code 3");
+        assert!(harness.manager.active.is_none());
+    }
+
+    #[test]
+    fn test_kill_dialog_releases_worktree() {
+        let mut harness = Harness::new("kill");
+        let session = harness.create_session("doomed");
+        let cwd = session.cwd.clone().unwrap();
+        // What another shepherd sees in the shared registry
+        let other = Registry::at(
+            harness.root.join("instances"),
+            std::os::unix::process::parent_id(),
+        );
+        assert!(other.foreign_worktrees().unwrap().contains_key(&cwd));
+
+        harness.keys(&[0x18]);
+        harness.keys(b"y");
+        assert!(harness.manager.active.is_none());
+        assert!(session.child.exited());
+        assert!(other.foreign_worktrees().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dead_background_sessions_are_reaped() {
+        let mut harness = Harness::new("reap");
+        let first = harness.create_session("first");
+        harness.create_session("second");
+        assert_eq!(harness.manager.live_session_count(), 2);
+
+        first.child.exit(1);
+        harness.wait_until("reaped", |m| m.live_session_count() == 1);
+        let log = crate::session_manager::read_event_log(&harness.root.join("events.log"));
+        assert!(
+            log.contains("Session 'first': Process exited with code 1"),
+            "{}",
+            log
+        );
+        assert_eq!(harness.manager.active.as_ref().unwrap().name, "second");
+    }
+
+    #[test]
+    fn test_task_of_dead_session_is_redispatched() {
+        let mut harness = Harness::new("redispatch");
+        harness.create_session("lead");
+        harness.manager.dispatcher.queue("fix lint");
+        harness.manager.dispatch_tasks();
+        let worker = harness.wait_for_spawned(2).remove(1);
+        let running = |m: &TuiSessionManager, name: &str| {
+            m.dispatcher.tasks()[0].state == TaskState::Running(name.to_string())
+        };
+        harness.wait_until("dispatched", |m| running(m, "task-1"));
+
+        worker.child.exit(1);
+        harness.wait_for_spawned(3);
+        harness.wait_until("redispatched", |m| running(m, "task-2"));
+        assert!(!harness.manager.is_live_session("task-1"));
+    }
+
+    #[test]
+    fn test_socket_creates_queue_and_reply_once_started() {
+        let mut harness = Harness::new("socket-create");
+        harness.create_session("lead");
+        let mut clients = Vec::new();
+        for name in ["one", "two"] {
+            let (request, client) = CommandRequest::pair(Command::Create {
+                session: name.to_string(),
+                prompt: None,
+            });
+            // Both arrive before the first workflow has run
+            harness
+                .manager
+                .dispatch(AppEvent::Command(request))
+                .unwrap();
+            clients.push(client);
+        }
+        harness.wait_for_spawned(3);
+
+        for (name, client) in ["one", "two"].into_iter().zip(clients) {
+            let mut reply = String::new();
+            BufReader::new(client).read_line(&mut reply).unwrap();
+            let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+            assert_eq!(reply["session"], name);
+            assert_eq!(reply["started"], true);
+        }
+        // Created in the background, keeping the session being typed in
+        assert_eq!(harness.manager.active.as_ref().unwrap().name, "lead");
+    }
+
+    #[test]
+    fn test_sessions_keep_their_repo() {
+        let mut harness = Harness::new("home");
+        harness.create_session("shared");
+        let home = harness.manager.repo.clone().unwrap();
+        let other = RepoLayout::detect(&harness.root.repo("other")).unwrap();
+        harness.manager.workspace_repos = vec![home.clone(), other.clone()];
+
+        // Switching repos in the new session dialog is undone by cancelling it
+        harness.keys(&[CTRL_N]);
+        harness.keys(&[0x12]);
+        assert_eq!(harness.manager.repo_dir(), other.root());
+        harness.keys(&[0x1b]);
+        assert_eq!(harness.manager.repo_dir(), home.root());
+
+        harness.manager.repo = Some(other);
+        harness.manager.record_prompt("shared", "fix the build");
+        assert_eq!(harness.manager.history.prompts("home").count(), 1);
+        assert_eq!(harness.manager.history.prompts("other").count(), 0);
+        assert_eq!(
+            harness.manager.find_name_collision("shared"),
+            Some(NameCollision::LiveElsewhere("home".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_scrolling_stops_at_the_oldest_line() {
+        let mut harness = Harness::new("scroll");
+        let session = harness.create_session("chatty");
+        let lines: String = (0..100).map(|i| format!("line {}\r\n", i)).collect();
+        session.child.feed(lines.as_bytes());
+        harness.wait_for("line 99");
+
+        harness.keys(&b"\x1b[<64;1;1M".repeat(500));
+        let pair = harness.manager.active.as_ref().unwrap();
+        let held = pair.claude.memory().scrollback_lines;
+        assert!(held > 0);
+        assert_eq!(pair.scroll_offset, held);
+    }
+
+    #[test]
+    fn test_target_repo_is_not_repeated() {
+        let root = TempDir::new("distinct-repos");
+        let a = RepoLayout::detect(&root.repo("a")).unwrap();
+        let b = RepoLayout::detect(&root.repo("b")).unwrap();
+        let workspace = [a.clone(), b.clone()];
+        let roots: Vec<&Path> = crate::session_manager::distinct_repos(Some(&a), &workspace)
+            .into_iter()
+            .map(RepoLayout::root)
+            .collect();
+        assert_eq!(roots, [a.root(), b.root()]);
+    }
+
+    #[test]
+    fn test_prompt_is_not_an_option() {
+        let mut harness = Harness::new("prompt");
+        harness.keys(&[CTRL_N]);
+        harness.type_text("flags");
+        harness.keys(b"\t");
+        harness.type_text("--help is broken");
+        harness.keys(b"\r");
+        let session = harness.wait_for_spawned(1).remove(0);
+        assert_eq!(
+            session.args[session.args.len() - 2..],
+            ["--", "--help is broken"]
+        );
+    }
+
+    #[test]
+    fn test_selector_switches_sessions() {
+        let mut harness = Harness::new("selector");
+        let first = harness.create_session("first");
+        first.child.feed("first screen");
+        harness.wait_for("first screen");
+        let second = harness.create_session("second");
+        second.child.feed("second screen");
+        harness.wait_for("second screen");
+
+        harness.keys(&[CTRL_L]);
+        assert!(harness.manager.mode == UiMode::ListSessions);
+        let screen = harness.wait_for("Filter");
+        assert!(
+            screen.contains("first") && screen.contains("second"),
+            "{}",
+            screen
+        );
+
+        harness.type_text("first");
+        harness.keys(b"\r");
+        harness.wait_for("first screen");
+        assert!(harness.manager.mode == UiMode::Normal);
+        assert_eq!(harness.manager.active.as_ref().unwrap().name, "first");
+    }
+
+    #[test]
+    fn test_selector_filter_takes_spaces() {
+        let mut harness = Harness::new("selector-space");
+        harness.create_session("first");
+        harness.keys(&[CTRL_L]);
+        harness.type_text("fix login");
+        assert_eq!(harness.manager.session_selector.query(), "fix login");
+        assert!(harness.manager.session_selector.marked().is_empty());
+
+        for _ in 0.."fix login".len() {
+            harness.keys(&[0x7f]);
+        }
+        harness.keys(b"\t");
+        assert_eq!(harness.manager.session_selector.marked().len(), 1);
+    }
+
+    #[test]
+    fn test_only_permission_prompts_await_approval() {
+        let mut harness = Harness::new("approvals");
+        let session = harness.create_session("idle");
+        let notify = |harness: &mut Harness, message: &str| {
+            let event = StatusSocket::parse_event(&format!(
+                r#"{{"session":"idle","event":"notification","message":"{}"}}"#,
+                message
+            ))
+            .unwrap();
+            harness
+                .manager
+                .dispatch(AppEvent::StatusSocket(event))
+                .unwrap();
+        };
+
+        // Alt+y must not submit whatever is half typed at an idle input box
+        notify(&mut harness, "Claude is waiting for your input");
+        harness.keys(b"\x1by");
+        assert!(!session.child.input().contains(&b'\r'));
+
+        notify(&mut harness, "Claude needs your permission to use Bash");
+        harness.keys(b"\x1by");
+        assert!(session.child.input().ends_with(b"\x1by\r"));
+    }
+
+    #[test]
+    fn test_script() {
+        let mut harness = Harness::new("script");
+        let script = "create fix-ci\n\
+                      wait-for stop fix-ci 60\n\
+                      send-prompt fix-ci Now add a test\n\
+                      quit\n";
+        harness.manager.script = Some(Script::parse(script).unwrap());
+        let session = harness.wait_for_spawned(1).remove(0);
+        assert!(session.cwd.unwrap().ends_with("fix-ci"));
+        // Held on the wait until claude stops
+        harness.settle();
+        assert!(!harness.manager.should_quit);
+        assert!(session.child.input().is_empty());
+
+        harness
+            .manager
+            .dispatch(AppEvent::StatusSocket(StatusEvent {
+                session: "fix-ci".to_string(),
+                event: EventKind::Stop,
+            }))
+            .unwrap();
+        assert!(harness.manager.should_quit);
+        let input = String::from_utf8_lossy(&session.child.input()).into_owned();
+        assert!(input.contains("Now add a test"), "{:?}", input);
+    }
+}
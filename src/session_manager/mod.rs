@@ -1,36 +1,89 @@
+mod actions;
+mod backend;
+mod checkpoints;
+mod debug_dump;
+mod dispatcher;
+mod events;
+mod file_refs;
+mod git_status;
+#[cfg(test)]
+mod harness;
+mod script;
 mod session_pair;
+mod tasks;
+mod typed_line;
 mod ui;
 
-pub use ui::StatusMessage;
+pub use script::Script;
 use ui::{
-    CreateDialog, DeleteConfirmDialog, HelpPopup, KillConfirmDialog, MainView, QuitConfirmDialog,
-    SelectorItemKind, SessionSelector, StatusBar, TerminalMultiplexer, WorktreeCleanupDialog,
+    ApprovalBar, AttentionInbox, CheckpointBrowser, CommandPalette, ComposeDialog, CreateDialog,
+    CreationProgress, Dashboard, DeleteConfirmDialog, DirectoryPrompt, DispatchDialog, GitHistory,
+    HelpPopup, HistoryView, InboxEntry, KillConfirmDialog, LogPane, MacroPicker, MainView,
+    MemoryRow, MemoryStats, NameCollision, PREVIEW_LINES, PlanField, PromptHistory,
+    QuitConfirmDialog, QuitEntry, RenderStats, RunDialog, SIGNALS, SelectorItemKind,
+    SessionCounters, SessionDetail, SessionPlanDialog, SessionSelector, SignalMenu, StatusBar,
+    TerminalMultiplexer, TranscriptViewer, UndoChangesDialog, WorktreeCleanupDialog, append_event,
+    render_throttled_badge,
 };
+pub use ui::{StatusLevel, StatusMessage, event_log_path, read_event_log};
 
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 
 use crossterm::ExecutableCommand;
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::cursor::SetCursorStyle;
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+};
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
+use ratatui::Terminal;
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::buffer::Buffer;
 
-use std::io::{self, Read, stdout};
+use std::io::{self, IsTerminal, stdout};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, Receiver};
-
-use crate::config::Config;
-use crate::history::SessionHistory;
-use crate::session::{AttachedSession, SharedSize};
-use crate::status_socket::{EventKind, StatusSocket};
-use crate::workflows::{Workflow, WorktreeWorkflow};
-
-use std::sync::mpsc::Sender;
-
-use session_pair::{ActivePair, BackgroundPair, SessionActivity, SessionView};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::archive::Archives;
+use crate::config::{ChainStep, Config, SessionLimitPolicy, WatchRule};
+use crate::error::ShepardError;
+use crate::git_backend;
+use crate::history::{SessionHistory, TimeEntry, format_duration};
+use crate::host_terminal::HostTerminal;
+use crate::instances::{Registry, instance_root};
+use crate::output_watch::WatchRules;
+use crate::pty_widget::PtyWidget;
+use crate::raw_log::{RawLog, raw_log_path};
+use crate::screens::Screens;
+use crate::session::{AttachedSession, ExitNotifier, Session, SharedSize, Spawner, exit_message};
+use crate::status_socket::{Command, CommandRequest, EventKind, StatusEvent, StatusSocket};
+use crate::transcript;
+use crate::trash::{Trash, TrashEntry};
+use crate::workflows::{
+    DirectoryWorkflow, PlanOverrides, Progress, RepoLayout, ScriptWorkflow, SessionInfo,
+    SessionMetadata, SetupStep, Workflow, WorktreeWorkflow, branch_name_from_prompt,
+    build_cache_env, merge_shared_workflows, resolve_branch_name,
+};
 
-const BUF_SIZE: usize = 1024;
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use actions::{ACTIONS, Action};
+use backend::UiBackend;
+use debug_dump::{DebugDump, ScreenDump, SessionRow, debug_dir};
+use dispatcher::Dispatcher;
+use events::{AppEvent, EventSource};
+use file_refs::{FileRef, last_file_ref};
+use git_status::{GitStatus, GitStatusWorker};
+use script::{ScriptAction, ScriptStep};
+use session_pair::{
+    ActivePair, BackgroundPair, CheckStatus, SessionActivity, SessionTimer, SessionView,
+};
+use tasks::{DeletedWorktrees, TaskRunner, WorktreeDeletion};
+use typed_line::TypedLine;
 
 /// Convert an absolute path to a home-relative path string with `~`.
 fn path_to_display(path: &Path) -> String {
@@ -52,18 +105,232 @@ fn display_path_to_actual(path_display: &str) -> PathBuf {
     PathBuf::from(path_display)
 }
 
-const CTRL_H: u8 = 0x08;
-const CTRL_T: u8 = 0x14;
-const CTRL_N: u8 = 0x0E;
-const CTRL_L: u8 = 0x0C;
+/// Undo the terminal setup done by `TuiSessionManager::new`. Safe to call more than once.
+pub fn restore_terminal() {
+    let _ = stdout().execute(DisableMouseCapture);
+    let _ = stdout().execute(DisableFocusChange);
+    let _ = stdout().execute(SetCursorStyle::DefaultUserShape);
+    let _ = disable_raw_mode();
+    let _ = stdout().execute(LeaveAlternateScreen);
+    let _ = stdout().execute(crossterm::cursor::Show);
+}
+
+/// Install a panic hook that logs the panic to the event log. A panic on the calling
+/// (UI) thread also restores the terminal before the panic is printed; panics on other
+/// threads are only logged, since printing would garble the running UI.
+pub fn install_panic_hook() {
+    let ui_thread = std::thread::current().id();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        tracing::error!(thread = thread.name(), "{}", info);
+        append_event(&StatusMessage::err(
+            "shepherd panicked",
+            // One line per event log entry
+            format!(
+                "Panic in thread '{}': {}",
+                thread.name().unwrap_or("<unnamed>"),
+                info
+            )
+            .replace('\n', " "),
+        ));
+        if thread.id() != ui_thread {
+            return;
+        }
+
+        restore_terminal();
+        default_hook(info);
+        if let Some(path) = event_log_path() {
+            eprintln!("shepherd crashed; recent events are in {}", path.display());
+        }
+    }));
+}
+
+/// Workflows sessions can go through, in the order the new session dialog cycles them:
+/// worktrees (in a git repo), the directory itself, and `script_workflow` when configured
+fn workflows_for(config: &Config, in_repo: bool) -> Vec<Arc<dyn Workflow>> {
+    let mut workflows: Vec<Arc<dyn Workflow>> = Vec::new();
+    if in_repo {
+        workflows.push(Arc::new(WorktreeWorkflow));
+    }
+    workflows.push(Arc::new(DirectoryWorkflow));
+    if let Some(script) = &config.script_workflow {
+        workflows.push(Arc::new(ScriptWorkflow::new(script.clone())));
+    }
+    workflows
+}
+
+/// The target repo followed by the workspace repos, each once: the target is usually one
+/// of them
+fn distinct_repos<'a>(
+    repo: Option<&'a RepoLayout>,
+    workspace: &'a [RepoLayout],
+) -> Vec<&'a RepoLayout> {
+    let mut repos: Vec<&RepoLayout> = Vec::new();
+    for layout in repo.into_iter().chain(workspace) {
+        if !repos.iter().any(|r| r.root() == layout.root()) {
+            repos.push(layout);
+        }
+    }
+    repos
+}
+
+/// A session to create through a workflow
+struct CreationRequest {
+    name: String,
+    prompt: Option<String>,
+    workflow: Arc<dyn Workflow>,
+    overrides: PlanOverrides,
+    /// Repository it is created in, the target when it was asked for
+    repo: Option<RepoLayout>,
+    /// Started without switching to it or showing progress: socket, chain and dispatch
+    /// sessions
+    background: bool,
+    /// Socket request answered once the session starts or fails
+    reply: Option<CommandRequest>,
+}
+
+/// A session being created, its workflow running on a worker thread
+struct Creation {
+    request: CreationRequest,
+    progress: Progress,
+    /// Where to go back to if the workflow fails, to fix what it complained about
+    return_to: UiMode,
+}
+
+/// A workflow hook run off the UI thread
+type WorkflowHook = fn(&dyn Workflow, &SessionInfo, &Config) -> Result<(), ShepardError>;
+
+/// Run a new worktree's setup steps on a background thread so the session can start
+/// right away, reporting progress through the status bar.
+fn spawn_setup_steps(
+    session: String,
+    path: PathBuf,
+    steps: Vec<SetupStep>,
+    status_tx: std::sync::mpsc::Sender<StatusMessage>,
+) {
+    std::thread::spawn(move || {
+        let mut failed = false;
+        for step in steps {
+            let _ = status_tx.send(StatusMessage::info(
+                format!("{}...", step.label),
+                format!("{} for session '{}'", step.label, session),
+            ));
+            let Err(error) = step.action.run(&path) else {
+                continue;
+            };
+            failed = true;
+            let _ = status_tx.send(StatusMessage::err(
+                format!("{} failed", step.label),
+                format!("{} for session '{}' failed: {}", step.label, session, error),
+            ));
+        }
+        if !failed {
+            let _ = status_tx.send(StatusMessage::success(
+                "Worktree setup done",
+                format!("Finished worktree setup for session '{}'", session),
+            ));
+        }
+    });
+}
+
 const CTRL_X: u8 = 0x18;
-const CTRL_BACKSLASH: u8 = 0x1c;
 const CTRL_W: u8 = 0x17;
 const CTRL_D: u8 = 0x04;
-const CTRL_K: u8 = 0x0B;
-const CTRL_Y: u8 = 0x19;
+const CTRL_B: u8 = 0x02;
+const CTRL_O: u8 = 0x0F;
+const CTRL_P: u8 = 0x10;
+const CTRL_R: u8 = 0x12;
+const CTRL_S: u8 = 0x13;
+const CTRL_F: u8 = 0x06;
+const CTRL_A: u8 = 0x01;
+
+/// Whether two sessions' repos are the same repository
+fn same_repo(a: Option<&RepoLayout>, b: Option<&RepoLayout>) -> bool {
+    a.map(RepoLayout::root) == b.map(RepoLayout::root)
+}
+
+/// Status message for a session whose claude exited: an error unless it exited cleanly
+fn claude_exit_message(name: &str, claude: &Session) -> StatusMessage {
+    let status = claude.exit_status();
+    let log_msg = claude
+        .get_error()
+        .unwrap_or_else(|| "Process exited".to_string());
+    tracing::info!(session = name, status = log_msg, "claude exited");
+    let display = format!(
+        "Session {} (claude) {}",
+        name,
+        exit_message(status.as_ref())
+    );
+    let log = format!("Session '{}': {}", name, log_msg);
+    if status.is_some_and(|s| s.success()) {
+        StatusMessage::info(display, log)
+    } else {
+        StatusMessage::err(display, log)
+    }
+}
+
+/// Sent to a session to accept Claude's permission prompt (its default option is "Yes")
+const APPROVE_KEYS: &[u8] = b"\r";
+/// Sent to a session to reject Claude's permission prompt
+const DENY_KEYS: &[u8] = b"\x1b";
+/// How long approve/deny confirmations stay in the status bar
+const APPROVAL_ACK_DURATION: Duration = Duration::from_secs(3);
+/// Size of the screen sessions get when a script runs without a terminal
+const HEADLESS_COLS: u16 = 120;
+const HEADLESS_ROWS: u16 = 40;
+/// How often the active session's git status is refreshed while it stays focused
+const GIT_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// Set in every session's environment (to shepherd's pid) so a shepherd started inside one
+/// can tell it is nested
+pub const NESTED_ENV: &str = "SHEPHERD_PID";
+/// How often background sessions are checked against `trim_scrollback_after_secs`
+const TRIM_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How often live sessions' screens are saved to ~/.shepherd/screens
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+/// How long after a worktree delete Alt+u can still restore it from the trash
+const TRASH_UNDO_WINDOW: Duration = Duration::from_secs(60);
+
+/// A rebase started with Alt+r, running in a shell pane of its session
+struct RebaseJob {
+    /// Process id of the pane running the rebase
+    pane_pid: Option<u32>,
+    /// Ref being rebased onto, e.g. origin/main
+    onto: String,
+    /// Claude was running before the rebase paused it, so resume it afterwards
+    resume_claude: bool,
+}
+
+/// A claude session held back by `max_live_sessions`, with what to start it with
+struct PendingSession {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    cwd: PathBuf,
+    repo: Option<RepoLayout>,
+    resumed: bool,
+}
+
+/// A one-off command started with Alt+j, running in a temporary pane of its session
+struct CommandRun {
+    session: String,
+    command: String,
+    /// Process id of the pane running the command
+    pane_pid: Option<u32>,
+    /// The pane writes the exit code here when the command finishes
+    result_path: PathBuf,
+}
+
+/// Checks started with Alt+c, running in a shell pane of their session
+struct CheckRun {
+    status: CheckStatus,
+    /// Process id of the pane running the checks
+    pane_pid: Option<u32>,
+    /// The pane writes the exit code here when the command finishes
+    result_path: PathBuf,
+}
 
-#[derive(Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 enum UiMode {
     #[default]
     Normal,
@@ -71,33 +338,126 @@ enum UiMode {
     ListSessions,
     NewSession,
     KillConfirmation,
+    /// Asking to kill the least recently used session to stay within `max_live_sessions`
+    SessionLimitConfirm,
     QuitConfirmation,
     WorktreeCleanup,
     WorktreeDeleteConfirm,
+    MacroPicker,
+    Inbox,
+    SignalMenu,
+    Dashboard,
+    DirectoryPrompt,
+    CommandPalette,
+    Compose,
+    PromptHistory,
+    Transcript,
+    GitHistory,
+    Checkpoints,
+    UndoChanges,
+    RunCommand,
+    /// What creating a session will do, waiting for confirmation
+    SessionPlan,
+    /// Progress of a session being created on a worker thread
+    Creating,
+    Dispatch,
+    MemoryStats,
+    /// Rendering suspended; the focused session's PTY talks to the terminal directly
+    Passthrough,
+}
+
+/// What the manager takes from the machine it runs on. `TuiSessionManager::new` gathers
+/// the real ones; tests pass stand-ins.
+struct Host {
+    terminal: Terminal<UiBackend>,
+    config: Config,
+    startup_path: PathBuf,
+    history: SessionHistory,
+    /// Where status messages are logged
+    event_log: Option<PathBuf>,
+    status_socket: Option<StatusSocket>,
+    instances: Option<Registry>,
+    screens: Option<Screens>,
+    spawner: Spawner,
+    /// Read stdin, SIGWINCH and the status socket on an event thread
+    listen: bool,
+    script: Option<Script>,
 }
 
 pub struct TuiSessionManager {
-    terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    terminal: Terminal<UiBackend>,
     active: Option<ActivePair>,
     background: Vec<BackgroundPair>,
     size: SharedSize,
     mode: UiMode,
-    input_rx: Receiver<Vec<u8>>,
+    event_rx: Receiver<AppEvent>,
+    /// Sender half of the event bus, cloned into subsystems (e.g. session exit notifiers)
+    event_tx: Sender<AppEvent>,
+    /// Thread delivering input, resize and status socket events (joined on drop)
+    event_source: Option<EventSource>,
+    /// Starts the child of every new session
+    spawner: Spawner,
     session_counter: usize,
-    workflow: Box<dyn Workflow>,
+    /// Monotonic counter assigned to each new session pair
+    session_seq: usize,
+    /// Workflows available for new sessions in the target repo
+    workflows: Vec<Arc<dyn Workflow>>,
+    /// Workflow each session created this run went through, for its later hooks
+    session_workflows: HashMap<String, Arc<dyn Workflow>>,
     config: Config,
+    /// Compiled `config.watch_rules`
+    watch_rules: WatchRules,
     startup_path: PathBuf,
+    /// Repository new sessions target: the one shepherd was launched in, or the one picked
+    /// from `workspace_repos`. History, the selector and cleanup are scoped to it.
+    repo: Option<RepoLayout>,
+    /// Repositories one level below the startup directory, when it is a workspace folder
+    /// rather than a repo itself
+    workspace_repos: Vec<RepoLayout>,
+    /// Target repo before Ctrl+R in the new session dialog switched it, put back if the
+    /// dialog is cancelled
+    dialog_return_repo: Option<Option<RepoLayout>>,
     // UI components
     main_view: MainView,
     help_popup: HelpPopup,
     session_selector: SessionSelector,
     create_dialog: CreateDialog,
+    directory_prompt: DirectoryPrompt,
+    run_dialog: RunDialog,
+    session_plan: SessionPlanDialog,
+    /// Session being created on a worker thread
+    creation: Option<Creation>,
+    /// Creations waiting for the running one to finish, oldest first
+    queued_creations: VecDeque<CreationRequest>,
+    creation_progress: CreationProgress,
     kill_confirm_dialog: KillConfirmDialog,
     quit_confirm_dialog: QuitConfirmDialog,
     worktree_cleanup_dialog: WorktreeCleanupDialog,
     delete_confirm_dialog: DeleteConfirmDialog,
+    macro_picker: MacroPicker,
+    command_palette: CommandPalette,
+    compose_dialog: ComposeDialog,
+    prompt_history: PromptHistory,
+    transcript_viewer: TranscriptViewer,
+    git_history: GitHistory,
+    checkpoint_browser: CheckpointBrowser,
+    undo_changes_dialog: UndoChangesDialog,
+    dispatch_dialog: DispatchDialog,
+    memory_stats: MemoryStats,
+    /// Line being typed into each session's claude view, to record submitted prompts
+    typed_lines: HashMap<String, TypedLine>,
+    signal_menu: SignalMenu,
+    dashboard: Dashboard,
+    /// Worktree paths of the sessions a batch kill from the selector is waiting to confirm
+    kill_batch: Vec<PathBuf>,
+    approval_bar: ApprovalBar,
+    attention_inbox: AttentionInbox,
+    log_pane: LogPane,
+    /// Whether the event log pane is shown under the session
+    log_pane_visible: bool,
+    render_stats: RenderStats,
     status_bar: StatusBar,
-    status_tx: Sender<StatusMessage>,
+    status_tx: std::sync::mpsc::Sender<StatusMessage>,
     /// Original active session name when selector opened (for revert on escape)
     selector_original_session: Option<String>,
     /// Cached session list when selector opened (indices stay consistent during preview)
@@ -106,55 +466,236 @@ pub struct TuiSessionManager {
     selector_live_count: usize,
     /// Number of recent sessions in selector_sessions (after live, before worktrees)
     selector_recent_count: usize,
+    /// List other repos' recent sessions in the selector too (Ctrl+A there)
+    selector_all_repos: bool,
+    /// Mode the worktree delete confirmation returns to (cleanup dialog or selector)
+    delete_return_mode: UiMode,
     /// Session history for most recent sessions per directory
     history: SessionHistory,
     /// Terminal multiplexers keyed by session name (persists across view switches)
     multiplexers: HashMap<String, TerminalMultiplexer>,
     /// Flag to signal the main loop to exit
     should_quit: bool,
-    /// Status socket for receiving hook events from Claude sessions
-    status_socket: Option<StatusSocket>,
+    /// Actions from `--script`, run in place of keyboard input
+    script: Option<Script>,
+    /// Path of the status socket passed to Claude hooks (the socket itself lives in the event source)
+    status_socket_path: Option<PathBuf>,
+    /// Set when UI state changed and the next loop iteration must redraw
+    needs_redraw: bool,
+    /// Sessions waiting for a permission decision, oldest first
+    pending_approvals: Vec<String>,
+    git_worker: GitStatusWorker,
+    /// Runs blocking git and filesystem work off the UI thread
+    tasks: TaskRunner,
+    /// Latest git status per session checkout
+    git_statuses: HashMap<PathBuf, GitStatus>,
+    /// Checkout last queued for a git status refresh, and when
+    last_git_refresh: Option<(PathBuf, Instant)>,
+    /// When the last background `git fetch` was queued (see `fetch_interval_secs`)
+    last_fetch: Option<Instant>,
+    /// Rebases in progress, by session name
+    rebases: HashMap<String, RebaseJob>,
+    /// Latest checks run per session name, running or finished
+    checks: HashMap<String, CheckRun>,
+    /// One-off commands still running
+    command_runs: Vec<CommandRun>,
+    /// Counter naming each checks or one-off run's result file
+    check_seq: usize,
+    /// Chain steps already run, as (index in `config.chains`, stopped session); claude stops
+    /// after every turn, but each step follows a session once
+    chains_run: HashSet<(usize, String)>,
+    /// Tasks queued from the dispatch dialog and the sessions working on them
+    dispatcher: Dispatcher,
+    /// Sessions waiting for a free slot under `max_live_sessions`, oldest first
+    queued_sessions: VecDeque<PendingSession>,
+    /// Session waiting on the answer to kill the least recently used one (by name)
+    limit_pending: Option<(PendingSession, String)>,
+    /// When idle background sessions were last checked for scrollback to trim
+    last_trim_check: Instant,
+    /// Saved screens, shown while resumed sessions start up (None without a home directory)
+    screens: Option<Screens>,
+    /// Hash of the screen last saved for each session directory, to skip unchanged ones
+    screen_hashes: HashMap<PathBuf, u64>,
+    /// When screens were last saved
+    last_snapshot: Instant,
+    /// Registry of running shepherds and the worktrees they hold (None without a home
+    /// directory)
+    instances: Option<Registry>,
+    /// Worktrees moved to the trash by the last delete, and when (Alt+u undoes it)
+    last_trashed: Option<(Vec<TrashEntry>, Instant)>,
+    /// Whether shepherd's own terminal has focus, from the host's focus reports
+    host_focused: bool,
+    /// DECSCUSR style last sent to the host terminal, None when unknown
+    host_cursor_style: Option<u8>,
+    /// Whether the last frame marked the focused session as throttled
+    throttle_shown: bool,
 }
 
 impl TuiSessionManager {
     pub fn new() -> anyhow::Result<Self> {
-        enable_raw_mode()?;
-        stdout().execute(EnterAlternateScreen)?;
-        stdout().execute(EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout());
-        let terminal = Terminal::new(backend)?;
+        Self::on_host(None)
+    }
+
+    /// A manager driven by `script` instead of the keyboard. Without a terminal on
+    /// stdout nothing is shown, so it can run in CI.
+    pub fn scripted(script: Script) -> anyhow::Result<Self> {
+        Self::on_host(Some(script))
+    }
+
+    fn on_host(script: Option<Script>) -> anyhow::Result<Self> {
+        let backend = if script.is_some() && !stdout().is_terminal() {
+            UiBackend::Headless(TestBackend::new(HEADLESS_COLS, HEADLESS_ROWS))
+        } else {
+            enable_raw_mode()?;
+            // Must run before the event source starts consuming stdin
+            HostTerminal::detect().install();
+            stdout().execute(EnterAlternateScreen)?;
+            stdout().execute(EnableMouseCapture)?;
+            stdout().execute(EnableFocusChange)?;
+            UiBackend::Host(CrosstermBackend::new(stdout()))
+        };
+        let config = Config::load()?;
+        git_backend::install(config.git_backend);
+        Self::with_host(Host {
+            terminal: Terminal::new(backend)?,
+            config,
+            startup_path: std::env::current_dir()?,
+            history: SessionHistory::load().unwrap_or_default(),
+            event_log: event_log_path(),
+            // Try to create status socket, but don't fail if it doesn't work
+            status_socket: StatusSocket::new().ok(),
+            instances: Registry::open().ok(),
+            screens: Screens::open().ok(),
+            spawner: Box::new(AttachedSession::new_with_env),
+            listen: true,
+            script,
+        })
+    }
 
+    fn with_host(host: Host) -> anyhow::Result<Self> {
+        let Host {
+            terminal,
+            mut config,
+            startup_path,
+            mut history,
+            event_log,
+            status_socket,
+            instances,
+            screens,
+            spawner,
+            listen,
+            script,
+        } = host;
         let term_size = terminal.size()?;
         let size = SharedSize::new(
             term_size.height.saturating_sub(2),
             term_size.width.saturating_sub(2),
         );
 
-        let (input_tx, input_rx) = mpsc::channel();
+        let (status_bar, status_tx) = StatusBar::with_event_log(&config, event_log);
+        let (watch_rules, watch_errors) = WatchRules::compile(&config.watch_rules);
+        for error in watch_errors {
+            let _ = status_tx.send(StatusMessage::err("Invalid watch rule", error));
+        }
+        let expired = history.set_retention(
+            config.history.max_recent_per_repo,
+            config
+                .history
+                .max_age_days
+                .map(|days| Duration::from_secs(days * 86400)),
+            config.history.max_time_entries,
+        );
+        if expired > 0 {
+            if let Err(e) = history.save() {
+                let _ = status_tx.send(StatusMessage::err("Failed to save history", e.to_string()));
+            }
+            let _ = status_tx.send(StatusMessage::info(
+                "History trimmed",
+                format!("Dropped {} old entries from history", expired),
+            ));
+        }
+        let repo = RepoLayout::detect(&startup_path);
+        let workspace_repos = if repo.is_none() {
+            RepoLayout::detect_children(&startup_path)
+        } else {
+            Vec::new()
+        };
+        let repo = repo.or_else(|| workspace_repos.first().cloned());
+        for layout in distinct_repos(repo.as_ref(), &workspace_repos) {
+            if let Err(error) = merge_shared_workflows(&mut config, layout) {
+                let _ = status_tx.send(error.into());
+            }
+        }
+        if let Some(repo) = &repo {
+            match repo.migrate_legacy(&config.workflows_path) {
+                Ok(0) => {}
+                Ok(moved) => {
+                    let _ = status_tx.send(StatusMessage::success(
+                        "Worktrees migrated",
+                        format!(
+                            "Moved {} worktree(s) to {}",
+                            moved,
+                            repo.worktrees_dir(&config.workflows_path).display()
+                        ),
+                    ));
+                }
+                Err(e) => {
+                    let _ = status_tx.send(StatusMessage::err(
+                        "Worktree migration failed",
+                        format!("Failed to migrate worktrees: {}", e),
+                    ));
+                }
+            }
+        }
+
+        if config.trash.enabled
+            && let Ok(trash) = Trash::open()
+        {
+            let purged = trash.purge_older_than(config.trash.keep_days);
+            if purged > 0 {
+                let _ = status_tx.send(StatusMessage::info(
+                    "Trash emptied",
+                    format!("Purged {} trashed worktree(s)", purged),
+                ));
+            }
+        }
 
-        std::thread::spawn(move || {
-            let mut stdin = std::io::stdin();
-            let mut buf = [0u8; BUF_SIZE];
-            loop {
-                match stdin.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        if input_tx.send(buf[..n].to_vec()).is_err() {
-                            break;
-                        }
-                    }
-                    Err(_) => break,
+        let status_socket_path = status_socket.as_ref().map(|s| s.socket_path().clone());
+
+        if let Some(ref instances) = instances {
+            let place = instance_root(&startup_path);
+            match instances.register(&place, status_socket_path.as_deref()) {
+                Ok(others) if !others.is_empty() => {
+                    let pids: Vec<String> = others.iter().map(|i| i.pid.to_string()).collect();
+                    let _ = status_tx.send(StatusMessage::warn(
+                        "Another shepherd is running here",
+                        format!(
+                            "shepherd (pid {}) is also running in {}; worktrees it has sessions in can't be used or deleted here",
+                            pids.join(", "),
+                            place.display()
+                        ),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let _ = status_tx.send(StatusMessage::warn(
+                        "Instance registry unavailable",
+                        format!("Failed to register in ~/.shepherd/instances.json: {}", e),
+                    ));
                 }
             }
-        });
+        }
 
-        let config = Config::load()?;
-        let startup_path = std::env::current_dir()?;
-        let (status_bar, status_tx) = StatusBar::new();
-        let history = SessionHistory::load().unwrap_or_default();
+        let create_dialog = CreateDialog::new();
 
-        // Try to create status socket, but don't fail if it doesn't work
-        let status_socket = StatusSocket::new().ok();
+        let (event_tx, event_rx) = unbounded();
+        // Headless there is no keyboard, and stdin may well be closed
+        let read_stdin = terminal.backend().is_host();
+        let event_source = listen
+            .then(|| EventSource::spawn(event_tx.clone(), status_socket, read_stdin))
+            .transpose()?;
+        let git_worker = GitStatusWorker::spawn(event_tx.clone(), config.base_branch.clone());
+        let tasks = TaskRunner::spawn(event_tx.clone());
 
         Ok(Self {
             terminal,
@@ -162,40 +703,141 @@ impl TuiSessionManager {
             background: Vec::new(),
             size,
             mode: UiMode::Normal,
-            input_rx,
+            event_rx,
+            event_tx,
+            event_source,
+            spawner,
             session_counter: 0,
-            workflow: Box::new(WorktreeWorkflow),
+            session_seq: 0,
+            workflows: workflows_for(&config, repo.is_some()),
+            session_workflows: HashMap::new(),
             config,
+            watch_rules,
             startup_path,
+            repo,
+            workspace_repos,
+            dialog_return_repo: None,
             main_view: MainView::new(),
             help_popup: HelpPopup::new(),
             session_selector: SessionSelector::new(),
-            create_dialog: CreateDialog::new(),
+            create_dialog,
+            directory_prompt: DirectoryPrompt::new(),
+            run_dialog: RunDialog::new(),
+            session_plan: SessionPlanDialog::new(),
+            creation: None,
+            queued_creations: VecDeque::new(),
+            creation_progress: CreationProgress::new(),
             kill_confirm_dialog: KillConfirmDialog::new(),
             quit_confirm_dialog: QuitConfirmDialog::new(),
             worktree_cleanup_dialog: WorktreeCleanupDialog::new(),
             delete_confirm_dialog: DeleteConfirmDialog::new(),
+            macro_picker: MacroPicker::new(),
+            command_palette: CommandPalette::new(),
+            compose_dialog: ComposeDialog::new(),
+            prompt_history: PromptHistory::new(),
+            transcript_viewer: TranscriptViewer::new(),
+            git_history: GitHistory::new(),
+            checkpoint_browser: CheckpointBrowser::new(),
+            undo_changes_dialog: UndoChangesDialog::new(),
+            dispatch_dialog: DispatchDialog::new(),
+            memory_stats: MemoryStats::new(),
+            typed_lines: HashMap::new(),
+            signal_menu: SignalMenu::new(),
+            dashboard: Dashboard::new(),
+            kill_batch: Vec::new(),
+            approval_bar: ApprovalBar::new(),
+            attention_inbox: AttentionInbox::new(),
+            log_pane: LogPane::new(),
+            log_pane_visible: false,
+            render_stats: RenderStats::default(),
             status_bar,
             status_tx,
             selector_original_session: None,
             selector_sessions: Vec::new(),
             selector_live_count: 0,
             selector_recent_count: 0,
+            selector_all_repos: false,
+            delete_return_mode: UiMode::WorktreeCleanup,
             history,
             multiplexers: HashMap::new(),
             should_quit: false,
-            status_socket,
+            script,
+            status_socket_path,
+            needs_redraw: true,
+            pending_approvals: Vec::new(),
+            git_worker,
+            tasks,
+            git_statuses: HashMap::new(),
+            last_git_refresh: None,
+            last_fetch: None,
+            rebases: HashMap::new(),
+            checks: HashMap::new(),
+            command_runs: Vec::new(),
+            check_seq: 0,
+            chains_run: HashSet::new(),
+            dispatcher: Dispatcher::default(),
+            queued_sessions: VecDeque::new(),
+            limit_pending: None,
+            last_trim_check: Instant::now(),
+            screens,
+            screen_hashes: HashMap::new(),
+            last_snapshot: Instant::now(),
+            instances,
+            last_trashed: None,
+            host_focused: true,
+            host_cursor_style: None,
+            throttle_shown: false,
+        })
+    }
+
+    /// Post a message to the status bar (and event log)
+    fn notify(&self, message: StatusMessage) {
+        let _ = self.status_tx.send(message);
+    }
+
+    /// Build a callback that posts `SessionDied` for `name` onto the event bus
+    fn exit_notifier(&self, name: &str) -> ExitNotifier {
+        let tx = self.event_tx.clone();
+        let name = name.to_string();
+        Box::new(move || {
+            let _ = tx.send(AppEvent::SessionDied(name));
         })
     }
 
+    /// Env vars shared by every session spawned in `cwd`: terminal identification, the
+    /// nesting marker and shared build caches
+    fn session_env(&self, cwd: &Path) -> Vec<(&'static str, String)> {
+        let mut env = HostTerminal::get().session_env(&self.config.terminal_env);
+        env.push((NESTED_ENV, std::process::id().to_string()));
+        if let Some(repo) = &self.repo {
+            env.extend(build_cache_env(&self.config, repo, cwd));
+        }
+        env
+    }
+
+    /// Create a plain session (e.g. a shell pane) owned by the session pair `owner`
     fn create_session(
         &self,
+        owner: &str,
         command: &str,
         args: &[&str],
         cwd: &Path,
-    ) -> anyhow::Result<AttachedSession> {
-        let (tx, _rx) = mpsc::channel();
-        AttachedSession::new(command, args, tx, self.size.clone(), Some(cwd))
+    ) -> Result<AttachedSession, ShepardError> {
+        let session_env = self.session_env(cwd);
+        let env_vars: Vec<(&str, &str)> = session_env
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect();
+
+        (self.spawner)(
+            command,
+            args,
+            Some(self.exit_notifier(owner)),
+            self.size.clone(),
+            Some(cwd),
+            &env_vars,
+            &self.config.session_limits,
+        )
     }
 
     fn create_claude_session(
@@ -204,660 +846,4046 @@ impl TuiSessionManager {
         command: &str,
         args: &[&str],
         cwd: &Path,
-    ) -> anyhow::Result<AttachedSession> {
-        let (tx, _rx) = mpsc::channel();
-
+    ) -> Result<AttachedSession, ShepardError> {
+        let _span = tracing::info_span!("session", name).entered();
+        tracing::info!(command, ?args, cwd = %cwd.display(), "starting claude");
         // Build env vars for shepherd hooks integration
         let socket_path = self
-            .status_socket
+            .status_socket_path
             .as_ref()
-            .map(|s| s.socket_path().to_string_lossy().to_string())
+            .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        let env_vars: Vec<(&str, &str)> = if !socket_path.is_empty() {
-            vec![
-                ("SHEPHERD_SESSION", name),
-                ("SHEPHERD_SOCKET", socket_path.as_str()),
-            ]
-        } else {
-            vec![]
-        };
+        let session_env = self.session_env(cwd);
+        let mut env_vars: Vec<(&str, &str)> = session_env
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect();
+        if !socket_path.is_empty() {
+            env_vars.push(("SHEPHERD_SESSION", name));
+            env_vars.push(("SHEPHERD_SOCKET", socket_path.as_str()));
+        }
 
-        AttachedSession::new_with_env(command, args, tx, self.size.clone(), Some(cwd), &env_vars)
+        let session = (self.spawner)(
+            command,
+            args,
+            Some(self.exit_notifier(name)),
+            self.size.clone(),
+            Some(cwd),
+            &env_vars,
+            &self.config.session_limits,
+        )?;
+        if self.config.raw_output_log.enabled {
+            let log = raw_log_path(self.repo.as_ref(), name)
+                .ok_or_else(|| std::io::Error::other("no home directory"))
+                .and_then(|path| RawLog::open(path, &self.config.raw_output_log));
+            match log {
+                Ok(log) => session.mirror_output(log),
+                Err(e) => self.notify(StatusMessage::warn(
+                    "Can't log session output",
+                    format!("Failed to open raw output log for {}: {}", name, e),
+                )),
+            }
+        }
+        Ok(session)
     }
 
+    /// Start a claude session of `repo` and make it active. Returns false when
+    /// `max_live_sessions` held it back instead (queued, or waiting on the kill prompt).
     pub fn add_claude_session(
         &mut self,
         name: &str,
         command: &str,
         args: &[&str],
         cwd: &Path,
+        repo: Option<RepoLayout>,
         resumed: bool,
-    ) -> anyhow::Result<()> {
-        let session = self.create_claude_session(name, command, args, cwd)?;
+    ) -> anyhow::Result<bool> {
+        // Session names key the multiplexers and hook events, so never run two at once
+        if let Some(live) = self.live_session_repo(name) {
+            if !same_repo(live, repo.as_ref()) {
+                self.notify(StatusMessage::err(
+                    format!("{} is running elsewhere", name),
+                    format!(
+                        "A session named '{}' is already running in {}",
+                        name,
+                        live.map_or("another directory", |r| r.name())
+                    ),
+                ));
+                return Ok(false);
+            }
+            self.switch_to_session_by_name(name)?;
+            return Ok(true);
+        }
+
+        if self.at_session_limit() {
+            self.hold_session(PendingSession {
+                name: name.to_string(),
+                command: command.to_string(),
+                args: args.iter().map(|a| a.to_string()).collect(),
+                cwd: cwd.to_path_buf(),
+                repo,
+                resumed,
+            });
+            return Ok(false);
+        }
+
+        if !self.claim_worktree(name, cwd, repo.as_ref()) {
+            return Ok(false);
+        }
+        let session = match self.create_claude_session(name, command, args, cwd) {
+            Ok(session) => session,
+            Err(e) => {
+                self.release_worktree(cwd);
+                return Err(e.into());
+            }
+        };
+        // Until claude --continue draws, show what the session last looked like
+        if resumed && let Some(screen) = self.screens.as_ref().and_then(|s| s.load(cwd)) {
+            session.seed_screen(&screen);
+        }
 
         if let Some(old_pair) = self.active.take() {
             self.background.push(old_pair.detach());
         }
 
+        self.session_seq += 1;
         self.active = Some(ActivePair::new(
             name.to_string(),
+            self.session_seq,
             cwd.to_path_buf(),
+            repo.clone(),
             session,
             resumed,
         ));
+        self.spawn_workflow_hook(name, cwd, repo.as_ref(), |w, s, c| {
+            w.post_session_start(s, c)
+        });
 
-        Ok(())
+        Ok(true)
     }
 
-    pub fn new_named_claude_session(&mut self, name: &str) -> anyhow::Result<()> {
-        let metadata = match self
-            .workflow
-            .pre_session_hook(name, &self.config, &self.startup_path)
-        {
-            Ok(m) => m,
-            Err(status_msg) => {
-                let _ = self.status_tx.send(status_msg);
-                self.mode = UiMode::NewSession;
-                return Ok(());
+    /// Describe the session `name` of `repo` in `path` to a workflow hook
+    fn session_info(&self, name: &str, path: &Path, repo: Option<&RepoLayout>) -> SessionInfo {
+        SessionInfo {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            dir: self.repo_dir_of(repo).to_path_buf(),
+        }
+    }
+
+    /// Run `hook` for the session on a background thread, posting its error if it fails
+    fn spawn_workflow_hook(
+        &self,
+        name: &str,
+        path: &Path,
+        repo: Option<&RepoLayout>,
+        hook: WorkflowHook,
+    ) {
+        let workflow = self.workflow_of(name);
+        let session = self.session_info(name, path, repo);
+        let config = self.config.clone();
+        let status_tx = self.status_tx.clone();
+        std::thread::spawn(move || {
+            if let Err(error) = hook(&*workflow, &session, &config) {
+                let _ = status_tx.send(error.into());
             }
-        };
+        });
+    }
 
-        // Get repo name and project path for history
-        if let (Some(repo_name), Some(project_path)) = (
-            self.get_current_repo_name(),
-            self.get_current_project_path(),
-        ) {
-            self.history
-                .set_recent_session(repo_name, name.to_string(), project_path)?;
+    /// Take `cwd` for this instance in the registry. Notifies and returns false when another
+    /// shepherd has a session there. The repo's own checkout is shared.
+    fn claim_worktree(&self, name: &str, cwd: &Path, repo: Option<&RepoLayout>) -> bool {
+        let Some(ref instances) = self.instances else {
+            return true;
+        };
+        if cwd == self.repo_dir_of(repo) || cwd == self.startup_path {
+            return true;
+        }
+        match instances.claim(cwd) {
+            Ok(Some(pid)) => {
+                self.notify(StatusMessage::err(
+                    format!("{} is in use by shepherd (pid {})", name, pid),
+                    format!(
+                        "Did not start '{}': shepherd (pid {}) has a session in {}",
+                        name,
+                        pid,
+                        cwd.display()
+                    ),
+                ));
+                false
+            }
+            // A broken registry shouldn't stop sessions from starting
+            Ok(None) | Err(_) => true,
         }
+    }
 
-        let args_owned = self.config.claude_args.clone();
-        let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
-        self.add_claude_session(name, "claude", &args, &metadata.path, false)
+    fn release_worktree(&self, cwd: &Path) {
+        if let Some(ref instances) = self.instances {
+            let _ = instances.release(cwd);
+        }
     }
 
-    pub fn try_resume(&mut self) -> anyhow::Result<bool> {
-        let repo_name = match self.get_current_repo_name() {
-            Some(r) => r,
-            None => return Ok(false),
-        };
+    /// Worktrees other running shepherds have sessions in, with their pids
+    fn foreign_worktrees(&self) -> BTreeMap<PathBuf, u32> {
+        self.instances
+            .as_ref()
+            .and_then(|instances| instances.foreign_worktrees().ok())
+            .unwrap_or_default()
+    }
 
-        let recent = match self.history.get_recent_session(&repo_name) {
-            Some(r) => r.clone(),
-            None => return Ok(false),
-        };
+    fn live_session_count(&self) -> usize {
+        self.background.len() + self.active.is_some() as usize
+    }
 
-        let worktree_path = self.worktree_path(&repo_name, &recent.name);
+    /// Whether starting another session would exceed `max_live_sessions`
+    fn at_session_limit(&self) -> bool {
+        self.config
+            .max_live_sessions
+            .is_some_and(|max| self.live_session_count() >= max)
+    }
 
-        if !worktree_path.exists() {
-            let _ = self.status_tx.send(StatusMessage::err(
-                "Resume failed",
-                format!("Session path no longer exists: {}", worktree_path.display()),
-            ));
-            return Ok(false);
+    /// Keep a session that would exceed the limit: queue it, or ask to kill the least
+    /// recently used session for it, per `session_limit_policy`
+    fn hold_session(&mut self, pending: PendingSession) {
+        let lru = self
+            .background
+            .iter()
+            .map(|p| (&p.name, p.last_used))
+            .chain(self.active.iter().map(|p| (&p.name, p.last_used)))
+            .filter(|(name, _)| !self.is_pinned(name))
+            .min_by_key(|(_, last_used)| *last_used)
+            .map(|(name, last_used)| (name.clone(), last_used.elapsed()));
+        if self.config.session_limit_policy == SessionLimitPolicy::KillLru
+            && self.limit_pending.is_none()
+            && let Some((lru, idle)) = lru
+        {
+            self.kill_confirm_dialog
+                .set_lru(&lru, &format_duration(idle), &pending.name);
+            self.limit_pending = Some((pending, lru));
+            return;
         }
 
-        let mut args_owned: Vec<String> = vec!["--continue".to_string()];
-        args_owned.extend(self.config.claude_args.clone());
-        let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
+        if self.queued_sessions.iter().any(|q| q.name == pending.name) {
+            return;
+        }
+        self.notify(StatusMessage::warn(
+            format!("Queued {}", pending.name),
+            format!(
+                "{} sessions are live (max_live_sessions); '{}' starts when one exits",
+                self.live_session_count(),
+                pending.name
+            ),
+        ));
+        self.queued_sessions.push_back(pending);
+    }
 
-        self.add_claude_session(&recent.name, "claude", &args, &worktree_path, true)?;
-        Ok(true)
+    /// Start queued sessions in the background while there is room under the limit
+    fn start_queued_sessions(&mut self) {
+        while !self.queued_sessions.is_empty() && !self.at_session_limit() {
+            let Some(pending) = self.queued_sessions.pop_front() else {
+                break;
+            };
+            let mode = self.mode.clone();
+            let original = self.active.as_ref().map(|p| p.name.clone());
+            let result = self.start_pending_session(&pending);
+            if let Some(ref original) = original
+                && self.active.as_ref().is_some_and(|p| &p.name != original)
+            {
+                let _ = self.switch_to_session_by_name(original);
+            }
+            self.mode = mode;
+            match result {
+                Ok(()) => self.notify(StatusMessage::info(
+                    format!("Started {}", pending.name),
+                    format!(
+                        "Started queued session '{}' in the background",
+                        pending.name
+                    ),
+                )),
+                Err(e) => self.notify(StatusMessage::err(
+                    "Failed to start queued session",
+                    format!("Failed to start '{}': {}", pending.name, e),
+                )),
+            }
+        }
     }
 
-    pub fn open_new_session(&mut self) {
-        self.create_dialog.clear();
-        self.mode = UiMode::NewSession;
+    fn start_pending_session(&mut self, pending: &PendingSession) -> anyhow::Result<()> {
+        let args: Vec<&str> = pending.args.iter().map(String::as_str).collect();
+        self.add_claude_session(
+            &pending.name,
+            &pending.command,
+            &args,
+            &pending.cwd,
+            pending.repo.clone(),
+            pending.resumed,
+        )?;
+        Ok(())
     }
 
-    pub fn run(&mut self) -> anyhow::Result<()> {
-        loop {
-            if self.should_quit {
-                break;
+    fn handle_session_limit_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let Some((pending, lru)) = self.limit_pending.take() else {
+            self.mode = UiMode::Normal;
+            return Ok(());
+        };
+        match bytes {
+            [b'y' | b'Y' | b'\r' | b'\n'] => {
+                self.mode = UiMode::Normal;
+                self.kill_session(&lru);
+                self.notify(StatusMessage::success(
+                    format!("Killed {}", lru),
+                    format!(
+                        "Killed least recently used session '{}' to start '{}'",
+                        lru, pending.name
+                    ),
+                ));
+                self.start_pending_session(&pending)?;
+            }
+            [0x1b] | [b'n' | b'N'] => {
+                self.mode = UiMode::Normal;
+                self.notify(StatusMessage::info(
+                    format!("Did not start {}", pending.name),
+                    format!(
+                        "Did not start '{}'; resume it from the session list later",
+                        pending.name
+                    ),
+                ));
             }
+            _ => self.limit_pending = Some((pending, lru)),
+        }
+        Ok(())
+    }
 
-            // Check for dead sessions before rendering
-            self.check_dead_sessions();
+    /// Create a session through the target repo's default workflow in the background,
+    /// keeping the current session. An initial `prompt` is passed as claude's positional
+    /// argument, so it is submitted once claude is ready instead of racing startup.
+    fn new_background_session(&mut self, name: &str, prompt: Option<&str>) {
+        let workflow = Arc::clone(&self.workflows[self.default_workflow()]);
+        self.spawn_workflow_session(CreationRequest {
+            name: name.to_string(),
+            prompt: prompt.map(str::to_string),
+            workflow,
+            overrides: PlanOverrides::default(),
+            repo: self.repo.clone(),
+            background: true,
+            reply: None,
+        });
+    }
 
-            // Poll for status events from Claude hooks
-            self.poll_status_events();
+    /// Sessions being created or waiting their turn
+    fn creating(&self) -> impl Iterator<Item = &CreationRequest> {
+        self.creation
+            .iter()
+            .map(|c| &c.request)
+            .chain(&self.queued_creations)
+    }
 
-            let inner_size = self.render_frame()?;
-            self.size.set(inner_size.height, inner_size.width);
+    fn is_creating(&self, name: &str) -> bool {
+        self.creating().any(|r| r.name == name)
+    }
 
-            match self
-                .input_rx
-                .recv_timeout(std::time::Duration::from_millis(16))
-            {
-                Ok(bytes) => {
-                    if !self.handle_hotkey(&bytes)? {
-                        match self.mode {
-                            UiMode::Normal => self.handle_normal_input(&bytes)?,
-                            UiMode::HelpPopup => self.handle_help_input(&bytes)?,
-                            UiMode::ListSessions => self.handle_list_input(&bytes)?,
-                            UiMode::NewSession => self.handle_new_session_input(&bytes)?,
-                            UiMode::KillConfirmation => {
-                                self.handle_kill_confirmation_input(&bytes)?
-                            }
-                            UiMode::QuitConfirmation => {
-                                self.handle_quit_confirmation_input(&bytes)?
-                            }
-                            UiMode::WorktreeCleanup => {
-                                self.handle_worktree_cleanup_input(&bytes)?
-                            }
-                            UiMode::WorktreeDeleteConfirm => {
-                                self.handle_delete_confirm_input(&bytes)?
-                            }
-                        }
-                    }
-                }
-                Err(mpsc::RecvTimeoutError::Timeout) => {}
-                Err(mpsc::RecvTimeoutError::Disconnected) => break,
-            }
+    /// Create a session through its workflow on a worker thread, showing its progress
+    /// until it is done unless it is a background one. The UI keeps running meanwhile.
+    /// One creation runs at a time; the rest wait in order.
+    fn spawn_workflow_session(&mut self, request: CreationRequest) {
+        if let Some(ref creation) = self.creation {
+            self.notify(StatusMessage::info(
+                format!("Queued {}", request.name),
+                format!(
+                    "'{}' is created once '{}' is done",
+                    request.name, creation.request.name
+                ),
+            ));
+            self.queued_creations.push_back(request);
+            return;
         }
+        let step_tx = self.event_tx.clone();
+        let progress = Progress::new(move |step| {
+            let _ = step_tx.send(AppEvent::CreationStep(step.to_string()));
+        });
+        let (tx, worker, worker_progress) = (
+            self.event_tx.clone(),
+            Arc::clone(&request.workflow),
+            progress.clone(),
+        );
+        let (config, dir, session, overrides) = (
+            self.config.clone(),
+            self.repo_dir_of(request.repo.as_ref()).to_path_buf(),
+            request.name.clone(),
+            request.overrides.clone(),
+        );
+        std::thread::spawn(move || {
+            let _span =
+                tracing::info_span!("workflow", session, workflow = worker.name()).entered();
+            let result =
+                worker.pre_session_hook(&session, &config, &dir, &overrides, &worker_progress);
+            match result {
+                Ok(_) => tracing::info!("session prepared"),
+                Err(ref e) => tracing::warn!(error = %e, "workflow failed"),
+            }
+            let _ = tx.send(AppEvent::SessionPrepared(session, result));
+        });
 
-        Ok(())
+        self.creation_progress
+            .open(request.name.clone(), request.workflow.name());
+        let return_to = self.mode.clone();
+        if !request.background {
+            self.mode = UiMode::Creating;
+        }
+        self.creation = Some(Creation {
+            request,
+            progress,
+            return_to,
+        });
     }
 
-    /// Check if the active session has died and handle cleanup
-    fn check_dead_sessions(&mut self) {
-        // First, clean up dead panes in multiplexers
-        self.cleanup_dead_multiplexer_panes();
-
-        // Collect info about dead claude session
-        let dead_session_info = if let Some(ref pair) = self.active {
-            // Only check claude session death when in Claude view
-            if pair.view == SessionView::Claude && pair.claude.is_dead() {
-                let error = pair.claude.get_error();
-                let log_msg = error.unwrap_or_else(|| "Process exited".to_string());
-                let _ = self.status_tx.send(StatusMessage::err(
-                    format!("Session {} (claude) died", pair.name),
-                    log_msg,
-                ));
-                Some((pair.name.clone(), pair.path.clone(), pair.resumed))
-            } else {
-                None
+    /// Start the session whose workflow finished on the worker thread, answer whoever
+    /// asked for it over the socket, then start the next queued creation
+    fn on_session_prepared(
+        &mut self,
+        name: String,
+        result: Result<SessionMetadata, ShepardError>,
+    ) -> anyhow::Result<()> {
+        let Some(mut creation) = self.creation.take_if(|c| c.request.name == name) else {
+            return Ok(());
+        };
+        let reply = creation.request.reply.take();
+        let outcome = if creation.request.background {
+            let mode = self.mode.clone();
+            let original = self.active.as_ref().map(|p| p.name.clone());
+            let outcome = self.start_prepared_session(creation, result);
+            if let Some(ref original) = original
+                && self.active.as_ref().is_some_and(|p| &p.name != original)
+            {
+                self.switch_to_session_by_name(original)?;
             }
+            self.mode = mode;
+            outcome
         } else {
-            None
+            self.start_prepared_session(creation, result)
         };
 
-        if let Some((name, path, was_resumed)) = dead_session_info {
-            // Shutdown and remove the active session
-            if let Some(pair) = self.active.take() {
-                pair.claude.shutdown();
-            }
+        // Held back by max_live_sessions, or its workflow failed
+        let started = self.is_live_session(&name);
+        if let Some(reply) = reply {
+            reply.reply(match outcome {
+                Ok(()) => Ok(serde_json::json!({ "session": name, "started": started })),
+                Err(ref e) => Err(e.to_string()),
+            });
+        }
+        if !started {
+            // A task dispatched to it waits for the next session to stop
+            self.dispatcher.requeue(&name);
+        }
+        if let Some(next) = self.queued_creations.pop_front() {
+            self.spawn_workflow_session(next);
+        }
+        outcome
+    }
 
-            // Also cleanup the multiplexer for this session
-            if let Some(mut multiplexer) = self.multiplexers.remove(&name) {
-                for pane in multiplexer.remove_dead_panes() {
-                    pane.shutdown();
-                }
-                // Shutdown any remaining live panes
-                while let Some(pane) = multiplexer.close_active_pane() {
-                    pane.shutdown();
+    fn start_prepared_session(
+        &mut self,
+        creation: Creation,
+        result: Result<SessionMetadata, ShepardError>,
+    ) -> anyhow::Result<()> {
+        let name = creation.request.name;
+        let showing = !creation.request.background && self.mode == UiMode::Creating;
+        if showing {
+            self.mode = UiMode::Normal;
+        }
+        if creation.progress.is_cancelled() {
+            match result {
+                Ok(metadata) => self.notify(StatusMessage::warn(
+                    "Creation finished before cancelling",
+                    format!(
+                        "'{}' was set up at {} before the cancel took effect; no session started",
+                        name,
+                        metadata.path.display()
+                    ),
+                )),
+                Err(_) => self.notify(StatusMessage::info(
+                    "Creation cancelled",
+                    format!("Cancelled creating '{}'", name),
+                )),
+            }
+            return Ok(());
+        }
+        match result {
+            Ok(metadata) => {
+                if !creation.request.background {
+                    self.create_dialog.clear();
                 }
+                self.finish_workflow_session(
+                    &name,
+                    creation.request.prompt.as_deref(),
+                    creation.request.workflow,
+                    metadata,
+                    creation.request.repo,
+                )
             }
-
-            // Close any popups and return to normal mode
-            if self.mode == UiMode::ListSessions {
-                self.mode = UiMode::Normal;
+            Err(error) => {
+                self.notify(error.into());
+                if showing {
+                    self.mode = creation.return_to;
+                }
+                Ok(())
             }
+        }
+    }
 
-            // If this was a resumed session, start a fresh session in the same directory
-            // without the --continue flag
-            if was_resumed {
-                let args_owned = self.config.claude_args.clone();
-                let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
-                if let Err(e) = self.add_claude_session(&name, "claude", &args, &path, false) {
-                    let _ = self.status_tx.send(StatusMessage::err(
-                        "Failed to restart session",
-                        format!("{}", e),
-                    ));
-                } else {
-                    let _ = self.status_tx.send(StatusMessage::info(
-                        "Session restarted",
-                        format!("Started fresh session in {}", path.display()),
-                    ));
+    /// Input while the creation progress is shown
+    fn handle_creation_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => {
+                if let Some(ref creation) = self.creation {
+                    creation.progress.cancel();
+                    self.creation_progress.set_cancelling();
                 }
             }
+            // Keep working while it finishes; the result lands on the status bar
+            [b'\r'] | [b'\n'] => self.mode = UiMode::Normal,
+            _ => {}
         }
+        Ok(())
     }
 
-    /// Poll the status socket for events from Claude hooks and update session states
-    fn poll_status_events(&mut self) {
-        let Some(ref socket) = self.status_socket else {
-            return;
+    /// Index in `workflows` of the one configured for the target repo
+    fn default_workflow(&self) -> usize {
+        let repo = self.get_current_repo_name().unwrap_or_default();
+        self.config
+            .workflow
+            .name_for(&repo)
+            .or(self.config.script_workflow.as_ref().map(|_| "script"))
+            .and_then(|name| self.workflows.iter().position(|w| w.name() == name))
+            .unwrap_or(0)
+    }
+
+    /// The workflow session `name` was created through, or the default one
+    fn workflow_of(&self, name: &str) -> Arc<dyn Workflow> {
+        self.session_workflows
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| Arc::clone(&self.workflows[self.default_workflow()]))
+    }
+
+    /// Start claude in the session `workflow` prepared
+    fn finish_workflow_session(
+        &mut self,
+        name: &str,
+        prompt: Option<&str>,
+        workflow: Arc<dyn Workflow>,
+        metadata: SessionMetadata,
+        repo: Option<RepoLayout>,
+    ) -> anyhow::Result<()> {
+        if let Some(ref repo) = repo {
+            self.history.set_recent_session(
+                repo.name().to_string(),
+                name.to_string(),
+                repo.root().to_path_buf(),
+                prompt,
+            )?;
+        }
+
+        self.session_workflows.insert(name.to_string(), workflow);
+        let mut args_owned = self.claude_args_for_path(&metadata.path);
+        // After `--` so a prompt starting with '-' isn't taken for an option
+        if let Some(prompt) = prompt {
+            args_owned.extend(["--".to_string(), prompt.to_string()]);
+        }
+        let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
+        let started =
+            self.add_claude_session(name, "claude", &args, &metadata.path, repo, false)?;
+
+        if started {
+            self.notify(StatusMessage::success(
+                "Session created",
+                match &metadata.base {
+                    Some(base) => format!(
+                        "Created session '{}' in {} from {}",
+                        name,
+                        metadata.path.display(),
+                        base
+                    ),
+                    None => format!("Created session '{}' in {}", name, metadata.path.display()),
+                },
+            ));
+        }
+
+        if !metadata.setup.is_empty() {
+            spawn_setup_steps(
+                name.to_string(),
+                metadata.path,
+                metadata.setup,
+                self.status_tx.clone(),
+            );
+        }
+        Ok(())
+    }
+
+    pub fn try_resume(&mut self) -> anyhow::Result<bool> {
+        let repo_name = match self.get_current_repo_name() {
+            Some(r) => r,
+            None => return Ok(false),
         };
 
-        let events = socket.poll();
-        for event in events {
-            let new_activity = match &event.event {
-                EventKind::Stop | EventKind::Notification => SessionActivity::Stopped,
-                EventKind::ToolStart(tool) => SessionActivity::RunningTool(tool.clone()),
-                EventKind::ToolEnd => SessionActivity::Active,
-            };
+        let recent = match self.history.get_recent_session(&repo_name) {
+            Some(r) => r.clone(),
+            None => return Ok(false),
+        };
 
-            // Update the activity state for the matching session
-            if let Some(ref mut pair) = self.active
-                && pair.name == event.session
-            {
-                pair.activity = new_activity;
-                continue;
-            }
+        let Some(worktree_path) = self.worktree_path(&recent.name) else {
+            return Ok(false);
+        };
 
-            // Check background sessions
-            for pair in &mut self.background {
-                if pair.name == event.session {
-                    pair.activity = new_activity;
-                    break;
-                }
-            }
+        if !worktree_path.exists() {
+            self.notify(StatusMessage::warn(
+                "Resume failed",
+                format!("Session path no longer exists: {}", worktree_path.display()),
+            ));
+            return Ok(false);
         }
+
+        let mut args_owned: Vec<String> = vec!["--continue".to_string()];
+        args_owned.extend(self.claude_args_for_path(&worktree_path));
+        let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
+
+        let repo = self.repo.clone();
+        self.add_claude_session(&recent.name, "claude", &args, &worktree_path, repo, true)
     }
 
-    /// Get count of sessions with stopped activity
-    pub fn stopped_session_count(&self) -> usize {
-        let active_stopped = self
-            .active
+    pub fn open_new_session(&mut self) {
+        // Show a creation started from here that is still running
+        if self
+            .creation
             .as_ref()
-            .map(|p| p.activity == SessionActivity::Stopped)
-            .unwrap_or(false) as usize;
+            .is_some_and(|c| !c.request.background)
+        {
+            self.mode = UiMode::Creating;
+            return;
+        }
+        self.create_dialog.clear();
+        self.dialog_return_repo = None;
+        self.reset_dialog_workflow();
+        self.mode = UiMode::NewSession;
+    }
 
-        let bg_stopped = self
-            .background
-            .iter()
-            .filter(|p| p.activity == SessionActivity::Stopped)
-            .count();
+    /// Offer the target repo's workflows in the new session dialog, starting on its default
+    fn reset_dialog_workflow(&mut self) {
+        let names = self.workflows.iter().map(|w| w.name()).collect();
+        self.create_dialog
+            .set_workflows(names, self.default_workflow());
+    }
 
-        active_stopped + bg_stopped
+    /// Handle input in the directory prompt shown when started outside a git repository
+    fn handle_directory_prompt_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [b'\r'] | [b'\n'] => {
+                let dir = display_path_to_actual(self.directory_prompt.input());
+                match dir.canonicalize() {
+                    Ok(dir) if dir.is_dir() => {
+                        self.set_work_dir(dir);
+                        self.open_new_session();
+                    }
+                    _ => self
+                        .directory_prompt
+                        .set_error(format!("Not a directory: {}", dir.display())),
+                }
+            }
+            [0x7f] => self.directory_prompt.pop(),
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    if !c.is_control() {
+                        self.directory_prompt.push(c);
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
-    /// Clean up dead panes in multiplexers and switch view if needed
-    fn cleanup_dead_multiplexer_panes(&mut self) {
-        let Some(ref mut pair) = self.active else {
-            return;
+    /// Work in `dir` from now on: git worktrees if it is in a repository (or holds some),
+    /// otherwise sessions run in `dir` itself
+    fn set_work_dir(&mut self, dir: PathBuf) {
+        self.repo = RepoLayout::detect(&dir);
+        self.workspace_repos = if self.repo.is_none() {
+            RepoLayout::detect_children(&dir)
+        } else {
+            Vec::new()
         };
+        if self.repo.is_none() {
+            self.repo = self.workspace_repos.first().cloned();
+        }
+        self.workflows = workflows_for(&self.config, self.repo.is_some());
+        self.startup_path = dir;
+        self.load_shared_workflows();
+    }
 
-        if pair.view != SessionView::Shell {
-            return;
+    /// Merge `shepard/workflows.toml` of the target repo and workspace repos into the config
+    fn load_shared_workflows(&mut self) {
+        let layouts: Vec<RepoLayout> = distinct_repos(self.repo.as_ref(), &self.workspace_repos)
+            .into_iter()
+            .cloned()
+            .collect();
+        for layout in layouts {
+            if let Err(error) = merge_shared_workflows(&mut self.config, &layout) {
+                self.notify(error.into());
+            }
         }
+    }
 
-        let name = pair.name.clone();
+    /// Arguments for claude in `path`, with those of the repository it belongs to
+    fn claude_args_for_path(&self, path: &Path) -> Vec<String> {
+        let repo = RepoLayout::detect(path)
+            .map(|layout| layout.name().to_string())
+            .or_else(|| self.get_current_repo_name())
+            .unwrap_or_default();
+        self.config.claude_args_for(&repo)
+    }
 
-        if let Some(multiplexer) = self.multiplexers.get_mut(&name) {
-            // Remove and shutdown dead panes
-            for dead_pane in multiplexer.remove_dead_panes() {
-                dead_pane.shutdown();
+    /// Plain keys offered by the "No Session" placeholder
+    fn handle_placeholder_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [b'n'] => self.open_new_session(),
+            [b'l'] => {
+                self.open_session_selector();
+                self.mode = UiMode::ListSessions;
             }
+            [b'r'] if !self.try_resume()? => {
+                self.notify(StatusMessage::info(
+                    "Nothing to resume",
+                    "No recent session to resume for this repository",
+                ));
+            }
+            [b'q'] => self.confirm_quit()?,
+            _ => {}
+        }
+        Ok(())
+    }
 
-            // If all panes are gone, switch back to Claude view
-            if multiplexer.is_empty() {
-                pair.view = SessionView::Claude;
+    /// Startup view when nothing was resumed: the session selector when there are recent
+    /// sessions or worktrees to pick from, otherwise the new session dialog
+    pub fn open_startup_picker(&mut self) {
+        // Outside a repository, first ask where sessions should run
+        if self.repo.is_none() {
+            self.directory_prompt
+                .reset(&path_to_display(&self.startup_path));
+            self.mode = UiMode::DirectoryPrompt;
+            return;
+        }
+        let (sessions, ..) = self.build_session_list();
+        if sessions.is_empty() {
+            self.open_new_session();
+        } else {
+            self.open_session_selector();
+            self.mode = UiMode::ListSessions;
+        }
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        self.advance_script()?;
+        while !self.should_quit {
+            // Only draw when something visible changed (never while passing through)
+            if self.mode != UiMode::Passthrough
+                && (self.needs_redraw || self.visible_sessions_dirty())
+            {
+                self.needs_redraw = false;
+                self.draw()?;
+                self.sync_cursor_style();
             }
+
+            let Ok(event) = self.event_rx.recv() else {
+                break;
+            };
+            self.dispatch(event)?;
         }
+
+        Ok(())
     }
 
-    /// Handle global hotkeys. Returns true if a hotkey was processed.
-    fn handle_hotkey(&mut self, bytes: &[u8]) -> anyhow::Result<bool> {
-        // Check if we're in shell view (for shell-specific hotkeys)
-        let in_shell_view = self
-            .active
-            .as_ref()
-            .map(|p| p.view == SessionView::Shell)
-            .unwrap_or(false);
+    /// Draw a frame and fit the sessions to the area it leaves them
+    fn draw(&mut self) -> anyhow::Result<()> {
+        let started = Instant::now();
+        let inner_size = self.render_frame(None)?;
+        let elapsed = started.elapsed();
+        self.render_stats.record(started, elapsed);
+        tracing::trace!(target: "shepherd::render", elapsed_us = elapsed.as_micros() as u64, "frame");
+        if self.size.get() != (inner_size.height, inner_size.width) {
+            self.size.set(inner_size.height, inner_size.width);
+            self.sync_session_sizes();
+        }
+        Ok(())
+    }
 
-        // Handle shell-specific hotkeys first (only in Normal mode and Shell view)
-        if self.mode == UiMode::Normal && in_shell_view {
-            match bytes {
-                [b] if *b == CTRL_BACKSLASH => {
-                    self.split_shell_pane()?;
-                    return Ok(true);
+    /// Handle an event as the run loop does
+    fn dispatch(&mut self, event: AppEvent) -> anyhow::Result<()> {
+        if let Err(error) = self.handle_event(event) {
+            self.report(error)?;
+        }
+        // Sessions start from many modes; ask about the limit once the event is handled
+        if self.limit_pending.is_some() && self.mode != UiMode::SessionLimitConfirm {
+            self.mode = UiMode::SessionLimitConfirm;
+            self.needs_redraw = true;
+        }
+        self.advance_script()
+    }
+
+    /// Run script steps until one has to wait, quitting once they have all run. Script
+    /// failures end the run loop so a scripted run exits with an error.
+    fn advance_script(&mut self) -> anyhow::Result<()> {
+        let Some(mut script) = self.script.take() else {
+            return Ok(());
+        };
+        let result = self.run_script(&mut script);
+        if script.is_done() {
+            self.should_quit = true;
+        }
+        self.script = Some(script);
+        result
+    }
+
+    fn run_script(&mut self, script: &mut Script) -> anyhow::Result<()> {
+        if let Some((step, waited)) = script.waiting() {
+            match &step.action {
+                ScriptAction::WaitForStop { session, timeout } => {
+                    if !self.is_live_session(session) {
+                        anyhow::bail!(
+                            "script line {}: session '{}' ended before it stopped",
+                            step.line,
+                            session
+                        );
+                    }
+                    if waited >= *timeout {
+                        anyhow::bail!(
+                            "script line {}: '{}' didn't stop within {}s",
+                            step.line,
+                            session,
+                            timeout.as_secs()
+                        );
+                    }
+                    return Ok(());
                 }
-                [b] if *b == CTRL_W => {
-                    self.close_shell_pane();
-                    return Ok(true);
+                ScriptAction::Command(Command::Create { session, .. }) => {
+                    // Already checked when the step ran
+                    let name = resolve_branch_name(session).unwrap_or_else(|_| session.clone());
+                    if self.is_creating(&name) {
+                        return Ok(());
+                    }
+                    // Held back by max_live_sessions, or its workflow failed
+                    if !self.is_live_session(&name) {
+                        anyhow::bail!("script line {}: session '{}' didn't start", step.line, name);
+                    }
+                    script.finish_wait();
                 }
-                [b] if *b == CTRL_Y => {
-                    self.cycle_shell_pane();
-                    return Ok(true);
+                ScriptAction::Sleep(duration) if waited < *duration => return Ok(()),
+                _ => script.finish_wait(),
+            }
+        }
+        while let Some(step) = script.next_step() {
+            self.run_script_step(script, step)?;
+            if self.should_quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn run_script_step(&mut self, script: &mut Script, step: ScriptStep) -> anyhow::Result<()> {
+        let line = step.line;
+        let failed = |e: String| anyhow::anyhow!("script line {}: {}", line, e);
+        self.needs_redraw = true;
+        match step.action {
+            ScriptAction::Command(ref command) => {
+                let reply = self.run_command(command.clone()).map_err(failed)?;
+                if let Some(name) = reply["session"].as_str() {
+                    if self.is_creating(name) {
+                        script.wait(step);
+                    } else if !self.is_live_session(name) {
+                        return Err(failed(format!("session '{}' didn't start", name)));
+                    }
                 }
-                _ => {}
             }
+            ScriptAction::WaitForStop { ref session, .. } => {
+                self.notify(StatusMessage::info(
+                    format!("Waiting for {}", session),
+                    format!("Script waiting for '{}' to stop", session),
+                ));
+                script.wait(step);
+            }
+            ScriptAction::ExportTranscript { session, path } => {
+                let count = self.export_transcript(&session, &path).map_err(failed)?;
+                self.notify(StatusMessage::success(
+                    "Transcript exported",
+                    format!(
+                        "Wrote {} entries from '{}' to {}",
+                        count,
+                        session,
+                        path.display()
+                    ),
+                ));
+            }
+            ScriptAction::Sleep(_) => script.wait(step),
+            ScriptAction::Quit => self.should_quit = true,
         }
+        Ok(())
+    }
 
-        // Handle global hotkeys
-        let hotkey = match bytes {
-            [b] if *b == CTRL_H => CTRL_H,
-            [b] if *b == CTRL_T => CTRL_T,
-            [b] if *b == CTRL_N => CTRL_N,
-            [b] if *b == CTRL_L => CTRL_L,
-            [b] if *b == CTRL_X => CTRL_X,
-            [b] if *b == CTRL_D => CTRL_D,
-            [b] if *b == CTRL_K => CTRL_K,
-            _ => return Ok(false),
-        };
+    /// Write the latest Claude transcript of live session `name` to `path` as markdown,
+    /// returning how many entries it held
+    fn export_transcript(&self, name: &str, path: &Path) -> Result<usize, String> {
+        let dir = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.path))
+            .chain(self.background.iter().map(|p| (&p.name, &p.path)))
+            .find(|(n, _)| *n == name)
+            .map(|(_, dir)| dir)
+            .ok_or_else(|| format!("no live session '{}'", name))?;
+        let transcript = transcript::locate(dir)
+            .ok_or_else(|| format!("no Claude transcript found in {}", dir.display()))?;
+        let entries = transcript::load(&transcript)
+            .map_err(|e| format!("failed to read {}: {}", transcript.display(), e))?;
+        std::fs::write(path, transcript::to_markdown(&entries))
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+        Ok(entries.len())
+    }
 
-        // Clean up current mode before switching
-        if self.mode == UiMode::NewSession {
-            self.create_dialog.clear();
+    /// Let the script carry on past a `wait-for stop` on `stopped`
+    fn finish_script_wait(&mut self, stopped: &str) {
+        if let Some(ref mut script) = self.script
+            && let Some((step, _)) = script.waiting()
+            && matches!(&step.action, ScriptAction::WaitForStop { session, .. } if session == stopped)
+        {
+            script.finish_wait();
         }
+    }
+
+    /// Show errors from library code in the status bar and carry on; anything else still
+    /// ends the run loop
+    fn report(&self, error: anyhow::Error) -> anyhow::Result<()> {
+        let error = error.downcast::<ShepardError>()?;
+        self.notify(error.into());
+        Ok(())
+    }
 
-        match hotkey {
-            CTRL_H => {
-                self.mode = if self.mode == UiMode::HelpPopup {
-                    UiMode::Normal
+    /// Handle a single event from the bus
+    fn handle_event(&mut self, event: AppEvent) -> anyhow::Result<()> {
+        match event {
+            AppEvent::Input(bytes) => {
+                // Any input may change mode, scroll position or dialog contents
+                self.needs_redraw = true;
+                self.handle_input(&bytes)?;
+            }
+            AppEvent::Resize => {
+                self.needs_redraw = true;
+                if self.mode == UiMode::Passthrough {
+                    self.fill_terminal_with_focused_session();
+                }
+            }
+            AppEvent::StatusSocket(event) => {
+                self.needs_redraw = true;
+                let stopped = (event.event == EventKind::Stop).then(|| event.session.clone());
+                if let Some(ref name) = stopped {
+                    self.finish_script_wait(name);
+                    self.run_chains(name);
+                }
+                self.handle_status_event(event);
+                if let Some(name) = stopped {
+                    self.finish_dispatched_task(&name);
+                }
+            }
+            AppEvent::Command(request) => {
+                self.needs_redraw = true;
+                let reply = self.run_command(request.command.clone());
+                // A create is answered once its workflow has run
+                if let Ok(ref value) = reply
+                    && value.get("started").is_none()
+                    && let Some(name) = value["session"].as_str()
+                    && let Some(creation) = self
+                        .creation
+                        .iter_mut()
+                        .map(|c| &mut c.request)
+                        .chain(&mut self.queued_creations)
+                        .find(|r| r.name == name)
+                {
+                    creation.reply = Some(request);
                 } else {
-                    UiMode::HelpPopup
-                };
+                    request.reply(reply);
+                }
             }
-            CTRL_T => {
-                self.mode = UiMode::Normal;
-                self.toggle_shell()?;
+            AppEvent::SessionDied(name) => {
+                tracing::debug!(session = name, "session died");
+                self.check_dead_sessions();
+                self.reclaim_dispatched_tasks();
+                // Room may have opened up under max_live_sessions
+                self.start_queued_sessions();
             }
-            CTRL_N => {
-                if self.mode != UiMode::NewSession {
-                    self.create_dialog.clear();
-                    self.mode = UiMode::NewSession;
+            AppEvent::GitStatus(path, status) => {
+                let previous = self.git_statuses.get(&path);
+                if previous == Some(&status) {
+                    return Ok(());
                 }
+                let newly_conflicting = !status.conflicts.is_empty()
+                    && previous.is_none_or(|prev| prev.conflicts.is_empty());
+                if newly_conflicting && let Some(name) = self.session_name_for_path(&path) {
+                    self.notify(StatusMessage::warn(
+                        format!("{} conflicts with base branch", name),
+                        format!(
+                            "Session {} no longer merges cleanly with the base branch; conflicting files: {}",
+                            name,
+                            status.conflicts.join(", ")
+                        ),
+                    ));
+                }
+                self.git_statuses.insert(path, status);
+                self.needs_redraw = true;
             }
-            CTRL_L => {
-                if self.mode == UiMode::ListSessions {
-                    self.mode = UiMode::Normal;
+            AppEvent::CreationStep(step) => {
+                if self.mode == UiMode::Creating {
+                    self.needs_redraw = true;
                 } else {
-                    self.open_session_selector();
-                    self.mode = UiMode::ListSessions;
+                    let name = self.creation_progress.name().to_string();
+                    self.notify(StatusMessage::info(
+                        format!("{}...", step),
+                        format!("{} for session '{}'", step, name),
+                    ));
                 }
+                self.creation_progress.push_step(step);
             }
-            CTRL_X => {
-                if self.active.is_some() {
-                    if let Some(ref pair) = self.active {
-                        self.kill_confirm_dialog.set_session_name(&pair.name);
-                    }
-                    self.mode = UiMode::KillConfirmation;
+            AppEvent::SessionPrepared(name, result) => {
+                self.needs_redraw = true;
+                self.on_session_prepared(name, result)?;
+            }
+            AppEvent::WorktreesLoaded(repo_dir, worktrees) => {
+                // A load for a repo that is no longer the target is stale
+                if repo_dir == self.repo_dir() {
+                    self.needs_redraw = true;
+                    let active_paths = self.get_active_session_paths();
+                    self.worktree_cleanup_dialog
+                        .set_worktrees_with_active(worktrees, active_paths);
                 }
             }
-            CTRL_D => {
-                self.mode = UiMode::QuitConfirmation;
+            AppEvent::WorktreesDeleted(outcome) => {
+                self.needs_redraw = true;
+                self.on_worktrees_deleted(outcome);
             }
-            CTRL_K => {
-                if self.mode == UiMode::WorktreeCleanup {
-                    self.mode = UiMode::Normal;
-                } else {
-                    self.open_worktree_cleanup();
-                    self.mode = UiMode::WorktreeCleanup;
+            AppEvent::Tick => {
+                // The progress popup shows elapsed time
+                if self.mode == UiMode::Creating {
+                    self.needs_redraw = true;
+                }
+                // Dead claude sessions are only reaped in claude view, so keep checking
+                self.check_dead_sessions();
+                if self.mode == UiMode::Passthrough && self.focused_session().is_none() {
+                    self.exit_passthrough();
+                }
+                self.prune_pending_approvals();
+                self.forward_clipboard_writes();
+                self.check_watch_rules();
+                self.poll_checks();
+                self.poll_command_runs();
+                self.reclaim_dispatched_tasks();
+                self.start_queued_sessions();
+                if self.last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+                    self.last_snapshot = Instant::now();
+                    self.snapshot_screens();
+                }
+                if self.last_trim_check.elapsed() >= TRIM_CHECK_INTERVAL {
+                    self.last_trim_check = Instant::now();
+                    self.trim_idle_scrollback(false);
+                }
+                if self.log_pane_visible && self.log_pane.refresh() {
+                    self.needs_redraw = true;
+                }
+                self.refresh_git_status();
+                // The throttled badge comes and goes without new output to redraw for
+                let throttled = self.focused_session().is_some_and(|s| s.is_throttled());
+                if throttled != self.throttle_shown {
+                    self.throttle_shown = throttled;
+                    self.needs_redraw = true;
+                }
+                if self.render_stats.visible {
+                    self.sample_render_stats();
+                    self.needs_redraw = true;
+                }
+                // Update status bar (check for new messages, clear expired)
+                if self.status_bar.update() {
+                    self.needs_redraw = true;
                 }
             }
-            _ => return Ok(false),
         }
-
-        Ok(true)
+        Ok(())
     }
 
-    fn render_frame(&mut self) -> anyhow::Result<ratatui::layout::Rect> {
-        // Update status bar (check for new messages, clear expired)
-        self.status_bar.update();
-
-        let (screen, active_view, scroll_offset) = match &self.active {
-            Some(pair) => {
-                let screen = match pair.view {
-                    SessionView::Claude => Some(pair.claude.get_screen()),
-                    // For shell view, we'll render the multiplexer instead
-                    SessionView::Shell => None,
-                };
-                (screen, pair.view, pair.scroll_offset)
-            }
-            None => (None, SessionView::Claude, 0),
+    /// Re-emit clipboard writes (OSC 52) from any session to the host terminal, so
+    /// yanking inside nested vim or tmux reaches the system clipboard
+    fn forward_clipboard_writes(&self) {
+        let sessions = self
+            .active
+            .iter()
+            .map(|pair| &*pair.claude)
+            .chain(self.background.iter().map(|pair| &*pair.claude))
+            .chain(
+                self.multiplexers
+                    .values()
+                    .flat_map(|m| m.panes().map(|p| &**p)),
+            );
+        // Only the last write survives in the clipboard anyway
+        let Some(clipboard) = sessions.filter_map(|s| s.take_clipboard()).last() else {
+            return;
         };
-        let active_name = self.active.as_ref().map(|p| p.name.clone());
-        let active_path = self.active.as_ref().map(|p| p.path.clone());
-        let background_count = self.background.len();
-        let mode = self.mode.clone();
-
-        // Get status bar render data
-        let stopped_count = self.stopped_session_count();
-        let bottom_left = self.status_bar.render_bottom_left();
-        let bottom_center = self.status_bar.render_bottom_center();
+        let mut out = stdout();
+        let _ = io::Write::write_all(&mut out, format!("\x1b]52;{}\x07", clipboard).as_bytes());
+        let _ = io::Write::flush(&mut out);
+    }
 
-        // Build map of session names to their activity states for selector rendering
-        let session_states: std::collections::HashMap<String, SessionActivity> = self
+    /// Match new output lines from every session against the watch rules and fire the
+    /// actions of those that match
+    fn check_watch_rules(&mut self) {
+        if self.watch_rules.is_empty() {
+            return;
+        }
+        let sessions = self
             .active
             .iter()
-            .map(|p| (p.name.clone(), p.activity.clone()))
+            .map(|pair| (&pair.name, &*pair.claude))
             .chain(
                 self.background
                     .iter()
-                    .map(|p| (p.name.clone(), p.activity.clone())),
+                    .map(|pair| (&pair.name, &*pair.claude)),
             )
-            .collect();
-
-        let mut inner_area = ratatui::layout::Rect::default();
-
-        // Get multiplexer for shell view rendering (if in shell view)
-        let multiplexer_name = if active_view == SessionView::Shell {
-            active_name.clone()
-        } else {
-            None
-        };
-
-        self.terminal.draw(|frame| {
-            let area = frame.area();
-
-            // Render main view (frame/borders)
-            let main_inner = self.main_view.render(
-                frame,
-                screen.as_ref(),
-                active_name.as_deref(),
-                active_path.as_deref(),
-                active_view,
-                background_count,
-                stopped_count,
-                bottom_left,
-                bottom_center,
-                scroll_offset,
+            .chain(
+                self.multiplexers
+                    .iter()
+                    .flat_map(|(name, m)| m.panes().map(move |p| (name, &**p))),
             );
-
-            // If in shell view, render the multiplexer inside the frame
-            if let Some(ref name) = multiplexer_name {
-                if let Some(multiplexer) = self.multiplexers.get(name) {
-                    inner_area = multiplexer.render(frame, main_inner);
-                } else {
-                    inner_area = main_inner;
+        let mut hits = Vec::new();
+        for (name, session) in sessions {
+            for line in session.take_output_lines() {
+                for rule in self.watch_rules.check(name, &line) {
+                    hits.push((name.clone(), rule.clone(), line.clone()));
                 }
-            } else {
-                inner_area = main_inner;
             }
+        }
+        for (name, rule, line) in hits {
+            self.fire_watch_rule(&name, &rule, &line);
+        }
+    }
 
-            // Render overlays based on mode
-            match mode {
-                UiMode::Normal => {}
-                UiMode::HelpPopup => {
-                    self.help_popup.render(frame, area);
-                }
-                UiMode::ListSessions => {
-                    self.session_selector.render(
-                        frame,
-                        area,
-                        &self.selector_sessions,
-                        &session_states,
-                    );
-                }
-                UiMode::NewSession => {
-                    self.create_dialog.render(frame, area);
-                }
-                UiMode::KillConfirmation => {
-                    self.kill_confirm_dialog.render(frame, area);
-                }
-                UiMode::QuitConfirmation => {
-                    self.quit_confirm_dialog.render(frame, area);
-                }
-                UiMode::WorktreeCleanup => {
-                    self.worktree_cleanup_dialog.render(frame, area);
-                }
-                UiMode::WorktreeDeleteConfirm => {
-                    self.delete_confirm_dialog.render(frame, area);
+    fn fire_watch_rule(&mut self, name: &str, rule: &WatchRule, line: &str) {
+        let label = rule.label();
+        if let Some(level) = rule.status {
+            self.notify(StatusMessage::new(
+                level,
+                format!("{}: {}", name, label),
+                format!("Watch rule '{}' matched in {}: {}", label, name, line),
+            ));
+        }
+        if rule.notify {
+            let text: String = format!("shepherd: {}: {}", name, label)
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect();
+            let mut out = stdout();
+            let _ = io::Write::write_all(&mut out, format!("\x1b]9;{}\x07", text).as_bytes());
+            let _ = io::Write::flush(&mut out);
+        }
+        let pair = match self.active.as_mut().filter(|p| p.name == name) {
+            Some(pair) => Some((&mut pair.activity, pair.path.clone())),
+            None => self
+                .background
+                .iter_mut()
+                .find(|p| p.name == name)
+                .map(|pair| (&mut pair.activity, pair.path.clone())),
+        };
+        let Some((activity, path)) = pair else {
+            return;
+        };
+        if rule.mark {
+            *activity = SessionActivity::Flagged(label.to_string());
+            self.needs_redraw = true;
+        }
+        if let Some(ref command) = rule.command {
+            let child = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .current_dir(&path)
+                .env("SHEPHERD_SESSION", name)
+                .env("SHEPHERD_MATCH", line)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn();
+            match child {
+                // Reap it in the background
+                Ok(mut child) => {
+                    std::thread::spawn(move || child.wait());
                 }
+                Err(e) => self.notify(StatusMessage::err(
+                    "Watch command failed",
+                    format!("Failed to run command for watch rule '{}': {}", label, e),
+                )),
             }
-        })?;
+        }
+    }
 
-        Ok(inner_area)
+    /// Queue a git status refresh for the active session when it gains focus, then
+    /// periodically while it stays focused
+    fn refresh_git_status(&mut self) {
+        let Some(ref pair) = self.active else {
+            return;
+        };
+        let due = match self.last_git_refresh {
+            Some((ref path, at)) => path != &pair.path || at.elapsed() >= GIT_REFRESH_INTERVAL,
+            None => true,
+        };
+        if due {
+            self.git_worker.refresh(&pair.path);
+            self.last_git_refresh = Some((pair.path.clone(), Instant::now()));
+        }
+        self.fetch_if_due();
     }
 
-    /// Check if bytes contain mouse events (SGR or legacy format).
-    /// Returns true if the bytes are entirely mouse events that should not be forwarded to PTY.
-    fn is_mouse_event(bytes: &[u8]) -> bool {
-        // Check if all content is mouse events (may be multiple concatenated)
-        let mut pos = 0;
-        while pos < bytes.len() {
-            // SGR mouse mode: ESC [ < ... M or ESC [ < ... m
-            if bytes[pos..].starts_with(b"\x1b[<") {
-                // Find the terminating M or m
-                if let Some(end) = bytes[pos..].iter().position(|&b| b == b'M' || b == b'm') {
-                    pos += end + 1;
-                    continue;
-                }
-            }
+    /// Name of the live session whose checkout is at `path`
+    fn session_name_for_path(&self, path: &Path) -> Option<String> {
+        self.active
+            .iter()
+            .map(|p| (&p.name, &p.path))
+            .chain(self.background.iter().map(|p| (&p.name, &p.path)))
+            .find(|(_, p)| p.as_path() == path)
+            .map(|(name, _)| name.clone())
+    }
 
-            // Legacy mouse mode: ESC [ M followed by 3 bytes
-            if bytes[pos..].len() >= 6 && bytes[pos..].starts_with(b"\x1b[M") {
-                pos += 6;
-                continue;
+    /// Paths of all live sessions' checkouts
+    fn live_session_paths(&self) -> Vec<PathBuf> {
+        self.active
+            .iter()
+            .map(|p| p.path.clone())
+            .chain(self.background.iter().map(|p| p.path.clone()))
+            .collect()
+    }
+
+    /// Queue a background `git fetch` once `fetch_interval_secs` has passed, refreshing
+    /// every live session's ahead/behind counts afterwards
+    fn fetch_if_due(&mut self) {
+        let Some(secs) = self.config.fetch_interval_secs else {
+            return;
+        };
+        let Some(ref repo) = self.repo else {
+            return;
+        };
+        if self
+            .last_fetch
+            .is_some_and(|at| at.elapsed() < Duration::from_secs(secs))
+        {
+            return;
+        }
+        // Worktrees share their remote-tracking refs, so one fetch covers every session
+        self.git_worker
+            .fetch(repo.root(), self.live_session_paths());
+        self.last_fetch = Some(Instant::now());
+    }
+
+    /// Dispatch input bytes to global hotkeys or the handler for the current mode
+    fn handle_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        // Focus reports from the host terminal are never keystrokes
+        if let [0x1b, b'[', focus @ (b'I' | b'O')] = bytes {
+            self.host_focused = *focus == b'I';
+            self.report_focus(self.host_focused);
+            return Ok(());
+        }
+        // Pass-through forwards everything except its own exit chord
+        if self.mode == UiMode::Passthrough {
+            if bytes == [0x1b, b't'] {
+                self.exit_passthrough();
+            } else if let Some(ref pair) = self.active {
+                let (name, view) = (pair.name.clone(), pair.view);
+                self.forward_to_active(&name, view, bytes);
             }
+            return Ok(());
+        }
+        if self.handle_hotkey(bytes)? {
+            return Ok(());
+        }
+        match self.mode {
+            UiMode::Normal => self.handle_normal_input(bytes),
+            UiMode::HelpPopup => self.handle_help_input(bytes),
+            UiMode::ListSessions => self.handle_list_input(bytes),
+            UiMode::NewSession => self.handle_new_session_input(bytes),
+            UiMode::KillConfirmation => self.handle_kill_confirmation_input(bytes),
+            UiMode::SessionLimitConfirm => self.handle_session_limit_input(bytes),
+            UiMode::QuitConfirmation => self.handle_quit_confirmation_input(bytes),
+            UiMode::WorktreeCleanup => self.handle_worktree_cleanup_input(bytes),
+            UiMode::WorktreeDeleteConfirm => self.handle_delete_confirm_input(bytes),
+            UiMode::MacroPicker => self.handle_macro_picker_input(bytes),
+            UiMode::Inbox => self.handle_inbox_input(bytes),
+            UiMode::SignalMenu => self.handle_signal_menu_input(bytes),
+            UiMode::Dashboard => self.handle_dashboard_input(bytes),
+            UiMode::DirectoryPrompt => self.handle_directory_prompt_input(bytes),
+            UiMode::RunCommand => self.handle_run_dialog_input(bytes),
+            UiMode::SessionPlan => self.handle_session_plan_input(bytes),
+            UiMode::Creating => self.handle_creation_input(bytes),
+            UiMode::CommandPalette => self.handle_command_palette_input(bytes),
+            UiMode::Compose => self.handle_compose_input(bytes),
+            UiMode::PromptHistory => self.handle_prompt_history_input(bytes),
+            UiMode::Transcript => self.handle_transcript_input(bytes),
+            UiMode::GitHistory => self.handle_git_history_input(bytes),
+            UiMode::Checkpoints => self.handle_checkpoints_input(bytes),
+            UiMode::UndoChanges => self.handle_undo_changes_input(bytes),
+            UiMode::Dispatch => self.handle_dispatch_input(bytes),
+            UiMode::MemoryStats => self.handle_memory_stats_input(bytes),
+            UiMode::Passthrough => Ok(()),
+        }
+    }
 
-            // Not a mouse event
-            return false;
+    /// The session input goes to: claude, or the focused shell pane in shell view
+    fn focused_session(&self) -> Option<&Session> {
+        let pair = self.active.as_ref()?;
+        match pair.view {
+            SessionView::Claude => Some(&*pair.claude),
+            SessionView::Shell => self
+                .multiplexers
+                .get(&pair.name)
+                .and_then(|m| m.active_pane())
+                .map(|pane| &**pane),
         }
+    }
 
-        // All bytes were mouse events
-        pos > 0
+    /// Forward a focus change to the focused session, if shepherd's terminal has focus
+    fn report_focus(&self, focused: bool) {
+        if self.host_focused
+            && let Some(session) = self.focused_session()
+        {
+            session.report_focus(focused);
+        }
     }
 
-    /// Parse mouse scroll events from escape sequences.
-    /// Returns Some(lines) where positive = scroll up, negative = scroll down.
-    /// Handles multiple concatenated events and sums up scroll deltas.
-    /// Returns None if no scroll events found.
-    fn parse_scroll_event(bytes: &[u8]) -> Option<i32> {
-        let mut total_delta = 0i32;
-        let mut pos = 0;
+    /// Give the host terminal the cursor shape the focused session asked for
+    fn sync_cursor_style(&mut self) {
+        // Headless there is no host cursor, and stdout may be a pipe
+        if !self.terminal.backend().is_host() {
+            return;
+        }
+        let style = self.focused_session().map_or(0, |s| s.cursor_style());
+        if self.host_cursor_style == Some(style) {
+            return;
+        }
+        let command = match style {
+            1 => SetCursorStyle::BlinkingBlock,
+            2 => SetCursorStyle::SteadyBlock,
+            3 => SetCursorStyle::BlinkingUnderScore,
+            4 => SetCursorStyle::SteadyUnderScore,
+            5 => SetCursorStyle::BlinkingBar,
+            6 => SetCursorStyle::SteadyBar,
+            _ => SetCursorStyle::DefaultUserShape,
+        };
+        let _ = stdout().execute(command);
+        self.host_cursor_style = Some(style);
+    }
 
-        while pos < bytes.len() {
-            // SGR mouse mode: ESC [ < Ps ; Px ; Py M (or m for release)
-            if bytes[pos..].starts_with(b"\x1b[<") {
-                // Find the end of this event (M or m)
-                if let Some(end_offset) = bytes[pos..].iter().position(|&b| b == b'M' || b == b'm')
-                {
-                    let event = &bytes[pos..pos + end_offset + 1];
+    /// Redraw the focused session from scratch, and the whole terminal with it
+    fn force_redraw(&mut self) {
+        if let Some(session) = self.focused_session()
+            && let Err(e) = session.force_redraw()
+        {
+            self.notify(StatusMessage::err(
+                "Redraw failed",
+                format!("Failed to force redraw: {}", e),
+            ));
+        }
+        let _ = self.terminal.clear();
+        self.needs_redraw = true;
+    }
 
-                    // Parse the button code (between '<' and first ';')
-                    if let Some(semi_pos) = event[3..].iter().position(|&b| b == b';')
-                        && let Ok(button_str) = std::str::from_utf8(&event[3..3 + semi_pos])
-                        && let Ok(button) = button_str.parse::<u8>()
-                    {
-                        // Button 64 = scroll up, 65 = scroll down
-                        let base_button = button & 0b11000011;
-                        match base_button {
-                            64 => total_delta += 1, // scroll up
-                            65 => total_delta -= 1, // scroll down
-                            _ => {}
-                        }
-                    }
+    /// Update the stats overlay's per-session counters and event backlog
+    fn sample_render_stats(&mut self) {
+        let counters = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &*p.claude))
+            .chain(self.background.iter().map(|p| (&p.name, &*p.claude)))
+            .map(|(name, claude)| SessionCounters {
+                name: name.clone(),
+                parsed: claude.bytes_parsed(),
+                backlog: claude.pending_output(),
+            })
+            .collect();
+        self.render_stats.sample(counters, self.event_rx.len());
+    }
 
-                    pos += end_offset + 1;
-                    continue;
-                }
-            }
+    /// Write the frame on screen, the focused session's screen, the UI mode and the
+    /// live sessions to a file under ~/.shepherd/debug
+    fn write_debug_dump(&mut self) -> anyhow::Result<()> {
+        let mut frame = Buffer::default();
+        self.render_frame(Some(&mut frame))?;
+        let active = self
+            .active
+            .as_ref()
+            .map(|p| (&p.name, p.view, p.scroll_offset));
+        let screen = active
+            .zip(self.focused_session())
+            .map(|((name, view, scroll), session)| {
+                session.with_screen(
+                    if view == SessionView::Claude {
+                        scroll
+                    } else {
+                        0
+                    },
+                    |screen| ScreenDump {
+                        title: match view {
+                            SessionView::Claude => name.clone(),
+                            SessionView::Shell => format!("{} [shell]", name),
+                        },
+                        contents: screen.contents(),
+                        cursor: screen.cursor_position(),
+                        alternate_screen: screen.alternate_screen(),
+                    },
+                )
+            });
+        let active_row = self.active.iter().map(|p| SessionRow {
+            name: p.name.clone(),
+            path: p.path.clone(),
+            active: true,
+            view: format!("{:?}", p.view),
+            activity: format!("{:?}", p.activity),
+            pid: p.claude.process_id(),
+            dead: p.claude.is_dead(),
+            scroll_offset: p.scroll_offset,
+        });
+        let background_rows = self.background.iter().map(|p| SessionRow {
+            name: p.name.clone(),
+            path: p.path.clone(),
+            active: false,
+            view: format!("{:?}", p.last_view),
+            activity: format!("{:?}", p.activity),
+            pid: p.claude.process_id(),
+            dead: p.claude.is_dead(),
+            scroll_offset: p.scroll_offset,
+        });
+        let dump = DebugDump {
+            mode: format!("{:?}", self.mode),
+            session_size: self.size.get(),
+            sessions: active_row.chain(background_rows).collect(),
+            frame,
+            screen,
+        };
+        let written = debug_dir()
+            .ok_or_else(|| io::Error::other("no home directory"))
+            .and_then(|dir| dump.write(&dir));
+        self.notify(match written {
+            Ok(path) => StatusMessage::success(
+                "Debug dump written",
+                format!("Wrote debug dump to {}", path.display()),
+            ),
+            Err(e) => StatusMessage::err(
+                "Debug dump failed",
+                format!("Failed to write debug dump: {}", e),
+            ),
+        });
+        Ok(())
+    }
 
-            // Legacy mouse mode: ESC [ M Cb Cx Cy
-            if bytes[pos..].len() >= 6 && bytes[pos..].starts_with(b"\x1b[M") {
-                let button = bytes[pos + 3];
-                let base_button = button & 0b11000011;
-                match base_button {
-                    96 => total_delta += 1, // scroll up
-                    97 => total_delta -= 1, // scroll down
-                    _ => {}
-                }
-                pos += 6;
-                continue;
-            }
+    /// Hand the terminal to the focused session: stop rendering and let its raw output
+    /// and our raw input flow straight through, for TUIs the vt100 re-rendering breaks
+    fn enter_passthrough(&mut self) {
+        if self.focused_session().is_none() {
+            return;
+        }
+        let _ = stdout().execute(DisableMouseCapture);
+        self.mode = UiMode::Passthrough;
+        self.fill_terminal_with_focused_session();
+        if let Some(session) = self.focused_session() {
+            session.start_passthrough();
+        }
+        self.notify(StatusMessage::info(
+            "Pass-through",
+            "Entered pass-through mode (alt+t to leave)",
+        ));
+    }
 
-            // Not a recognized event at this position
-            break;
+    /// Size the focused session to the whole terminal, as no frame is drawn around it
+    fn fill_terminal_with_focused_session(&self) {
+        if let Ok(size) = self.terminal.size() {
+            self.size.set(size.height, size.width);
+        }
+        if let Some(session) = self.focused_session() {
+            session.sync_size();
         }
+    }
 
-        if total_delta != 0 {
-            Some(total_delta)
-        } else {
-            None
+    /// Take the terminal back from pass-through and redraw from the parser's screen. The
+    /// next frame shrinks the session back into the frame, so the child redraws too.
+    fn exit_passthrough(&mut self) {
+        if let Some(session) = self.focused_session() {
+            session.stop_passthrough();
         }
+        // Undo modes the child may have left on the host terminal: application cursor
+        // keys and keypad, mouse reporting and a switch out of the alternate screen
+        let _ = io::Write::write_all(&mut stdout(), b"\x1b[?1l\x1b>");
+        let _ = stdout().execute(DisableMouseCapture);
+        let _ = stdout().execute(EnterAlternateScreen);
+        let _ = stdout().execute(EnableMouseCapture);
+        let _ = stdout().execute(EnableFocusChange);
+        // The child may have changed the cursor shape behind our back
+        self.host_cursor_style = None;
+        let _ = self.terminal.clear();
+        self.mode = UiMode::Normal;
+        self.needs_redraw = true;
     }
 
-    fn handle_normal_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+    /// Push the current terminal size to every session, including backgrounded ones
+    fn sync_session_sizes(&self) {
+        if let Some(ref pair) = self.active {
+            pair.claude.sync_size();
+        }
+        for pair in &self.background {
+            pair.claude.sync_size();
+        }
+        for multiplexer in self.multiplexers.values() {
+            multiplexer.sync_sizes();
+        }
+    }
+
+    /// Check whether the session(s) currently on screen have unrendered output
+    fn visible_sessions_dirty(&self) -> bool {
+        if self.mode == UiMode::Dashboard {
+            return self
+                .active
+                .iter()
+                .map(|p| (&p.name, &*p.claude))
+                .chain(self.background.iter().map(|p| (&p.name, &*p.claude)))
+                .any(|(name, session)| {
+                    self.dashboard.sessions().contains(name) && session.is_dirty()
+                });
+        }
         let Some(ref pair) = self.active else {
-            return Ok(());
+            return false;
         };
+        match pair.view {
+            SessionView::Claude => pair.claude.is_dirty(),
+            SessionView::Shell => self
+                .multiplexers
+                .get(&pair.name)
+                .map(|m| m.is_dirty())
+                .unwrap_or(false),
+        }
+    }
 
-        let name = pair.name.clone();
-        let view = pair.view;
+    /// Check if the active session has died and handle cleanup
+    fn check_dead_sessions(&mut self) {
+        // Rebases first: cleaning up dead panes would lose how a rebase pane exited
+        self.reap_rebases();
+        // Then clean up dead panes in multiplexers
+        self.cleanup_dead_multiplexer_panes();
+        self.reap_background_sessions();
 
-        // Handle scroll events - adjust scroll offset instead of forwarding to PTY
-        if let Some(scroll_delta) = Self::parse_scroll_event(bytes) {
-            if let Some(ref mut pair) = self.active {
-                // vt100 will clamp the scrollback position to the actual scrollback buffer size
-                // The max is SCROLLBACK (1000) lines from session.rs
-                const MAX_SCROLLBACK: usize = 1000;
+        // Collect info about dead claude session
+        let dead_session_info = if let Some(ref pair) = self.active {
+            // Only check claude session death when in Claude view
+            if pair.view == SessionView::Claude && pair.claude.is_dead() {
+                self.notify(claude_exit_message(&pair.name, &pair.claude));
+                Some((
+                    pair.name.clone(),
+                    pair.path.clone(),
+                    pair.repo.clone(),
+                    pair.resumed,
+                ))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        if let Some((name, path, repo, was_resumed)) = dead_session_info {
+            self.needs_redraw = true;
+
+            // Shutdown and remove the active session
+            if let Some(pair) = self.active.take() {
+                pair.claude.shutdown();
+                self.record_session_time(&pair.name, &pair.timer, pair.repo.as_ref());
+            }
+            self.release_worktree(&path);
+            self.spawn_workflow_hook(&name, &path, repo.as_ref(), |w, s, c| {
+                w.on_session_exit(s, c)
+            });
+
+            // Also cleanup the multiplexer for this session
+            self.rebases.remove(&name);
+            if let Some(mut multiplexer) = self.multiplexers.remove(&name) {
+                for pane in multiplexer.remove_dead_panes() {
+                    pane.shutdown();
+                }
+                // Shutdown any remaining live panes
+                while let Some(pane) = multiplexer.close_active_pane() {
+                    pane.shutdown();
+                }
+            }
+
+            // Close any popups and return to normal mode
+            if self.mode == UiMode::ListSessions {
+                self.mode = UiMode::Normal;
+            }
+
+            // If this was a resumed session, start a fresh session in the same directory
+            // without the --continue flag
+            if was_resumed {
+                let args_owned = self.claude_args_for_path(&path);
+                let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
+                match self.add_claude_session(&name, "claude", &args, &path, repo, false) {
+                    Err(e) => self.notify(StatusMessage::err(
+                        "Failed to restart session",
+                        format!("{}", e),
+                    )),
+                    Ok(false) => {}
+                    Ok(true) => self.notify(StatusMessage::success(
+                        "Session restarted",
+                        format!("Started fresh session in {}", path.display()),
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Remove background sessions whose claude has exited, the way a kill would: their
+    /// time is recorded, their worktree released and the exit hook run
+    fn reap_background_sessions(&mut self) {
+        let dead: Vec<String> = self
+            .background
+            .iter()
+            .filter(|p| p.claude.is_dead())
+            .map(|p| p.name.clone())
+            .collect();
+        for name in dead {
+            if let Some(pair) = self.background.iter().find(|p| p.name == name) {
+                self.notify(claude_exit_message(&name, &pair.claude));
+            }
+            self.kill_session(&name);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Apply a status event from Claude hooks to the matching session
+    fn handle_status_event(&mut self, event: StatusEvent) {
+        // Progress lines only update what's shown for the session
+        if let EventKind::Progress(ref message) = event.event {
+            let progress = (!message.is_empty()).then(|| message.clone());
+            if let Some(ref mut pair) = self.active
+                && pair.name == event.session
+            {
+                pair.progress = progress;
+            } else if let Some(pair) = self.background.iter_mut().find(|p| p.name == event.session)
+            {
+                pair.progress = progress;
+            }
+            self.needs_redraw = true;
+            return;
+        }
+
+        // Only permission prompts wait on an answer; any other event, the idle reminder
+        // included, means claude is back at its input box
+        if matches!(event.event, EventKind::PermissionPrompt(_)) {
+            if !self.pending_approvals.contains(&event.session) {
+                self.pending_approvals.push(event.session.clone());
+            }
+        } else {
+            self.pending_approvals.retain(|name| name != &event.session);
+        }
+
+        let new_activity = match &event.event {
+            EventKind::Stop | EventKind::PermissionPrompt(_) | EventKind::Notification(_) => {
+                SessionActivity::Stopped
+            }
+            EventKind::ToolStart(tool) => SessionActivity::RunningTool(tool.clone()),
+            EventKind::ToolEnd => SessionActivity::Active,
+            EventKind::Progress(_) => return,
+        };
+        let stopped = event.event == EventKind::Stop;
+
+        // A watch rule's flag outlasts tool activity; only stopping replaces it
+        let apply = |activity: &mut SessionActivity| {
+            if !matches!(activity, SessionActivity::Flagged(_))
+                || new_activity == SessionActivity::Stopped
+            {
+                *activity = new_activity.clone();
+            }
+        };
+
+        // Update the activity state for the matching session
+        if let Some(ref mut pair) = self.active
+            && pair.name == event.session
+        {
+            apply(&mut pair.activity);
+            if stopped {
+                pair.progress = None;
+            }
+            return;
+        }
+
+        // Check background sessions
+        if let Some(pair) = self.background.iter_mut().find(|p| p.name == event.session) {
+            apply(&mut pair.activity);
+            if stopped {
+                pair.progress = None;
+            }
+        }
+    }
+
+    /// Start the follow-up sessions configured for `stopped` that haven't run for it yet
+    fn run_chains(&mut self, stopped: &str) {
+        let Some(path) = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.path))
+            .chain(self.background.iter().map(|p| (&p.name, &p.path)))
+            .find(|(name, _)| *name == stopped)
+            .map(|(_, path)| path.clone())
+        else {
+            return;
+        };
+        let steps: Vec<(usize, ChainStep)> = self
+            .config
+            .chains
+            .iter()
+            .enumerate()
+            .filter(|(i, step)| {
+                step.matches(stopped) && !self.chains_run.contains(&(*i, stopped.to_string()))
+            })
+            .map(|(i, step)| (i, step.clone()))
+            .collect();
+        for (i, step) in steps {
+            self.chains_run.insert((i, stopped.to_string()));
+            let name = ChainStep::fill(&step.start, stopped, &path);
+            if name.is_empty() || name == stopped {
+                continue;
+            }
+            let prompt = ChainStep::fill(&step.prompt, stopped, &path);
+            self.start_or_prompt_session(&name, &prompt);
+        }
+    }
+
+    /// Carry out a command from the socket. Sessions are created in the background so
+    /// whatever is being typed here keeps its session.
+    fn run_command(&mut self, command: Command) -> Result<serde_json::Value, String> {
+        match command {
+            Command::Create { session, prompt } => {
+                let name = resolve_branch_name(&session)?;
+                if self.is_live_session(&name) || self.is_creating(&name) {
+                    return Ok(serde_json::json!({ "session": name, "started": false }));
+                }
+                self.notify(StatusMessage::info(
+                    format!("Creating {}", name),
+                    format!("Creating '{}' requested on the command socket", name),
+                ));
+                self.start_or_prompt_session(&name, prompt.as_deref().unwrap_or_default());
+                // Whether it started is known once its workflow has run
+                Ok(serde_json::json!({ "session": name }))
+            }
+            Command::Kill { session } => {
+                if !self.is_live_session(&session) {
+                    return Err(format!("no live session '{}'", session));
+                }
+                self.kill_session(&session);
+                self.notify(StatusMessage::info(
+                    format!("Killed {}", session),
+                    format!("Killed '{}' on request from the command socket", session),
+                ));
+                Ok(serde_json::Value::Null)
+            }
+            Command::SendPrompt { session, prompt } => {
+                if !self.send_prompt(&session, &prompt) {
+                    return Err(format!("no live session '{}'", session));
+                }
+                Ok(serde_json::Value::Null)
+            }
+            Command::List => {
+                let active = self.active.as_ref().map(|p| p.name.clone());
+                let mut sessions: Vec<(usize, serde_json::Value)> = self
+                    .active
+                    .iter()
+                    .map(|p| (p.seq, &p.name, &p.path, &p.activity))
+                    .chain(
+                        self.background
+                            .iter()
+                            .map(|p| (p.seq, &p.name, &p.path, &p.activity)),
+                    )
+                    .map(|(seq, name, path, activity)| {
+                        let state = match activity {
+                            SessionActivity::Active => "working",
+                            SessionActivity::RunningTool(_) => "running_tool",
+                            SessionActivity::Stopped => "stopped",
+                            SessionActivity::Paused => "paused",
+                            SessionActivity::Flagged(_) => "flagged",
+                        };
+                        let session = serde_json::json!({
+                            "name": name,
+                            "path": path,
+                            "state": state,
+                            "active": active.as_ref() == Some(name),
+                        });
+                        (seq, session)
+                    })
+                    .collect();
+                sessions.sort_by_key(|(seq, _)| *seq);
+                let sessions: Vec<serde_json::Value> =
+                    sessions.into_iter().map(|(_, session)| session).collect();
+                Ok(serde_json::json!({ "sessions": sessions }))
+            }
+            Command::Open { path, line } => {
+                let root = self
+                    .active
+                    .as_ref()
+                    .map_or_else(|| self.repo_dir().to_path_buf(), |p| p.path.clone());
+                let path = root.join(path);
+                if !path.is_file() {
+                    return Err(format!("no such file: {}", path.display()));
+                }
+                self.open_in_editor(&path, line)
+                    .map_err(|e| e.to_string())?;
+                Ok(serde_json::json!({ "path": path }))
+            }
+        }
+    }
+
+    /// Start `name` in the background with `prompt`, or send the prompt if it is already live
+    fn start_or_prompt_session(&mut self, name: &str, prompt: &str) {
+        if self.is_live_session(name) {
+            self.send_prompt(name, prompt);
+            return;
+        }
+        let prompt = (!prompt.trim().is_empty()).then_some(prompt);
+        self.new_background_session(name, prompt);
+    }
+
+    /// Hand queued tasks to idle sessions, taking turns, and create sessions for the rest
+    /// while fewer than `dispatch.max_sessions` are live
+    fn dispatch_tasks(&mut self) {
+        while self.dispatcher.has_queued() {
+            let live = self.live_sessions_by_slot();
+            let idle = self
+                .dispatcher
+                .next_session(&live, |name| self.is_idle_for_dispatch(name))
+                .map(str::to_string);
+            if let Some(name) = idle {
+                let Some(task) = self.dispatcher.assign(&name) else {
+                    break;
+                };
+                if !self.send_prompt(&name, &task) {
+                    self.dispatcher.requeue(&name);
+                    break;
+                }
+                self.notify(StatusMessage::info(
+                    format!("Task sent to {}", name),
+                    format!("Dispatched '{}' to {}", task, name),
+                ));
+                continue;
+            }
+
+            let starting = live.len() + self.creating().count();
+            if starting >= self.config.dispatch.max_sessions || self.at_session_limit() {
+                break;
+            }
+            let name = self.dispatch_session_name();
+            let Some(task) = self.dispatcher.assign(&name) else {
+                break;
+            };
+            // Requeued if it doesn't start
+            self.start_or_prompt_session(&name, &task);
+        }
+    }
+
+    /// Whether live session `name` is waiting for a prompt and free to take a task
+    fn is_idle_for_dispatch(&self, name: &str) -> bool {
+        let stopped = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.activity, p.claude.is_dead()))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (&p.name, &p.activity, p.claude.is_dead())),
+            )
+            .any(|(n, activity, dead)| n == name && *activity == SessionActivity::Stopped && !dead);
+        stopped
+            && !self.pending_approvals.iter().any(|p| p == name)
+            && !self.dispatcher.is_busy(name)
+    }
+
+    /// First `<prefix>-N` not taken by a live session or an existing worktree
+    fn dispatch_session_name(&self) -> String {
+        let prefix = &self.config.dispatch.name_prefix;
+        (1..)
+            .map(|n| format!("{}-{}", prefix, n))
+            .find(|name| {
+                !self.is_live_session(name)
+                    && !self.is_creating(name)
+                    && self.worktree_path(name).is_none_or(|path| !path.exists())
+            })
+            .unwrap_or_else(|| prefix.clone())
+    }
+
+    /// Mark the task `name` was working on as done, then hand out more
+    fn finish_dispatched_task(&mut self, name: &str) {
+        if let Some(task) = self.dispatcher.complete(name) {
+            let finished = self.dispatcher.is_finished();
+            self.notify(StatusMessage::success(
+                format!("Task done in {}", name),
+                if finished {
+                    format!(
+                        "'{}' finished in {}; all dispatched tasks are done",
+                        task, name
+                    )
+                } else {
+                    format!("'{}' finished in {}", task, name)
+                },
+            ));
+        }
+        self.dispatch_tasks();
+    }
+
+    /// Requeue tasks whose session was killed or died
+    fn reclaim_dispatched_tasks(&mut self) {
+        let mut live = self.live_sessions_by_slot();
+        live.extend(self.creating().map(|r| r.name.clone()));
+        if self.dispatcher.requeue_missing(&live) > 0 {
+            self.dispatch_tasks();
+        }
+    }
+
+    fn handle_dispatch_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [b] if *b == CTRL_S => {
+                let text = self.dispatch_dialog.text().to_string();
+                self.dispatch_dialog.clear();
+                if self.dispatcher.queue(&text) > 0 {
+                    self.dispatch_tasks();
+                }
+            }
+            [b] if *b == CTRL_X => self.dispatcher.clear_done(),
+            [0x7f] => self.dispatch_dialog.pop(),
+            [0x1b, ..] => {}
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    match c {
+                        '\r' | '\n' => self.dispatch_dialog.push('\n'),
+                        c if !c.is_control() => self.dispatch_dialog.push(c),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get count of sessions with stopped activity
+    pub fn stopped_session_count(&self) -> usize {
+        let active_stopped = self
+            .active
+            .as_ref()
+            .map(|p| p.activity == SessionActivity::Stopped)
+            .unwrap_or(false) as usize;
+
+        let bg_stopped = self
+            .background
+            .iter()
+            .filter(|p| p.activity == SessionActivity::Stopped)
+            .count();
+
+        active_stopped + bg_stopped
+    }
+
+    /// Names of live sessions whose claude is still running, in creation order. Position + 1
+    /// is the quick-jump slot.
+    fn live_sessions_by_slot(&self) -> Vec<String> {
+        let mut live: Vec<(usize, String)> = self
+            .active
+            .iter()
+            .map(|p| (p.seq, &p.name, p.claude.is_dead()))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (p.seq, &p.name, p.claude.is_dead())),
+            )
+            .filter(|(_, _, dead)| !dead)
+            .map(|(seq, name, _)| (seq, name.clone()))
+            .collect();
+        live.sort_by_key(|(seq, _)| *seq);
+        live.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Map of live session names to their quick-jump slot (1-based)
+    fn session_slots(&self) -> HashMap<String, usize> {
+        self.live_sessions_by_slot()
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name, i + 1))
+            .collect()
+    }
+
+    /// Switch to the live session in the given quick-jump slot (1-based)
+    fn jump_to_slot(&mut self, slot: usize) -> anyhow::Result<()> {
+        let names = self.live_sessions_by_slot();
+        if let Some(name) = names.get(slot - 1) {
+            self.touch_active();
+            self.switch_to_session_by_name(name)?;
+            self.touch_active();
+        }
+        Ok(())
+    }
+
+    /// Mark the active session as just used (for MRU ordering)
+    fn touch_active(&mut self) {
+        if let Some(ref mut pair) = self.active {
+            pair.last_used = Instant::now();
+        }
+    }
+
+    /// Mark a session as just used, whether it is active or in the background
+    fn touch_session(&mut self, name: &str) {
+        let now = Instant::now();
+        if let Some(ref mut pair) = self.active
+            && pair.name == name
+        {
+            pair.last_used = now;
+        } else if let Some(pair) = self.background.iter_mut().find(|p| p.name == name) {
+            pair.last_used = now;
+        }
+    }
+
+    /// Clean up dead panes in multiplexers and switch view if needed
+    fn cleanup_dead_multiplexer_panes(&mut self) {
+        let Some(ref mut pair) = self.active else {
+            return;
+        };
+
+        if pair.view != SessionView::Shell {
+            return;
+        }
+
+        if let Some(multiplexer) = self.multiplexers.get_mut(&pair.name) {
+            // Remove and shutdown dead panes
+            for dead_pane in multiplexer.remove_dead_panes() {
+                dead_pane.shutdown();
+                self.needs_redraw = true;
+            }
+
+            // If all panes are gone, switch back to Claude view
+            if multiplexer.is_empty() {
+                pair.view = SessionView::Claude;
+                self.needs_redraw = true;
+            }
+        }
+    }
+
+    /// Finish every rebase whose pane has exited or been closed, whichever session and
+    /// view are on screen, so no agent stays paused behind a rebase nobody is watching
+    fn reap_rebases(&mut self) {
+        let finished: Vec<(String, bool)> = self
+            .rebases
+            .iter()
+            .filter_map(|(name, job)| {
+                let pane = self.multiplexers.get(name).and_then(|multiplexer| {
+                    multiplexer
+                        .panes()
+                        .find(|pane| job.pane_pid.is_some() && pane.process_id() == job.pane_pid)
+                });
+                match pane {
+                    Some(pane) if !pane.is_dead() => None,
+                    Some(pane) => Some((
+                        name.clone(),
+                        pane.exit_status().is_some_and(|status| status.success()),
+                    )),
+                    // Closed by hand
+                    None => Some((name.clone(), false)),
+                }
+            })
+            .collect();
+        for (name, succeeded) in finished {
+            self.finish_rebase(&name, succeeded);
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Pause the active session's agent and rebase its branch onto the base branch in a
+    /// new shell pane. On conflict the pane stays open as a shell for resolving it.
+    fn start_rebase(&mut self) -> anyhow::Result<()> {
+        let Some(ref pair) = self.active else {
+            return Ok(());
+        };
+        let name = pair.name.clone();
+        let path = pair.path.clone();
+        if self.rebases.contains_key(&name) {
+            self.notify(StatusMessage::warn(
+                "Rebase already running",
+                format!("Session '{}' is already being rebased", name),
+            ));
+            return Ok(());
+        }
+        let Some((branch, onto)) = git_status::base_ref(&path, self.config.base_branch.as_deref())
+        else {
+            self.notify(StatusMessage::err(
+                "Rebase failed",
+                format!("No base branch found to rebase session '{}' onto", name),
+            ));
+            return Ok(());
+        };
+
+        // Fetch the base branch first when rebasing onto origin's copy
+        let fetch_branch = if onto.starts_with("origin/") {
+            branch
+        } else {
+            String::new()
+        };
+        let script = r#"{ [ -z "$1" ] || git fetch origin "$1"; } && git rebase "$2" && exit 0
+printf '
+%s
+' "Rebase did not finish. Resolve it here (git rebase --continue or --abort), then exit."
+exec "${SHELL:-/bin/sh}""#;
+        let pane = self.create_session(
+            &name,
+            "/bin/sh",
+            &["-c", script, "sh", &fetch_branch, &onto],
+            &path,
+        )?;
+
+        // Keep the agent from touching the worktree mid-rebase
+        let resume_claude = !pair.claude.is_paused() && pair.claude.set_paused(true).is_ok();
+        self.rebases.insert(
+            name.clone(),
+            RebaseJob {
+                pane_pid: pane.process_id(),
+                onto: onto.clone(),
+                resume_claude,
+            },
+        );
+        self.multiplexers
+            .entry(name.clone())
+            .or_default()
+            .add_pane(pane);
+        if let Some(ref mut pair) = self.active {
+            pair.view = SessionView::Shell;
+        }
+        self.notify(StatusMessage::info(
+            format!("Rebasing {}", name),
+            format!("Rebasing session '{}' onto {}", name, onto),
+        ));
+        Ok(())
+    }
+
+    /// Report how a rebase pane ended and let the agent continue
+    fn finish_rebase(&mut self, name: &str, succeeded: bool) {
+        let Some(job) = self.rebases.remove(name) else {
+            return;
+        };
+        let pair = match self.active.as_ref().filter(|p| p.name == name) {
+            Some(pair) => Some((&*pair.claude, &pair.path)),
+            None => self
+                .background
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| (&*p.claude, &p.path)),
+        };
+        let Some((claude, path)) = pair else {
+            return;
+        };
+        if job.resume_claude {
+            let _ = claude.set_paused(false);
+        }
+        let path = path.clone();
+        let message = if git_status::rebase_in_progress(&path) {
+            StatusMessage::warn(
+                format!("{}: rebase unfinished", name),
+                format!(
+                    "Rebase of session '{}' onto {} is still in progress",
+                    name, job.onto
+                ),
+            )
+        } else if succeeded {
+            StatusMessage::success(
+                format!("Rebased {}", name),
+                format!("Rebased session '{}' onto {}", name, job.onto),
+            )
+        } else {
+            StatusMessage::warn(
+                format!("{}: rebase not applied", name),
+                format!(
+                    "Rebase of session '{}' onto {} did not complete",
+                    name, job.onto
+                ),
+            )
+        };
+        self.git_worker.refresh(&path);
+        self.notify(message);
+    }
+
+    /// Run the repo's checks command for the active session in a new shell pane. The pane
+    /// stays open as a shell afterwards so the output can be read.
+    fn start_checks(&mut self) -> anyhow::Result<()> {
+        let Some(ref pair) = self.active else {
+            return Ok(());
+        };
+        let name = pair.name.clone();
+        let path = pair.path.clone();
+        let repo = self.get_current_repo_name().unwrap_or_default();
+        let Some(command) = self.config.checks.command_for(&repo).map(str::to_string) else {
+            self.notify(StatusMessage::warn(
+                "No checks configured",
+                format!("No checks command configured for repo '{}'", repo),
+            ));
+            return Ok(());
+        };
+        if self
+            .checks
+            .get(&name)
+            .is_some_and(|run| run.status == CheckStatus::Running)
+        {
+            self.notify(StatusMessage::warn(
+                "Checks already running",
+                format!("Checks are already running for session '{}'", name),
+            ));
+            return Ok(());
+        }
+
+        self.check_seq += 1;
+        let result_path = std::env::temp_dir().join(format!(
+            "shepherd-checks-{}-{}",
+            std::process::id(),
+            self.check_seq
+        ));
+        let _ = std::fs::remove_file(&result_path);
+        let script = r#"sh -c "$1"
+code=$?
+printf '%s' "$code" > "$2.tmp" && mv "$2.tmp" "$2"
+if [ "$code" -eq 0 ]; then msg="Checks passed"; else msg="Checks failed (exit $code)"; fi
+printf '\n%s. Exit this shell to close the pane.\n' "$msg"
+exec "${SHELL:-/bin/sh}""#;
+        let result_arg = result_path.to_string_lossy().to_string();
+        let pane = self.create_session(
+            &name,
+            "/bin/sh",
+            &["-c", script, "sh", &command, &result_arg],
+            &path,
+        )?;
+
+        self.checks.insert(
+            name.clone(),
+            CheckRun {
+                status: CheckStatus::Running,
+                pane_pid: pane.process_id(),
+                result_path,
+            },
+        );
+        self.multiplexers
+            .entry(name.clone())
+            .or_default()
+            .add_pane(pane);
+        if let Some(ref mut pair) = self.active {
+            pair.view = SessionView::Shell;
+        }
+        self.notify(StatusMessage::info(
+            format!("Running checks for {}", name),
+            format!("Running checks for session '{}': {}", name, command),
+        ));
+        Ok(())
+    }
+
+    /// Pick up exit codes of finished checks. Checks whose pane went away first are dropped.
+    fn poll_checks(&mut self) {
+        let mut finished = Vec::new();
+        for (name, run) in &mut self.checks {
+            if run.status != CheckStatus::Running {
+                continue;
+            }
+            if let Ok(code) = std::fs::read_to_string(&run.result_path) {
+                let _ = std::fs::remove_file(&run.result_path);
+                run.status = match code.trim().parse::<i32>() {
+                    Ok(0) => CheckStatus::Passed,
+                    Ok(code) => CheckStatus::Failed(code),
+                    Err(_) => CheckStatus::Failed(-1),
+                };
+                finished.push((name.clone(), Some(run.status)));
+                continue;
+            }
+            let pane_alive = self.multiplexers.get(name).is_some_and(|m| {
+                m.panes()
+                    .any(|p| p.process_id() == run.pane_pid && !p.is_dead())
+            });
+            if !pane_alive {
+                finished.push((name.clone(), None));
+            }
+        }
+
+        for (name, status) in finished {
+            let message = match status {
+                Some(CheckStatus::Passed) => StatusMessage::success(
+                    format!("{}: checks passed", name),
+                    format!("Checks passed for session '{}'", name),
+                ),
+                Some(CheckStatus::Failed(code)) => StatusMessage::warn(
+                    format!("{}: checks failed ({})", name, code),
+                    format!(
+                        "Checks failed for session '{}' with exit code {}",
+                        name, code
+                    ),
+                ),
+                _ => {
+                    self.checks.remove(&name);
+                    StatusMessage::warn(
+                        format!("{}: checks cancelled", name),
+                        format!("Checks pane for session '{}' closed before finishing", name),
+                    )
+                }
+            };
+            self.needs_redraw = true;
+            self.notify(message);
+        }
+    }
+
+    fn handle_run_dialog_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [b'\r'] | [b'\n'] => {
+                let command = self.run_dialog.input().to_string();
+                if !command.is_empty() {
+                    self.run_dialog.remember(&command);
+                    self.mode = UiMode::Normal;
+                    self.start_command_run(command)?;
+                }
+            }
+            [0x1b, b'[', b'A'] => self.run_dialog.recall_older(),
+            [0x1b, b'[', b'B'] => self.run_dialog.recall_newer(),
+            [0x7f] => self.run_dialog.pop(),
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    if !c.is_control() {
+                        self.run_dialog.push(c);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run `command` in a new pane of the active session's worktree. The pane shows the
+    /// exit code when it finishes and closes on the next key.
+    fn start_command_run(&mut self, command: String) -> anyhow::Result<()> {
+        let Some((name, path)) = self
+            .active
+            .as_ref()
+            .map(|p| (p.name.clone(), p.path.clone()))
+        else {
+            return Ok(());
+        };
+
+        self.check_seq += 1;
+        let result_path = std::env::temp_dir().join(format!(
+            "shepherd-run-{}-{}",
+            std::process::id(),
+            self.check_seq
+        ));
+        let _ = std::fs::remove_file(&result_path);
+        let script = r#"printf '$ %s\n' "$1"
+sh -c "$1"
+code=$?
+printf '%s' "$code" > "$2.tmp" && mv "$2.tmp" "$2"
+printf '\n[exit %s] Press any key to close.' "$code"
+stty raw -echo 2>/dev/null
+dd bs=1 count=1 >/dev/null 2>&1"#;
+        let result_arg = result_path.to_string_lossy().to_string();
+        let pane = self.create_session(
+            &name,
+            "/bin/sh",
+            &["-c", script, "sh", &command, &result_arg],
+            &path,
+        )?;
+
+        self.command_runs.push(CommandRun {
+            session: name.clone(),
+            command,
+            pane_pid: pane.process_id(),
+            result_path,
+        });
+        self.multiplexers.entry(name).or_default().add_pane(pane);
+        if let Some(ref mut pair) = self.active {
+            pair.view = SessionView::Shell;
+        }
+        Ok(())
+    }
+
+    /// Report one-off commands that finished. Runs whose pane was closed first are dropped.
+    fn poll_command_runs(&mut self) {
+        let mut finished = Vec::new();
+        self.command_runs
+            .retain(|run| match std::fs::read_to_string(&run.result_path) {
+                Ok(code) => {
+                    let _ = std::fs::remove_file(&run.result_path);
+                    let code = code.trim().parse::<i32>().unwrap_or(-1);
+                    finished.push((run.session.clone(), run.command.clone(), code));
+                    false
+                }
+                Err(_) => self.multiplexers.get(&run.session).is_some_and(|m| {
+                    m.panes()
+                        .any(|p| p.process_id() == run.pane_pid && !p.is_dead())
+                }),
+            });
+
+        for (session, command, code) in finished {
+            let message = if code == 0 {
+                StatusMessage::success(
+                    format!("`{}` done", command),
+                    format!("`{}` finished in session '{}'", command, session),
+                )
+            } else {
+                StatusMessage::warn(
+                    format!("`{}` failed ({})", command, code),
+                    format!(
+                        "`{}` failed in session '{}' with exit code {}",
+                        command, session, code
+                    ),
+                )
+            };
+            self.needs_redraw = true;
+            self.notify(message);
+        }
+    }
+
+    /// Handle global hotkeys. Returns true if a hotkey was processed.
+    fn handle_hotkey(&mut self, bytes: &[u8]) -> anyhow::Result<bool> {
+        // Alt+1..Alt+9 jumps to the Nth live session
+        if self.mode == UiMode::Normal
+            && self.config.quick_jump_hotkeys
+            && let [0x1b, digit @ b'1'..=b'9'] = bytes
+        {
+            self.jump_to_slot((digit - b'0') as usize)?;
+            return Ok(true);
+        }
+
+        match actions::action_for_key(bytes) {
+            Some(action) if self.hotkey_applies(action) => {
+                self.run_hotkey(action)?;
+                Ok(true)
+            }
+            _ => Ok(self.mode == UiMode::Normal && self.run_macro_for_key(bytes)),
+        }
+    }
+
+    /// Whether `action`'s hotkey is live in the current mode. When it is not, the key goes
+    /// to the mode's own input handling, or to the session.
+    fn hotkey_applies(&self, action: Action) -> bool {
+        let normal = self.mode == UiMode::Normal;
+        match action {
+            Action::Help
+            | Action::CommandPalette
+            | Action::NewSession
+            | Action::ListSessions
+            | Action::ToggleShell
+            | Action::Macros
+            | Action::CleanupWorktrees
+            | Action::DebugDump => true,
+            // In the selector Ctrl+X and Ctrl+D act on its entries instead
+            Action::KillSession | Action::Quit => self.mode != UiMode::ListSessions,
+            Action::SplitPane | Action::ClosePane | Action::CyclePane => {
+                normal
+                    && self
+                        .active
+                        .as_ref()
+                        .is_some_and(|p| p.view == SessionView::Shell)
+            }
+            // Without a pending approval these reach the session like any other key
+            Action::Approve | Action::Deny => normal && !self.pending_approvals.is_empty(),
+            Action::Inbox => matches!(self.mode, UiMode::Normal | UiMode::Inbox),
+            // Also acts on the session previewed in the selector
+            Action::TogglePause | Action::UndoDelete => {
+                matches!(self.mode, UiMode::Normal | UiMode::ListSessions)
+            }
+            Action::Rebase => normal && self.repo.is_some(),
+            Action::SendSignal | Action::RunChecks | Action::RunCommand | Action::Transcript => {
+                normal && self.active.is_some()
+            }
+            _ => normal,
+        }
+    }
+
+    /// Run the action behind a hotkey. Pressing a dialog's own key again closes it.
+    fn run_hotkey(&mut self, action: Action) -> anyhow::Result<()> {
+        let toggles = match action {
+            Action::Help => Some(UiMode::HelpPopup),
+            Action::CommandPalette => Some(UiMode::CommandPalette),
+            Action::ListSessions => Some(UiMode::ListSessions),
+            Action::Macros => Some(UiMode::MacroPicker),
+            Action::CleanupWorktrees => Some(UiMode::WorktreeCleanup),
+            Action::Inbox => Some(UiMode::Inbox),
+            _ => None,
+        };
+        if action == Action::CleanupWorktrees && self.repo.is_none() {
+            self.notify(StatusMessage::warn(
+                "Not in a git repository",
+                "Worktree cleanup needs a git repository",
+            ));
+            return Ok(());
+        }
+        // Clean up current mode before switching
+        if self.mode == UiMode::NewSession && action != Action::DebugDump {
+            self.create_dialog.clear();
+        }
+        if toggles.as_ref() == Some(&self.mode) {
+            self.mode = UiMode::Normal;
+            return Ok(());
+        }
+        if action == Action::ToggleShell {
+            self.mode = UiMode::Normal;
+        }
+        self.run_action(action)
+    }
+
+    /// Draw a frame, copying it into `capture` when given
+    fn render_frame(
+        &mut self,
+        capture: Option<&mut Buffer>,
+    ) -> anyhow::Result<ratatui::layout::Rect> {
+        let (active_view, scroll_offset) = match &self.active {
+            Some(pair) => (pair.view, pair.scroll_offset),
+            None => (SessionView::Claude, 0),
+        };
+        let active_name = self.active.as_ref().map(|p| p.name.clone());
+        let active_path = self.active.as_ref().map(|p| p.path.clone());
+        let background_count = self.background.len();
+        let mode = self.mode.clone();
+
+        // Get status bar render data
+        let stopped_count = self.stopped_session_count();
+        let bottom_left = self.status_bar.render_bottom_left();
+        let bottom_center = self.status_bar.render_bottom_center();
+
+        // Build map of session names to their activity states for selector rendering
+        let session_states: std::collections::HashMap<String, SessionActivity> = self
+            .active
+            .iter()
+            .map(|p| (p.name.clone(), &p.activity, p.claude.is_paused()))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (p.name.clone(), &p.activity, p.claude.is_paused())),
+            )
+            .map(|(name, activity, paused)| {
+                let activity = if paused {
+                    SessionActivity::Paused
+                } else {
+                    activity.clone()
+                };
+                (name, activity)
+            })
+            .collect();
+        let active_paused = self.active.as_ref().is_some_and(|p| p.claude.is_paused());
+
+        let session_times: HashMap<String, (Duration, Duration)> = self
+            .active
+            .iter()
+            .map(|p| (p.name.clone(), &p.timer))
+            .chain(self.background.iter().map(|p| (p.name.clone(), &p.timer)))
+            .map(|(name, timer)| (name, (timer.running(), timer.focused())))
+            .collect();
+        let session_git: HashMap<String, GitStatus> = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.path))
+            .chain(self.background.iter().map(|p| (&p.name, &p.path)))
+            .filter_map(|(name, path)| Some((name.clone(), self.git_statuses.get(path)?.clone())))
+            .collect();
+        let active_timer = active_name
+            .as_ref()
+            .and_then(|name| session_times.get(name).copied());
+
+        let session_slots = self.session_slots();
+        let active_slot = active_name
+            .as_ref()
+            .and_then(|name| session_slots.get(name).copied());
+
+        let pending_approval = self
+            .pending_approvals
+            .first()
+            .map(|name| (name.clone(), session_slots.get(name).copied()));
+        let pending_count = self.pending_approvals.len();
+        let inbox_entries = if mode == UiMode::Inbox {
+            self.inbox_entries()
+        } else {
+            Vec::new()
+        };
+
+        // Live sessions: time since last use; recent ones: when they last ran. Both show
+        // the prompt they were created with.
+        let now = chrono::Utc::now();
+        let mut session_details: HashMap<String, SessionDetail> = self
+            .get_current_repo_name()
+            .map(|repo| {
+                self.history
+                    .get_recent_sessions(&repo)
+                    .map(|recent| {
+                        let detail = SessionDetail {
+                            last_active: recent.last_active.and_then(|at| (now - at).to_std().ok()),
+                            excerpt: recent.prompt.clone(),
+                            check: None,
+                            progress: None,
+                            pinned: self.history.is_pinned(&repo, &recent.name),
+                            workflow: None,
+                        };
+                        (recent.name.clone(), detail)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if self.selector_all_repos {
+            let repo = self.get_current_repo_name().unwrap_or_default();
+            for (repo, recent) in self.history.recent_elsewhere(&repo) {
+                session_details.insert(
+                    format!("{}/{}", repo, recent.name),
+                    SessionDetail {
+                        last_active: recent.last_active.and_then(|at| (now - at).to_std().ok()),
+                        excerpt: recent.prompt.clone(),
+                        pinned: self.history.is_pinned(repo, &recent.name),
+                        ..SessionDetail::default()
+                    },
+                );
+            }
+        }
+        for (name, last_used, progress) in self
+            .active
+            .iter()
+            .map(|p| (&p.name, p.last_used, &p.progress))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (&p.name, p.last_used, &p.progress)),
+            )
+        {
+            let detail = session_details.entry(name.clone()).or_default();
+            detail.last_active = Some(last_used.elapsed());
+            detail.progress = progress.clone();
+            detail.pinned = self.is_pinned(name);
+            detail.workflow = self
+                .session_workflows
+                .get(name)
+                .map(|w| w.name())
+                .filter(|&workflow| workflow != self.workflows[self.default_workflow()].name());
+        }
+        let session_progress: HashMap<String, String> = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.progress))
+            .chain(self.background.iter().map(|p| (&p.name, &p.progress)))
+            .filter_map(|(name, progress)| Some((name.clone(), progress.clone()?)))
+            .collect();
+        for (name, run) in &self.checks {
+            if let Some(detail) = session_details.get_mut(name) {
+                detail.check = Some(run.status);
+            }
+        }
+
+        let latest_session = self
+            .get_current_repo_name()
+            .and_then(|repo| self.history.get_recent_session(&repo))
+            .map(|session| session.name.clone());
+
+        let has_repo = self.repo.is_some();
+        // Only offered when there is a choice of repo
+        let target_repo = (self.workspace_repos.len() > 1)
+            .then(|| self.get_current_repo_name())
+            .flatten();
+
+        // Live count against the limit and its policy, with sessions waiting for a slot
+        let session_limit = self.config.max_live_sessions.map(|max| {
+            let mut text = format!(
+                "{}/{} live ({})",
+                self.live_session_count(),
+                max,
+                self.config.session_limit_policy.label()
+            );
+            if !self.queued_sessions.is_empty() {
+                text.push_str(&format!(" +{} queued", self.queued_sessions.len()));
+            }
+            text
+        });
+
+        let mut inner_area = ratatui::layout::Rect::default();
+
+        // Get multiplexer for shell view rendering (if in shell view)
+        let multiplexer_name = if active_view == SessionView::Shell {
+            active_name.clone()
+        } else {
+            None
+        };
+
+        let completed = self.terminal.draw(|frame| {
+            let area = frame.area();
+
+            // Render main view (frame/borders)
+            let main_inner = self.main_view.render(
+                frame,
+                active_name.as_deref(),
+                active_slot,
+                active_path.as_deref(),
+                active_view,
+                active_paused,
+                active_path
+                    .as_ref()
+                    .and_then(|path| self.git_statuses.get(path)),
+                background_count,
+                stopped_count,
+                bottom_left,
+                bottom_center,
+                active_timer,
+                session_limit,
+            );
+            let main_inner = if self.log_pane_visible {
+                self.log_pane.render(frame, main_inner)
+            } else {
+                main_inner
+            };
+
+            // Render the claude screen in place (shell view renders the multiplexer instead)
+            if let Some(ref pair) = self.active
+                && pair.view == SessionView::Claude
+            {
+                pair.claude.with_screen(scroll_offset, |screen| {
+                    frame.render_widget(PtyWidget::new(screen), main_inner);
+                });
+                if pair.claude.is_throttled() {
+                    render_throttled_badge(frame, main_inner);
+                }
+            }
+
+            if self.active.is_none() {
+                self.main_view.render_placeholder(
+                    frame,
+                    main_inner,
+                    self.repo.as_ref().map(|repo| repo.name()),
+                    latest_session.as_deref(),
+                );
+            }
+
+            // If in shell view, render the multiplexer inside the frame
+            if let Some(ref name) = multiplexer_name {
+                if let Some(multiplexer) = self.multiplexers.get(name) {
+                    inner_area = multiplexer.render(frame, main_inner);
+                } else {
+                    inner_area = main_inner;
+                }
+            } else {
+                inner_area = main_inner;
+            }
+
+            // Render overlays based on mode
+            match mode {
+                // Never drawn; the loop skips rendering while passing through
+                UiMode::Passthrough => {}
+                UiMode::Normal => {
+                    if let Some((ref name, slot)) = pending_approval {
+                        self.approval_bar
+                            .render(frame, main_inner, name, slot, pending_count);
+                    }
+                }
+                UiMode::HelpPopup => {
+                    self.help_popup.render(frame, area, has_repo);
+                }
+                UiMode::ListSessions => {
+                    self.session_selector.render(
+                        frame,
+                        area,
+                        &self.selector_sessions,
+                        &session_states,
+                        &session_slots,
+                        &session_times,
+                        &session_git,
+                        &session_details,
+                    );
+                }
+                UiMode::NewSession => {
+                    self.create_dialog
+                        .render(frame, area, target_repo.as_deref());
+                }
+                UiMode::KillConfirmation | UiMode::SessionLimitConfirm => {
+                    self.kill_confirm_dialog.render(frame, area);
+                }
+                UiMode::QuitConfirmation => {
+                    self.quit_confirm_dialog.render(frame, area);
+                }
+                UiMode::WorktreeCleanup => {
+                    self.worktree_cleanup_dialog.render(frame, area);
+                }
+                UiMode::WorktreeDeleteConfirm => {
+                    self.delete_confirm_dialog
+                        .render(frame, area, self.config.trash.enabled);
+                }
+                UiMode::Inbox => {
+                    self.attention_inbox.render(frame, area, &inbox_entries);
+                }
+                UiMode::MacroPicker => {
+                    self.macro_picker.render(frame, area, &self.config.macros);
+                }
+                UiMode::DirectoryPrompt => {
+                    self.directory_prompt.render(frame, area);
+                }
+                UiMode::SessionPlan => {
+                    self.session_plan.render(frame, area);
+                }
+                UiMode::Creating => {
+                    self.creation_progress.render(frame, area);
+                }
+                UiMode::RunCommand => {
+                    let session = self.active.as_ref().map_or("", |p| p.name.as_str());
+                    self.run_dialog.render(frame, area, session);
+                }
+                UiMode::CommandPalette => {
+                    self.command_palette.render(frame, area);
+                }
+                UiMode::Compose => {
+                    let target = active_name.as_deref().unwrap_or_default();
+                    self.compose_dialog.render(frame, area, target);
+                }
+                UiMode::PromptHistory => {
+                    self.prompt_history.render(frame, area);
+                }
+                UiMode::Transcript => {
+                    self.transcript_viewer.render(frame, area);
+                }
+                UiMode::GitHistory => {
+                    self.git_history.render(frame, area);
+                }
+                UiMode::Checkpoints => {
+                    self.checkpoint_browser.render(frame, area);
+                }
+                UiMode::UndoChanges => {
+                    self.undo_changes_dialog.render(frame, area);
+                }
+                UiMode::MemoryStats => {
+                    self.memory_stats.render(frame, area);
+                }
+                UiMode::Dispatch => {
+                    self.dispatch_dialog.render(
+                        frame,
+                        area,
+                        self.dispatcher.tasks(),
+                        self.config.dispatch.max_sessions,
+                    );
+                }
+                UiMode::Dashboard => {
+                    let tiles = self.dashboard.render(
+                        frame,
+                        area,
+                        &session_slots,
+                        &session_states,
+                        &session_progress,
+                    );
+                    for (name, tile) in self.dashboard.sessions().iter().zip(tiles) {
+                        let session = match self.active.as_ref().filter(|p| &p.name == name) {
+                            Some(pair) => &*pair.claude,
+                            None => match self.background.iter().find(|p| &p.name == name) {
+                                Some(pair) => &*pair.claude,
+                                None => continue,
+                            },
+                        };
+                        session.with_screen(0, |screen| {
+                            frame.render_widget(PtyWidget::new(screen).tail(true), tile);
+                        });
+                    }
+                }
+                UiMode::SignalMenu => {
+                    let target = match (&active_name, active_view) {
+                        (Some(name), SessionView::Shell) => format!("{} [shell]", name),
+                        (Some(name), SessionView::Claude) => name.clone(),
+                        (None, _) => String::new(),
+                    };
+                    self.signal_menu.render(frame, area, &target);
+                }
+            }
+            self.render_stats.render(frame, area);
+        })?;
+        if let Some(capture) = capture {
+            *capture = completed.buffer.clone();
+        }
+
+        Ok(inner_area)
+    }
+
+    /// Check if bytes contain mouse events (SGR or legacy format).
+    /// Returns true if the bytes are entirely mouse events that should not be forwarded to PTY.
+    fn is_mouse_event(bytes: &[u8]) -> bool {
+        // Check if all content is mouse events (may be multiple concatenated)
+        let mut pos = 0;
+        while pos < bytes.len() {
+            // SGR mouse mode: ESC [ < ... M or ESC [ < ... m
+            if bytes[pos..].starts_with(b"\x1b[<") {
+                // Find the terminating M or m
+                if let Some(end) = bytes[pos..].iter().position(|&b| b == b'M' || b == b'm') {
+                    pos += end + 1;
+                    continue;
+                }
+            }
+
+            // Legacy mouse mode: ESC [ M followed by 3 bytes
+            if bytes[pos..].len() >= 6 && bytes[pos..].starts_with(b"\x1b[M") {
+                pos += 6;
+                continue;
+            }
+
+            // Not a mouse event
+            return false;
+        }
+
+        // All bytes were mouse events
+        pos > 0
+    }
+
+    /// Parse mouse scroll events from escape sequences.
+    /// Returns Some(lines) where positive = scroll up, negative = scroll down.
+    /// Handles multiple concatenated events and sums up scroll deltas.
+    /// Returns None if no scroll events found.
+    fn parse_scroll_event(bytes: &[u8]) -> Option<i32> {
+        let mut total_delta = 0i32;
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            // SGR mouse mode: ESC [ < Ps ; Px ; Py M (or m for release)
+            if bytes[pos..].starts_with(b"\x1b[<") {
+                // Find the end of this event (M or m)
+                if let Some(end_offset) = bytes[pos..].iter().position(|&b| b == b'M' || b == b'm')
+                {
+                    let event = &bytes[pos..pos + end_offset + 1];
+
+                    // Parse the button code (between '<' and first ';')
+                    if let Some(semi_pos) = event[3..].iter().position(|&b| b == b';')
+                        && let Ok(button_str) = std::str::from_utf8(&event[3..3 + semi_pos])
+                        && let Ok(button) = button_str.parse::<u8>()
+                    {
+                        // Button 64 = scroll up, 65 = scroll down
+                        let base_button = button & 0b11000011;
+                        match base_button {
+                            64 => total_delta += 1, // scroll up
+                            65 => total_delta -= 1, // scroll down
+                            _ => {}
+                        }
+                    }
+
+                    pos += end_offset + 1;
+                    continue;
+                }
+            }
+
+            // Legacy mouse mode: ESC [ M Cb Cx Cy
+            if bytes[pos..].len() >= 6 && bytes[pos..].starts_with(b"\x1b[M") {
+                let button = bytes[pos + 3];
+                let base_button = button & 0b11000011;
+                match base_button {
+                    96 => total_delta += 1, // scroll up
+                    97 => total_delta -= 1, // scroll down
+                    _ => {}
+                }
+                pos += 6;
+                continue;
+            }
+
+            // Not a recognized event at this position
+            break;
+        }
+
+        if total_delta != 0 {
+            Some(total_delta)
+        } else {
+            None
+        }
+    }
+
+    fn handle_normal_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let Some(ref pair) = self.active else {
+            return self.handle_placeholder_input(bytes);
+        };
+
+        let name = pair.name.clone();
+        let view = pair.view;
+
+        // Handle scroll events - adjust scroll offset instead of forwarding to PTY
+        if let Some(scroll_delta) = Self::parse_scroll_event(bytes) {
+            if let Some(ref mut pair) = self.active {
+                if scroll_delta > 0 {
+                    // Scroll up (show older content), no further than the parser holds
+                    let held = pair.claude.memory().scrollback_lines;
+                    pair.scroll_offset = (pair.scroll_offset + scroll_delta as usize).min(held);
+                } else {
+                    // Scroll down (show newer content)
+                    let abs_delta = (-scroll_delta) as usize;
+                    pair.scroll_offset = pair.scroll_offset.saturating_sub(abs_delta);
+                }
+            }
+            return Ok(());
+        }
+
+        // Filter out all other mouse events (clicks, motion, etc.) - don't forward to PTY
+        if Self::is_mouse_event(bytes) {
+            return Ok(());
+        }
+
+        // Any other input resets scroll to bottom
+        if let Some(ref mut pair) = self.active {
+            pair.scroll_offset = 0;
+        }
+
+        self.forward_to_active(&name, view, bytes);
+        Ok(())
+    }
+
+    /// Write input to whatever the active session is showing: claude, or the focused shell pane
+    fn forward_to_active(&mut self, name: &str, view: SessionView, bytes: &[u8]) {
+        match view {
+            SessionView::Claude => {
+                // Typing into the session answers any prompt it was waiting on
+                self.pending_approvals.retain(|pending| pending != name);
+                if let Some(ref mut pair) = self.active {
+                    if pair.claude.is_dead() {
+                        return;
+                    }
+                    // Clear stopped state when user interacts with session
+                    pair.activity = SessionActivity::Active;
+                    pair.last_used = Instant::now();
+                    // Ignore write errors - check_dead_sessions will handle cleanup
+                    let _ = pair.claude.write_input(bytes);
+                }
+                let submitted = self
+                    .typed_lines
+                    .entry(name.to_string())
+                    .or_default()
+                    .feed(bytes);
+                for prompt in submitted {
+                    self.record_prompt(name, &prompt);
+                }
+            }
+            SessionView::Shell => {
+                // Route input to the multiplexer's active pane
+                if let Some(multiplexer) = self.multiplexers.get_mut(name)
+                    && let Some(pane) = multiplexer.active_pane_mut()
+                {
+                    if pane.is_dead() {
+                        return;
+                    }
+                    // Ignore write errors - check_dead_sessions will handle cleanup
+                    let _ = pane.write_input(bytes);
+                }
+            }
+        }
+    }
+
+    /// Persist a finished run of `repo`'s session `name` to the history time log
+    fn record_session_time(&mut self, name: &str, timer: &SessionTimer, repo: Option<&RepoLayout>) {
+        let entry = TimeEntry {
+            repo: repo.map(|r| r.name().to_string()).unwrap_or_default(),
+            session: name.to_string(),
+            started_at: timer.started_at(),
+            ended_at: chrono::Utc::now(),
+            running_secs: timer.running().as_secs(),
+            focused_secs: timer.focused().as_secs(),
+        };
+        if let Err(e) = self.history.record_time(entry) {
+            self.notify(StatusMessage::err(
+                "Failed to save session time",
+                format!("Failed to record time for session {}: {}", name, e),
+            ));
+        }
+    }
+
+    /// Add a prompt sent to `name` to its repo's prompt history
+    fn record_prompt(&mut self, name: &str, prompt: &str) {
+        let repo = self.session_repo_name(name).unwrap_or_default();
+        if let Err(e) = self.history.record_prompt(repo, name.to_string(), prompt) {
+            self.notify(StatusMessage::err(
+                "Failed to save prompt history",
+                format!("Failed to record prompt sent to {}: {}", name, e),
+            ));
+        }
+    }
+
+    /// Drop pending approvals for sessions that no longer exist
+    fn prune_pending_approvals(&mut self) {
+        let before = self.pending_approvals.len();
+        let live: Vec<&str> = self
+            .active
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(self.background.iter().map(|p| p.name.as_str()))
+            .collect();
+        self.pending_approvals
+            .retain(|name| live.contains(&name.as_str()));
+        if self.pending_approvals.len() != before {
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Answer a session's pending permission prompt, whether it is active or in the background
+    fn answer_approval(&mut self, name: &str, approve: bool) {
+        let Some(index) = self.pending_approvals.iter().position(|n| n == name) else {
+            return;
+        };
+        self.pending_approvals.remove(index);
+        let keys = if approve { APPROVE_KEYS } else { DENY_KEYS };
+
+        let result = if let Some(pair) = self.active.as_ref().filter(|p| p.name == name) {
+            pair.claude.write_input(keys)
+        } else if let Some(pair) = self.background.iter().find(|p| p.name == name) {
+            pair.claude.write_input(keys)
+        } else {
+            return;
+        };
+
+        let message = match result {
+            // Brief confirmations: the next approval usually follows right behind
+            Ok(()) if approve => StatusMessage::info(
+                format!("Approved {}", name),
+                format!("Approved tool use in session: {}", name),
+            )
+            .with_duration(APPROVAL_ACK_DURATION),
+            Ok(()) => StatusMessage::info(
+                format!("Denied {}", name),
+                format!("Denied tool use in session: {}", name),
+            )
+            .with_duration(APPROVAL_ACK_DURATION),
+            Err(e) => StatusMessage::err(
+                "Approval failed",
+                format!("Failed to answer approval in session {}: {}", name, e),
+            ),
+        };
+        self.notify(message);
+    }
+
+    /// Suspend the active session's processes, or continue them if already suspended
+    fn toggle_pause_active(&mut self) {
+        let Some(ref pair) = self.active else {
+            return;
+        };
+        let pause = !pair.claude.is_paused();
+        let message = match pair.claude.set_paused(pause) {
+            Ok(()) if pause => StatusMessage::info(
+                format!("Paused {}", pair.name),
+                format!("Paused session '{}'", pair.name),
+            ),
+            Ok(()) => StatusMessage::info(
+                format!("Resumed {}", pair.name),
+                format!("Resumed paused session '{}'", pair.name),
+            ),
+            Err(e) => StatusMessage::err(
+                "Pause failed",
+                format!("Failed to pause/resume session '{}': {}", pair.name, e),
+            ),
+        };
+        self.notify(message);
+    }
+
+    /// Sessions needing input, in the order the inbox works through them:
+    /// permission prompts first (oldest first), then idle sessions least recently used first
+    fn inbox_sessions(&self) -> Vec<String> {
+        let mut stopped: Vec<(&str, Instant)> = self
+            .active
+            .iter()
+            .map(|p| (p.name.as_str(), &p.activity, p.last_used))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (p.name.as_str(), &p.activity, p.last_used)),
+            )
+            .filter(|(name, activity, _)| {
+                matches!(
+                    activity,
+                    SessionActivity::Stopped | SessionActivity::Flagged(_)
+                ) && !self.pending_approvals.iter().any(|n| n == name)
+            })
+            .map(|(name, _, last_used)| (name, last_used))
+            .collect();
+        stopped.sort_by_key(|(_, last_used)| *last_used);
+
+        self.pending_approvals
+            .iter()
+            .cloned()
+            .chain(stopped.into_iter().map(|(name, _)| name.to_string()))
+            .collect()
+    }
+
+    /// Build inbox rows, including the tail of each session's screen
+    fn inbox_entries(&self) -> Vec<InboxEntry> {
+        let slots = self.session_slots();
+        self.inbox_sessions()
+            .into_iter()
+            .filter_map(|name| {
+                let session = match self.active.as_ref().filter(|p| p.name == name) {
+                    Some(pair) => &*pair.claude,
+                    None => &*self.background.iter().find(|p| p.name == name)?.claude,
+                };
+                let preview = session.with_screen(0, |screen| {
+                    let contents = screen.contents();
+                    let mut lines: Vec<String> = contents
+                        .lines()
+                        .map(str::trim_end)
+                        .filter(|line| !line.trim().is_empty())
+                        .rev()
+                        .take(PREVIEW_LINES)
+                        .map(str::to_string)
+                        .collect();
+                    lines.reverse();
+                    lines
+                });
+                Some(InboxEntry {
+                    slot: slots.get(&name).copied(),
+                    needs_approval: self.pending_approvals.contains(&name),
+                    name,
+                    preview,
+                })
+            })
+            .collect()
+    }
+
+    fn handle_inbox_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let sessions = self.inbox_sessions();
+        let selected = self
+            .attention_inbox
+            .selected(sessions.len())
+            .and_then(|i| sessions.get(i).cloned());
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] => self.attention_inbox.move_up(sessions.len()),
+            [0x1b, b'[', b'B', ..] => self.attention_inbox.move_down(sessions.len()),
+            [b'\r' | b'\n'] => {
+                if let Some(name) = selected {
+                    self.touch_active();
+                    self.switch_to_session_by_name(&name)?;
+                    self.touch_active();
+                }
+                self.mode = UiMode::Normal;
+            }
+            // Answer in place and stay open, so the queue can be worked through
+            [key @ (b'y' | b'n')] => {
+                if let Some(name) = selected {
+                    self.answer_approval(&name, *key == b'y');
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Send the macro's payload to the active session
+    fn run_macro(&mut self, index: usize) {
+        let Some(payload) = self.config.macros.get(index).map(|m| m.payload.clone()) else {
+            return;
+        };
+        let Some((name, view)) = self.active.as_ref().map(|p| (p.name.clone(), p.view)) else {
+            self.notify(StatusMessage::warn(
+                "No active session",
+                "Macro not sent: no active session",
+            ));
+            return;
+        };
+        if let Some(ref mut pair) = self.active {
+            pair.scroll_offset = 0;
+        }
+        self.forward_to_active(&name, view, payload.as_bytes());
+    }
+
+    /// Run the macro bound to this key chord, if any. Returns true if one fired.
+    fn run_macro_for_key(&mut self, bytes: &[u8]) -> bool {
+        let Some(index) = self
+            .config
+            .macros
+            .iter()
+            .position(|m| m.key_bytes().as_deref() == Some(bytes))
+        else {
+            return false;
+        };
+        self.run_macro(index);
+        true
+    }
+
+    /// Write a prompt for the focused session in $VISUAL / $EDITOR, or in the compose
+    /// dialog when neither is set
+    fn compose(&mut self) {
+        if self.focused_session().is_none() {
+            return;
+        }
+        let editor = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|editor| !editor.trim().is_empty());
+        match editor {
+            Some(editor) => match self.compose_in_editor(&editor) {
+                Ok(text) => self.send_composed(&text),
+                Err(e) => self.notify(StatusMessage::err(
+                    "Compose failed",
+                    format!("Failed to compose in {}: {}", editor, e),
+                )),
+            },
+            None => {
+                self.compose_dialog.clear();
+                self.mode = UiMode::Compose;
+            }
+        }
+    }
+
+    /// Hand the terminal to `editor` on a scratch file and return what was saved
+    fn compose_in_editor(&mut self, editor: &str) -> anyhow::Result<String> {
+        let path = std::env::temp_dir().join(format!("shepherd-compose-{}.md", std::process::id()));
+        std::fs::write(&path, "")?;
+
+        // Run through the shell so EDITOR may carry arguments, e.g. "code --wait"
+        let status = self.run_in_foreground(&format!("{} \"$1\"", editor), &[path.as_os_str()]);
+
+        let text = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        let status = status?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", editor, status);
+        }
+        Ok(text?)
+    }
+
+    /// Run the shell `script` with `args` as $1.. on shepherd's terminal, taking it back
+    /// once the script exits
+    fn run_in_foreground(
+        &mut self,
+        script: &str,
+        args: &[&std::ffi::OsStr],
+    ) -> std::io::Result<std::process::ExitStatus> {
+        if let Some(ref events) = self.event_source {
+            events.pause_stdin(true);
+        }
+        restore_terminal();
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .arg("sh")
+            .args(args)
+            .status();
+        let _ = enable_raw_mode();
+        let _ = stdout().execute(EnterAlternateScreen);
+        let _ = stdout().execute(EnableMouseCapture);
+        let _ = stdout().execute(EnableFocusChange);
+        if let Some(ref events) = self.event_source {
+            events.pause_stdin(false);
+        }
+        self.host_cursor_style = None;
+        let _ = self.terminal.clear();
+        self.needs_redraw = true;
+        status
+    }
+
+    /// The file mentioned nearest the bottom of the focused screen, e.g. one claude just
+    /// edited, with the active session's worktree. Notifies when there is none.
+    fn file_on_screen(&self) -> Option<(FileRef, PathBuf)> {
+        let pair = self.active.as_ref()?;
+        let offset = match pair.view {
+            SessionView::Claude => pair.scroll_offset,
+            SessionView::Shell => 0,
+        };
+        let text = self
+            .focused_session()?
+            .with_screen(offset, |screen| screen.contents());
+        let file = last_file_ref(&text, &pair.path);
+        if file.is_none() {
+            self.notify(StatusMessage::warn(
+                "No file on screen",
+                format!(
+                    "No file under {} is mentioned on screen",
+                    pair.path.display()
+                ),
+            ));
+        }
+        Some((file?, pair.path.clone()))
+    }
+
+    /// Open the file mentioned on screen in the editor
+    fn open_file_from_output(&mut self) {
+        let Some((file, root)) = self.file_on_screen() else {
+            return;
+        };
+        if let Err(e) = self.open_in_editor(&file.resolve(&root), file.line) {
+            self.notify(StatusMessage::err("Open failed", e.to_string()));
+        }
+    }
+
+    /// Show `git log` of the file mentioned on screen, in the active session's worktree
+    fn open_git_history(&mut self) {
+        let Some((file, _)) = self.file_on_screen() else {
+            return;
+        };
+        let line = file.line.map(|l| l as usize);
+        self.show_git_history(file.path, HistoryView::Log, line);
+    }
+
+    fn show_git_history(&mut self, file: PathBuf, view: HistoryView, line: Option<usize>) {
+        let Some(root) = self.active.as_ref().map(|p| p.path.clone()) else {
+            return;
+        };
+        let lines = match view {
+            HistoryView::Log => git_status::file_log(&root, &file),
+            HistoryView::Blame => git_status::file_blame(&root, &file),
+        };
+        match lines {
+            Ok(lines) => {
+                self.git_history.open(file, view, lines, line);
+                self.mode = UiMode::GitHistory;
+            }
+            Err(e) => self.notify(StatusMessage::err(
+                "git history failed",
+                format!("git history of {} failed: {}", file.display(), e),
+            )),
+        }
+    }
+
+    /// Save the active worktree's current state as a checkpoint
+    fn create_checkpoint(&mut self) {
+        let Some((name, path)) = self
+            .active
+            .as_ref()
+            .map(|p| (p.name.clone(), p.path.clone()))
+        else {
+            return;
+        };
+        match checkpoints::create(&path, &name) {
+            Ok(checkpoint) => self.notify(StatusMessage::success(
+                format!("Checkpoint #{} saved (alt+a to browse)", checkpoint.number),
+                format!("Saved checkpoint {}", checkpoint.message),
+            )),
+            Err(e) => self.notify(StatusMessage::err(
+                "Checkpoint failed",
+                format!("Failed to checkpoint '{}': {}", name, e),
+            )),
+        }
+    }
+
+    fn open_checkpoints(&mut self) {
+        let Some((name, path)) = self
+            .active
+            .as_ref()
+            .map(|p| (p.name.clone(), p.path.clone()))
+        else {
+            return;
+        };
+        let list = checkpoints::list(&path);
+        if list.is_empty() {
+            self.notify(StatusMessage::info(
+                "No checkpoints yet",
+                format!("'{}' has no checkpoints; alt+k saves one", name),
+            ));
+            return;
+        }
+        self.checkpoint_browser.open(name, list);
+        self.load_checkpoint_diff();
+        self.mode = UiMode::Checkpoints;
+    }
+
+    /// Show the diff from the highlighted checkpoint to the worktree now
+    fn load_checkpoint_diff(&mut self) {
+        let (Some(path), Some(checkpoint)) = (
+            self.active.as_ref().map(|p| p.path.clone()),
+            self.checkpoint_browser.selected().cloned(),
+        ) else {
+            return;
+        };
+        let diff = if self.checkpoint_browser.full_diff() {
+            checkpoints::diff(&path, &checkpoint)
+        } else {
+            checkpoints::diff_stat(&path, &checkpoint)
+        };
+        let lines = match diff {
+            Ok(diff) => diff.lines().map(str::to_string).collect(),
+            Err(e) => vec![e.to_string()],
+        };
+        self.checkpoint_browser.set_diff(lines);
+    }
+
+    /// Restore the highlighted checkpoint, checkpointing the current state first so the
+    /// restore can itself be undone
+    fn restore_checkpoint(&mut self) {
+        let (Some((name, path)), Some(checkpoint)) = (
+            self.active
+                .as_ref()
+                .map(|p| (p.name.clone(), p.path.clone())),
+            self.checkpoint_browser.selected().cloned(),
+        ) else {
+            return;
+        };
+        let result = checkpoints::create(&path, &format!("{} before restore", name))
+            .and_then(|saved| checkpoints::restore(&path, &checkpoint).map(|()| saved));
+        match result {
+            Ok(saved) => self.notify(StatusMessage::success(
+                format!("Restored checkpoint #{}", checkpoint.number),
+                format!(
+                    "Restored '{}' to checkpoint #{}; the state before is checkpoint #{}",
+                    name, checkpoint.number, saved.number
+                ),
+            )),
+            Err(e) => self.notify(StatusMessage::err(
+                "Restore failed",
+                format!(
+                    "Failed to restore '{}' to checkpoint #{}: {}",
+                    name, checkpoint.number, e
+                ),
+            )),
+        }
+        self.git_worker.refresh(&path);
+    }
+
+    /// List the files changed since the active worktree's latest checkpoint, to revert some
+    fn open_undo_changes(&mut self) {
+        let Some((name, path)) = self
+            .active
+            .as_ref()
+            .map(|p| (p.name.clone(), p.path.clone()))
+        else {
+            return;
+        };
+        let Some(checkpoint) = checkpoints::latest(&path) else {
+            self.notify(StatusMessage::info(
+                "No checkpoints yet",
+                format!("'{}' has no checkpoints; alt+k saves one", name),
+            ));
+            return;
+        };
+        match checkpoints::changed_files(&path, &checkpoint) {
+            Ok(files) if files.is_empty() => self.notify(StatusMessage::info(
+                format!("No changes since checkpoint #{}", checkpoint.number),
+                format!(
+                    "'{}' has no changes since checkpoint #{}",
+                    name, checkpoint.number
+                ),
+            )),
+            Ok(files) => {
+                self.undo_changes_dialog.open(name, checkpoint, files);
+                self.mode = UiMode::UndoChanges;
+            }
+            Err(e) => self.notify(StatusMessage::err(
+                "Could not list changes",
+                format!("Failed to diff '{}' against its checkpoint: {}", name, e),
+            )),
+        }
+    }
+
+    fn revert_changes(&mut self) {
+        let (Some((name, path)), Some(checkpoint)) = (
+            self.active
+                .as_ref()
+                .map(|p| (p.name.clone(), p.path.clone())),
+            self.undo_changes_dialog.checkpoint().cloned(),
+        ) else {
+            return;
+        };
+        let files = self.undo_changes_dialog.to_revert();
+        match checkpoints::revert_files(&path, &checkpoint, &files) {
+            Ok(()) => self.notify(StatusMessage::success(
+                format!("Reverted {} file(s)", files.len()),
+                format!(
+                    "Reverted {} in '{}' to checkpoint #{}",
+                    files
+                        .iter()
+                        .map(|f| f.path.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    name,
+                    checkpoint.number
+                ),
+            )),
+            Err(e) => self.notify(StatusMessage::err(
+                "Revert failed",
+                format!("Failed to revert files in '{}': {}", name, e),
+            )),
+        }
+        self.git_worker.refresh(&path);
+    }
+
+    fn handle_undo_changes_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        if self.undo_changes_dialog.confirming() {
+            self.undo_changes_dialog.set_confirm(false);
+            if let [b'y' | b'Y'] = bytes {
+                self.revert_changes();
+                self.mode = UiMode::Normal;
+            }
+            return Ok(());
+        }
+        match bytes {
+            [0x1b] | [b'q'] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] | [b'k'] => self.undo_changes_dialog.move_up(),
+            [0x1b, b'[', b'B', ..] | [b'j'] => self.undo_changes_dialog.move_down(),
+            [b' '] => self.undo_changes_dialog.toggle_mark(),
+            [b'a'] => self.undo_changes_dialog.toggle_all(),
+            [b'\r'] | [b'\n'] => self.undo_changes_dialog.set_confirm(true),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_checkpoints_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        if self.checkpoint_browser.confirming_restore() {
+            self.checkpoint_browser.set_confirm_restore(false);
+            if let [b'y' | b'Y'] = bytes {
+                self.restore_checkpoint();
+                self.mode = UiMode::Normal;
+            }
+            return Ok(());
+        }
+        let page = self.size.get().0.max(2) as usize / 2;
+        match bytes {
+            [0x1b] | [b'q'] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] | [b'k'] => {
+                self.checkpoint_browser.move_up();
+                self.load_checkpoint_diff();
+            }
+            [0x1b, b'[', b'B', ..] | [b'j'] => {
+                self.checkpoint_browser.move_down();
+                self.load_checkpoint_diff();
+            }
+            [0x1b, b'[', b'5', b'~'] => self.checkpoint_browser.scroll_up(page),
+            [0x1b, b'[', b'6', b'~'] => self.checkpoint_browser.scroll_down(page),
+            [b'd'] => {
+                self.checkpoint_browser.toggle_full_diff();
+                self.load_checkpoint_diff();
+            }
+            [b'r'] => self.checkpoint_browser.set_confirm_restore(true),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_git_history_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let page = self.size.get().0.max(2) as usize / 2;
+        match bytes {
+            [0x1b] | [b'q'] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] | [b'k'] => self.git_history.scroll_up(1),
+            [0x1b, b'[', b'B', ..] | [b'j'] => self.git_history.scroll_down(1),
+            [0x1b, b'[', b'5', b'~'] => self.git_history.scroll_up(page),
+            [0x1b, b'[', b'6', b'~'] => self.git_history.scroll_down(page),
+            [0x1b, b'[', b'H'] | [b'g'] => self.git_history.scroll_to_top(),
+            [0x1b, b'[', b'F'] | [b'G'] => self.git_history.scroll_to_bottom(),
+            [b'\t'] => {
+                let view = match self.git_history.view() {
+                    HistoryView::Log => HistoryView::Blame,
+                    HistoryView::Blame => HistoryView::Log,
+                };
+                let file = self.git_history.file().clone();
+                let line = self.git_history.line();
+                self.show_git_history(file, view, line);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Open `path` at `line` with `editor_command` in the background, or $VISUAL/$EDITOR on
+    /// shepherd's terminal
+    fn open_in_editor(&mut self, path: &Path, line: Option<u32>) -> anyhow::Result<()> {
+        let line = line.unwrap_or(1).to_string();
+        let args = [path.as_os_str(), std::ffi::OsStr::new(&line)];
+        if let Some(command) = self.config.editor_command.clone() {
+            let script = command
+                .replace("{path}", "\"$1\"")
+                .replace("{line}", "\"$2\"");
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .arg("sh")
+                .args(args)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()?;
+            return Ok(());
+        }
+        let editor = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|editor| !editor.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("set editor_command, $VISUAL or $EDITOR"))?;
+        let status = self.run_in_foreground(&format!("{} +\"$2\" \"$1\"", editor), &args)?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", editor, status);
+        }
+        Ok(())
+    }
+
+    /// Paste `text` into the focused session and submit it
+    fn send_composed(&mut self, text: &str) {
+        let text = text.trim_end();
+        let Some(ref pair) = self.active else {
+            return;
+        };
+        let (name, view) = (pair.name.clone(), pair.view);
+        let Some(bracketed) = self
+            .focused_session()
+            .map(|s| s.with_screen(0, |screen| screen.bracketed_paste()))
+        else {
+            return;
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        self.forward_to_active(&name, view, &submit_bytes(text, bracketed));
+    }
+
+    /// Submit `text` to the claude view of live session `name`. Returns false if the
+    /// session is gone.
+    fn send_prompt(&mut self, name: &str, text: &str) -> bool {
+        if self.active.as_ref().is_some_and(|p| p.name == name) {
+            let Some(ref pair) = self.active else {
+                return false;
+            };
+            let bracketed = pair
+                .claude
+                .with_screen(0, |screen| screen.bracketed_paste());
+            // Recorded again on the way through, moving it to the top of the history
+            self.forward_to_active(name, SessionView::Claude, &submit_bytes(text, bracketed));
+            return true;
+        }
+        let Some(pair) = self.background.iter_mut().find(|p| p.name == name) else {
+            return false;
+        };
+        if pair.claude.is_dead() {
+            return false;
+        }
+        let bracketed = pair
+            .claude
+            .with_screen(0, |screen| screen.bracketed_paste());
+        let _ = pair.claude.write_input(&submit_bytes(text, bracketed));
+        pair.activity = SessionActivity::Active;
+        self.pending_approvals.retain(|pending| pending != name);
+        self.record_prompt(name, text);
+        true
+    }
+
+    /// Show the latest Claude transcript for the active session's worktree
+    fn open_transcript(&mut self) {
+        let Some(ref pair) = self.active else {
+            return;
+        };
+        let name = pair.name.clone();
+        let Some(path) = transcript::locate(&pair.path) else {
+            self.notify(StatusMessage::warn(
+                "No transcript found",
+                format!(
+                    "No Claude transcript found for session '{}' in {}",
+                    name,
+                    pair.path.display()
+                ),
+            ));
+            return;
+        };
+        match transcript::load(&path) {
+            Ok(entries) => {
+                self.transcript_viewer.open(name, entries);
+                self.mode = UiMode::Transcript;
+            }
+            Err(e) => self.notify(StatusMessage::err(
+                "Failed to read transcript",
+                format!("Failed to read transcript {}: {}", path.display(), e),
+            )),
+        }
+    }
+
+    fn handle_transcript_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let page = self.size.get().0.max(2) as usize / 2;
+        match bytes {
+            [0x1b] | [b'q'] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] | [b'k'] => self.transcript_viewer.move_up(1),
+            [0x1b, b'[', b'B', ..] | [b'j'] => self.transcript_viewer.move_down(1),
+            [0x1b, b'[', b'5', b'~'] => self.transcript_viewer.move_up(page),
+            [0x1b, b'[', b'6', b'~'] => self.transcript_viewer.move_down(page),
+            [0x1b, b'[', b'H'] | [b'g'] => self.transcript_viewer.select_first(),
+            [0x1b, b'[', b'F'] | [b'G'] => self.transcript_viewer.select_last(),
+            [b'\r' | b'\n' | b' '] => self.transcript_viewer.toggle(),
+            [b'a'] => self.transcript_viewer.toggle_all(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Browse the prompt history, sending to the current session or any other live one
+    fn open_prompt_history(&mut self) {
+        let repo = self.get_current_repo_name().unwrap_or_default();
+        let prompts = self.history.prompts(&repo).cloned().collect();
+        let targets = self
+            .active
+            .iter()
+            .map(|p| p.name.clone())
+            .chain(self.background.iter().map(|p| p.name.clone()))
+            .collect();
+        self.prompt_history.open(prompts, targets);
+        self.mode = UiMode::PromptHistory;
+    }
 
-                if scroll_delta > 0 {
-                    // Scroll up (show older content)
-                    pair.scroll_offset =
-                        (pair.scroll_offset + scroll_delta as usize).min(MAX_SCROLLBACK);
-                } else {
-                    // Scroll down (show newer content)
-                    let abs_delta = (-scroll_delta) as usize;
-                    pair.scroll_offset = pair.scroll_offset.saturating_sub(abs_delta);
+    fn handle_prompt_history_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] => self.prompt_history.move_up(),
+            [0x1b, b'[', b'B', ..] => self.prompt_history.move_down(),
+            [0x1b, ..] => {}
+            [b'\t'] => self.prompt_history.cycle_target(),
+            [b'\r' | b'\n'] => {
+                let selected = self
+                    .prompt_history
+                    .selected()
+                    .zip(self.prompt_history.target())
+                    .map(|(text, target)| (text.to_string(), target.to_string()));
+                if let Some((text, target)) = selected {
+                    self.mode = UiMode::Normal;
+                    let background = self.active.as_ref().is_none_or(|p| p.name != target);
+                    if self.send_prompt(&target, &text) && background {
+                        self.notify(StatusMessage::info(
+                            format!("Sent prompt to {}", target),
+                            format!("Re-sent a prompt from history to {}", target),
+                        ));
+                    }
+                }
+            }
+            [0x7f] => self.prompt_history.pop_char(),
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    if !c.is_control() {
+                        self.prompt_history.push_char(c);
+                    }
                 }
             }
-            return Ok(());
         }
+        Ok(())
+    }
 
-        // Filter out all other mouse events (clicks, motion, etc.) - don't forward to PTY
-        if Self::is_mouse_event(bytes) {
-            return Ok(());
+    fn handle_compose_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [b] if *b == CTRL_S => {
+                self.mode = UiMode::Normal;
+                let text = self.compose_dialog.text().to_string();
+                self.compose_dialog.clear();
+                self.send_composed(&text);
+            }
+            [0x7f] => self.compose_dialog.pop(),
+            [0x1b, ..] => {}
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    match c {
+                        '\r' | '\n' => self.compose_dialog.push('\n'),
+                        '\t' => self.compose_dialog.push(c),
+                        c if !c.is_control() => self.compose_dialog.push(c),
+                        _ => {}
+                    }
+                }
+            }
         }
+        Ok(())
+    }
 
-        // Any other input resets scroll to bottom
-        if let Some(ref mut pair) = self.active {
-            pair.scroll_offset = 0;
-        }
+    /// Offer the actions that can run in the current context
+    fn open_command_palette(&mut self) {
+        let in_shell_view = self
+            .active
+            .as_ref()
+            .is_some_and(|p| p.view == SessionView::Shell);
+        let actions = ACTIONS
+            .iter()
+            .filter(|info| info.action != Action::CommandPalette)
+            .filter(|info| self.repo.is_some() || !info.git_only())
+            .filter(|info| in_shell_view || !info.shell_only())
+            .collect();
+        self.command_palette.open(actions);
+        self.mode = UiMode::CommandPalette;
+    }
 
-        match view {
-            SessionView::Claude => {
-                if let Some(ref mut pair) = self.active {
-                    if pair.claude.is_dead() {
-                        return Ok(());
-                    }
-                    // Clear stopped state when user interacts with session
-                    pair.activity = SessionActivity::Active;
-                    // Ignore write errors - check_dead_sessions will handle cleanup
-                    let _ = pair.claude.write_input(bytes);
+    fn handle_command_palette_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] => self.command_palette.move_up(),
+            [0x1b, b'[', b'B', ..] => self.command_palette.move_down(),
+            [0x1b, ..] => {}
+            [b'\r' | b'\n'] => {
+                self.mode = UiMode::Normal;
+                if let Some(action) = self.command_palette.selected() {
+                    self.run_action(action)?;
                 }
             }
-            SessionView::Shell => {
-                // Route input to the multiplexer's active pane
-                if let Some(multiplexer) = self.multiplexers.get_mut(&name)
-                    && let Some(pane) = multiplexer.active_pane_mut()
-                {
-                    if pane.is_dead() {
-                        return Ok(());
+            [0x7f] => self.command_palette.pop_char(),
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    if !c.is_control() {
+                        self.command_palette.push_char(c);
                     }
-                    // Ignore write errors - check_dead_sessions will handle cleanup
-                    let _ = pane.write_input(bytes);
                 }
             }
         }
         Ok(())
     }
 
+    /// Do what `action`'s hotkey does from normal mode
+    fn run_action(&mut self, action: Action) -> anyhow::Result<()> {
+        match action {
+            Action::Help => {
+                self.help_popup.reset();
+                self.mode = UiMode::HelpPopup;
+            }
+            Action::CommandPalette => self.open_command_palette(),
+            Action::NewSession => {
+                self.create_dialog.clear();
+                self.mode = UiMode::NewSession;
+            }
+            Action::ListSessions => {
+                self.open_session_selector();
+                self.mode = UiMode::ListSessions;
+            }
+            Action::ToggleShell => self.toggle_shell()?,
+            Action::Compose => self.compose(),
+            Action::PromptHistory => self.open_prompt_history(),
+            Action::Transcript => self.open_transcript(),
+            Action::OpenFile => self.open_file_from_output(),
+            Action::GitHistory => self.open_git_history(),
+            Action::Checkpoint => self.create_checkpoint(),
+            Action::Checkpoints => self.open_checkpoints(),
+            Action::UndoChanges => self.open_undo_changes(),
+            Action::Dispatch => {
+                self.dispatch_dialog.clear();
+                self.mode = UiMode::Dispatch;
+            }
+            Action::SplitPane => self.split_shell_pane()?,
+            Action::ClosePane => self.close_shell_pane(),
+            Action::CyclePane => self.cycle_shell_pane(),
+            Action::Macros => {
+                self.macro_picker.reset();
+                self.mode = UiMode::MacroPicker;
+            }
+            Action::Approve | Action::Deny => {
+                if let Some(name) = self.pending_approvals.first().cloned() {
+                    self.answer_approval(&name, action == Action::Approve);
+                }
+            }
+            Action::Inbox => {
+                self.attention_inbox.reset();
+                self.mode = UiMode::Inbox;
+            }
+            Action::TogglePause => self.toggle_pause_active(),
+            Action::SendSignal => {
+                if self.active.is_some() {
+                    self.signal_menu.reset();
+                    self.mode = UiMode::SignalMenu;
+                }
+            }
+            Action::Passthrough => self.enter_passthrough(),
+            Action::Rebase => self.start_rebase()?,
+            Action::RunChecks => self.start_checks()?,
+            Action::RunCommand => {
+                if self.active.is_some() {
+                    self.run_dialog.reset();
+                    self.mode = UiMode::RunCommand;
+                }
+            }
+            Action::MemoryStats => {
+                self.refresh_memory_stats();
+                self.mode = UiMode::MemoryStats;
+            }
+            Action::ToggleEventLog => {
+                self.log_pane_visible = !self.log_pane_visible;
+                if self.log_pane_visible {
+                    self.log_pane.refresh();
+                }
+            }
+            Action::ForceRedraw => self.force_redraw(),
+            Action::DebugDump => self.write_debug_dump()?,
+            Action::RenderStats => {
+                self.render_stats.visible = !self.render_stats.visible;
+                self.sample_render_stats();
+            }
+            Action::DismissStatus => {
+                self.status_bar.dismiss();
+            }
+            Action::CleanupWorktrees => {
+                self.open_worktree_cleanup();
+                self.mode = UiMode::WorktreeCleanup;
+            }
+            Action::UndoDelete => self.undo_last_delete(),
+            Action::KillSession => {
+                if let Some(ref pair) = self.active {
+                    self.kill_confirm_dialog.set_session_name(&pair.name);
+                    self.confirm_kill()?;
+                }
+            }
+            Action::Quit => self.confirm_quit()?,
+        }
+        Ok(())
+    }
+
+    fn handle_macro_picker_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let len = self.config.macros.len();
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A', ..] => self.macro_picker.move_up(len),
+            [0x1b, b'[', b'B', ..] => self.macro_picker.move_down(len),
+            [b'\r' | b'\n'] => {
+                self.mode = UiMode::Normal;
+                if let Some(index) = self.macro_picker.selected() {
+                    self.run_macro(index);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_signal_menu_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        let choice = match bytes {
+            [0x1b] => {
+                self.mode = UiMode::Normal;
+                return Ok(());
+            }
+            [0x1b, b'[', b'A', ..] => {
+                self.signal_menu.move_up();
+                return Ok(());
+            }
+            [0x1b, b'[', b'B', ..] => {
+                self.signal_menu.move_down();
+                return Ok(());
+            }
+            [b'\r' | b'\n'] => self.signal_menu.selected(),
+            [key] => match SIGNALS.iter().find(|choice| choice.key == *key) {
+                Some(choice) => choice,
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        self.mode = UiMode::Normal;
+
+        let Some(ref pair) = self.active else {
+            return Ok(());
+        };
+        // The shell view targets its focused pane, the claude view claude itself
+        let (target, result) = match pair.view {
+            SessionView::Claude => (pair.name.clone(), pair.claude.send_signal(choice.signal)),
+            SessionView::Shell => match self
+                .multiplexers
+                .get(&pair.name)
+                .and_then(|m| m.active_pane())
+            {
+                Some(pane) => (
+                    format!("{} [shell]", pair.name),
+                    pane.send_signal(choice.signal),
+                ),
+                None => return Ok(()),
+            },
+        };
+        let message = match result {
+            Ok(()) => StatusMessage::info(
+                format!("Sent SIG{} to {}", choice.name, target),
+                format!("Sent SIG{} to session '{}'", choice.name, target),
+            ),
+            Err(e) => StatusMessage::err(
+                format!("Failed to send SIG{}", choice.name),
+                format!("Failed to send SIG{} to '{}': {}", choice.name, target, e),
+            ),
+        };
+        self.notify(message);
+        Ok(())
+    }
+
     fn toggle_shell(&mut self) -> anyhow::Result<()> {
         // Get info about current state without holding any borrows
         let (name, path, current_view) = match &self.active {
@@ -876,9 +4904,7 @@ impl TuiSessionManager {
 
                 if needs_pane {
                     // Create session first (no borrows held)
-                    let shell_cmd =
-                        std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-                    let shell_session = self.create_session(&shell_cmd, &[], &path)?;
+                    let shell_session = self.create_shell(&name, &path)?;
 
                     // Then add to multiplexer
                     self.multiplexers
@@ -901,6 +4927,15 @@ impl TuiSessionManager {
         Ok(())
     }
 
+    /// Start a shell view pane for `owner` in `cwd`, as configured for the current repo
+    fn create_shell(&self, owner: &str, cwd: &Path) -> anyhow::Result<AttachedSession> {
+        let repo = self.get_current_repo_name().unwrap_or_default();
+        let default_shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        let argv = self.config.shell.argv_for(&repo, &default_shell);
+        let args: Vec<&str> = argv[1..].iter().map(String::as_str).collect();
+        Ok(self.create_session(owner, &argv[0], &args, cwd)?)
+    }
+
     /// Split the current shell pane (add a new pane to the multiplexer)
     fn split_shell_pane(&mut self) -> anyhow::Result<()> {
         let Some(ref pair) = self.active else {
@@ -914,8 +4949,7 @@ impl TuiSessionManager {
         let name = pair.name.clone();
         let path = pair.path.clone();
 
-        let shell_cmd = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-        let shell_session = self.create_session(&shell_cmd, &[], &path)?;
+        let shell_session = self.create_shell(&name, &path)?;
 
         if let Some(multiplexer) = self.multiplexers.get_mut(&name) {
             multiplexer.add_pane(shell_session);
@@ -962,10 +4996,129 @@ impl TuiSessionManager {
         }
     }
 
+    /// Save the screen of each live claude session that changed since it was last saved.
+    /// Failures are skipped; the next snapshot tries again.
+    fn snapshot_screens(&mut self) {
+        let Some(ref screens) = self.screens else {
+            return;
+        };
+        let sessions = self
+            .active
+            .iter()
+            .map(|p| (&p.path, &*p.claude))
+            .chain(self.background.iter().map(|p| (&p.path, &*p.claude)));
+        for (path, claude) in sessions {
+            if claude.is_dead() {
+                continue;
+            }
+            let screen = claude.screen_snapshot();
+            let mut hasher = DefaultHasher::new();
+            screen.hash(&mut hasher);
+            let hash = hasher.finish();
+            if self.screen_hashes.get(path) == Some(&hash) {
+                continue;
+            }
+            if screens.save(path, &screen).is_ok() {
+                self.screen_hashes.insert(path.clone(), hash);
+            }
+        }
+    }
+
+    /// Move the scrollback of background sessions unused for `trim_scrollback_after_secs`
+    /// (or of every background session, with `all`) to disk. Returns the lines moved.
+    fn trim_idle_scrollback(&mut self, all: bool) -> usize {
+        let threshold = match self.config.session_limits.trim_scrollback_after_secs {
+            _ if all => Duration::ZERO,
+            Some(secs) => Duration::from_secs(secs),
+            None => return 0,
+        };
+        let mut trimmed = 0;
+        let mut error = None;
+        for pair in self
+            .background
+            .iter()
+            .filter(|p| p.last_used.elapsed() >= threshold && (all || !self.is_pinned(&p.name)))
+        {
+            let panes = self
+                .multiplexers
+                .get(&pair.name)
+                .into_iter()
+                .flat_map(|m| m.panes());
+            for session in std::iter::once(&*pair.claude).chain(panes.map(|p| &**p)) {
+                match session.trim_scrollback() {
+                    Ok(lines) => trimmed += lines,
+                    Err(e) => error = Some(format!("'{}': {}", pair.name, e)),
+                }
+            }
+        }
+        if let Some(error) = error {
+            self.notify(StatusMessage::warn(
+                "Scrollback trim failed",
+                format!("Failed to move scrollback to disk for {}", error),
+            ));
+        }
+        trimmed
+    }
+
+    fn refresh_memory_stats(&mut self) {
+        let pairs = self
+            .active
+            .iter()
+            .map(|p| (&p.name, None, &*p.claude))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (&p.name, Some(p.last_used.elapsed()), &*p.claude)),
+            );
+        let rows = pairs
+            .map(|(name, idle, claude)| {
+                let panes: Vec<_> = self
+                    .multiplexers
+                    .get(name)
+                    .into_iter()
+                    .flat_map(|m| m.panes())
+                    .map(|pane| pane.memory())
+                    .collect();
+                let claude = claude.memory();
+                let all = || std::iter::once(&claude).chain(&panes);
+                MemoryRow {
+                    name: name.clone(),
+                    idle,
+                    panes: panes.len(),
+                    scrollback_lines: all().map(|m| m.scrollback_lines).sum(),
+                    stashed_lines: all().map(|m| m.stashed_lines).sum(),
+                    bytes: all().map(|m| m.bytes).sum(),
+                }
+            })
+            .collect();
+        self.memory_stats.set_rows(rows);
+    }
+
+    fn handle_memory_stats_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        match bytes {
+            [b't'] => {
+                let lines = self.trim_idle_scrollback(true);
+                self.refresh_memory_stats();
+                self.notify(StatusMessage::info(
+                    "Scrollback trimmed",
+                    format!("Moved {} lines of background scrollback to disk", lines),
+                ));
+            }
+            [] => {}
+            _ => self.mode = UiMode::Normal,
+        }
+        Ok(())
+    }
+
     fn handle_help_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
-        // Any non-hotkey key closes help
-        if !bytes.is_empty() {
-            self.mode = UiMode::Normal;
+        match bytes {
+            [0x1b, b'[', b'A'] | [b'k'] => self.help_popup.scroll_by(-1),
+            [0x1b, b'[', b'B'] | [b'j'] => self.help_popup.scroll_by(1),
+            [0x1b, b'[', b'5', b'~'] => self.help_popup.scroll_by(-10),
+            [0x1b, b'[', b'6', b'~'] => self.help_popup.scroll_by(10),
+            // Any other non-hotkey key closes help
+            [] => {}
+            _ => self.mode = UiMode::Normal,
         }
         Ok(())
     }
@@ -975,29 +5128,39 @@ impl TuiSessionManager {
             return Ok(());
         }
 
+        // A batch kill started from the selector returns there either way
+        if !self.kill_batch.is_empty() {
+            match bytes[0] {
+                0x1b if bytes.len() == 1 => {}
+                b'n' | b'N' => {}
+                b'y' | b'Y' | b'\r' | b'\n' => {
+                    let paths = std::mem::take(&mut self.kill_batch);
+                    for path in &paths {
+                        self.kill_session_at_path(path);
+                    }
+                    self.notify(StatusMessage::success(
+                        format!("Killed {} sessions", paths.len()),
+                        format!("Killed {} sessions from the selector", paths.len()),
+                    ));
+                    self.refresh_session_selector();
+                }
+                _ => return Ok(()),
+            }
+            self.kill_batch.clear();
+            self.mode = UiMode::ListSessions;
+            return Ok(());
+        }
+
         match bytes[0] {
             // Escape key
             0x1b if bytes.len() == 1 => {
                 self.mode = UiMode::Normal;
             }
-            // 'y' or 'Y' - confirm kill
-            b'y' | b'Y' => {
-                if let Some(pair) = self.active.take() {
-                    let name = pair.name.clone();
-                    pair.claude.shutdown();
-
-                    // Also cleanup the multiplexer for this session
-                    if let Some(mut multiplexer) = self.multiplexers.remove(&name) {
-                        for pane in multiplexer.remove_dead_panes() {
-                            pane.shutdown();
-                        }
-                        // Shutdown any remaining live panes
-                        while let Some(pane) = multiplexer.close_active_pane() {
-                            pane.shutdown();
-                        }
-                    }
-
-                    let _ = self.status_tx.send(StatusMessage::info(
+            // 'y', 'Y' or Enter - confirm kill
+            b'y' | b'Y' | b'\r' | b'\n' => {
+                if let Some(name) = self.active.as_ref().map(|p| p.name.clone()) {
+                    self.kill_session(&name);
+                    self.notify(StatusMessage::success(
                         "Session killed",
                         format!("Killed session '{}'", name),
                     ));
@@ -1014,12 +5177,81 @@ impl TuiSessionManager {
         Ok(())
     }
 
+    /// Show the kill dialog, or kill right away when `confirm_kill` is off
+    fn confirm_kill(&mut self) -> anyhow::Result<()> {
+        self.mode = UiMode::KillConfirmation;
+        if !self.config.confirm_kill {
+            self.handle_kill_confirmation_input(b"y")?;
+        }
+        Ok(())
+    }
+
+    /// Show the quit dialog, or quit right away when `confirm_quit` is off
+    fn confirm_quit(&mut self) -> anyhow::Result<()> {
+        self.refresh_quit_dialog();
+        self.mode = UiMode::QuitConfirmation;
+        if !self.config.confirm_quit {
+            self.handle_quit_confirmation_input(b"y")?;
+        }
+        Ok(())
+    }
+
+    /// Show the delete dialog for the worktrees set on it, or delete right away when
+    /// `confirm_worktree_delete` is off
+    fn confirm_worktree_delete(&mut self, return_mode: UiMode) -> anyhow::Result<()> {
+        self.delete_return_mode = return_mode;
+        self.mode = UiMode::WorktreeDeleteConfirm;
+        if !self.config.confirm_worktree_delete {
+            self.handle_delete_confirm_input(b"y")?;
+        }
+        Ok(())
+    }
+
+    /// List the live sessions quitting would kill, with their shell pane counts
+    fn refresh_quit_dialog(&mut self) {
+        let names = self
+            .active
+            .iter()
+            .map(|p| p.name.clone())
+            .chain(self.background.iter().map(|p| p.name.clone()));
+        let sessions = names
+            .map(|name| QuitEntry {
+                shells: self
+                    .multiplexers
+                    .get(&name)
+                    .map_or(0, |m| m.panes().count()),
+                name,
+            })
+            .collect();
+        self.quit_confirm_dialog.set_sessions(sessions);
+    }
+
     fn handle_quit_confirmation_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
-        if bytes.is_empty() {
-            return Ok(());
+        match bytes {
+            [0x1b, b'[', b'A'] => {
+                self.quit_confirm_dialog.move_up();
+                return Ok(());
+            }
+            [0x1b, b'[', b'B'] => {
+                self.quit_confirm_dialog.move_down();
+                return Ok(());
+            }
+            [] => return Ok(()),
+            _ => {}
         }
 
         match bytes[0] {
+            // 'x' - kill the highlighted session and stay in the dialog
+            b'x' | b'X' => {
+                if let Some(name) = self.quit_confirm_dialog.selected().map(str::to_string) {
+                    self.kill_session(&name);
+                    self.notify(StatusMessage::success(
+                        "Session killed",
+                        format!("Killed session '{}'", name),
+                    ));
+                    self.refresh_quit_dialog();
+                }
+            }
             // Escape key
             0x1b if bytes.len() == 1 => {
                 self.mode = UiMode::Normal;
@@ -1040,6 +5272,10 @@ impl TuiSessionManager {
 
     fn open_session_selector(&mut self) {
         self.session_selector.reset();
+        // Background sessions' git status is only refreshed on demand
+        for path in self.live_session_paths() {
+            self.git_worker.refresh(&path);
+        }
 
         // Save original active session name for revert on escape
         self.selector_original_session = self.active.as_ref().map(|p| p.name.clone());
@@ -1050,24 +5286,169 @@ impl TuiSessionManager {
         }
 
         // Cache session list (indices remain consistent during preview)
-        let (sessions, live_count, recent_count) = self.build_session_list();
+        let (sessions, live_count, recent_count, other_count) = self.build_session_list();
+        self.selector_sessions = sessions;
+        self.selector_live_count = live_count;
+        self.selector_recent_count = recent_count;
+        self.session_selector
+            .set_counts(live_count, recent_count, other_count);
+        self.session_selector.set_all_repos(self.selector_all_repos);
+        self.session_selector.update_filter(&self.selector_sessions);
+    }
+
+    /// Rebuild the selector's list after entries were removed, keeping the filter
+    fn refresh_session_selector(&mut self) {
+        if let Some(ref original) = self.selector_original_session
+            && !self.is_live_session(original)
+        {
+            self.selector_original_session = None;
+        }
+        self.session_selector
+            .set_active_index(self.active.is_some().then_some(0));
+        // Indices shift when the list is rebuilt
+        self.session_selector.clear_marks();
+
+        let (sessions, live_count, recent_count, other_count) = self.build_session_list();
         self.selector_sessions = sessions;
         self.selector_live_count = live_count;
         self.selector_recent_count = recent_count;
-        self.session_selector.set_counts(live_count, recent_count);
+        self.session_selector
+            .set_counts(live_count, recent_count, other_count);
+        self.session_selector.set_all_repos(self.selector_all_repos);
         self.session_selector.update_filter(&self.selector_sessions);
     }
 
-    /// Build session list with live sessions first, then recent sessions, then worktree directories.
-    /// Returns (list, live_count, recent_count).
-    fn build_session_list(&self) -> (Vec<(String, String)>, usize, usize) {
+    /// Marked selector entries of `kind`, as (name, display path)
+    fn marked_entries(&self, kind: SelectorItemKind) -> Vec<(String, String)> {
+        self.session_selector
+            .marked()
+            .into_iter()
+            .filter(|(_, k)| *k == kind)
+            .filter_map(|(idx, _)| self.selector_sessions.get(idx).cloned())
+            .collect()
+    }
+
+    /// Ask to kill every marked live session
+    fn kill_marked_sessions(&mut self) -> anyhow::Result<()> {
+        let paths: Vec<PathBuf> = self
+            .marked_entries(SelectorItemKind::Live)
+            .iter()
+            .map(|(_, path_display)| display_path_to_actual(path_display))
+            .collect();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        self.kill_confirm_dialog.set_session_count(paths.len());
+        self.kill_batch = paths;
+        self.confirm_kill()
+    }
+
+    /// Start every marked recent session or worktree without leaving the current session
+    fn resume_marked_in_background(&mut self) -> anyhow::Result<()> {
+        let recent = self.marked_entries(SelectorItemKind::Recent);
+        let worktrees = self.marked_entries(SelectorItemKind::Worktree);
+        if recent.is_empty() && worktrees.is_empty() {
+            return Ok(());
+        }
+
+        let original = self.active.as_ref().map(|p| p.name.clone());
+        for (name, path_display) in &recent {
+            self.resume_recent_session(name, path_display)?;
+        }
+        for (_, path_display) in &worktrees {
+            self.start_worktree_session(path_display)?;
+        }
+        if let Some(ref original) = original {
+            self.switch_to_session_by_name(original)?;
+        }
+        self.selector_original_session = original;
+
+        self.session_selector.clear_marks();
+        self.refresh_session_selector();
+        Ok(())
+    }
+
+    /// Show every marked live session side by side
+    fn open_dashboard(&mut self) {
+        let names: Vec<String> = self
+            .marked_entries(SelectorItemKind::Live)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        self.dashboard.set_sessions(names);
+        self.mode = UiMode::Dashboard;
+    }
+
+    fn handle_dashboard_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.dashboard.retain(|name| {
+            self.active.as_ref().is_some_and(|p| p.name == name)
+                || self.background.iter().any(|p| p.name == name)
+        });
+        let columns = self.dashboard.columns() as isize;
+        match bytes {
+            [0x1b] => self.mode = UiMode::Normal,
+            [0x1b, b'[', b'A'] => self.dashboard.move_focus(-columns),
+            [0x1b, b'[', b'B'] => self.dashboard.move_focus(columns),
+            [0x1b, b'[', b'C'] | [b'\t'] => self.dashboard.move_focus(1),
+            [0x1b, b'[', b'D'] => self.dashboard.move_focus(-1),
+            [b'\r'] | [b'\n'] => self.open_dashboard_focus()?,
+            [b @ b'1'..=b'9'] if self.dashboard.focus((b - b'1') as usize) => {
+                self.open_dashboard_focus()?;
+            }
+            _ => {}
+        }
+        if self.dashboard.sessions().is_empty() {
+            self.mode = UiMode::Normal;
+        }
+        Ok(())
+    }
+
+    /// Switch to the dashboard's focused session and close the dashboard
+    fn open_dashboard_focus(&mut self) -> anyhow::Result<()> {
+        if let Some(name) = self.dashboard.focused_session().map(str::to_string) {
+            self.touch_active();
+            self.switch_to_session_by_name(&name)?;
+            self.touch_active();
+        }
+        self.mode = UiMode::Normal;
+        Ok(())
+    }
+
+    /// Ask to delete the worktree of the highlighted selector entry (killing its session
+    /// if it is live)
+    fn delete_selected_entry(&mut self) -> anyhow::Result<()> {
+        let Some((_, path_display)) = self
+            .session_selector
+            .selected_original_index()
+            .and_then(|i| self.selector_sessions.get(i))
+        else {
+            return Ok(());
+        };
+        let path = display_path_to_actual(path_display);
+        self.delete_confirm_dialog
+            .set_worktrees_with_active(vec![path], self.get_active_session_paths());
+        self.confirm_worktree_delete(UiMode::ListSessions)
+    }
+
+    /// Build session list with live sessions first, then recent sessions, then (with
+    /// `selector_all_repos`) other repos' recent sessions, then worktree directories.
+    /// Live sessions are ordered most-recently-used first, with the active session pinned on top.
+    /// Returns (list, live_count, recent_count, other_count).
+    fn build_session_list(&self) -> (Vec<(String, String)>, usize, usize, usize) {
+        // Pinned sessions first, then most recently used
+        let mut background: Vec<&BackgroundPair> = self.background.iter().collect();
+        background.sort_by_key(|p| (!self.is_pinned(&p.name), std::cmp::Reverse(p.last_used)));
+
         // Collect live sessions first
         let live: Vec<(String, String)> = self
             .active
             .iter()
             .map(|p| (p.name.clone(), path_to_display(&p.path)))
             .chain(
-                self.background
+                background
                     .iter()
                     .map(|p| (p.name.clone(), path_to_display(&p.path))),
             )
@@ -1085,27 +5466,54 @@ impl TuiSessionManager {
 
         // Collect recent sessions from history that aren't currently live
         let repo_name = self.get_current_repo_name();
-        let recent_items: Vec<(String, String)> = repo_name
+        let mut recent_items: Vec<(String, String)> = repo_name
             .as_ref()
             .map(|rn| {
                 self.history
                     .get_recent_sessions(rn)
-                    .map(|s| (s.name.clone(), self.worktree_path(rn, &s.name)))
+                    .filter_map(|s| Some((s.name.clone(), self.worktree_path(&s.name)?)))
                     .filter(|(_, path)| !live_paths.contains(path))
                     .map(|(name, path)| (name, path_to_display(&path)))
                     .collect()
             })
             .unwrap_or_default();
+        recent_items.sort_by_key(|(name, _)| {
+            !repo_name
+                .as_ref()
+                .is_some_and(|repo| self.history.is_pinned(repo, name))
+        });
 
         let recent_count = recent_items.len();
 
+        // Other repos' recent sessions, named `repo/session`
+        let other_items: Vec<(String, String)> = if self.selector_all_repos {
+            let mut layouts: HashMap<&Path, Option<RepoLayout>> = HashMap::new();
+            self.history
+                .recent_elsewhere(repo_name.as_deref().unwrap_or_default())
+                .into_iter()
+                .filter_map(|(repo, s)| {
+                    let layout = layouts
+                        .entry(&s.project_path)
+                        .or_insert_with(|| RepoLayout::detect(&s.project_path))
+                        .as_ref()?;
+                    let path = layout.worktree_path(&self.config.workflows_path, &s.name);
+                    Some((format!("{}/{}", repo, s.name), path))
+                })
+                .filter(|(_, path)| !live_paths.contains(path))
+                .map(|(name, path)| (name, path_to_display(&path)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let other_count = other_items.len();
+
         // Collect worktree directories that aren't currently live or recent
         let recent_paths: std::collections::HashSet<_> = repo_name
             .as_ref()
             .map(|rn| {
                 self.history
                     .get_recent_sessions(rn)
-                    .map(|s| self.worktree_path(rn, &s.name))
+                    .filter_map(|s| self.worktree_path(&s.name))
                     .collect()
             })
             .unwrap_or_default();
@@ -1119,34 +5527,31 @@ impl TuiSessionManager {
 
         let mut list = live;
         list.extend(recent_items);
+        list.extend(other_items);
         list.extend(worktree_items);
 
-        (list, live_count, recent_count)
+        (list, live_count, recent_count, other_count)
     }
 
     /// List worktree directories for the current repo.
-    /// Worktrees are stored at <workflows_path>/<reponame>/<feature-name>.
+    /// Worktrees are stored at <workflows_path>/<reponame>-<hash>/<feature-name>.
     fn list_worktree_dirs(&self) -> Vec<PathBuf> {
-        // Get the current repo name
-        let Some(repo_name) = self.get_current_repo_name() else {
+        let Some(repo) = &self.repo else {
             return Vec::new();
         };
 
-        // Build path to repo's worktrees: <workflows_path>/<reponame>/
-        let repo_worktrees_path = self.config.workflows_path.join(&repo_name);
-
-        if !repo_worktrees_path.exists() {
-            return Vec::new();
-        }
-
-        let Ok(entries) = std::fs::read_dir(&repo_worktrees_path) else {
+        let repo_worktrees_path = repo.worktrees_dir(&self.config.workflows_path);
+        let Ok(canonical_path) = repo_worktrees_path.canonicalize() else {
             return Vec::new();
         };
 
-        let mut dirs: Vec<PathBuf> = entries
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_dir())
-            .map(|e| e.path())
+        // Git may know a worktree by its resolved path; report it under ours
+        let mut dirs: Vec<PathBuf> = git_backend::get()
+            .worktrees(repo.root())
+            .into_iter()
+            .filter_map(|worktree| worktree.canonicalize().ok())
+            .filter(|worktree| worktree.parent() == Some(canonical_path.as_path()))
+            .filter_map(|worktree| Some(repo_worktrees_path.join(worktree.file_name()?)))
             .collect();
 
         // Sort alphabetically
@@ -1157,60 +5562,39 @@ impl TuiSessionManager {
 
     /// Get the current repository name from git.
     fn get_current_repo_name(&self) -> Option<String> {
-        // Use --git-common-dir to get the main repo's .git path, not the worktree's path.
-        // This ensures consistent repo names regardless of whether shepherd is launched
-        // from the main repo or from a worktree.
-        let output = std::process::Command::new("git")
-            .args(["rev-parse", "--git-common-dir"])
-            .current_dir(&self.startup_path)
-            .output()
-            .ok()?;
-
-        if !output.status.success() {
-            return None;
-        }
-
-        let git_common_dir = String::from_utf8(output.stdout).ok()?.trim().to_string();
-        let git_path = std::path::Path::new(&git_common_dir);
-
-        // The common dir is either ".git" (relative) or "/path/to/repo/.git" (absolute).
-        // If relative, resolve against startup_path first.
-        let absolute_git_path = if git_path.is_relative() {
-            self.startup_path.join(git_path)
-        } else {
-            git_path.to_path_buf()
-        };
+        self.repo.as_ref().map(|repo| repo.name().to_string())
+    }
 
-        // Get the parent to find the repo root, then extract the repo name.
-        absolute_git_path
-            .parent()
-            .and_then(|p| p.file_name())
-            .and_then(|n| n.to_str())
-            .map(|s| s.to_string())
+    /// Directory git commands for the target repo run in: its root, or the startup
+    /// directory when there is no repo
+    fn repo_dir(&self) -> &Path {
+        self.repo_dir_of(self.repo.as_ref())
     }
 
-    /// Get the current repository root path from git.
-    fn get_current_project_path(&self) -> Option<PathBuf> {
-        let output = std::process::Command::new("git")
-            .args(["rev-parse", "--show-toplevel"])
-            .current_dir(&self.startup_path)
-            .output()
-            .ok()?;
+    /// Directory git commands for `repo` run in
+    fn repo_dir_of<'a>(&'a self, repo: Option<&'a RepoLayout>) -> &'a Path {
+        repo.map_or(self.startup_path.as_path(), |repo| repo.root())
+    }
 
-        if !output.status.success() {
-            return None;
+    /// Make the next workspace repo the target for new sessions, history and cleanup
+    fn cycle_target_repo(&mut self) {
+        if self.workspace_repos.len() < 2 {
+            return;
         }
-
-        let repo_path = String::from_utf8(output.stdout).ok()?.trim().to_string();
-        Some(PathBuf::from(repo_path))
+        let current = self.repo.as_ref().and_then(|repo| {
+            self.workspace_repos
+                .iter()
+                .position(|r| r.root() == repo.root())
+        });
+        let next = current.map_or(0, |i| (i + 1) % self.workspace_repos.len());
+        self.repo = Some(self.workspace_repos[next].clone());
     }
 
-    /// Compute the worktree path for a given repo name and session name.
-    fn worktree_path(&self, repo_name: &str, session_name: &str) -> PathBuf {
-        self.config
-            .workflows_path
-            .join(repo_name)
-            .join(session_name)
+    /// Compute the worktree path for a session in the current repo.
+    fn worktree_path(&self, session_name: &str) -> Option<PathBuf> {
+        self.repo
+            .as_ref()
+            .map(|repo| repo.worktree_path(&self.config.workflows_path, session_name))
     }
 
     fn handle_list_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
@@ -1249,7 +5633,12 @@ impl TuiSessionManager {
                 // Enter - confirm selection based on item kind
                 match self.session_selector.selected_kind() {
                     Some(SelectorItemKind::Live) => {
-                        // Live session - already previewed, just close
+                        // Live session - already previewed, just close.
+                        // The session we left and the one we landed on both count as used.
+                        if let Some(original) = self.selector_original_session.clone() {
+                            self.touch_session(&original);
+                        }
+                        self.touch_active();
                     }
                     Some(SelectorItemKind::Recent) => {
                         // Recent session - resume it
@@ -1260,6 +5649,14 @@ impl TuiSessionManager {
                             self.resume_recent_session(&name, &path_display)?;
                         }
                     }
+                    Some(SelectorItemKind::OtherRepo) => {
+                        if let Some(selected) = self.session_selector.selected_original_index()
+                            && let Some((label, path_display)) =
+                                self.selector_sessions.get(selected).cloned()
+                        {
+                            self.resume_other_repo_session(&label, &path_display)?;
+                        }
+                    }
                     Some(SelectorItemKind::Worktree) => {
                         // Worktree directory - start fresh session
                         if let Some(selected) = self.session_selector.selected_original_index()
@@ -1269,10 +5666,38 @@ impl TuiSessionManager {
                             self.start_worktree_session(&path_display)?;
                         }
                     }
-                    None => {}
+                    None => {
+                        // Nothing matches: create a session named after the filter, going
+                        // through the create dialog so naming rules and errors still apply
+                        let query = self.session_selector.query().trim().to_string();
+                        if !query.is_empty() {
+                            self.create_dialog.clear();
+                            query.chars().for_each(|c| self.create_dialog.push(c));
+                            self.mode = UiMode::NewSession;
+                            return self.handle_new_session_input(b"\r");
+                        }
+                    }
                 }
                 self.mode = UiMode::Normal;
             }
+            // Outside git, entries share the work directory; never delete it
+            CTRL_D
+                if self.repo.is_some()
+                    && self.session_selector.selected_kind()
+                        != Some(SelectorItemKind::OtherRepo) =>
+            {
+                self.delete_selected_entry()?
+            }
+            CTRL_A => {
+                self.selector_all_repos = !self.selector_all_repos;
+                self.refresh_session_selector();
+            }
+            // Tab, so spaces still go into the filter
+            b'\t' => self.session_selector.toggle_mark(),
+            CTRL_X => self.kill_marked_sessions()?,
+            CTRL_B => self.resume_marked_in_background()?,
+            CTRL_O => self.open_dashboard(),
+            CTRL_F => self.toggle_pin_selected(),
             0x7f => {
                 // Backspace - remove character from filter
                 self.session_selector.pop_char();
@@ -1291,6 +5716,76 @@ impl TuiSessionManager {
         Ok(())
     }
 
+    /// Whether session `name` is pinned in its repo's history
+    fn is_pinned(&self, name: &str) -> bool {
+        self.session_repo_name(name)
+            .is_some_and(|repo| self.history.is_pinned(&repo, name))
+    }
+
+    /// Name of the repo live session `name` belongs to, or of the target repo when no
+    /// session by that name is live
+    fn session_repo_name(&self, name: &str) -> Option<String> {
+        match self.live_session_repo(name) {
+            Some(repo) => repo.map(|r| r.name().to_string()),
+            None => self.get_current_repo_name(),
+        }
+    }
+
+    /// The repo of live session `name`, if one is live
+    fn live_session_repo(&self, name: &str) -> Option<Option<&RepoLayout>> {
+        self.active
+            .iter()
+            .map(|p| (&p.name, &p.repo))
+            .chain(self.background.iter().map(|p| (&p.name, &p.repo)))
+            .find(|(n, _)| *n == name)
+            .map(|(_, repo)| repo.as_ref())
+    }
+
+    /// Pin or unpin the highlighted live or recent session in the selector
+    fn toggle_pin_selected(&mut self) {
+        let kind = self.session_selector.selected_kind();
+        if !matches!(
+            kind,
+            Some(SelectorItemKind::Live | SelectorItemKind::Recent)
+        ) {
+            return;
+        }
+        let Some(name) = self
+            .session_selector
+            .selected_original_index()
+            .and_then(|i| self.selector_sessions.get(i))
+            .map(|(name, _)| name.clone())
+            .filter(|name| !name.is_empty())
+        else {
+            return;
+        };
+        // Recent entries are the target repo's; live sessions may be another's
+        let repo = match kind {
+            Some(SelectorItemKind::Live) => self.session_repo_name(&name),
+            _ => self.get_current_repo_name(),
+        };
+        let Some(repo) = repo else {
+            return;
+        };
+        match self.history.toggle_pin(&repo, &name) {
+            Ok(pinned) => {
+                self.notify(StatusMessage::info(
+                    if pinned { "Pinned" } else { "Unpinned" },
+                    format!(
+                        "{} session '{}'",
+                        if pinned { "Pinned" } else { "Unpinned" },
+                        name
+                    ),
+                ));
+                self.refresh_session_selector();
+            }
+            Err(e) => self.notify(StatusMessage::err(
+                "Failed to save pin",
+                format!("Failed to pin session '{}': {}", name, e),
+            )),
+        }
+    }
+
     /// Preview the currently selected session (switch to it without closing selector).
     /// Only previews live sessions, not recent or worktree items.
     fn preview_selected_session(&mut self) -> anyhow::Result<()> {
@@ -1315,7 +5810,7 @@ impl TuiSessionManager {
 
         // Check if path still exists
         if !path.exists() {
-            let _ = self.status_tx.send(StatusMessage::err(
+            self.notify(StatusMessage::err(
                 "Path not found",
                 format!("Session path no longer exists: {}", path.display()),
             ));
@@ -1324,11 +5819,14 @@ impl TuiSessionManager {
 
         // Resume with --continue flag
         let mut args_owned: Vec<String> = vec!["--continue".to_string()];
-        args_owned.extend(self.config.claude_args.clone());
+        args_owned.extend(self.claude_args_for_path(&path));
         let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
-        self.add_claude_session(name, "claude", &args, &path, true)?;
+        let repo = self.repo.clone();
+        if !self.add_claude_session(name, "claude", &args, &path, repo, true)? {
+            return Ok(());
+        }
 
-        let _ = self.status_tx.send(StatusMessage::info(
+        self.notify(StatusMessage::info(
             "Resumed session",
             format!("Resumed '{}' from history", name),
         ));
@@ -1336,6 +5834,48 @@ impl TuiSessionManager {
         Ok(())
     }
 
+    /// Resume another repository's recent session (`label` is `repo/session`), making
+    /// that repository the target for new sessions, history and cleanup
+    fn resume_other_repo_session(&mut self, label: &str, path_display: &str) -> anyhow::Result<()> {
+        let Some((_, name)) = label.split_once('/') else {
+            return Ok(());
+        };
+        let path = display_path_to_actual(path_display);
+        let Some(layout) = RepoLayout::detect(&path) else {
+            self.notify(StatusMessage::err(
+                "Path not found",
+                format!("Session path no longer exists: {}", path.display()),
+            ));
+            return Ok(());
+        };
+        self.switch_target_repo(layout);
+        self.resume_recent_session(name, path_display)
+    }
+
+    /// Target `layout` from now on. It joins the repos the new session dialog's Ctrl+R
+    /// cycles through, along with the one targeted before.
+    fn switch_target_repo(&mut self, layout: RepoLayout) {
+        let Some(current) = self.repo.clone() else {
+            self.set_work_dir(layout.root().to_path_buf());
+            return;
+        };
+        if self.workspace_repos.is_empty() {
+            self.workspace_repos.push(current);
+        }
+        // The others' shared workflows were merged when they joined
+        if !self
+            .workspace_repos
+            .iter()
+            .any(|r| r.root() == layout.root())
+        {
+            self.workspace_repos.push(layout.clone());
+            if let Err(error) = merge_shared_workflows(&mut self.config, &layout) {
+                self.notify(error.into());
+            }
+        }
+        self.repo = Some(layout);
+    }
+
     /// Start a new session in a worktree directory.
     fn start_worktree_session(&mut self, path_display: &str) -> anyhow::Result<()> {
         // Convert display path back to actual path
@@ -1343,7 +5883,7 @@ impl TuiSessionManager {
 
         // Check if path still exists
         if !path.exists() {
-            let _ = self.status_tx.send(StatusMessage::err(
+            self.notify(StatusMessage::err(
                 "Path not found",
                 format!("Directory no longer exists: {}", path.display()),
             ));
@@ -1357,11 +5897,14 @@ impl TuiSessionManager {
             .unwrap_or_else(|| "unnamed".to_string());
 
         // Start a new session (no --continue flag)
-        let args_owned = self.config.claude_args.clone();
+        let args_owned = self.claude_args_for_path(&path);
         let args: Vec<&str> = args_owned.iter().map(|s| s.as_str()).collect();
-        self.add_claude_session(&name, "claude", &args, &path, false)?;
+        let repo = self.repo.clone();
+        if !self.add_claude_session(&name, "claude", &args, &path, repo, false)? {
+            return Ok(());
+        }
 
-        let _ = self.status_tx.send(StatusMessage::info(
+        self.notify(StatusMessage::info(
             "New session",
             format!("Started session '{}' in {}", name, path.display()),
         ));
@@ -1385,11 +5928,21 @@ impl TuiSessionManager {
         if let Some(idx) = bg_index {
             let bg_pair = self.background.remove(idx);
 
+            self.report_focus(false);
             if let Some(old_pair) = self.active.take() {
                 self.background.push(old_pair.detach());
             }
 
             self.active = Some(bg_pair.attach()?);
+            if let Some(multiplexer) = self.multiplexers.get(name) {
+                for pane in multiplexer.panes() {
+                    pane.restore_scrollback();
+                }
+            }
+            self.report_focus(true);
+            if self.config.redraw_on_attach {
+                self.force_redraw();
+            }
 
             return Ok(true);
         }
@@ -1404,41 +5957,249 @@ impl TuiSessionManager {
 
         if bytes[0] == 0x1b && bytes.len() == 1 {
             self.create_dialog.clear();
+            if let Some(repo) = self.dialog_return_repo.take() {
+                self.repo = repo;
+            }
             self.mode = UiMode::Normal;
             return Ok(());
         }
 
         match bytes[0] {
-            b'\r' | b'\n' => {
-                let input = self.create_dialog.take_input();
-                let name = if input.trim().is_empty() {
-                    self.session_counter += 1;
-                    format!("claude-{}", self.session_counter)
-                } else {
-                    input.trim().to_string()
-                };
-                self.new_named_claude_session(&name)?;
-                self.mode = UiMode::Normal;
-            }
+            b'\r' | b'\n' => self.submit_new_session(self.config.confirm_new_session)?,
+            CTRL_P => self.submit_new_session(true)?,
             0x7f => {
                 self.create_dialog.pop();
             }
-            b if b.is_ascii_graphic() || b == b' ' => {
-                self.create_dialog.push(b as char);
+            b'\t' => self.create_dialog.toggle_field(),
+            CTRL_R => {
+                self.dialog_return_repo
+                    .get_or_insert_with(|| self.repo.clone());
+                self.cycle_target_repo();
+                self.create_dialog.clear_collision();
+                self.reset_dialog_workflow();
             }
-            _ => {}
+            CTRL_W => self.create_dialog.cycle_workflow(),
+            0x1b => {}
+            _ => {
+                for c in String::from_utf8_lossy(bytes).chars() {
+                    if !c.is_control() {
+                        self.create_dialog.push(c);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the session the new session dialog describes, or with `preview`, show what
+    /// creating it would do first
+    fn submit_new_session(&mut self, preview: bool) -> anyhow::Result<()> {
+        let prompt = self.create_dialog.prompt().to_string();
+
+        // Nothing typed yet: ask for the task to name the session after
+        if self.create_dialog.is_empty()
+            && prompt.is_empty()
+            && self.config.name_from_prompt
+            && !self.create_dialog.prompt_focused()
+        {
+            self.create_dialog.focus_prompt();
+            return Ok(());
+        }
+
+        let name = if self.create_dialog.is_empty() {
+            // Name the session after the task
+            let derived = branch_name_from_prompt(&prompt);
+            if derived.is_empty() {
+                self.session_counter += 1;
+                format!("claude-{}", self.session_counter)
+            } else {
+                self.unique_session_name(&derived)
+            }
+        } else {
+            match self.create_dialog.resolved_name() {
+                Ok(name) => name,
+                // The dialog already shows why; keep it open for editing
+                Err(_) => return Ok(()),
+            }
+        };
+
+        // A taken name is never created twice: warn on the first Enter,
+        // attach to / resume the existing session on the second
+        if let Some(collision) = self.create_dialog.confirmed_collision(&name).cloned() {
+            self.create_dialog.clear();
+            self.mode = UiMode::Normal;
+            match collision {
+                NameCollision::Live => {
+                    self.touch_active();
+                    self.switch_to_session_by_name(&name)?;
+                    self.touch_active();
+                }
+                NameCollision::Recent(path_display) => {
+                    self.resume_recent_session(&name, &path_display)?;
+                }
+                NameCollision::Worktree(path_display) => {
+                    self.start_worktree_session(&path_display)?;
+                }
+                // Never confirmed
+                NameCollision::LiveElsewhere(_) => {}
+            }
+            return Ok(());
+        }
+        if let Some(collision) = self.find_name_collision(&name) {
+            self.create_dialog.set_collision(name, collision);
+            return Ok(());
+        }
+
+        let index = self.create_dialog.workflow();
+        let workflow = Arc::clone(&self.workflows[index]);
+        let prompt = (!prompt.is_empty()).then_some(prompt);
+        if preview {
+            let overrides = PlanOverrides::default();
+            match workflow.plan(&name, &self.config, self.repo_dir(), &overrides) {
+                Ok(plan) => {
+                    self.session_plan
+                        .open(name, prompt, (index, workflow.name()), plan);
+                    self.mode = UiMode::SessionPlan;
+                }
+                Err(error) => self.notify(error.into()),
+            }
+            return Ok(());
         }
+        self.spawn_workflow_session(CreationRequest {
+            name,
+            prompt,
+            workflow,
+            overrides: PlanOverrides::default(),
+            repo: self.repo.clone(),
+            background: false,
+            reply: None,
+        });
+        Ok(())
+    }
 
+    /// Handle input in the plan shown before creating a session
+    fn handle_session_plan_input(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        if self.session_plan.editing() {
+            match bytes {
+                [0x1b] => self.session_plan.cancel_edit(),
+                [b'\r'] | [b'\n'] => {
+                    self.session_plan.finish_edit();
+                    self.replan_session();
+                }
+                [0x7f] => self.session_plan.pop(),
+                _ => {
+                    for c in String::from_utf8_lossy(bytes).chars() {
+                        if !c.is_control() {
+                            self.session_plan.push(c);
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+        match bytes {
+            // Back to the dialog, still filled in
+            [0x1b] => self.mode = UiMode::NewSession,
+            [b'\r'] | [b'\n'] => {
+                let name = self.session_plan.name().to_string();
+                let prompt = self.session_plan.prompt().map(str::to_string);
+                let overrides = self.session_plan.overrides().clone();
+                let workflow = Arc::clone(&self.workflows[self.session_plan.workflow()]);
+                self.spawn_workflow_session(CreationRequest {
+                    name,
+                    prompt,
+                    workflow,
+                    overrides,
+                    repo: self.repo.clone(),
+                    background: false,
+                    reply: None,
+                });
+            }
+            [b'p'] => self.session_plan.start_edit(PlanField::Path),
+            [b'b'] => self.session_plan.start_edit(PlanField::Base),
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Plan the session in the plan dialog again after an edit
+    fn replan_session(&mut self) {
+        let workflow = Arc::clone(&self.workflows[self.session_plan.workflow()]);
+        match workflow.plan(
+            self.session_plan.name(),
+            &self.config,
+            self.repo_dir(),
+            self.session_plan.overrides(),
+        ) {
+            Ok(plan) => self.session_plan.set_plan(plan),
+            Err(error) => self.session_plan.set_error(error.to_string()),
+        }
+    }
+
+    /// Find an existing live session, history entry or worktree already using `name`
+    fn find_name_collision(&self, name: &str) -> Option<NameCollision> {
+        if let Some(repo) = self.live_session_repo(name) {
+            if same_repo(repo, self.repo.as_ref()) {
+                return Some(NameCollision::Live);
+            }
+            let repo = repo.map_or_else(
+                || path_to_display(&self.startup_path),
+                |r| r.name().to_string(),
+            );
+            return Some(NameCollision::LiveElsewhere(repo));
+        }
+
+        let repo_name = self.get_current_repo_name()?;
+        let path = self.worktree_path(name)?;
+        if self
+            .history
+            .get_recent_sessions(&repo_name)
+            .any(|recent| recent.name == name)
+            && path.exists()
+        {
+            return Some(NameCollision::Recent(path_to_display(&path)));
+        }
+        if path.exists() {
+            return Some(NameCollision::Worktree(path_to_display(&path)));
+        }
+        None
+    }
+
+    /// `base`, or `base-2`, `base-3`... if that name is already taken
+    fn unique_session_name(&self, base: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{}-{}", base, n),
+            })
+            .find(|name| self.find_name_collision(name).is_none())
+            .unwrap_or_else(|| base.to_string())
+    }
+
+    fn is_live_session(&self, name: &str) -> bool {
+        self.active.as_ref().is_some_and(|p| p.name == name)
+            || self.background.iter().any(|p| p.name == name)
+    }
+
     /// Open the worktree cleanup dialog
     fn open_worktree_cleanup(&mut self) {
         self.worktree_cleanup_dialog.reset();
-        let worktrees = self.list_worktree_dirs();
-        let active_paths = self.get_active_session_paths();
+        self.load_worktree_entries();
+    }
+
+    /// Fill the cleanup dialog with this repo's worktrees and their branch state, read on
+    /// the task runner since that runs git in every worktree
+    fn load_worktree_entries(&mut self) {
         self.worktree_cleanup_dialog
-            .set_worktrees_with_active(worktrees, active_paths);
+            .set_pending("Reading worktrees...");
+        let (repo_dir, dirs) = (self.repo_dir().to_path_buf(), self.list_worktree_dirs());
+        let base_branch = self.config.base_branch.clone();
+        let owners = self.foreign_worktrees();
+        self.tasks.run(move || {
+            let entries = tasks::worktree_entries(&repo_dir, dirs, base_branch.as_deref(), &owners);
+            AppEvent::WorktreesLoaded(repo_dir, entries)
+        });
     }
 
     /// Get paths of all active/background sessions.
@@ -1492,7 +6253,7 @@ impl TuiSessionManager {
                     let active_paths = self.get_active_session_paths();
                     self.delete_confirm_dialog
                         .set_worktrees_with_active(to_delete, active_paths);
-                    self.mode = UiMode::WorktreeDeleteConfirm;
+                    self.confirm_worktree_delete(UiMode::WorktreeCleanup)?;
                 }
             }
             0x7f => {
@@ -1519,16 +6280,20 @@ impl TuiSessionManager {
 
         match bytes[0] {
             0x1b if bytes.len() == 1 => {
-                // Escape - cancel, return to cleanup dialog
-                self.mode = UiMode::WorktreeCleanup;
+                // Escape - cancel, return to where the delete was started
+                self.mode = self.delete_return_mode.clone();
             }
             b'y' | b'Y' => {
                 // Confirm - delete worktrees
-                self.delete_selected_worktrees()?;
+                self.delete_selected_worktrees(false)?;
+            }
+            b'a' | b'A' => {
+                // Archive unmerged work first, then delete
+                self.delete_selected_worktrees(true)?;
             }
             b'n' | b'N' => {
-                // Cancel - return to cleanup dialog
-                self.mode = UiMode::WorktreeCleanup;
+                // Cancel - return to where the delete was started
+                self.mode = self.delete_return_mode.clone();
             }
             _ => {}
         }
@@ -1536,12 +6301,12 @@ impl TuiSessionManager {
         Ok(())
     }
 
-    /// Delete selected worktrees
-    fn delete_selected_worktrees(&mut self) -> anyhow::Result<()> {
+    /// Delete selected worktrees on the task runner. With `archive`, each worktree's
+    /// uncommitted changes and unmerged commits are exported to ~/.shepherd/archives
+    /// first; a worktree that fails to archive is kept.
+    fn delete_selected_worktrees(&mut self, archive: bool) -> anyhow::Result<()> {
         let worktrees = self.delete_confirm_dialog.get_worktrees().to_vec();
         let active_paths = self.delete_confirm_dialog.get_active_paths().clone();
-        let mut deleted_count = 0;
-        let mut errors = Vec::new();
 
         // First, kill any active sessions for worktrees being deleted
         for worktree_path in &worktrees {
@@ -1550,137 +6315,268 @@ impl TuiSessionManager {
             }
         }
 
-        // Now delete the worktrees
-        let repo_name = self.get_current_repo_name();
-        for worktree_path in &worktrees {
-            match self.delete_worktree(worktree_path) {
-                Ok(()) => {
-                    deleted_count += 1;
-                    // Remove from history - extract session name from path
-                    if let (Some(rn), Some(session_name)) = (
-                        &repo_name,
-                        worktree_path.file_name().and_then(|n| n.to_str()),
-                    ) {
-                        self.history.remove_by_name(rn, session_name);
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("{}: {}", worktree_path.display(), e));
+        let foreign = self.foreign_worktrees();
+        let (refused, worktrees): (Vec<_>, Vec<_>) = worktrees
+            .into_iter()
+            .partition(|path| foreign.contains_key(path));
+        let deletion = WorktreeDeletion {
+            repo_dir: self.repo_dir().to_path_buf(),
+            worktrees: worktrees
+                .into_iter()
+                .map(|path| {
+                    let name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    (path, self.workflow_of(&name))
+                })
+                .collect(),
+            archives: if archive {
+                Some(Archives::open()?)
+            } else {
+                None
+            },
+            config: self.config.clone(),
+            screens: self.screens.clone(),
+            status_tx: self.status_tx.clone(),
+            refused: refused
+                .iter()
+                .map(|path| {
+                    format!(
+                        "{}: in use by shepherd (pid {})",
+                        path.display(),
+                        foreign[path]
+                    )
+                })
+                .collect(),
+        };
+        let count = deletion.worktrees.len();
+        self.tasks
+            .run(move || AppEvent::WorktreesDeleted(deletion.run()));
+
+        if self.delete_return_mode == UiMode::ListSessions {
+            self.mode = UiMode::ListSessions;
+        } else {
+            self.worktree_cleanup_dialog.reset();
+            self.worktree_cleanup_dialog
+                .set_pending(format!("Deleting {} worktree(s)...", count));
+            self.mode = UiMode::WorktreeCleanup;
+        }
+        Ok(())
+    }
+
+    /// Report a finished deletion, forget the deleted sessions and refresh whichever
+    /// list is showing
+    fn on_worktrees_deleted(&mut self, outcome: DeletedWorktrees) {
+        let repo_name = self
+            .workspace_repos
+            .iter()
+            .chain(self.repo.iter())
+            .find(|repo| repo.root() == outcome.repo_dir)
+            .map(|repo| repo.name().to_string());
+        if let Some(ref repo_name) = repo_name {
+            for path in &outcome.deleted {
+                if let Some(session_name) = path.file_name().and_then(|n| n.to_str()) {
+                    self.history.remove_by_name(repo_name, session_name);
                 }
             }
+            if let Err(e) = self.history.save() {
+                self.notify(StatusMessage::err("Failed to save history", e.to_string()));
+            }
         }
 
-        // Save history after all deletions
-        let _ = self.history.save();
-
         // Show status message
-        if errors.is_empty() {
-            let _ = self.status_tx.send(StatusMessage::info(
-                format!("Deleted {} worktree(s)", deleted_count),
+        let deleted_count = outcome.deleted.len();
+        let undo_hint = if outcome.trashed.is_empty() {
+            ""
+        } else {
+            " (alt+u to undo)"
+        };
+        if !outcome.trashed.is_empty() {
+            self.last_trashed = Some((outcome.trashed, Instant::now()));
+        }
+        if !outcome.archived.is_empty() {
+            let files: Vec<String> = outcome
+                .archived
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            self.notify(StatusMessage::info(
+                format!(
+                    "Archived {} file(s) to ~/.shepherd/archives",
+                    outcome.archived.len()
+                ),
+                format!("Archived worktree changes: {}", files.join(", ")),
+            ));
+        }
+        if outcome.errors.is_empty() {
+            self.notify(StatusMessage::success(
+                format!("Deleted {} worktree(s){}", deleted_count, undo_hint),
                 format!("Successfully deleted {} worktree(s)", deleted_count),
             ));
         } else {
-            let _ = self.status_tx.send(StatusMessage::err(
+            self.notify(StatusMessage::err(
                 format!(
                     "Deleted {} of {} worktree(s)",
+                    deleted_count, outcome.requested
+                ),
+                format!(
+                    "Deleted {} of {} worktree(s): {}",
                     deleted_count,
-                    worktrees.len()
+                    outcome.requested,
+                    outcome.errors.join("; ")
                 ),
-                errors.join("; "),
             ));
         }
 
-        // Refresh the worktree list
-        let remaining = self.list_worktree_dirs();
-        let active_paths = self.get_active_session_paths();
-        self.worktree_cleanup_dialog
-            .set_worktrees_with_active(remaining, active_paths);
-
-        // Return to cleanup mode if worktrees remain, otherwise normal
-        if self.worktree_cleanup_dialog.is_empty() {
-            self.mode = UiMode::Normal;
-        } else {
-            self.mode = UiMode::WorktreeCleanup;
+        match self.mode {
+            UiMode::ListSessions => self.refresh_session_selector(),
+            // Stay in cleanup if worktrees remain
+            UiMode::WorktreeCleanup if self.list_worktree_dirs().is_empty() => {
+                self.mode = UiMode::Normal;
+            }
+            UiMode::WorktreeCleanup => self.load_worktree_entries(),
+            _ => {}
         }
-
-        Ok(())
     }
 
     /// Kill a session at the given path (active or background)
     fn kill_session_at_path(&mut self, path: &Path) {
-        // Check if it's the active session
-        if let Some(ref pair) = self.active
-            && pair.path == path
-        {
-            if let Some(pair) = self.active.take() {
-                let name = pair.name.clone();
-                pair.claude.shutdown();
-
-                // Also cleanup the multiplexer for this session
-                if let Some(mut multiplexer) = self.multiplexers.remove(&name) {
-                    for pane in multiplexer.remove_dead_panes() {
-                        pane.shutdown();
-                    }
-                    while let Some(pane) = multiplexer.close_active_pane() {
-                        pane.shutdown();
-                    }
-                }
-            }
-            return;
+        let name = self
+            .active
+            .iter()
+            .map(|p| (&p.name, &p.path))
+            .chain(self.background.iter().map(|p| (&p.name, &p.path)))
+            .find(|(_, p)| *p == path)
+            .map(|(name, _)| name.clone());
+        if let Some(name) = name {
+            self.kill_session(&name);
         }
+    }
 
-        // Check background sessions
-        if let Some(idx) = self.background.iter().position(|p| p.path == path) {
-            let bg_pair = self.background.remove(idx);
-            let name = bg_pair.name.clone();
+    /// Kill the named session (active or background) along with its shell panes
+    fn kill_session(&mut self, name: &str) {
+        let (claude, timer, path, repo) = if self.active.as_ref().is_some_and(|p| p.name == name) {
+            let Some(pair) = self.active.take() else {
+                return;
+            };
+            (pair.claude.detach(), pair.timer, pair.path, pair.repo)
+        } else if let Some(idx) = self.background.iter().position(|p| p.name == name) {
+            let pair = self.background.remove(idx);
+            (pair.claude, pair.timer, pair.path, pair.repo)
+        } else {
+            return;
+        };
+        tracing::info!(session = name, "killing session");
+        self.release_worktree(&path);
+        self.spawn_workflow_hook(name, &path, repo.as_ref(), |w, s, c| {
+            w.on_session_exit(s, c)
+        });
 
-            // Cleanup the multiplexer for this session
-            if let Some(mut multiplexer) = self.multiplexers.remove(&name) {
-                for pane in multiplexer.remove_dead_panes() {
-                    pane.shutdown();
-                }
-                while let Some(pane) = multiplexer.close_active_pane() {
-                    pane.shutdown();
-                }
+        // Also cleanup the multiplexer for this session
+        self.rebases.remove(name);
+        if let Some(mut multiplexer) = self.multiplexers.remove(name) {
+            for pane in multiplexer.remove_dead_panes() {
+                pane.shutdown();
+            }
+            while let Some(pane) = multiplexer.close_active_pane() {
+                pane.shutdown();
             }
-
-            // Note: BackgroundPair doesn't have a shutdown method, but dropping it should clean up
         }
-    }
-
-    /// Delete a single worktree (git worktree remove + directory cleanup)
-    fn delete_worktree(&self, worktree_path: &Path) -> anyhow::Result<()> {
-        let worktree_str = worktree_path
-            .to_str()
-            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?;
 
-        // First try git worktree remove
-        let output = std::process::Command::new("git")
-            .args(["worktree", "remove", worktree_str])
-            .current_dir(&self.startup_path)
-            .output()?;
+        claude.shutdown();
+        self.typed_lines.remove(name);
+        self.record_session_time(name, &timer, repo.as_ref());
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow::anyhow!(
-                "git worktree remove failed: {}",
-                stderr.trim()
+    /// Move the worktrees trashed by the last delete back, if that was recent enough
+    fn undo_last_delete(&mut self) {
+        let Some((entries, at)) = self.last_trashed.take() else {
+            return;
+        };
+        if at.elapsed() > TRASH_UNDO_WINDOW {
+            self.notify(StatusMessage::warn(
+                "Too late to undo",
+                "Undo window for the last worktree delete has passed; see `shepherd trash`",
             ));
+            return;
         }
+        let trash = match Trash::open() {
+            Ok(trash) => trash,
+            Err(e) => {
+                self.notify(StatusMessage::err("Undo failed", e.to_string()));
+                return;
+            }
+        };
 
-        // If directory still exists (shouldn't normally), remove it
-        if worktree_path.exists() {
-            std::fs::remove_dir_all(worktree_path)?;
+        let mut errors = Vec::new();
+        for entry in &entries {
+            if let Err(e) = trash.restore(entry) {
+                errors.push(format!("{}: {}", entry.name(), e));
+            }
+        }
+        let restored = entries.len() - errors.len();
+        if errors.is_empty() {
+            self.notify(StatusMessage::success(
+                format!("Restored {} worktree(s)", restored),
+                format!("Restored {} worktree(s) from the trash", restored),
+            ));
+        } else {
+            self.notify(StatusMessage::err(
+                format!("Restored {} of {} worktree(s)", restored, entries.len()),
+                format!(
+                    "Restored {} of {} worktree(s): {}",
+                    restored,
+                    entries.len(),
+                    errors.join("; ")
+                ),
+            ));
+        }
+        if self.mode == UiMode::ListSessions {
+            self.refresh_session_selector();
         }
-
-        Ok(())
     }
 }
 
 impl Drop for TuiSessionManager {
     fn drop(&mut self) {
-        let _ = stdout().execute(DisableMouseCapture);
-        let _ = disable_raw_mode();
-        let _ = stdout().execute(LeaveAlternateScreen);
+        // Sessions still alive at exit end here too
+        let timers: Vec<(String, SessionTimer, Option<RepoLayout>)> = self
+            .active
+            .iter()
+            .map(|p| (p.name.clone(), p.timer.clone(), p.repo.clone()))
+            .chain(
+                self.background
+                    .iter()
+                    .map(|p| (p.name.clone(), p.timer.clone(), p.repo.clone())),
+            )
+            .collect();
+        for (name, timer, repo) in &timers {
+            self.record_session_time(name, timer, repo.as_ref());
+        }
+        self.snapshot_screens();
+        if let Some(ref instances) = self.instances {
+            let _ = instances.unregister();
+        }
+
+        if self.terminal.backend().is_host() {
+            restore_terminal();
+        }
+    }
+}
+
+/// Bytes that paste `text` into a session and submit it. Bracketed when the session asked
+/// for bracketed paste, so newlines don't submit it line by line.
+fn submit_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    let body = text.replace("\r\n", "\r").replace('\n', "\r");
+    let mut bytes = Vec::with_capacity(body.len() + 13);
+    if bracketed {
+        bytes.extend_from_slice(b"\x1b[200~");
+    }
+    bytes.extend_from_slice(body.as_bytes());
+    if bracketed {
+        bytes.extend_from_slice(b"\x1b[201~");
     }
+    bytes.push(b'\r');
+    bytes
 }
@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::status_socket::Command;
+
+/// How long `wait-for stop` waits unless the line says otherwise
+const DEFAULT_WAIT: Duration = Duration::from_secs(600);
+
+/// One line of a `--script` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    /// `create`, `kill` and `send-prompt`, run as if they came from the command socket
+    Command(Command),
+    /// Wait for the session's next `stop` hook event
+    WaitForStop {
+        session: String,
+        timeout: Duration,
+    },
+    /// Write the session's Claude transcript to a file
+    ExportTranscript {
+        session: String,
+        path: PathBuf,
+    },
+    Sleep(Duration),
+    Quit,
+}
+
+/// An action with the line it came from, for error messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStep {
+    pub line: usize,
+    pub action: ScriptAction,
+}
+
+/// Actions fed to the manager in place of keyboard input, one step at a time. A step
+/// that waits holds back the rest until it is finished.
+#[derive(Debug)]
+pub struct Script {
+    steps: VecDeque<ScriptStep>,
+    /// The step being waited on, with when the wait started
+    waiting: Option<(ScriptStep, Instant)>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        Self::parse(&contents).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+
+    /// One action per line; blank lines and lines starting with `#` are skipped
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut steps = VecDeque::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let action = parse_action(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            steps.push_back(ScriptStep {
+                line: i + 1,
+                action,
+            });
+        }
+        Ok(Self {
+            steps,
+            waiting: None,
+        })
+    }
+
+    /// The next step to run, unless a step is still waiting
+    pub fn next_step(&mut self) -> Option<ScriptStep> {
+        if self.waiting.is_some() {
+            return None;
+        }
+        self.steps.pop_front()
+    }
+
+    /// Hold the script on `step` until `finish_wait`
+    pub fn wait(&mut self, step: ScriptStep) {
+        self.waiting = Some((step, Instant::now()));
+    }
+
+    /// The step being waited on, and for how long so far
+    pub fn waiting(&self) -> Option<(&ScriptStep, Duration)> {
+        self.waiting
+            .as_ref()
+            .map(|(step, since)| (step, since.elapsed()))
+    }
+
+    pub fn finish_wait(&mut self) {
+        self.waiting = None;
+    }
+
+    /// Whether every step has run
+    pub fn is_done(&self) -> bool {
+        self.waiting.is_none() && self.steps.is_empty()
+    }
+}
+
+fn parse_action(line: &str) -> Result<ScriptAction, String> {
+    let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    // The first word of `rest`, and what follows it
+    let (first, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let tail = tail.trim();
+    let session = |usage: &str| {
+        if first.is_empty() {
+            Err(format!("usage: {}", usage))
+        } else {
+            Ok(first.to_string())
+        }
+    };
+    let action = match verb {
+        "create" => ScriptAction::Command(Command::Create {
+            session: session("create <name> [<prompt>]")?,
+            prompt: (!tail.is_empty()).then(|| tail.to_string()),
+        }),
+        "kill" => ScriptAction::Command(Command::Kill {
+            session: session("kill <name>")?,
+        }),
+        "send-prompt" if !tail.is_empty() => ScriptAction::Command(Command::SendPrompt {
+            session: session("send-prompt <name> <prompt>")?,
+            prompt: tail.to_string(),
+        }),
+        "send-prompt" => return Err("usage: send-prompt <name> <prompt>".to_string()),
+        "wait-for" => {
+            const USAGE: &str = "usage: wait-for stop <name> [<seconds>]";
+            let mut words = rest.split_whitespace();
+            if words.next() != Some("stop") {
+                return Err(USAGE.to_string());
+            }
+            let session = words.next().ok_or(USAGE)?.to_string();
+            let timeout = match words.next() {
+                Some(secs) => Duration::from_secs(secs.parse().map_err(|_| USAGE)?),
+                None => DEFAULT_WAIT,
+            };
+            ScriptAction::WaitForStop { session, timeout }
+        }
+        "export-transcript" if !tail.is_empty() => ScriptAction::ExportTranscript {
+            session: first.to_string(),
+            path: PathBuf::from(tail),
+        },
+        "export-transcript" => return Err("usage: export-transcript <name> <file>".to_string()),
+        "sleep" => {
+            let secs: f64 = rest
+                .parse()
+                .map_err(|_| "usage: sleep <seconds>".to_string())?;
+            ScriptAction::Sleep(Duration::from_secs_f64(secs.max(0.0)))
+        }
+        "quit" => ScriptAction::Quit,
+        _ => return Err(format!("unknown action '{}'", verb)),
+    };
+    Ok(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut script = Script::parse(
+            "# smoke test\n\
+             create fix-ci Fix the failing build\n\
+             \n\
+             wait-for stop fix-ci 120\n\
+             send-prompt fix-ci Now add a test\n\
+             export-transcript fix-ci out/fix ci.md\n\
+             quit\n",
+        )
+        .unwrap();
+        let actions: Vec<(usize, ScriptAction)> = std::iter::from_fn(|| script.next_step())
+            .map(|step| (step.line, step.action))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                (
+                    2,
+                    ScriptAction::Command(Command::Create {
+                        session: "fix-ci".to_string(),
+                        prompt: Some("Fix the failing build".to_string()),
+                    })
+                ),
+                (
+                    4,
+                    ScriptAction::WaitForStop {
+                        session: "fix-ci".to_string(),
+                        timeout: Duration::from_secs(120),
+                    }
+                ),
+                (
+                    5,
+                    ScriptAction::Command(Command::SendPrompt {
+                        session: "fix-ci".to_string(),
+                        prompt: "Now add a test".to_string(),
+                    })
+                ),
+                (
+                    6,
+                    ScriptAction::ExportTranscript {
+                        session: "fix-ci".to_string(),
+                        path: PathBuf::from("out/fix ci.md"),
+                    }
+                ),
+                (7, ScriptAction::Quit),
+            ]
+        );
+
+        assert_eq!(
+            Script::parse("create a\nwait-for exit a").unwrap_err(),
+            "line 2: usage: wait-for stop <name> [<seconds>]"
+        );
+        assert_eq!(
+            Script::parse("launch a").unwrap_err(),
+            "line 1: unknown action 'launch'"
+        );
+    }
+}
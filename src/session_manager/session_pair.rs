@@ -1,9 +1,13 @@
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
 
 use crate::session::{AttachedSession, DetachedSession};
+use crate::workflows::RepoLayout;
 
 /// Which view is currently active in a session pair
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum SessionView {
     #[default]
     Claude,
@@ -11,7 +15,7 @@ pub enum SessionView {
 }
 
 /// Activity status of a Claude session (for hook notifications)
-#[derive(Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum SessionActivity {
     /// Claude is working (default state)
     #[default]
@@ -20,13 +24,77 @@ pub enum SessionActivity {
     RunningTool(String),
     /// Claude stopped and needs user attention
     Stopped,
+    /// The session's processes are suspended (display only; derived from the session)
+    Paused,
+    /// A watch rule matched the session's output; holds the rule's label
+    Flagged(String),
+}
+
+/// Outcome of the last checks run (Alt+c) in a session's worktree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    Running,
+    Passed,
+    /// Failed with this exit code
+    Failed(i32),
+}
+
+/// Tracks how long a session has been running and how long it has been on screen
+#[derive(Clone)]
+pub struct SessionTimer {
+    started: Instant,
+    started_at: DateTime<Utc>,
+    /// Focused time accumulated over previous attachments
+    focused: Duration,
+    /// When the current attachment began, if the session is active
+    focused_since: Option<Instant>,
+}
+
+impl SessionTimer {
+    /// Start timing a session that is created in the foreground
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            started_at: Utc::now(),
+            focused: Duration::ZERO,
+            focused_since: Some(now),
+        }
+    }
+
+    pub fn focus(&mut self) {
+        self.focused_since.get_or_insert_with(Instant::now);
+    }
+
+    pub fn unfocus(&mut self) {
+        if let Some(since) = self.focused_since.take() {
+            self.focused += since.elapsed();
+        }
+    }
+
+    pub fn running(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn focused(&self) -> Duration {
+        self.focused + self.focused_since.map_or(Duration::ZERO, |s| s.elapsed())
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
 }
 
 /// An active session pair - claude session is attached (can receive input)
 /// Shell sessions are managed separately in TerminalMultiplexer
 pub struct ActivePair {
     pub name: String,
+    /// Creation sequence number, used for stable quick-jump numbering
+    pub seq: usize,
     pub path: PathBuf,
+    /// Repository the session was started for; its history, pins and hooks are keyed by
+    /// it rather than by the repo targeted now
+    pub repo: Option<RepoLayout>,
     pub view: SessionView,
     pub claude: AttachedSession,
     /// Whether this session was started via resume (--continue flag)
@@ -35,30 +103,54 @@ pub struct ActivePair {
     pub scroll_offset: usize,
     /// Activity status from hook notifications
     pub activity: SessionActivity,
+    /// Latest progress line reported by a hook, cleared when claude stops
+    pub progress: Option<String>,
+    /// Last time the user interacted with this session (for MRU ordering)
+    pub last_used: Instant,
+    /// Running and focused time for this session
+    pub timer: SessionTimer,
 }
 
 impl ActivePair {
-    pub fn new(name: String, path: PathBuf, claude: AttachedSession, resumed: bool) -> Self {
+    pub fn new(
+        name: String,
+        seq: usize,
+        path: PathBuf,
+        repo: Option<RepoLayout>,
+        claude: AttachedSession,
+        resumed: bool,
+    ) -> Self {
         Self {
             name,
+            seq,
             path,
+            repo,
             view: SessionView::Claude,
             claude,
             resumed,
             scroll_offset: 0,
             activity: SessionActivity::Active,
+            progress: None,
+            last_used: Instant::now(),
+            timer: SessionTimer::start(),
         }
     }
 
-    pub fn detach(self) -> BackgroundPair {
+    pub fn detach(mut self) -> BackgroundPair {
+        self.timer.unfocus();
         BackgroundPair {
             name: self.name,
+            seq: self.seq,
             path: self.path,
+            repo: self.repo,
             last_view: self.view,
             claude: self.claude.detach(),
             resumed: self.resumed,
             scroll_offset: self.scroll_offset,
             activity: self.activity,
+            progress: self.progress,
+            last_used: self.last_used,
+            timer: self.timer,
         }
     }
 }
@@ -67,7 +159,12 @@ impl ActivePair {
 /// Shell sessions are managed separately in TerminalMultiplexer
 pub struct BackgroundPair {
     pub name: String,
+    /// Creation sequence number, used for stable quick-jump numbering
+    pub seq: usize,
     pub path: PathBuf,
+    /// Repository the session was started for; its history, pins and hooks are keyed by
+    /// it rather than by the repo targeted now
+    pub repo: Option<RepoLayout>,
     pub last_view: SessionView,
     pub claude: DetachedSession,
     /// Whether this session was started via resume (--continue flag)
@@ -76,19 +173,31 @@ pub struct BackgroundPair {
     pub scroll_offset: usize,
     /// Activity status from hook notifications
     pub activity: SessionActivity,
+    /// Latest progress line reported by a hook, cleared when claude stops
+    pub progress: Option<String>,
+    /// Last time the user interacted with this session (for MRU ordering)
+    pub last_used: Instant,
+    /// Running and focused time for this session
+    pub timer: SessionTimer,
 }
 
 impl BackgroundPair {
-    pub fn attach(self) -> anyhow::Result<ActivePair> {
+    pub fn attach(mut self) -> anyhow::Result<ActivePair> {
+        self.timer.focus();
         Ok(ActivePair {
             name: self.name,
+            seq: self.seq,
             path: self.path,
+            repo: self.repo,
             view: self.last_view,
             claude: self.claude.attach()?,
             resumed: self.resumed,
             scroll_offset: self.scroll_offset,
             // Preserve activity state - only cleared when user sends input
             activity: self.activity,
+            progress: self.progress,
+            last_used: self.last_used,
+            timer: self.timer,
         })
     }
 }
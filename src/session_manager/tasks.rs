@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{Sender, unbounded};
+
+use crate::archive::Archives;
+use crate::config::Config;
+use crate::screens::Screens;
+use crate::trash::{Trash, TrashEntry};
+use crate::workflows::{SessionInfo, Workflow};
+
+use super::checkpoints;
+use super::events::AppEvent;
+use super::git_status::{self, BranchInfo};
+use super::ui::{StatusMessage, WorktreeEntry};
+
+/// Tasks run at once; more wait in the queue
+const TASK_THREADS: usize = 2;
+
+type Task = Box<dyn FnOnce() -> AppEvent + Send>;
+
+/// Small thread pool for blocking work the UI asks for, like git commands over every
+/// worktree. A task returns the event carrying its result, which the run loop handles
+/// like any other. Queued tasks finish before the threads are joined on drop.
+pub struct TaskRunner {
+    tasks: Option<Sender<Task>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl TaskRunner {
+    pub fn spawn(events: Sender<AppEvent>) -> Self {
+        let (tasks, rx) = unbounded::<Task>();
+        let threads = (0..TASK_THREADS)
+            .map(|_| {
+                let (rx, events) = (rx.clone(), events.clone());
+                std::thread::spawn(move || {
+                    while let Ok(task) = rx.recv() {
+                        if events.send(task()).is_err() {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+        Self {
+            tasks: Some(tasks),
+            threads,
+        }
+    }
+
+    /// Queue `task`, sending the event it returns when it's done
+    pub fn run(&self, task: impl FnOnce() -> AppEvent + Send + 'static) {
+        if let Some(ref tasks) = self.tasks {
+            let _ = tasks.send(Box::new(task));
+        }
+    }
+}
+
+impl Drop for TaskRunner {
+    fn drop(&mut self) {
+        self.tasks.take();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The worktrees in `dirs` with what their branches hold against the base branch, for
+/// the cleanup dialog. `owners` are worktrees other shepherds have sessions in.
+pub fn worktree_entries(
+    repo_dir: &Path,
+    dirs: Vec<PathBuf>,
+    base_branch: Option<&str>,
+    owners: &BTreeMap<PathBuf, u32>,
+) -> Vec<WorktreeEntry> {
+    let base = git_status::base_ref(repo_dir, base_branch).map(|(_, base_ref)| base_ref);
+    dirs.into_iter()
+        .map(|path| WorktreeEntry {
+            info: BranchInfo::load(&path, base.as_deref()),
+            owner: owners.get(&path).copied(),
+            path,
+        })
+        .collect()
+}
+
+/// Worktrees to delete, with everything deleting them needs from the manager
+pub struct WorktreeDeletion {
+    pub repo_dir: PathBuf,
+    /// Each worktree with the workflow whose delete hooks run around it
+    pub worktrees: Vec<(PathBuf, Arc<dyn Workflow>)>,
+    /// Where each worktree is exported first, when archiving
+    pub archives: Option<Archives>,
+    pub config: Config,
+    pub screens: Option<Screens>,
+    /// Receives failures of `post_worktree_delete`, which no longer keep anything
+    pub status_tx: std::sync::mpsc::Sender<StatusMessage>,
+    /// Worktrees refused before deleting started, e.g. in use by another shepherd
+    pub refused: Vec<String>,
+}
+
+/// What a `WorktreeDeletion` did
+#[derive(Debug, Default)]
+pub struct DeletedWorktrees {
+    pub repo_dir: PathBuf,
+    /// Worktrees asked for, including refused ones
+    pub requested: usize,
+    pub deleted: Vec<PathBuf>,
+    pub trashed: Vec<TrashEntry>,
+    /// Archive files written
+    pub archived: Vec<PathBuf>,
+    pub errors: Vec<String>,
+}
+
+impl WorktreeDeletion {
+    /// Delete every worktree. With archives, a worktree that fails to archive is kept.
+    pub fn run(self) -> DeletedWorktrees {
+        let mut outcome = DeletedWorktrees {
+            repo_dir: self.repo_dir.clone(),
+            requested: self.worktrees.len() + self.refused.len(),
+            errors: self.refused.clone(),
+            ..Default::default()
+        };
+        for (path, workflow) in &self.worktrees {
+            if let Some(ref archives) = self.archives {
+                let base = git_status::base_ref(path, self.config.base_branch.as_deref());
+                match archives.archive(path, base.as_ref().map(|(_, r)| r.as_str())) {
+                    Ok(saved) => outcome
+                        .archived
+                        .extend(saved.patch.into_iter().chain(saved.bundle)),
+                    Err(e) => {
+                        outcome
+                            .errors
+                            .push(format!("{}: archive failed: {}", path.display(), e));
+                        continue;
+                    }
+                }
+            }
+            match self.delete(path, &**workflow) {
+                Ok(entry) => {
+                    outcome.deleted.push(path.clone());
+                    outcome.trashed.extend(entry);
+                }
+                Err(e) => outcome.errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        outcome
+    }
+
+    /// Delete a worktree, moving it to the trash when that is enabled. Returns the trash
+    /// entry if it was trashed. The workflow's delete hooks run around it.
+    fn delete(&self, path: &Path, workflow: &dyn Workflow) -> anyhow::Result<Option<TrashEntry>> {
+        let session = SessionInfo {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_path_buf(),
+            dir: self.repo_dir.clone(),
+        };
+        workflow.pre_worktree_delete(&session, &self.config)?;
+        let entry = self.remove(path)?;
+        if let Err(error) = workflow.post_worktree_delete(&session, &self.config) {
+            let _ = self.status_tx.send(error.into());
+        }
+        Ok(entry)
+    }
+
+    /// Remove a worktree with git, or move it to the trash when that is enabled
+    fn remove(&self, worktree_path: &Path) -> anyhow::Result<Option<TrashEntry>> {
+        let entry = if self.config.trash.enabled {
+            Some(Trash::open()?.trash_worktree(&self.repo_dir, worktree_path)?)
+        } else {
+            self.remove_worktree(worktree_path)?;
+            None
+        };
+        // Checkpoints are keyed by the worktree's name, so a new session reusing the name
+        // would otherwise inherit these
+        checkpoints::remove_all(&self.repo_dir, worktree_path);
+        // Nor should it show this session's screen while it starts
+        if let Some(ref screens) = self.screens {
+            screens.remove(worktree_path);
+        }
+        Ok(entry)
+    }
+
+    fn remove_worktree(&self, worktree_path: &Path) -> anyhow::Result<()> {
+        let worktree_str = worktree_path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?;
+
+        // First try git worktree remove
+        let output = std::process::Command::new("git")
+            .args(["worktree", "remove", worktree_str])
+            .current_dir(&self.repo_dir)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!(
+                "git worktree remove failed: {}",
+                stderr.trim()
+            ));
+        }
+
+        // If directory still exists (shouldn't normally), remove it
+        if worktree_path.exists() {
+            std::fs::remove_dir_all(worktree_path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runner_delivers_every_task() {
+        let (events, rx) = unbounded();
+        let runner = TaskRunner::spawn(events);
+        for i in 0..5 {
+            runner.run(move || AppEvent::SessionDied(format!("task-{}", i)));
+        }
+        // Dropping waits for the queue to drain
+        drop(runner);
+        let mut names: Vec<String> = rx
+            .try_iter()
+            .filter_map(|event| match event {
+                AppEvent::SessionDied(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, ["task-0", "task-1", "task-2", "task-3", "task-4"]);
+    }
+}
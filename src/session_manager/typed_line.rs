@@ -0,0 +1,97 @@
+/// Escape sequence state while following keystrokes
+#[derive(Debug, Default, PartialEq)]
+enum Escape {
+    #[default]
+    None,
+    /// After a lone ESC
+    Esc,
+    /// Inside a CSI sequence, collecting its parameters
+    Csi(String),
+    /// Inside a bracketed paste, until ESC [ 201 ~. Holds whether the last character was
+    /// a carriage return, so CRLF counts as one newline.
+    Paste(bool),
+}
+
+/// Follows the keystrokes sent to a claude session to recover the prompt lines submitted
+/// with Enter. Cursor movement within the line isn't tracked; history recall (up/down)
+/// drops the line since its contents are unknown.
+#[derive(Debug, Default)]
+pub struct TypedLine {
+    line: String,
+    escape: Escape,
+}
+
+impl TypedLine {
+    /// Feed input bytes, returning every non-empty line they submitted
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut submitted = Vec::new();
+        for c in String::from_utf8_lossy(bytes).chars() {
+            match std::mem::take(&mut self.escape) {
+                Escape::None => match c {
+                    '\r' => {
+                        let line = std::mem::take(&mut self.line);
+                        let line = line.trim();
+                        if !line.is_empty() {
+                            submitted.push(line.to_string());
+                        }
+                    }
+                    '\x1b' => self.escape = Escape::Esc,
+                    '\x7f' | '\x08' => {
+                        self.line.pop();
+                    }
+                    // Ctrl+U and Ctrl+C discard the line
+                    '\x15' | '\x03' => self.line.clear(),
+                    c if !c.is_control() || c == '\t' => self.line.push(c),
+                    _ => {}
+                },
+                Escape::Esc => match c {
+                    '[' => self.escape = Escape::Csi(String::new()),
+                    // Alt+Enter inserts a newline
+                    '\r' => self.line.push('\n'),
+                    _ => {}
+                },
+                Escape::Csi(mut params) => match c {
+                    '0'..='?' => {
+                        params.push(c);
+                        self.escape = Escape::Csi(params);
+                    }
+                    '~' if params == "200" => self.escape = Escape::Paste(false),
+                    'A' | 'B' => self.line.clear(),
+                    _ => {}
+                },
+                Escape::Paste(after_cr) => {
+                    match c {
+                        '\r' => self.line.push('\n'),
+                        '\n' if after_cr => {}
+                        c => self.line.push(c),
+                    }
+                    match self.line.strip_suffix("\x1b[201~") {
+                        Some(rest) => self.line.truncate(rest.len()),
+                        None => self.escape = Escape::Paste(c == '\r'),
+                    }
+                }
+            }
+        }
+        submitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_line() {
+        let mut typed = TypedLine::default();
+        assert!(typed.feed(b"fix the tesx").is_empty());
+        assert_eq!(typed.feed(b"\x7ft\r"), vec!["fix the test"]);
+        // Arrow keys and blank lines don't submit anything
+        assert!(typed.feed(b"\x1b[D\r \r").is_empty());
+        assert_eq!(
+            typed.feed(b"see:\x1b[200~a\r\nb\x1b[201~ ok\r"),
+            vec!["see:a\nb ok"]
+        );
+        assert_eq!(typed.feed(b"junk\x15done\r"), vec!["done"]);
+        assert!(typed.feed(b"recalled\x1b[A\r").is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+/// One-line bar shown while a session is waiting for a permission decision.
+pub struct ApprovalBar;
+
+impl ApprovalBar {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Render over the top row of `area`. `pending` counts all waiting sessions,
+    /// including `session`.
+    pub fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        session: &str,
+        slot: Option<usize>,
+        pending: usize,
+    ) {
+        if area.height == 0 {
+            return;
+        }
+        let bar_area = Rect::new(area.x, area.y, area.width, 1);
+
+        let key_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let label = match slot {
+            Some(slot) => format!(" {}: {} ", slot, session),
+            None => format!(" {} ", session),
+        };
+
+        let mut spans = vec![
+            Span::styled(" ⚠ approval ", key_style),
+            Span::styled(label, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                "alt+y",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" approve  "),
+            Span::styled(
+                "alt+n",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" deny"),
+        ];
+        if pending > 1 {
+            spans.push(Span::styled(
+                format!("  (+{} waiting)", pending - 1),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+
+        let bar = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black));
+        frame.render_widget(bar, bar_area);
+    }
+}
+
+impl Default for ApprovalBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
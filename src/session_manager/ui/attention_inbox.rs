@@ -0,0 +1,170 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+/// Lines of each session's screen shown under its name
+pub const PREVIEW_LINES: usize = 3;
+
+/// A session waiting on the user, as shown in the inbox
+pub struct InboxEntry {
+    pub name: String,
+    pub slot: Option<usize>,
+    /// Waiting on a permission prompt (answerable with y/n) rather than just idle
+    pub needs_approval: bool,
+    /// Last non-blank lines of the session's screen
+    pub preview: Vec<String>,
+}
+
+/// Popup listing every session that needs input, worked through as a queue.
+pub struct AttentionInbox {
+    state: ListState,
+}
+
+impl AttentionInbox {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { state }
+    }
+
+    /// Reset the selection for a fresh view.
+    pub fn reset(&mut self) {
+        self.state.select(Some(0));
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0).min(len - 1);
+        let next = if current == 0 { len - 1 } else { current - 1 };
+        self.state.select(Some(next));
+    }
+
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current + 1 >= len { 0 } else { current + 1 };
+        self.state.select(Some(next));
+    }
+
+    /// Index of the highlighted entry, clamped to the current queue length.
+    pub fn selected(&self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        Some(self.state.selected().unwrap_or(0).min(len - 1))
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, entries: &[InboxEntry]) {
+        let popup_width = (area.width * 3 / 4)
+            .max(50)
+            .min(area.width.saturating_sub(4));
+        let entry_height = 1 + PREVIEW_LINES as u16;
+        let list_height = (entries.len().max(1) as u16 * entry_height).min(4 * entry_height);
+        let popup_height = (list_height + 2 + 2).min(area.height.saturating_sub(2));
+
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" Inbox ({}) ", entries.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y,
+            inner.width,
+            inner.height.saturating_sub(2),
+        );
+
+        if entries.is_empty() {
+            let empty_msg = Paragraph::new("No sessions need attention")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty_msg, list_area);
+        } else {
+            let preview_width = (inner.width as usize).saturating_sub(6);
+            let items: Vec<ListItem> = entries
+                .iter()
+                .map(|entry| {
+                    let label = match entry.slot {
+                        Some(slot) => format!("{}: {}", slot, entry.name),
+                        None => entry.name.clone(),
+                    };
+                    let (state, state_color) = if entry.needs_approval {
+                        ("  [approval]", Color::Yellow)
+                    } else {
+                        ("  [waiting]", Color::DarkGray)
+                    };
+                    let mut lines = vec![Line::from(vec![
+                        Span::styled(
+                            label,
+                            Style::default()
+                                .fg(Color::White)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(state, Style::default().fg(state_color)),
+                    ])];
+                    lines.extend(entry.preview.iter().map(|line| {
+                        let line: String = line.chars().take(preview_width).collect();
+                        Line::from(Span::styled(
+                            format!("  {}", line),
+                            Style::default().fg(Color::Gray),
+                        ))
+                    }));
+                    // Keep every entry the same height so the list scrolls evenly
+                    lines.resize(1 + PREVIEW_LINES, Line::default());
+                    ListItem::new(lines)
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(Style::default().bg(Color::DarkGray))
+                .highlight_symbol("> ");
+
+            // The queue can shrink while open; keep the selection on a real entry
+            self.state.select(self.selected(entries.len()));
+            frame.render_stateful_widget(list, list_area, &mut self.state);
+        }
+
+        let footer_area = Rect::new(
+            inner.x,
+            inner.y + inner.height.saturating_sub(1),
+            inner.width,
+            1,
+        );
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled("Enter", key_style),
+            Span::raw(": jump  "),
+            Span::styled("y/n", key_style),
+            Span::raw(": approve/deny  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": close"),
+        ]));
+        frame.render_widget(footer, footer_area);
+    }
+}
+
+impl Default for AttentionInbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,203 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::session_manager::checkpoints::Checkpoint;
+
+/// Checkpoints of the active session's worktree, newest first, with the diff from the
+/// highlighted one to the worktree as it is now
+pub struct CheckpointBrowser {
+    session: String,
+    checkpoints: Vec<Checkpoint>,
+    state: ListState,
+    /// Diff of the highlighted checkpoint, stat or full
+    diff: Vec<String>,
+    full_diff: bool,
+    scroll: usize,
+    /// Waiting for y/n on restoring the highlighted checkpoint
+    confirm_restore: bool,
+}
+
+impl CheckpointBrowser {
+    pub fn new() -> Self {
+        Self {
+            session: String::new(),
+            checkpoints: Vec::new(),
+            state: ListState::default(),
+            diff: Vec::new(),
+            full_diff: false,
+            scroll: 0,
+            confirm_restore: false,
+        }
+    }
+
+    pub fn open(&mut self, session: String, checkpoints: Vec<Checkpoint>) {
+        self.session = session;
+        self.checkpoints = checkpoints;
+        self.state.select(Some(0));
+        self.full_diff = false;
+        self.confirm_restore = false;
+        self.set_diff(Vec::new());
+    }
+
+    pub fn selected(&self) -> Option<&Checkpoint> {
+        self.checkpoints.get(self.state.selected()?)
+    }
+
+    pub fn move_up(&mut self) {
+        let i = self.state.selected().unwrap_or(0).saturating_sub(1);
+        self.state.select(Some(i));
+    }
+
+    pub fn move_down(&mut self) {
+        let last = self.checkpoints.len().saturating_sub(1);
+        let i = (self.state.selected().unwrap_or(0) + 1).min(last);
+        self.state.select(Some(i));
+    }
+
+    pub fn full_diff(&self) -> bool {
+        self.full_diff
+    }
+
+    pub fn toggle_full_diff(&mut self) {
+        self.full_diff = !self.full_diff;
+    }
+
+    pub fn set_diff(&mut self, diff: Vec<String>) {
+        self.diff = diff;
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self, n: usize) {
+        self.scroll = self.scroll.saturating_sub(n);
+    }
+
+    pub fn scroll_down(&mut self, n: usize) {
+        self.scroll = (self.scroll + n).min(self.diff.len().saturating_sub(1));
+    }
+
+    pub fn confirming_restore(&self) -> bool {
+        self.confirm_restore
+    }
+
+    pub fn set_confirm_restore(&mut self, confirm: bool) {
+        self.confirm_restore = confirm;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let popup_area = Rect::new(
+            area.x + 2,
+            area.y + 1,
+            area.width.saturating_sub(4),
+            area.height.saturating_sub(2),
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let footer = if self.confirm_restore {
+            " restore this checkpoint? the current state is checkpointed first  y/n ".to_string()
+        } else {
+            format!(
+                " up/down: select  d: {}  pgup/pgdn: scroll  r: restore  esc: close ",
+                if self.full_diff { "stat" } else { "full diff" }
+            )
+        };
+        let block = Block::default()
+            .title(format!(" Checkpoints: {} ", self.session))
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if self.confirm_restore {
+                Color::Yellow
+            } else {
+                Color::White
+            }))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let list_height = (self.checkpoints.len() as u16).clamp(1, inner.height / 3);
+        let items: Vec<ListItem> = self
+            .checkpoints
+            .iter()
+            .map(|checkpoint| {
+                let time = chrono::DateTime::from_timestamp(checkpoint.time, 0)
+                    .map(|t| {
+                        t.with_timezone(&chrono::Local)
+                            .format("%m-%d %H:%M:%S")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", checkpoint.short()),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(format!("{}  ", time), Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        checkpoint.message.clone(),
+                        Style::default().fg(Color::White),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(
+            list,
+            Rect::new(inner.x, inner.y, inner.width, list_height),
+            &mut self.state,
+        );
+
+        let separator = Line::styled(
+            "─".repeat(inner.width as usize),
+            Style::default().fg(Color::DarkGray),
+        );
+        frame.render_widget(
+            Paragraph::new(separator),
+            Rect::new(inner.x, inner.y + list_height, inner.width, 1),
+        );
+
+        let diff_area = Rect::new(
+            inner.x,
+            inner.y + list_height + 1,
+            inner.width,
+            inner.height.saturating_sub(list_height + 1),
+        );
+        if self.diff.is_empty() {
+            let empty = Paragraph::new("No changes since this checkpoint")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, diff_area);
+            return;
+        }
+        let lines: Vec<Line> = self
+            .diff
+            .iter()
+            .skip(self.scroll)
+            .take(diff_area.height as usize)
+            .map(|line| {
+                let color = match line.chars().next() {
+                    Some('+') if !line.starts_with("+++") => Color::Green,
+                    Some('-') if !line.starts_with("---") => Color::Red,
+                    Some('@') => Color::Cyan,
+                    _ => Color::Gray,
+                };
+                Line::styled(line.clone(), Style::default().fg(color))
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), diff_area);
+    }
+}
+
+impl Default for CheckpointBrowser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,204 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::session_manager::actions::{Action, ActionInfo};
+
+/// Fuzzy-searchable list of every action available right now; Enter runs the highlighted one
+pub struct CommandPalette {
+    query: String,
+    /// Actions offered when the palette was opened
+    actions: Vec<&'static ActionInfo>,
+    /// Indices into `actions` matching the query, best match first
+    matches: Vec<usize>,
+    state: ListState,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            actions: Vec::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+        }
+    }
+
+    /// Start a fresh search over `actions`
+    pub fn open(&mut self, actions: Vec<&'static ActionInfo>) {
+        self.actions = actions;
+        self.query.clear();
+        self.update_matches();
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current == 0 {
+            self.matches.len() - 1
+        } else {
+            current - 1
+        };
+        self.state.select(Some(next));
+    }
+
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        self.state.select(Some((current + 1) % self.matches.len()));
+    }
+
+    /// The highlighted action, if anything matches
+    pub fn selected(&self) -> Option<Action> {
+        let index = self.matches.get(self.state.selected()?)?;
+        Some(self.actions[*index].action)
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i32, usize)> = self
+            .actions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, info)| {
+                let score = fuzzy_score(&self.query, info.description)
+                    .max(fuzzy_score(&self.query, info.key))?;
+                Some((score, i))
+            })
+            .collect();
+        // Stable sort keeps registry order among equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let popup_width = 56.min(area.width.saturating_sub(4));
+        let list_height = self.actions.len().clamp(1, 12) as u16;
+        let popup_height = (list_height + 2 + 2).min(area.height.saturating_sub(2));
+
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 3;
+        let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Commands ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let query_line = Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Magenta)),
+            Span::raw(&self.query),
+            Span::styled("_", Style::default().fg(Color::Magenta)),
+        ]);
+        frame.render_widget(
+            Paragraph::new(query_line),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y + 2,
+            inner.width,
+            inner.height.saturating_sub(2),
+        );
+        if self.matches.is_empty() {
+            let empty =
+                Paragraph::new("No matching commands").style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, list_area);
+            return;
+        }
+
+        // Right-align the hotkey hints
+        let width = list_area.width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|&i| {
+                let info = self.actions[i];
+                let gap = width.saturating_sub(info.description.len() + info.key.len());
+                Line::from(vec![
+                    Span::styled(info.description, Style::default().fg(Color::White)),
+                    Span::raw(" ".repeat(gap)),
+                    Span::styled(info.key, Style::default().fg(Color::DarkGray)),
+                ])
+            })
+            .map(ListItem::new)
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Score `text` against `query` when every query character appears in it in order,
+/// ignoring case. Consecutive matches and matches at word starts score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("ns", "New session").is_some());
+        assert!(fuzzy_score("nx", "New session").is_none());
+        assert_eq!(fuzzy_score("", "Quit"), Some(0));
+        // Word starts and runs beat scattered letters
+        assert!(fuzzy_score("kill", "Kill session") > fuzzy_score("kill", "Toggle skill list"));
+        assert!(fuzzy_score("ts", "Toggle shell") > fuzzy_score("ts", "List sessions"));
+    }
+}
@@ -0,0 +1,93 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+/// Multi-line prompt editor used when no $EDITOR is set. Enter starts a new line;
+/// Ctrl+S sends the text to the session.
+pub struct ComposeDialog {
+    text: String,
+}
+
+impl ComposeDialog {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, target: &str) {
+        let popup_width = 72u16.min(area.width.saturating_sub(4));
+        let popup_height = 16u16.min(area.height.saturating_sub(2));
+
+        let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+        let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" Compose for {} ", target))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let mut lines: Vec<Line> = self.text.split('\n').map(Line::raw).collect();
+        if let Some(last) = lines.last_mut() {
+            last.push_span(Span::styled("_", Style::default().fg(Color::Magenta)));
+        }
+        // Keep the end of the text, where typing happens, in view
+        let text_height = inner.height.saturating_sub(2);
+        let overflow = lines.len().saturating_sub(text_height as usize);
+        let text = Paragraph::new(lines.split_off(overflow)).wrap(Wrap { trim: false });
+        frame.render_widget(text, Rect::new(inner.x, inner.y, inner.width, text_height));
+
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled("Ctrl+S", key_style),
+            Span::raw(": send  "),
+            Span::styled("Enter", key_style),
+            Span::raw(": new line  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": cancel"),
+        ]));
+        frame.render_widget(
+            footer,
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+    }
+}
+
+impl Default for ComposeDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
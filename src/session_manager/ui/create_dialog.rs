@@ -3,39 +3,165 @@ use ratatui::{
     layout::Rect,
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
+use crate::workflows::{FORBIDDEN_CHARS, branch_name_from_prompt, resolve_branch_name};
+
+/// Something that already uses the name typed into the dialog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameCollision {
+    /// A live session with this name
+    Live,
+    /// A live session of another repo with this name; names key live sessions, so it
+    /// can't be reused here. Holds that repo's name.
+    LiveElsewhere(String),
+    /// A session in history; holds its display path for resuming
+    Recent(String),
+    /// A worktree directory with no session; holds its display path
+    Worktree(String),
+}
+
 pub struct CreateDialog {
     input: String,
+    /// Optional task prompt handed to claude on start; names the session when the name
+    /// is left blank
+    prompt: String,
+    /// Whether typing goes to the prompt field rather than the name
+    prompt_focused: bool,
+    /// Collision found for `.0` on the last Enter; a second Enter acts on it
+    collision: Option<(String, NameCollision)>,
+    /// Whether the name becomes a git branch (only the worktree workflow makes one)
+    creates_branch: bool,
+    /// Names of the workflows the session can go through, cycled with Ctrl+W
+    workflows: Vec<&'static str>,
+    workflow: usize,
 }
 
 impl CreateDialog {
     pub fn new() -> Self {
         Self {
             input: String::new(),
+            prompt: String::new(),
+            prompt_focused: false,
+            collision: None,
+            creates_branch: true,
+            workflows: Vec::new(),
+            workflow: 0,
         }
     }
 
+    /// Offer `workflows`, starting on the one at `selected`
+    pub fn set_workflows(&mut self, workflows: Vec<&'static str>, selected: usize) {
+        self.workflows = workflows;
+        self.select_workflow(selected);
+    }
+
+    /// Switch to the next workflow (Ctrl+W)
+    pub fn cycle_workflow(&mut self) {
+        if !self.workflows.is_empty() {
+            self.select_workflow((self.workflow + 1) % self.workflows.len());
+        }
+    }
+
+    fn select_workflow(&mut self, index: usize) {
+        self.workflow = index;
+        self.creates_branch = self.workflows.get(index) == Some(&"worktree");
+        self.collision = None;
+    }
+
+    /// Index of the chosen workflow in the list given to `set_workflows`
+    pub fn workflow(&self) -> usize {
+        self.workflow
+    }
+
     pub fn clear(&mut self) {
         self.input.clear();
+        self.prompt.clear();
+        self.prompt_focused = false;
+        self.collision = None;
     }
 
+    /// Append a typed character to the focused field. Characters git never allows
+    /// in a branch name are dropped from the name.
     pub fn push(&mut self, c: char) {
-        self.input.push(c);
+        if self.prompt_focused {
+            self.prompt.push(c);
+        } else if !FORBIDDEN_CHARS.contains(&c) {
+            self.input.push(c);
+            self.collision = None;
+        }
     }
 
     pub fn pop(&mut self) -> Option<char> {
+        if self.prompt_focused {
+            return self.prompt.pop();
+        }
+        self.collision = None;
         self.input.pop()
     }
 
-    pub fn take_input(&mut self) -> String {
-        std::mem::take(&mut self.input)
+    /// Move typing to the other field (Tab)
+    pub fn toggle_field(&mut self) {
+        self.prompt_focused = !self.prompt_focused;
+    }
+
+    pub fn focus_prompt(&mut self) {
+        self.prompt_focused = true;
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
-        let popup_width = 40u16;
-        let popup_height = 5u16;
+    pub fn prompt_focused(&self) -> bool {
+        self.prompt_focused
+    }
+
+    /// The task prompt, trimmed; empty when none was typed
+    pub fn prompt(&self) -> &str {
+        self.prompt.trim()
+    }
+
+    /// Show that `name` is already taken; the next Enter confirms acting on it.
+    pub fn set_collision(&mut self, name: String, collision: NameCollision) {
+        self.collision = Some((name, collision));
+    }
+
+    /// Forget a collision warning, e.g. after switching to another repo
+    pub fn clear_collision(&mut self) {
+        self.collision = None;
+    }
+
+    /// The collision the user has been warned about, if it still applies to `name` and
+    /// there is something to do about it
+    pub fn confirmed_collision(&self, name: &str) -> Option<&NameCollision> {
+        self.collision
+            .as_ref()
+            .filter(|(n, c)| n == name && !matches!(c, NameCollision::LiveElsewhere(_)))
+            .map(|(_, c)| c)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input.trim().is_empty()
+    }
+
+    /// The branch/session name the input resolves to: the input itself if git
+    /// accepts it, otherwise its slugified form. Errors if nothing usable remains.
+    pub fn resolved_name(&self) -> Result<String, String> {
+        resolve_branch_name(&self.input)
+    }
+
+    /// `target_repo` is shown (with Ctrl+R to change it) when there are several repos to
+    /// choose from
+    pub fn render(&self, frame: &mut Frame, area: Rect, target_repo: Option<&str>) {
+        let popup_width = 60u16.min(area.width.saturating_sub(4));
+        let mut popup_height = 10u16;
+        if target_repo.is_some() {
+            popup_height += 1;
+        }
+        if self.workflows.len() > 1 {
+            popup_height += 1;
+        }
+        if self.collision.is_some() {
+            popup_height += 1;
+        }
 
         let popup_x = (area.width.saturating_sub(popup_width)) / 2;
         let popup_y = (area.height.saturating_sub(popup_height)) / 2;
@@ -52,21 +178,122 @@ impl CreateDialog {
         let inner = block.inner(popup_area);
         frame.render_widget(block, popup_area);
 
-        let display_text = if self.input.is_empty() {
-            Line::from(vec![
-                Span::styled("Name: ", Style::default().fg(Color::Gray)),
-                Span::styled("_", Style::default().fg(Color::Magenta)),
-            ])
+        let field = |label: &'static str, value: &str, focused: bool| {
+            let label_style = if focused {
+                Style::default().fg(Color::Magenta)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let mut spans = vec![
+                Span::styled(label, label_style),
+                Span::raw(value.to_string()),
+            ];
+            if focused {
+                spans.push(Span::styled("_", Style::default().fg(Color::Magenta)));
+            }
+            Line::from(spans)
+        };
+        let name_line = field("Name:   ", &self.input, !self.prompt_focused);
+        let prompt_line = field("Prompt: ", &self.prompt, self.prompt_focused);
+
+        // Live preview of the branch that will be created
+        let label = if self.creates_branch {
+            "Branch: "
+        } else {
+            "Session: "
+        };
+        let branch_line = if self.is_empty() {
+            let derived = branch_name_from_prompt(self.prompt());
+            if derived.is_empty() {
+                Line::from(Span::styled(
+                    format!("{}(auto)", label),
+                    Style::default().fg(Color::DarkGray),
+                ))
+            } else {
+                Line::from(vec![
+                    Span::styled(label, Style::default().fg(Color::Gray)),
+                    Span::styled(derived, Style::default().fg(Color::Green)),
+                    Span::styled(" (from prompt)", Style::default().fg(Color::DarkGray)),
+                ])
+            }
         } else {
-            Line::from(vec![
-                Span::styled("Name: ", Style::default().fg(Color::Gray)),
-                Span::raw(&self.input),
-                Span::styled("_", Style::default().fg(Color::Magenta)),
-            ])
+            match self.resolved_name() {
+                Ok(name) if name == self.input.trim() => Line::from(vec![
+                    Span::styled(label, Style::default().fg(Color::Gray)),
+                    Span::styled(name, Style::default().fg(Color::Green)),
+                ]),
+                Ok(slug) => Line::from(vec![
+                    Span::styled(label, Style::default().fg(Color::Gray)),
+                    Span::styled(slug, Style::default().fg(Color::Yellow)),
+                    Span::styled(" (adjusted)", Style::default().fg(Color::DarkGray)),
+                ]),
+                Err(reason) => Line::from(Span::styled(
+                    format!("Invalid: {}", reason),
+                    Style::default().fg(Color::Red),
+                )),
+            }
         };
 
-        let paragraph = Paragraph::new(display_text);
-        frame.render_widget(paragraph, inner);
+        let mut lines = vec![branch_line];
+        if let Some(repo) = target_repo {
+            lines.push(Line::from(vec![
+                Span::styled("Repo: ", Style::default().fg(Color::Gray)),
+                Span::styled(repo.to_string(), Style::default().fg(Color::Cyan)),
+                Span::styled(" (ctrl+r to change)", Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+        if self.workflows.len() > 1 {
+            lines.push(Line::from(vec![
+                Span::styled("Workflow: ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    self.workflows[self.workflow],
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::styled(" (ctrl+w to change)", Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+        if let Some((ref name, ref collision)) = self.collision {
+            let message = match collision {
+                NameCollision::Live => format!("'{}' is running - Enter to attach", name),
+                NameCollision::LiveElsewhere(repo) => {
+                    format!("'{}' is running in {} - pick another name", name, repo)
+                }
+                NameCollision::Recent(_) => format!("'{}' exists - Enter to resume", name),
+                NameCollision::Worktree(_) => format!("Worktree '{}' exists - Enter to open", name),
+            };
+            lines.push(Line::from(Span::styled(
+                message,
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+        lines.push(Line::from(Span::styled(
+            "Tab: switch field  Enter: create  Ctrl+P: preview  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )));
+
+        // Name on top, the prompt wrapping below it, details pinned to the bottom
+        let details_height = lines.len() as u16;
+        frame.render_widget(
+            Paragraph::new(name_line),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+        let prompt_area = Rect::new(
+            inner.x,
+            inner.y + 2,
+            inner.width,
+            inner.height.saturating_sub(3 + details_height),
+        );
+        frame.render_widget(
+            Paragraph::new(prompt_line).wrap(Wrap { trim: false }),
+            prompt_area,
+        );
+        let details_area = Rect::new(
+            inner.x,
+            inner.y + inner.height.saturating_sub(details_height),
+            inner.width,
+            details_height.min(inner.height),
+        );
+        frame.render_widget(Paragraph::new(lines), details_area);
     }
 }
 
@@ -0,0 +1,122 @@
+use std::time::Instant;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Steps of a session being created on a worker thread, the last one still running
+pub struct CreationProgress {
+    name: String,
+    workflow: &'static str,
+    steps: Vec<String>,
+    started: Instant,
+    cancelling: bool,
+}
+
+impl CreationProgress {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            workflow: "",
+            steps: Vec::new(),
+            started: Instant::now(),
+            cancelling: false,
+        }
+    }
+
+    pub fn open(&mut self, name: String, workflow: &'static str) {
+        self.name = name;
+        self.workflow = workflow;
+        self.steps.clear();
+        self.started = Instant::now();
+        self.cancelling = false;
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn push_step(&mut self, step: String) {
+        self.steps.push(step);
+    }
+
+    pub fn set_cancelling(&mut self) {
+        self.cancelling = true;
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_width = 72u16.min(area.width.saturating_sub(4));
+        let popup_height = (self.steps.len() as u16 + 5).clamp(6, area.height.saturating_sub(2));
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(popup_width)) / 2,
+            area.y + (area.height.saturating_sub(popup_height)) / 2,
+            popup_width,
+            popup_height,
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let last = self.steps.len().saturating_sub(1);
+        let mut lines: Vec<Line> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                if i < last {
+                    Line::from(vec![
+                        Span::styled("✓ ", Style::default().fg(Color::Green)),
+                        Span::styled(step.clone(), Style::default().fg(Color::Gray)),
+                    ])
+                } else {
+                    Line::from(vec![
+                        Span::styled("… ", Style::default().fg(Color::Yellow)),
+                        Span::styled(step.clone(), Style::default().fg(Color::White)),
+                    ])
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                "Starting...",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::default());
+        let key = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        lines.push(if self.cancelling {
+            Line::styled("Cancelling...", Style::default().fg(Color::Yellow))
+        } else {
+            Line::from(vec![
+                Span::styled("Esc", key),
+                Span::raw(": cancel  "),
+                Span::styled("Enter", key),
+                Span::raw(": hide"),
+            ])
+        });
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(
+                    " Creating {} ({}) {}s ",
+                    self.name,
+                    self.workflow,
+                    self.started.elapsed().as_secs()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .style(Style::default().bg(Color::Black)),
+        );
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for CreationProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear},
+};
+
+use crate::session_manager::session_pair::SessionActivity;
+
+/// Tiled view of several live sessions at once, opened from the selector's marked entries
+pub struct Dashboard {
+    sessions: Vec<String>,
+    focused: usize,
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self {
+            sessions: Vec::new(),
+            focused: 0,
+        }
+    }
+
+    /// Show `sessions`, focusing the first
+    pub fn set_sessions(&mut self, sessions: Vec<String>) {
+        self.sessions = sessions;
+        self.focused = 0;
+    }
+
+    pub fn sessions(&self) -> &[String] {
+        &self.sessions
+    }
+
+    /// Drop sessions that are no longer live
+    pub fn retain(&mut self, is_live: impl Fn(&str) -> bool) {
+        self.sessions.retain(|name| is_live(name));
+        self.focused = self.focused.min(self.sessions.len().saturating_sub(1));
+    }
+
+    pub fn focused_session(&self) -> Option<&str> {
+        self.sessions.get(self.focused).map(String::as_str)
+    }
+
+    /// Focus the tile at `index`, if there is one
+    pub fn focus(&mut self, index: usize) -> bool {
+        if index < self.sessions.len() {
+            self.focused = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move focus by `delta` tiles, wrapping around
+    pub fn move_focus(&mut self, delta: isize) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        let len = self.sessions.len() as isize;
+        self.focused = (self.focused as isize + delta).rem_euclid(len) as usize;
+    }
+
+    /// Tiles per row for the current number of sessions
+    pub fn columns(&self) -> usize {
+        (1..)
+            .find(|cols| cols * cols >= self.sessions.len())
+            .unwrap_or(1)
+    }
+
+    /// Draw one framed tile per session in a grid over `area`. Returns the area inside
+    /// each tile, in session order, for the caller to fill with the session's screen.
+    pub fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        session_slots: &HashMap<String, usize>,
+        session_states: &HashMap<String, SessionActivity>,
+        session_progress: &HashMap<String, String>,
+    ) -> Vec<Rect> {
+        frame.render_widget(Clear, area);
+        if self.sessions.is_empty() {
+            return Vec::new();
+        }
+
+        let cols = self.columns();
+        let rows = self.sessions.len().div_ceil(cols);
+        let row_areas = Layout::vertical(vec![Constraint::Ratio(1, rows as u32); rows]).split(area);
+
+        let mut inners = Vec::with_capacity(self.sessions.len());
+        for (i, name) in self.sessions.iter().enumerate() {
+            let row_area = row_areas[i / cols];
+            let col_areas =
+                Layout::horizontal(vec![Constraint::Ratio(1, cols as u32); cols]).split(row_area);
+            let tile = col_areas[i % cols];
+
+            let focused = i == self.focused;
+            let border_color = if focused {
+                Color::Magenta
+            } else {
+                Color::DarkGray
+            };
+            let (indicator, indicator_color) = match session_states.get(name) {
+                Some(SessionActivity::Stopped) => ("● ", Color::Yellow),
+                Some(SessionActivity::RunningTool(_)) => ("● ", Color::Cyan),
+                Some(SessionActivity::Paused) => ("‖ ", Color::Blue),
+                Some(SessionActivity::Flagged(_)) => ("! ", Color::Red),
+                _ => ("● ", Color::Magenta),
+            };
+            let mut title = vec![Span::raw(" ")];
+            if let Some(slot) = session_slots.get(name) {
+                title.push(Span::styled(
+                    format!("{} ", slot),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            title.push(Span::styled(
+                indicator,
+                Style::default().fg(indicator_color),
+            ));
+            let name_style = if focused {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            title.push(Span::styled(format!("{} ", name), name_style));
+
+            let mut block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .title(Line::from(title));
+            if let Some(progress) = session_progress.get(name) {
+                block = block.title_bottom(Line::from(Span::styled(
+                    format!(" {} ", progress),
+                    Style::default().fg(Color::Gray),
+                )));
+            }
+            inners.push(block.inner(tile));
+            frame.render_widget(block, tile);
+        }
+        inners
+    }
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
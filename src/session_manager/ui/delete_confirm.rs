@@ -38,7 +38,8 @@ impl DeleteConfirmDialog {
         &self.active_paths
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
+    /// `to_trash`: deleted worktrees go to the trash rather than being removed
+    pub fn render(&self, frame: &mut Frame, area: Rect, to_trash: bool) {
         let count = self.worktrees.len();
         let active_count = self
             .worktrees
@@ -46,16 +47,23 @@ impl DeleteConfirmDialog {
             .filter(|p| self.active_paths.contains(*p))
             .count();
 
-        let mut lines = vec![Line::from(vec![
-            Span::styled(
-                "WARNING: ",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                "This action cannot be undone!",
-                Style::default().fg(Color::Red),
-            ),
-        ])];
+        let mut lines = if to_trash {
+            vec![Line::from(Span::styled(
+                "Worktrees are moved to ~/.shepherd/trash (alt+u to undo)",
+                Style::default().fg(Color::DarkGray),
+            ))]
+        } else {
+            vec![Line::from(vec![
+                Span::styled(
+                    "WARNING: ",
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    "This action cannot be undone!",
+                    Style::default().fg(Color::Red),
+                ),
+            ])]
+        };
 
         // Show active session warning if any
         if active_count > 0 {
@@ -123,7 +131,20 @@ impl DeleteConfirmDialog {
                     .fg(Color::Magenta)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw(" - Yes, delete permanently"),
+            Span::raw(if to_trash {
+                " - Yes, move to trash"
+            } else {
+                " - Yes, delete permanently"
+            }),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled(
+                "a",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" - Archive changes to ~/.shepherd/archives, then delete"),
         ]));
         lines.push(Line::from(vec![
             Span::styled(
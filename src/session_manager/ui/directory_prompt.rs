@@ -0,0 +1,102 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Asks where sessions should run when shepherd was started outside a git repository
+pub struct DirectoryPrompt {
+    input: String,
+    /// Why the last Enter was rejected
+    error: Option<String>,
+}
+
+impl DirectoryPrompt {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    /// Start over with `dir` as the suggested answer
+    pub fn reset(&mut self, dir: &str) {
+        self.input = dir.to_string();
+        self.error = None;
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.error = None;
+    }
+
+    pub fn pop(&mut self) {
+        self.input.pop();
+        self.error = None;
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.trim()
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_width = 64u16.min(area.width.saturating_sub(4));
+        let popup_height = 8u16;
+
+        let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+        let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "Not in a git repository. Where should sessions run?",
+                Style::default().fg(Color::Gray),
+            )),
+            Line::default(),
+            Line::from(vec![
+                Span::styled("Directory: ", Style::default().fg(Color::Gray)),
+                Span::raw(&self.input),
+                Span::styled("_", Style::default().fg(Color::Magenta)),
+            ]),
+        ];
+        match self.error {
+            Some(ref error) => lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(Color::Red),
+            ))),
+            None => lines.push(Line::default()),
+        }
+        lines.push(Line::from(vec![
+            Span::styled("Enter", key_style),
+            Span::raw(": use directory  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": cancel"),
+        ]));
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Session Directory ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .style(Style::default().bg(Color::Black)),
+        );
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for DirectoryPrompt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
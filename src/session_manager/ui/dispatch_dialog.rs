@@ -0,0 +1,141 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::session_manager::dispatcher::{Task, TaskState};
+
+/// Task list for the dispatcher: the tasks handed out so far with where each went, and an
+/// editor for queueing more, one task per line.
+pub struct DispatchDialog {
+    text: String,
+}
+
+impl DispatchDialog {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, tasks: &[Task], max_sessions: usize) {
+        let popup_width = 80u16.min(area.width.saturating_sub(4));
+        let popup_height = 24u16.min(area.height.saturating_sub(2));
+
+        let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+        let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" Dispatch (up to {} sessions) ", max_sessions))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        // Tasks on top, the editor below a separator, keys on the last line
+        let list_height = inner.height.saturating_sub(2) / 2;
+        let width = inner.width as usize;
+        let mut task_lines: Vec<Line> = tasks
+            .iter()
+            .map(|task| {
+                let (marker, color, session) = match &task.state {
+                    TaskState::Queued => ("·", Color::DarkGray, String::new()),
+                    TaskState::Running(s) => ("…", Color::Yellow, format!(" → {}", s)),
+                    TaskState::Done(s) => ("✓", Color::Green, format!(" ✓ {}", s)),
+                };
+                let text: String = task
+                    .text
+                    .chars()
+                    .take(width.saturating_sub(session.chars().count() + 2))
+                    .collect();
+                Line::from(vec![
+                    Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                    Span::styled(text, Style::default().fg(Color::White)),
+                    Span::styled(session, Style::default().fg(color)),
+                ])
+            })
+            .collect();
+        if task_lines.is_empty() {
+            task_lines.push(Line::styled(
+                "No tasks yet. Type one per line below.",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        // Keep the newest tasks in view
+        let overflow = task_lines.len().saturating_sub(list_height as usize);
+        frame.render_widget(
+            Paragraph::new(task_lines.split_off(overflow)),
+            Rect::new(inner.x, inner.y, inner.width, list_height),
+        );
+
+        let separator = Line::styled("─".repeat(width), Style::default().fg(Color::DarkGray));
+        frame.render_widget(
+            Paragraph::new(separator),
+            Rect::new(inner.x, inner.y + list_height, inner.width, 1),
+        );
+
+        let mut lines: Vec<Line> = self.text.split('\n').map(Line::raw).collect();
+        if let Some(last) = lines.last_mut() {
+            last.push_span(Span::styled("_", Style::default().fg(Color::Magenta)));
+        }
+        let text_height = inner.height.saturating_sub(list_height + 2);
+        let overflow = lines.len().saturating_sub(text_height as usize);
+        let text = Paragraph::new(lines.split_off(overflow)).wrap(Wrap { trim: false });
+        frame.render_widget(
+            text,
+            Rect::new(inner.x, inner.y + list_height + 1, inner.width, text_height),
+        );
+
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled("Ctrl+S", key_style),
+            Span::raw(": queue  "),
+            Span::styled("Enter", key_style),
+            Span::raw(": next task  "),
+            Span::styled("Ctrl+X", key_style),
+            Span::raw(": clear done  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": close"),
+        ]));
+        frame.render_widget(
+            footer,
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+    }
+}
+
+impl Default for DispatchDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+use crate::config::EventLogConfig;
+use crate::rotating_file::{RotatingFile, rotated_path};
+
+use super::StatusMessage;
+
+/// Location of the persistent event log (~/.shepherd/events.log)
+pub fn event_log_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".shepherd").join("events.log"))
+}
+
+/// Read the whole event log, oldest first: rotated files followed by the live one
+pub fn read_event_log(path: &Path) -> String {
+    let mut rotated = Vec::new();
+    for n in 1.. {
+        let Ok(file) = File::open(rotated_path(path, n, true)) else {
+            break;
+        };
+        let mut contents = String::new();
+        let _ = GzDecoder::new(file).read_to_string(&mut contents);
+        rotated.push(contents);
+    }
+
+    let mut log: String = rotated.into_iter().rev().collect();
+    log.push_str(&std::fs::read_to_string(path).unwrap_or_default());
+    log
+}
+
+/// Append one entry straight to the live log, bypassing the status bar (e.g. crash reports)
+pub fn append_event(msg: &StatusMessage) {
+    EventLog::new(EventLogConfig::default()).append(msg);
+}
+
+/// Append-only log of every status message, rotated into gzipped files by size
+pub(super) struct EventLog {
+    path: Option<PathBuf>,
+    config: EventLogConfig,
+    /// Opened on the first entry
+    file: Option<RotatingFile>,
+}
+
+impl EventLog {
+    pub(super) fn new(config: EventLogConfig) -> Self {
+        Self::at(event_log_path(), config)
+    }
+
+    pub(super) fn at(path: Option<PathBuf>, config: EventLogConfig) -> Self {
+        Self {
+            path,
+            config,
+            file: None,
+        }
+    }
+
+    pub(super) fn append(&mut self, msg: &StatusMessage) {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let entry = format!(
+            "[{}] [{}] {}\n",
+            timestamp,
+            msg.level.log_tag(),
+            msg.log_message
+        );
+        self.write_entry(&entry);
+    }
+
+    fn write_entry(&mut self, entry: &str) {
+        if self.file.is_none()
+            && let Some(path) = self.path.clone()
+        {
+            let max_bytes = self.config.max_size_kb * 1024;
+            self.file = RotatingFile::open(path, max_bytes, self.config.keep_rotated)
+                .ok()
+                .map(RotatingFile::compressed);
+        }
+        if let Some(ref mut file) = self.file {
+            let _ = file.write(entry.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_rotation() {
+        let dir = TempDir::new("events");
+        let path = dir.join("events.log");
+
+        let config = EventLogConfig {
+            max_size_kb: 1,
+            keep_rotated: 2,
+        };
+        let mut log = EventLog::at(Some(path.clone()), config);
+        let line = format!("{}\n", "x".repeat(99));
+        for i in 0..40 {
+            log.write_entry(&format!("{:03}{}", i, line));
+        }
+
+        assert!(rotated_path(&path, 1, true).exists());
+        assert!(rotated_path(&path, 2, true).exists());
+        assert!(!rotated_path(&path, 3, true).exists());
+        assert!(std::fs::metadata(&path).unwrap().len() <= 1024);
+
+        // Everything still on disk reads back in order, ending with the newest entry
+        let all = read_event_log(&path);
+        let ids: Vec<usize> = all.lines().map(|l| l[..3].parse().unwrap()).collect();
+        assert!(ids.windows(2).all(|w| w[1] == w[0] + 1));
+        assert_eq!(ids.last(), Some(&39));
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// What the git history popup shows for its file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryView {
+    Log,
+    Blame,
+}
+
+/// Scrollable `git log` or `git blame` output for one file of a session's worktree
+pub struct GitHistory {
+    /// File relative to the worktree
+    file: PathBuf,
+    view: HistoryView,
+    lines: Vec<String>,
+    /// 1-based line to highlight in blame, the one referenced on screen
+    line: Option<usize>,
+    scroll: usize,
+}
+
+impl GitHistory {
+    pub fn new() -> Self {
+        Self {
+            file: PathBuf::new(),
+            view: HistoryView::Log,
+            lines: Vec::new(),
+            line: None,
+            scroll: 0,
+        }
+    }
+
+    /// Show `lines` for `file`. Blame starts a few lines above `line`.
+    pub fn open(
+        &mut self,
+        file: PathBuf,
+        view: HistoryView,
+        lines: Vec<String>,
+        line: Option<usize>,
+    ) {
+        self.scroll = match view {
+            HistoryView::Log => 0,
+            HistoryView::Blame => line.map_or(0, |l| l.saturating_sub(5)),
+        };
+        self.file = file;
+        self.view = view;
+        self.lines = lines;
+        self.line = line;
+    }
+
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    pub fn view(&self) -> HistoryView {
+        self.view
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn scroll_up(&mut self, n: usize) {
+        self.scroll = self.scroll.saturating_sub(n);
+    }
+
+    pub fn scroll_down(&mut self, n: usize) {
+        self.scroll = (self.scroll + n).min(self.lines.len().saturating_sub(1));
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = self.lines.len().saturating_sub(1);
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_area = Rect::new(
+            area.x + 2,
+            area.y + 1,
+            area.width.saturating_sub(4),
+            area.height.saturating_sub(2),
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let (title, other) = match self.view {
+            HistoryView::Log => ("git log", "blame"),
+            HistoryView::Blame => ("git blame", "log"),
+        };
+        let block = Block::default()
+            .title(format!(" {}: {} ", title, self.file.display()))
+            .title_bottom(format!(" up/down: scroll  tab: {}  esc: close ", other))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.lines.is_empty() {
+            let empty = Paragraph::new("No history for this file")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, inner);
+            return;
+        }
+
+        let highlight = match self.view {
+            HistoryView::Blame => self.line.map(|l| l.saturating_sub(1)),
+            HistoryView::Log => None,
+        };
+        let visible: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(inner.height as usize)
+            .map(|(i, line)| {
+                let style = if Some(i) == highlight {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::Gray)
+                };
+                Line::styled(line.clone(), style)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(visible), inner);
+    }
+}
+
+impl Default for GitHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
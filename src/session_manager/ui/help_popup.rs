@@ -6,58 +6,80 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
-pub struct HelpPopup;
+use crate::session_manager::actions::help_sections;
+
+/// Every key binding, grouped by where it applies. Scrolls when taller than the screen.
+pub struct HelpPopup {
+    scroll: u16,
+}
 
 impl HelpPopup {
     pub fn new() -> Self {
-        Self
+        Self { scroll: 0 }
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
-        let hotkeys = [
-            ("ctrl+h", "Help"),
-            ("ctrl+t", "Toggle shell"),
-            ("ctrl+n", "New session"),
-            ("ctrl+l", "List sessions"),
-            ("ctrl+k", "Cleanup worktrees"),
-            ("ctrl+x", "Kill session"),
-            ("ctrl+d", "Quit"),
-        ];
+    /// Back to the top for a fresh view
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+    }
 
-        let content_width = hotkeys
+    /// Scroll by `delta` lines; clamped to the content when rendered
+    pub fn scroll_by(&mut self, delta: i16) {
+        self.scroll = self.scroll.saturating_add_signed(delta);
+    }
+
+    /// `git`: include the hotkeys that only work inside a git repository
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, git: bool) {
+        let sections = help_sections(git);
+        let key_width = sections
             .iter()
-            .map(|(k, v)| k.len() + 3 + v.len())
+            .flat_map(|(_, bindings)| bindings.iter().map(|(key, _)| key.len()))
             .max()
-            .unwrap_or(20);
+            .unwrap_or(0);
 
+        let heading_style = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let mut lines: Vec<Line> = Vec::new();
+        for (context, bindings) in &sections {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(Span::styled(context.title(), heading_style)));
+            for (key, desc) in bindings {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:<width$}", key, width = key_width), key_style),
+                    Span::raw(" - "),
+                    Span::raw(*desc),
+                ]));
+            }
+        }
+
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(20);
         let popup_width = (content_width as u16 + 4).min(area.width.saturating_sub(4));
-        let popup_height = (hotkeys.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let popup_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
 
         let popup_x = (area.width.saturating_sub(popup_width)) / 2;
         let popup_y = (area.height.saturating_sub(popup_height)) / 2;
         let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
 
-        frame.render_widget(Clear, popup_area);
+        let visible = popup_height.saturating_sub(2);
+        let max_scroll = (lines.len() as u16).saturating_sub(visible);
+        self.scroll = self.scroll.min(max_scroll);
+        let title = if max_scroll > 0 {
+            " Help (up/down to scroll) "
+        } else {
+            " Help "
+        };
 
-        let lines: Vec<Line> = hotkeys
-            .iter()
-            .map(|(key, desc)| {
-                Line::from(vec![
-                    Span::styled(
-                        *key,
-                        Style::default()
-                            .fg(Color::Magenta)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" - "),
-                    Span::raw(*desc),
-                ])
-            })
-            .collect();
+        frame.render_widget(Clear, popup_area);
 
-        let paragraph = Paragraph::new(lines).block(
+        let paragraph = Paragraph::new(lines).scroll((self.scroll, 0)).block(
             Block::default()
-                .title(" Help ")
+                .title(title)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::White))
                 .style(Style::default().bg(Color::Black)),
@@ -7,23 +7,36 @@ use ratatui::{
 };
 
 pub struct KillConfirmDialog {
-    session_name: String,
+    question: String,
 }
 
 impl KillConfirmDialog {
     pub fn new() -> Self {
         Self {
-            session_name: String::new(),
+            question: String::new(),
         }
     }
 
     pub fn set_session_name(&mut self, name: &str) {
-        self.session_name = name.to_string();
+        self.question = format!("Kill session '{}'?", name);
+    }
+
+    /// Ask about killing the least recently used session so `new` can start
+    pub fn set_lru(&mut self, lru: &str, idle: &str, new: &str) {
+        self.question = format!(
+            "Session limit reached. Kill '{}' (unused for {}) to start '{}'?",
+            lru, idle, new
+        );
+    }
+
+    /// Ask about killing several sessions at once
+    pub fn set_session_count(&mut self, count: usize) {
+        self.question = format!("Kill {} sessions?", count);
     }
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
         let lines = vec![
-            Line::from(format!("Kill session '{}'?", self.session_name)),
+            Line::from(self.question.clone()),
             Line::from(""),
             Line::from(vec![
                 Span::styled(
@@ -32,6 +45,13 @@ impl KillConfirmDialog {
                         .fg(Color::Magenta)
                         .add_modifier(Modifier::BOLD),
                 ),
+                Span::raw(" / "),
+                Span::styled(
+                    "Enter",
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                ),
                 Span::raw(" - Yes, kill it"),
             ]),
             Line::from(vec![
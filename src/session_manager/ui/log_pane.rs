@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use super::event_log_path;
+
+/// Rows taken by the pane, including its top border
+const LOG_PANE_HEIGHT: u16 = 8;
+/// Most recent event log lines kept in memory
+const TAIL_LINES: usize = 200;
+
+/// Bottom panel tailing shepherd's own event log
+pub struct LogPane {
+    path: Option<PathBuf>,
+    lines: Vec<String>,
+    /// Size and mtime of the log when last read, to skip rereading an unchanged file
+    last_seen: Option<(u64, SystemTime)>,
+}
+
+impl LogPane {
+    pub fn new() -> Self {
+        Self {
+            path: event_log_path(),
+            lines: Vec::new(),
+            last_seen: None,
+        }
+    }
+
+    /// Reread the log if it changed since the last call. Returns true if the lines changed.
+    pub fn refresh(&mut self) -> bool {
+        let Some(ref path) = self.path else {
+            return false;
+        };
+        let seen = std::fs::metadata(path)
+            .ok()
+            .and_then(|m| Some((m.len(), m.modified().ok()?)));
+        if seen.is_none() || seen == self.last_seen {
+            return false;
+        }
+        self.last_seen = seen;
+
+        let contents = std::fs::read_to_string(path).unwrap_or_default();
+        let lines: Vec<&str> = contents.lines().collect();
+        let start = lines.len().saturating_sub(TAIL_LINES);
+        self.lines = lines[start..].iter().map(|l| l.to_string()).collect();
+        true
+    }
+
+    /// Render at the bottom of `area`. Returns the area left above the pane.
+    pub fn render(&self, frame: &mut Frame, area: Rect) -> Rect {
+        let height = LOG_PANE_HEIGHT.min(area.height / 2);
+        if height < 2 {
+            return area;
+        }
+        let pane_area = Rect::new(area.x, area.y + area.height - height, area.width, height);
+
+        let block = Block::default()
+            .title(" Event log ")
+            .borders(Borders::TOP)
+            .border_style(Style::default().fg(Color::DarkGray));
+        let inner = block.inner(pane_area);
+        frame.render_widget(block, pane_area);
+
+        let visible = inner.height as usize;
+        let start = self.lines.len().saturating_sub(visible);
+        let lines: Vec<Line> = self.lines[start..]
+            .iter()
+            .map(|line| {
+                let color = if line.contains("] [ERR] ") {
+                    Color::Red
+                } else if line.contains("] [WARN] ") {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                };
+                Line::from(Span::styled(line.clone(), Style::default().fg(color)))
+            })
+            .collect();
+
+        if lines.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No events yet").style(Style::default().fg(Color::DarkGray)),
+                inner,
+            );
+        } else {
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
+
+        Rect::new(area.x, area.y, area.width, area.height - height)
+    }
+}
+
+impl Default for LogPane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
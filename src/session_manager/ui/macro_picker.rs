@@ -0,0 +1,165 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::config::Macro;
+
+/// A popup listing configured macros; Enter sends the highlighted one.
+pub struct MacroPicker {
+    state: ListState,
+}
+
+impl MacroPicker {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { state }
+    }
+
+    /// Reset the selection for a fresh view.
+    pub fn reset(&mut self) {
+        self.state.select(Some(0));
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current == 0 { len - 1 } else { current - 1 };
+        self.state.select(Some(next));
+    }
+
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current + 1 >= len { 0 } else { current + 1 };
+        self.state.select(Some(next));
+    }
+
+    /// Index of the highlighted macro.
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, macros: &[Macro]) {
+        let key_width = macros
+            .iter()
+            .map(|m| m.key.as_deref().map_or(0, str::len))
+            .max()
+            .unwrap_or(0);
+        let content_width = macros
+            .iter()
+            .map(|m| key_width + 2 + m.name.len() + 3 + escape_payload(&m.payload).len())
+            .max()
+            .unwrap_or(0);
+
+        let popup_width = (content_width as u16 + 6)
+            .max(40)
+            .min(area.width.saturating_sub(4));
+        let list_height = macros.len().clamp(1, 10) as u16;
+        let popup_height = (list_height + 2 + 2).min(area.height.saturating_sub(2));
+
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Macros ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y,
+            inner.width,
+            inner.height.saturating_sub(2),
+        );
+
+        if macros.is_empty() {
+            let empty_msg = Paragraph::new("No macros defined in ~/.shepherd/config.json")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty_msg, list_area);
+        } else {
+            let items: Vec<ListItem> = macros
+                .iter()
+                .map(|m| {
+                    let key = m.key.as_deref().unwrap_or("");
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:<width$}  ", key, width = key_width),
+                            Style::default()
+                                .fg(Color::Magenta)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(m.name.clone(), Style::default().fg(Color::White)),
+                        Span::raw(" - "),
+                        Span::styled(
+                            escape_payload(&m.payload),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ])
+                })
+                .map(ListItem::new)
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(
+                    Style::default()
+                        .bg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("> ");
+
+            frame.render_stateful_widget(list, list_area, &mut self.state);
+        }
+
+        let footer_area = Rect::new(
+            inner.x,
+            inner.y + inner.height.saturating_sub(1),
+            inner.width,
+            1,
+        );
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled(
+                "Enter",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": send  "),
+            Span::styled(
+                "Esc",
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": close"),
+        ]));
+        frame.render_widget(footer, footer_area);
+    }
+}
+
+impl Default for MacroPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Show control characters in a payload as escapes so they don't garble the popup
+fn escape_payload(payload: &str) -> String {
+    payload.escape_debug().to_string()
+}
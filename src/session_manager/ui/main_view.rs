@@ -1,17 +1,39 @@
 use std::path::Path;
-use std::sync::Arc;
+use std::time::Duration;
 
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
-use vt100::Screen;
 
+use super::super::git_status::GitStatus;
 use super::super::session_pair::SessionView;
-use crate::pty_widget::PtyWidget;
+use crate::history::format_duration;
+
+/// Label shown over a session's screen while its output is rate limited
+const THROTTLED_BADGE: &str = " output throttled ";
+
+/// Mark the top right of `area`, a session's screen, as having its output held back by
+/// `max_output_rate`
+pub fn render_throttled_badge(frame: &mut Frame, area: Rect) {
+    let width = THROTTLED_BADGE.len() as u16;
+    if area.width < width || area.height == 0 {
+        return;
+    }
+    let badge = Rect::new(area.right() - width, area.y, width, 1);
+    frame.render_widget(
+        Paragraph::new(THROTTLED_BADGE).style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        badge,
+    );
+}
 
 pub struct MainView;
 
@@ -24,15 +46,18 @@ impl MainView {
     pub fn render(
         &self,
         frame: &mut Frame,
-        screen: Option<&Arc<Screen>>,
         active_name: Option<&str>,
+        active_slot: Option<usize>,
         active_path: Option<&Path>,
         active_view: SessionView,
+        active_paused: bool,
+        git_status: Option<&GitStatus>,
         background_count: usize,
         stopped_count: usize,
         bottom_left: Line<'static>,
         bottom_center: Option<Line<'static>>,
-        scroll_offset: usize,
+        active_timer: Option<(Duration, Duration)>,
+        session_limit: Option<String>,
     ) -> Rect {
         let area = frame.area();
 
@@ -42,13 +67,21 @@ impl MainView {
                     SessionView::Claude => "",
                     SessionView::Shell => " [shell]",
                 };
-                format!(" {}{} ", name, view_indicator)
+                let paused_indicator = if active_paused { " [paused]" } else { "" };
+                match active_slot {
+                    Some(slot) => {
+                        format!(" {}: {}{}{} ", slot, name, view_indicator, paused_indicator)
+                    }
+                    None => format!(" {}{}{} ", name, view_indicator, paused_indicator),
+                }
             }
             None => " No Session ".to_string(),
         };
 
         let total_sessions = background_count + if active_name.is_some() { 1 } else { 0 };
-        let session_count_text = if total_sessions > 1 {
+        let session_count_text = if let Some(limit) = session_limit {
+            limit
+        } else if total_sessions > 1 {
             format!("{} Sessions", total_sessions)
         } else {
             String::new()
@@ -59,7 +92,22 @@ impl MainView {
         let mut block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::White))
-            .title(Line::from(top_title).left_aligned());
+            .title(Line::from(title_spans(top_title, git_status)).left_aligned());
+
+        // Top right: running / focused time of the active session
+        if let Some((running, focused)) = active_timer {
+            block = block.title(
+                Line::from(Span::styled(
+                    format!(
+                        " running {} / focused {} ",
+                        format_duration(running),
+                        format_duration(focused)
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ))
+                .right_aligned(),
+            );
+        }
 
         // Bottom left: hotkeys
         block = block.title_bottom(bottom_left.left_aligned());
@@ -117,12 +165,70 @@ impl MainView {
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        if let Some(screen) = screen {
-            let widget = PtyWidget::new(screen.as_ref()).scroll_offset(scroll_offset);
-            frame.render_widget(widget, inner);
+        inner
+    }
+
+    /// Centered panel shown in place of a session when none is active, listing the plain
+    /// keys that work in that state
+    pub fn render_placeholder(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        repo_name: Option<&str>,
+        latest_session: Option<&str>,
+    ) {
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let hint = |key: &'static str, action: String| {
+            Line::from(vec![
+                Span::styled(format!("  {}  ", key), key_style),
+                Span::raw(action),
+            ])
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "No Session",
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .centered(),
+            Line::from(Span::styled(
+                repo_name
+                    .map(|name| format!("repo: {}", name))
+                    .unwrap_or_else(|| "not in a git repository".to_string()),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .centered(),
+            Line::default(),
+            hint("n", "new session".to_string()),
+            hint("l", "list sessions".to_string()),
+        ];
+        if let Some(latest) = latest_session {
+            lines.push(hint("r", format!("resume {}", latest)));
         }
+        lines.push(hint("q", "quit".to_string()));
 
-        inner
+        let width = lines
+            .iter()
+            .map(|line| line.width() as u16 + 4)
+            .max()
+            .unwrap_or(0)
+            .max(32)
+            .min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let panel = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        frame.render_widget(Clear, panel);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(Paragraph::new(lines).block(block), panel);
     }
 }
 
@@ -140,3 +246,43 @@ fn path_relative_to_home(path: &Path) -> String {
     }
     path.display().to_string()
 }
+
+/// Session title followed by the checkout's branch, short HEAD and a dirty marker
+fn title_spans(title: String, git_status: Option<&GitStatus>) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::raw(title)];
+    let Some(git) = git_status else {
+        return spans;
+    };
+
+    let label = match (&git.branch, &git.head) {
+        (Some(branch), Some(head)) => format!("{}@{}", branch, head),
+        (Some(branch), None) => branch.clone(),
+        (None, Some(head)) => format!("detached@{}", head),
+        (None, None) => return spans,
+    };
+    spans.push(Span::styled(
+        format!("⎇ {} ", label),
+        Style::default().fg(Color::DarkGray),
+    ));
+    if git.dirty {
+        spans.push(Span::styled(
+            "* ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(ab) = git.ahead_behind_label() {
+        spans.push(Span::styled(
+            format!("{} ", ab),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if !git.conflicts.is_empty() {
+        spans.push(Span::styled(
+            format!("✗ {} conflicting ", git.conflicts.len()),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans
+}
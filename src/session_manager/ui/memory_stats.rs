@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::history::format_duration;
+
+/// Parser memory of one live session, its claude view and shell panes together
+pub struct MemoryRow {
+    pub name: String,
+    /// Time since the session was last used; None for the active session
+    pub idle: Option<Duration>,
+    pub panes: usize,
+    pub scrollback_lines: usize,
+    /// Scrollback lines trimmed to disk
+    pub stashed_lines: usize,
+    pub bytes: usize,
+}
+
+/// Estimated memory per live session, largest first
+pub struct MemoryStats {
+    rows: Vec<MemoryRow>,
+}
+
+impl MemoryStats {
+    pub fn new() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    pub fn set_rows(&mut self, mut rows: Vec<MemoryRow>) {
+        rows.sort_by_key(|row| std::cmp::Reverse(row.bytes));
+        self.rows = rows;
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let name_width = self
+            .rows
+            .iter()
+            .map(|row| row.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Session".len());
+        let header_style = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        let row_line = |cells: [String; 5], style: Style| {
+            Line::styled(
+                format!(
+                    "{:<name_width$}  {:>8}  {:>5}  {:>16}  {:>9}",
+                    cells[0], cells[1], cells[2], cells[3], cells[4]
+                ),
+                style,
+            )
+        };
+
+        let mut lines = vec![row_line(
+            [
+                "Session".into(),
+                "Idle".into(),
+                "Panes".into(),
+                "Scrollback".into(),
+                "Memory".into(),
+            ],
+            header_style,
+        )];
+        for row in &self.rows {
+            let idle = row.idle.map_or("active".to_string(), format_duration);
+            let scrollback = if row.stashed_lines > 0 {
+                format!("{} +{} disk", row.scrollback_lines, row.stashed_lines)
+            } else {
+                row.scrollback_lines.to_string()
+            };
+            lines.push(row_line(
+                [
+                    row.name.clone(),
+                    idle,
+                    row.panes.to_string(),
+                    scrollback,
+                    format_bytes(row.bytes),
+                ],
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        let total: usize = self.rows.iter().map(|row| row.bytes).sum();
+        lines.push(Line::default());
+        lines.push(Line::from(vec![
+            Span::styled("Total ", header_style),
+            Span::raw(format_bytes(total)),
+            Span::styled(
+                "  (screen and scrollback cells)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+        lines.push(Line::default());
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        lines.push(Line::from(vec![
+            Span::styled("t", key_style),
+            Span::raw(": trim background sessions  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": close"),
+        ]));
+
+        let content_width = lines.iter().map(Line::width).max().unwrap_or(20);
+        let popup_width = (content_width as u16 + 4).min(area.width.saturating_sub(4));
+        let popup_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+
+        let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+        let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(" Memory ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .style(Style::default().bg(Color::Black)),
+        );
+
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for MemoryStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `bytes` in the largest unit that keeps it at least 1, e.g. "3.2 MiB"
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
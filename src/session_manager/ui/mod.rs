@@ -1,21 +1,63 @@
+mod approval_bar;
+mod attention_inbox;
+mod checkpoint_browser;
+mod command_palette;
+mod compose_dialog;
 mod create_dialog;
+mod creation_progress;
+mod dashboard;
 mod delete_confirm;
+mod directory_prompt;
+mod dispatch_dialog;
+mod event_log;
+mod git_history;
 mod help_popup;
 mod kill_confirm;
+mod log_pane;
+mod macro_picker;
 mod main_view;
+mod memory_stats;
+mod prompt_history;
 mod quit_confirm;
+mod render_stats;
+mod run_dialog;
+mod session_plan;
 mod session_selector;
+mod signal_menu;
 mod status_bar;
 mod terminal_multiplexer;
+mod transcript_viewer;
+mod undo_changes;
 mod worktree_cleanup;
 
-pub use create_dialog::CreateDialog;
+pub use approval_bar::ApprovalBar;
+pub use attention_inbox::{AttentionInbox, InboxEntry, PREVIEW_LINES};
+pub use checkpoint_browser::CheckpointBrowser;
+pub use command_palette::CommandPalette;
+pub use compose_dialog::ComposeDialog;
+pub use create_dialog::{CreateDialog, NameCollision};
+pub use creation_progress::CreationProgress;
+pub use dashboard::Dashboard;
 pub use delete_confirm::DeleteConfirmDialog;
+pub use directory_prompt::DirectoryPrompt;
+pub use dispatch_dialog::DispatchDialog;
+pub use event_log::{append_event, event_log_path, read_event_log};
+pub use git_history::{GitHistory, HistoryView};
 pub use help_popup::HelpPopup;
 pub use kill_confirm::KillConfirmDialog;
-pub use main_view::MainView;
-pub use quit_confirm::QuitConfirmDialog;
-pub use session_selector::{SelectorItemKind, SessionSelector};
-pub use status_bar::{StatusBar, StatusMessage};
+pub use log_pane::LogPane;
+pub use macro_picker::MacroPicker;
+pub use main_view::{MainView, render_throttled_badge};
+pub use memory_stats::{MemoryRow, MemoryStats};
+pub use prompt_history::PromptHistory;
+pub use quit_confirm::{QuitConfirmDialog, QuitEntry};
+pub use render_stats::{RenderStats, SessionCounters};
+pub use run_dialog::RunDialog;
+pub use session_plan::{PlanField, SessionPlanDialog};
+pub use session_selector::{SelectorItemKind, SessionDetail, SessionSelector};
+pub use signal_menu::{SIGNALS, SignalMenu};
+pub use status_bar::{StatusBar, StatusLevel, StatusMessage};
 pub use terminal_multiplexer::TerminalMultiplexer;
-pub use worktree_cleanup::WorktreeCleanupDialog;
+pub use transcript_viewer::TranscriptViewer;
+pub use undo_changes::UndoChangesDialog;
+pub use worktree_cleanup::{WorktreeCleanupDialog, WorktreeEntry};
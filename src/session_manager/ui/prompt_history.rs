@@ -0,0 +1,222 @@
+use chrono::Utc;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::history::{PromptEntry, format_duration};
+
+/// Prompts sent in this repo, filterable by typing. Enter re-sends the highlighted one to
+/// the target session; Tab picks another target.
+pub struct PromptHistory {
+    query: String,
+    prompts: Vec<PromptEntry>,
+    /// Indices into `prompts` containing the query
+    matches: Vec<usize>,
+    state: ListState,
+    /// Live sessions a prompt can be sent to, current one first
+    targets: Vec<String>,
+    target: usize,
+}
+
+impl PromptHistory {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            prompts: Vec::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+            targets: Vec::new(),
+            target: 0,
+        }
+    }
+
+    /// Start browsing `prompts` (most recent first), sending to one of `targets`
+    pub fn open(&mut self, prompts: Vec<PromptEntry>, targets: Vec<String>) {
+        self.prompts = prompts;
+        self.targets = targets;
+        self.target = 0;
+        self.query.clear();
+        self.update_matches();
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current == 0 {
+            self.matches.len() - 1
+        } else {
+            current - 1
+        };
+        self.state.select(Some(next));
+    }
+
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        self.state.select(Some((current + 1) % self.matches.len()));
+    }
+
+    /// Send to the next live session
+    pub fn cycle_target(&mut self) {
+        if !self.targets.is_empty() {
+            self.target = (self.target + 1) % self.targets.len();
+        }
+    }
+
+    pub fn target(&self) -> Option<&str> {
+        self.targets.get(self.target).map(String::as_str)
+    }
+
+    /// Text of the highlighted prompt
+    pub fn selected(&self) -> Option<&str> {
+        let index = self.matches.get(self.state.selected()?)?;
+        Some(&self.prompts[*index].text)
+    }
+
+    fn update_matches(&mut self) {
+        let query = self.query.to_lowercase();
+        self.matches = self
+            .prompts
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.text.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let popup_width = 80.min(area.width.saturating_sub(4));
+        let popup_height = 20.min(area.height.saturating_sub(2));
+
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 3;
+        let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(" Prompt history ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let query_line = Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Magenta)),
+            Span::raw(&self.query),
+            Span::styled("_", Style::default().fg(Color::Magenta)),
+        ]);
+        frame.render_widget(
+            Paragraph::new(query_line),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let footer = Line::from(vec![
+            Span::raw("Send to "),
+            Span::styled(
+                self.target().unwrap_or("-").to_string(),
+                Style::default().fg(Color::White),
+            ),
+            Span::raw("  "),
+            Span::styled("Tab", key_style),
+            Span::raw(": change  "),
+            Span::styled("Enter", key_style),
+            Span::raw(": send  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": close"),
+        ]);
+        frame.render_widget(
+            Paragraph::new(footer),
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y + 2,
+            inner.width,
+            inner.height.saturating_sub(4),
+        );
+        if self.matches.is_empty() {
+            let message = if self.prompts.is_empty() {
+                "No prompts sent in this repository yet"
+            } else {
+                "No matching prompts"
+            };
+            let empty = Paragraph::new(message).style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, list_area);
+            return;
+        }
+
+        // First line of each prompt, with where and when it was sent right-aligned
+        let width = list_area.width.saturating_sub(2) as usize;
+        let now = Utc::now();
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|&i| {
+                let prompt = &self.prompts[i];
+                let age = format_duration((now - prompt.sent_at).to_std().unwrap_or_default());
+                let meta = format!(" {} {} ago", prompt.session, age);
+                let mut text: String = prompt.text.lines().next().unwrap_or_default().into();
+                if prompt.text.contains('\n') {
+                    text.push_str(" ...");
+                }
+                let text: String = text
+                    .chars()
+                    .take(width.saturating_sub(meta.chars().count()))
+                    .collect();
+                let gap = width.saturating_sub(text.chars().count() + meta.chars().count());
+                Line::from(vec![
+                    Span::styled(text, Style::default().fg(Color::White)),
+                    Span::raw(" ".repeat(gap)),
+                    Span::styled(meta, Style::default().fg(Color::DarkGray)),
+                ])
+            })
+            .map(ListItem::new)
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+    }
+}
+
+impl Default for PromptHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
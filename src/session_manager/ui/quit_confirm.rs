@@ -3,51 +3,137 @@ use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
 
-pub struct QuitConfirmDialog;
+/// A live session that quitting would kill
+pub struct QuitEntry {
+    pub name: String,
+    /// Open shell panes besides the claude process
+    pub shells: usize,
+}
+
+/// Asks before quitting, listing the live sessions that would be killed. Sessions can be
+/// killed one at a time from the list first.
+pub struct QuitConfirmDialog {
+    sessions: Vec<QuitEntry>,
+    state: ListState,
+}
 
 impl QuitConfirmDialog {
     pub fn new() -> Self {
-        Self
+        Self {
+            sessions: Vec::new(),
+            state: ListState::default(),
+        }
+    }
+
+    /// Show `sessions`, keeping the selection in place where possible
+    pub fn set_sessions(&mut self, sessions: Vec<QuitEntry>) {
+        let selected = self.state.selected().unwrap_or(0);
+        self.state
+            .select((!sessions.is_empty()).then(|| selected.min(sessions.len() - 1)));
+        self.sessions = sessions;
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current == 0 {
+            self.sessions.len() - 1
+        } else {
+            current - 1
+        };
+        self.state.select(Some(next));
     }
 
-    pub fn render(&self, frame: &mut Frame, area: Rect) {
-        let lines = vec![
-            Line::from("Quit Shepard?"),
-            Line::from(""),
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        self.state.select(Some((current + 1) % self.sessions.len()));
+    }
+
+    /// Name of the highlighted session
+    pub fn selected(&self) -> Option<&str> {
+        let entry = self.sessions.get(self.state.selected()?)?;
+        Some(&entry.name)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let processes: usize = self.sessions.iter().map(|s| 1 + s.shells).sum();
+        let question = match self.sessions.len() {
+            0 => "Quit Shepard?".to_string(),
+            1 => format!(
+                "Quit Shepard? 1 live session ({} processes) will be killed:",
+                processes
+            ),
+            n => format!(
+                "Quit Shepard? {} live sessions ({} processes) will be killed:",
+                n, processes
+            ),
+        };
+        let mut footer = vec![
             Line::from(vec![
-                Span::styled(
-                    "y",
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled("y", key_style),
                 Span::raw(" - Yes, quit"),
             ]),
             Line::from(vec![
-                Span::styled(
-                    "n",
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled("n", key_style),
                 Span::raw(" / "),
-                Span::styled(
-                    "Esc",
-                    Style::default()
-                        .fg(Color::Magenta)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled("Esc", key_style),
                 Span::raw(" - Cancel"),
             ]),
         ];
+        if !self.sessions.is_empty() {
+            footer.insert(
+                0,
+                Line::from(vec![
+                    Span::styled("x", key_style),
+                    Span::raw(" - Kill highlighted session"),
+                ]),
+            );
+        }
 
-        let max_line_len = lines.iter().map(|l| l.width()).max().unwrap_or(20);
+        let items: Vec<ListItem> = self
+            .sessions
+            .iter()
+            .map(|entry| {
+                let mut spans = vec![Span::raw(entry.name.clone())];
+                if entry.shells > 0 {
+                    spans.push(Span::styled(
+                        format!(
+                            " +{} shell{}",
+                            entry.shells,
+                            if entry.shells == 1 { "" } else { "s" }
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list_height = self.sessions.len().min(10) as u16;
 
-        let popup_width = (max_line_len as u16 + 4).min(area.width.saturating_sub(4));
-        let popup_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let content_width = footer
+            .iter()
+            .map(Line::width)
+            .chain([question.len()])
+            .chain(self.sessions.iter().map(|s| s.name.len() + 14))
+            .max()
+            .unwrap_or(20);
+        let popup_width = (content_width as u16 + 4).min(area.width.saturating_sub(4));
+        let spacer = if self.sessions.is_empty() { 0 } else { 1 };
+        let popup_height = (1 + list_height + spacer + 1 + footer.len() as u16 + 2)
+            .min(area.height.saturating_sub(2));
 
         let popup_x = (area.width.saturating_sub(popup_width)) / 2;
         let popup_y = (area.height.saturating_sub(popup_height)) / 2;
@@ -55,15 +141,41 @@ impl QuitConfirmDialog {
 
         frame.render_widget(Clear, popup_area);
 
-        let paragraph = Paragraph::new(lines).block(
-            Block::default()
-                .title(" Quit ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White))
-                .style(Style::default().bg(Color::Black)),
+        let block = Block::default()
+            .title(" Quit ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        frame.render_widget(
+            Paragraph::new(question),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+        let list_area = Rect::new(
+            inner.x,
+            inner.y + 1 + spacer,
+            inner.width,
+            list_height.min(inner.height.saturating_sub(1 + spacer)),
         );
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
 
-        frame.render_widget(paragraph, popup_area);
+        let footer_height = footer.len() as u16;
+        let footer_area = Rect::new(
+            inner.x,
+            inner.y + inner.height.saturating_sub(footer_height),
+            inner.width,
+            footer_height.min(inner.height),
+        );
+        frame.render_widget(Paragraph::new(footer), footer_area);
     }
 }
 
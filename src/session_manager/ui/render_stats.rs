@@ -0,0 +1,210 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Render times kept for the overlay
+const SAMPLES: usize = 120;
+/// How often per-session output rates are recomputed
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Output a session has parsed, for its bytes/sec
+struct Throughput {
+    total: u64,
+    since: Instant,
+    per_sec: f64,
+    /// Bytes waiting in the PTY, not yet read
+    backlog: Option<usize>,
+}
+
+/// One live session's counters, as sampled from the UI thread
+pub struct SessionCounters {
+    pub name: String,
+    /// Bytes of output parsed since the session started
+    pub parsed: u64,
+    pub backlog: Option<usize>,
+}
+
+/// Frame rate, render times, per-session output rates and queue backlogs, shown in the
+/// top right corner while toggled on from the command palette
+#[derive(Default)]
+pub struct RenderStats {
+    /// When each recent frame started and how long it took
+    frames: VecDeque<(Instant, Duration)>,
+    sessions: Vec<(String, Throughput)>,
+    /// Events waiting on the UI thread's bus
+    event_backlog: usize,
+    pub visible: bool,
+}
+
+impl RenderStats {
+    pub fn record(&mut self, started: Instant, elapsed: Duration) {
+        if self.frames.len() == SAMPLES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((started, elapsed));
+    }
+
+    /// Frames started in the last second
+    pub fn fps(&self) -> usize {
+        let Some(now) = self.frames.back().map(|(at, _)| *at) else {
+            return 0;
+        };
+        self.frames
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) < Duration::from_secs(1))
+            .count()
+    }
+
+    /// The last, average and slowest of the recent render times
+    pub fn summary(&self) -> Option<(Duration, Duration, Duration)> {
+        let last = self.frames.back()?.1;
+        let total: Duration = self.frames.iter().map(|(_, elapsed)| *elapsed).sum();
+        let max = self.frames.iter().map(|(_, elapsed)| *elapsed).max();
+        Some((
+            last,
+            total / self.frames.len() as u32,
+            max.unwrap_or_default(),
+        ))
+    }
+
+    /// Take new counters for the live sessions, recomputing rates at most once a second
+    pub fn sample(&mut self, counters: Vec<SessionCounters>, event_backlog: usize) {
+        self.event_backlog = event_backlog;
+        let mut previous: HashMap<String, Throughput> = self.sessions.drain(..).collect();
+        for counter in counters {
+            let throughput = match previous.remove(&counter.name) {
+                Some(mut t) if t.since.elapsed() >= RATE_INTERVAL => {
+                    let secs = t.since.elapsed().as_secs_f64();
+                    t.per_sec = counter.parsed.saturating_sub(t.total) as f64 / secs;
+                    t.total = counter.parsed;
+                    t.since = Instant::now();
+                    t.backlog = counter.backlog;
+                    t
+                }
+                Some(mut t) => {
+                    t.backlog = counter.backlog;
+                    t
+                }
+                None => Throughput {
+                    total: counter.parsed,
+                    since: Instant::now(),
+                    per_sec: 0.0,
+                    backlog: counter.backlog,
+                },
+            };
+            self.sessions.push((counter.name, throughput));
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        if !self.visible {
+            return;
+        }
+        let mut lines = vec![
+            match self.summary() {
+                Some((last, avg, max)) => format!(
+                    "{} fps  last {:.1}ms avg {:.1}ms max {:.1}ms",
+                    self.fps(),
+                    ms(last),
+                    ms(avg),
+                    ms(max)
+                ),
+                None => "no frames yet".to_string(),
+            },
+            format!("events queued: {}", self.event_backlog),
+        ];
+        let name_width = self
+            .sessions
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (name, t) in &self.sessions {
+            let backlog = t
+                .backlog
+                .map_or_else(|| "-".to_string(), |b| bytes(b as f64));
+            lines.push(format!(
+                "{:<width$}  {:>9}/s  pty {}",
+                name,
+                bytes(t.per_sec),
+                backlog,
+                width = name_width
+            ));
+        }
+
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 2;
+        let height = lines.len() as u16 + 2;
+        if area.width < width || area.height < height {
+            return;
+        }
+        let overlay = Rect::new(area.right() - width, area.y, width, height);
+        frame.render_widget(Clear, overlay);
+        frame.render_widget(
+            Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .style(Style::default().fg(Color::Cyan))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" render stats ")
+                        .border_style(Style::default().fg(Color::DarkGray)),
+                ),
+            overlay,
+        );
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// `n` bytes in B, KB or MB
+fn bytes(n: f64) -> String {
+    if n >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", n / (1024.0 * 1024.0))
+    } else if n >= 1024.0 {
+        format!("{:.1} KB", n / 1024.0)
+    } else {
+        format!("{:.0} B", n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut stats = RenderStats::default();
+        assert_eq!(stats.summary(), None);
+        let start = Instant::now();
+        for (i, ms) in [1, 4, 1].into_iter().enumerate() {
+            stats.record(
+                start + Duration::from_millis(400 * i as u64),
+                Duration::from_millis(ms),
+            );
+        }
+        assert_eq!(
+            stats.summary(),
+            Some((
+                Duration::from_millis(1),
+                Duration::from_millis(2),
+                Duration::from_millis(4)
+            ))
+        );
+        // The first frame was 800ms before the last one, so all three fall in a second
+        assert_eq!(stats.fps(), 3);
+        stats.record(
+            start + Duration::from_millis(1500),
+            Duration::from_millis(1),
+        );
+        assert_eq!(stats.fps(), 2);
+        assert_eq!(bytes(1536.0), "1.5 KB");
+    }
+}
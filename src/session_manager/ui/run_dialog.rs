@@ -0,0 +1,125 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Recent commands kept for recall with up/down
+const MAX_RECENT: usize = 20;
+
+/// Asks for a one-off command to run in a temporary pane of the active session's worktree
+pub struct RunDialog {
+    input: String,
+    /// Commands run before, newest first
+    recent: Vec<String>,
+    /// Position in `recent` while recalling with up/down
+    recall: Option<usize>,
+}
+
+impl RunDialog {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            recent: Vec::new(),
+            recall: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.recall = None;
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+        self.recall = None;
+    }
+
+    pub fn pop(&mut self) {
+        self.input.pop();
+        self.recall = None;
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.trim()
+    }
+
+    /// Remember `command` as the most recent one
+    pub fn remember(&mut self, command: &str) {
+        self.recent.retain(|c| c != command);
+        self.recent.insert(0, command.to_string());
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// Fill in the next older recent command
+    pub fn recall_older(&mut self) {
+        let i = self.recall.map_or(0, |i| i + 1);
+        if let Some(command) = self.recent.get(i) {
+            self.input = command.clone();
+            self.recall = Some(i);
+        }
+    }
+
+    /// Fill in the next newer recent command, or clear the input past the newest
+    pub fn recall_newer(&mut self) {
+        match self.recall {
+            Some(0) => {
+                self.input.clear();
+                self.recall = None;
+            }
+            Some(i) => {
+                self.input = self.recent[i - 1].clone();
+                self.recall = Some(i - 1);
+            }
+            None => {}
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, session: &str) {
+        let popup_width = 72u16.min(area.width.saturating_sub(4));
+        let popup_height = 6u16;
+
+        let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+        let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("$ ", Style::default().fg(Color::Gray)),
+                Span::raw(&self.input),
+                Span::styled("_", Style::default().fg(Color::Magenta)),
+            ]),
+            Line::default(),
+            Line::from(vec![
+                Span::styled("Enter", key_style),
+                Span::raw(": run  "),
+                Span::styled("Up/Down", key_style),
+                Span::raw(": recent  "),
+                Span::styled("Esc", key_style),
+                Span::raw(": cancel"),
+            ]),
+        ];
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(format!(" Run in {} ", session))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .style(Style::default().bg(Color::Black)),
+        );
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for RunDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,233 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::workflows::{PlanOverrides, SessionPlan};
+
+/// A plan field that can be edited before creating the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanField {
+    Path,
+    Base,
+}
+
+/// What creating a session will do, to confirm before any of it happens. The path and
+/// base can be changed, which plans again.
+pub struct SessionPlanDialog {
+    name: String,
+    prompt: Option<String>,
+    /// Index of the workflow in the manager's list, and its name
+    workflow: usize,
+    workflow_name: &'static str,
+    plan: SessionPlan,
+    overrides: PlanOverrides,
+    /// Field being edited and its text so far
+    editing: Option<(PlanField, String)>,
+    /// Why the last edit can't be planned
+    error: Option<String>,
+}
+
+impl SessionPlanDialog {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            prompt: None,
+            workflow: 0,
+            workflow_name: "",
+            plan: SessionPlan::default(),
+            overrides: PlanOverrides::default(),
+            editing: None,
+            error: None,
+        }
+    }
+
+    pub fn open(
+        &mut self,
+        name: String,
+        prompt: Option<String>,
+        workflow: (usize, &'static str),
+        plan: SessionPlan,
+    ) {
+        self.name = name;
+        self.prompt = prompt;
+        (self.workflow, self.workflow_name) = workflow;
+        self.plan = plan;
+        self.overrides = PlanOverrides::default();
+        self.editing = None;
+        self.error = None;
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt.as_deref()
+    }
+
+    pub fn workflow(&self) -> usize {
+        self.workflow
+    }
+
+    pub fn overrides(&self) -> &PlanOverrides {
+        &self.overrides
+    }
+
+    pub fn set_plan(&mut self, plan: SessionPlan) {
+        self.plan = plan;
+        self.error = None;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Start editing `field`, if the workflow lets it be chosen
+    pub fn start_edit(&mut self, field: PlanField) {
+        let current = match field {
+            PlanField::Path => self.plan.path.as_ref().map(|p| p.display().to_string()),
+            PlanField::Base => self.plan.base.clone(),
+        };
+        if let Some(current) = current {
+            self.editing = Some((field, current));
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some((_, ref mut text)) = self.editing {
+            text.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if let Some((_, ref mut text)) = self.editing {
+            text.pop();
+        }
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.editing = None;
+    }
+
+    /// Apply the edit to the overrides; an emptied field goes back to the workflow's choice
+    pub fn finish_edit(&mut self) {
+        let Some((field, text)) = self.editing.take() else {
+            return;
+        };
+        let text = text.trim();
+        let value = (!text.is_empty()).then(|| text.to_string());
+        match field {
+            PlanField::Path => self.overrides.path = value.map(Into::into),
+            PlanField::Base => self.overrides.base = value,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup_width = 80u16.min(area.width.saturating_sub(4));
+        let popup_height =
+            (self.plan.operations.len() as u16 + 10).min(area.height.saturating_sub(2));
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(popup_width)) / 2,
+            area.y + (area.height.saturating_sub(popup_height)) / 2,
+            popup_width,
+            popup_height,
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let label = Style::default().fg(Color::Gray);
+        let value = Style::default().fg(Color::Cyan);
+        let dim = Style::default().fg(Color::DarkGray);
+        let key = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+
+        let field_line = |name: &'static str, field: PlanField, current: Option<String>| {
+            let mut spans = vec![Span::styled(name, label)];
+            match (&self.editing, current) {
+                (Some((editing, text)), _) if *editing == field => {
+                    spans.push(Span::raw(text.clone()));
+                    spans.push(Span::styled("_", Style::default().fg(Color::Magenta)));
+                }
+                (_, Some(current)) => spans.push(Span::styled(current, value)),
+                (_, None) => spans.push(Span::styled("chosen by the workflow", dim)),
+            }
+            Line::from(spans)
+        };
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Workflow: ", label),
+                Span::styled(self.workflow_name, value),
+            ]),
+            field_line(
+                "Path:     ",
+                PlanField::Path,
+                self.plan.path.as_ref().map(|p| p.display().to_string()),
+            ),
+        ];
+        if self.plan.base.is_some() || self.overrides.base.is_some() {
+            lines.push(field_line(
+                "Base:     ",
+                PlanField::Base,
+                self.plan.base.clone(),
+            ));
+        }
+        lines.push(Line::default());
+        if self.plan.operations.is_empty() {
+            lines.push(Line::styled("Nothing to set up", dim));
+        }
+        for (i, operation) in self.plan.operations.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}. ", i + 1), dim),
+                Span::styled(operation.clone(), Style::default().fg(Color::White)),
+            ]));
+        }
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}. ", self.plan.operations.len() + 1), dim),
+            Span::styled("start claude", Style::default().fg(Color::White)),
+        ]));
+        if let Some(ref error) = self.error {
+            lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+        }
+        lines.push(Line::default());
+        let hints: &[(&str, &str)] = if self.editing.is_some() {
+            &[("Enter", ": apply  "), ("Esc", ": cancel edit")]
+        } else {
+            &[
+                ("Enter", ": create  "),
+                ("p", ": edit path  "),
+                ("b", ": edit base  "),
+                ("Esc", ": back"),
+            ]
+        };
+        lines.push(Line::from(
+            hints
+                .iter()
+                .flat_map(|(k, desc)| [Span::styled(*k, key), Span::raw(*desc)])
+                .collect::<Vec<_>>(),
+        ));
+
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(format!(" Create {}? ", self.name))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .style(Style::default().bg(Color::Black)),
+        );
+        frame.render_widget(paragraph, popup_area);
+    }
+}
+
+impl Default for SessionPlanDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
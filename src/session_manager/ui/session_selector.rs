@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
 
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
 
-use crate::session_manager::session_pair::SessionActivity;
+use crate::history::format_duration;
+use crate::session_manager::git_status::GitStatus;
+use crate::session_manager::session_pair::{CheckStatus, SessionActivity};
 
 /// Categories of items in the session selector
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,10 +20,34 @@ pub enum SelectorItemKind {
     Live,
     /// A recent session from history (should be resumed)
     Recent,
+    /// A recent session of another repository, named `repo/session`; resuming it switches
+    /// the target repo
+    OtherRepo,
     /// A worktree directory (start fresh session)
     Worktree,
 }
 
+/// Extra context shown after a session's name, so sessions can be recognized by what
+/// they were doing
+#[derive(Debug, Clone, Default)]
+pub struct SessionDetail {
+    /// Time since the session was last used
+    pub last_active: Option<Duration>,
+    /// First line of the prompt the session was created with
+    pub excerpt: Option<String>,
+    /// Last checks run in the session's worktree
+    pub check: Option<CheckStatus>,
+    /// What a live session's agent reports doing, shown under its name
+    pub progress: Option<String>,
+    /// Pinned sessions are listed first and left alone by automatic cleanup
+    pub pinned: bool,
+    /// Workflow a live session was created through, when not the repo's default
+    pub workflow: Option<&'static str>,
+}
+
+/// Longest prompt excerpt shown in a row
+const MAX_EXCERPT_WIDTH: usize = 40;
+
 /// A filterable session selector with incremental search.
 pub struct SessionSelector {
     /// The current filter query
@@ -33,8 +60,14 @@ pub struct SessionSelector {
     active_index: Option<usize>,
     /// Number of live sessions
     live_count: usize,
-    /// Number of recent sessions (after live, before worktrees)
+    /// Number of recent sessions (after live, before other repos' sessions)
     recent_count: usize,
+    /// Number of other repos' recent sessions (after recent, before worktrees)
+    other_count: usize,
+    /// Whether other repos' recent sessions are listed
+    all_repos: bool,
+    /// Indices (in the original list) of entries marked for a batch action
+    marked: BTreeSet<usize>,
 }
 
 impl SessionSelector {
@@ -48,6 +81,9 @@ impl SessionSelector {
             active_index: None,
             live_count: 0,
             recent_count: 0,
+            other_count: 0,
+            all_repos: false,
+            marked: BTreeSet::new(),
         }
     }
 
@@ -58,6 +94,29 @@ impl SessionSelector {
         self.state.select(Some(0));
         self.live_count = 0;
         self.recent_count = 0;
+        self.other_count = 0;
+        self.marked.clear();
+    }
+
+    /// Mark or unmark the highlighted entry for a batch action.
+    pub fn toggle_mark(&mut self) {
+        if let Some(idx) = self.selected_original_index()
+            && !self.marked.remove(&idx)
+        {
+            self.marked.insert(idx);
+        }
+    }
+
+    /// Marked entries in list order, with their kinds.
+    pub fn marked(&self) -> Vec<(usize, SelectorItemKind)> {
+        self.marked
+            .iter()
+            .map(|&idx| (idx, self.item_kind(idx)))
+            .collect()
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
     }
 
     /// Set the index of the active session (will be highlighted green).
@@ -66,9 +125,14 @@ impl SessionSelector {
     }
 
     /// Set the counts for different item categories.
-    pub fn set_counts(&mut self, live_count: usize, recent_count: usize) {
+    pub fn set_counts(&mut self, live_count: usize, recent_count: usize, other_count: usize) {
         self.live_count = live_count;
         self.recent_count = recent_count;
+        self.other_count = other_count;
+    }
+
+    pub fn set_all_repos(&mut self, all_repos: bool) {
+        self.all_repos = all_repos;
     }
 
     /// Add a character to the query and update the filter.
@@ -81,6 +145,11 @@ impl SessionSelector {
         self.query.pop();
     }
 
+    /// The current filter text
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
     /// Get the currently selected index in the original session list.
     /// Returns None if no sessions match the filter.
     pub fn selected_original_index(&self) -> Option<usize> {
@@ -100,6 +169,8 @@ impl SessionSelector {
             SelectorItemKind::Live
         } else if idx < self.live_count + self.recent_count {
             SelectorItemKind::Recent
+        } else if idx < self.live_count + self.recent_count + self.other_count {
+            SelectorItemKind::OtherRepo
         } else {
             SelectorItemKind::Worktree
         }
@@ -156,10 +227,10 @@ impl SessionSelector {
         if self.filtered_indices.is_empty() {
             self.state.select(None);
         } else {
+            // Reselect after a filter that matched nothing, e.g. one mid-word
             let current = self.state.selected().unwrap_or(0);
-            if current >= self.filtered_indices.len() {
-                self.state.select(Some(self.filtered_indices.len() - 1));
-            }
+            self.state
+                .select(Some(current.min(self.filtered_indices.len() - 1)));
         }
     }
 
@@ -167,12 +238,21 @@ impl SessionSelector {
     /// `sessions` is a slice of (name, path) tuples.
     /// For worktree directories, name is empty and only path is shown.
     /// `session_states` maps session names to their current activity state.
+    /// `session_slots` maps live session names to their quick-jump number.
+    /// `session_times` maps live session names to their (running, focused) time.
+    /// `session_git` maps live session names to their checkout's git status.
+    /// `session_details` maps live and recent session names to their last activity and prompt.
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &mut self,
         frame: &mut Frame,
         area: Rect,
         sessions: &[(String, String)],
         session_states: &HashMap<String, SessionActivity>,
+        session_slots: &HashMap<String, usize>,
+        session_times: &HashMap<String, (Duration, Duration)>,
+        session_git: &HashMap<String, GitStatus>,
+        session_details: &HashMap<String, SessionDetail>,
     ) {
         // Calculate popup dimensions
         let max_name_len = sessions
@@ -196,7 +276,18 @@ impl SessionSelector {
 
         // Height: input box (3) + list items + borders
         let max_visible = 10usize;
-        let list_height = self.filtered_indices.len().min(max_visible).max(1) as u16;
+        // Sessions reporting progress take a second row
+        let rows = self.filtered_indices.len()
+            + self
+                .filtered_indices
+                .iter()
+                .filter(|&&i| {
+                    session_details
+                        .get(&sessions[i].0)
+                        .is_some_and(|d| d.progress.is_some())
+                })
+                .count();
+        let list_height = rows.min(max_visible).max(1) as u16;
         let popup_height = (3 + list_height + 2).min(area.height - 2);
 
         // Center the popup
@@ -216,14 +307,36 @@ impl SessionSelector {
             popup_area.height - 3,
         );
 
-        // Render input box
+        // Render input box, with the size of each section
+        let worktree_count = sessions
+            .len()
+            .saturating_sub(self.live_count + self.recent_count + self.other_count);
+        let mut counts = vec![
+            format!("{} live", self.live_count),
+            format!("{} recent", self.recent_count),
+        ];
+        if self.all_repos {
+            counts.push(format!("{} other repos", self.other_count));
+        }
+        counts.push(format!("{} worktrees", worktree_count));
         let input_text = format!("{}_", self.query);
         let input = Paragraph::new(input_text)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::White))
-                    .title(" Filter "),
+                    .title(if self.all_repos {
+                        " Filter (all repos) "
+                    } else {
+                        " Filter "
+                    })
+                    .title(
+                        Line::styled(
+                            format!(" {} ", counts.join(" · ")),
+                            Style::default().fg(Color::DarkGray),
+                        )
+                        .right_aligned(),
+                    ),
             )
             .style(Style::default().fg(Color::White));
         frame.render_widget(input, input_area);
@@ -237,7 +350,17 @@ impl SessionSelector {
                 let is_active = self.active_index == Some(i);
                 let kind = self.item_kind(i);
                 let activity = session_states.get(name);
-                let available_width = (popup_width as usize).saturating_sub(4);
+                // With anything marked, every row gets a mark column so names stay aligned
+                let marking = !self.marked.is_empty();
+                let available_width =
+                    (popup_width as usize).saturating_sub(if marking { 6 } else { 4 });
+                let mark_span = marking.then(|| {
+                    if self.marked.contains(&i) {
+                        Span::styled("✓ ", Style::default().fg(Color::Green))
+                    } else {
+                        Span::raw("  ")
+                    }
+                });
 
                 // For worktree directories (empty name), show only the path
                 if name.is_empty() {
@@ -252,13 +375,76 @@ impl SessionSelector {
 
                     let path_style = Style::default().fg(Color::DarkGray);
 
-                    return Line::from(vec![Span::styled(path_display, path_style)]);
+                    let mut spans = Vec::new();
+                    spans.extend(mark_span);
+                    spans.push(Span::styled(path_display, path_style));
+                    return Text::from(Line::from(spans));
                 }
 
                 // Account for status indicator in width calculation (2 chars: "● ")
                 // Live sessions always have an indicator (purple=running, yellow=stopped)
                 let has_indicator = kind == SelectorItemKind::Live;
-                let indicator_width = if has_indicator { 2 } else { 0 };
+                let slot_label = if has_indicator {
+                    session_slots.get(name).map(|slot| format!("{} ", slot))
+                } else {
+                    None
+                };
+                let time_label = if has_indicator {
+                    session_times.get(name).map(|(running, focused)| {
+                        format!(
+                            "  running {} / focused {}",
+                            format_duration(*running),
+                            format_duration(*focused)
+                        )
+                    })
+                } else {
+                    None
+                };
+                let git = session_git.get(name).filter(|_| has_indicator);
+                let sync_label = git
+                    .and_then(|git| git.ahead_behind_label())
+                    .map(|label| format!("  {}", label));
+                let conflict_label = git
+                    .filter(|git| !git.conflicts.is_empty())
+                    .map(|_| "  ✗ conflicts".to_string());
+                let check_label = session_details
+                    .get(name)
+                    .and_then(|d| d.check)
+                    .map(|check| match check {
+                        CheckStatus::Running => ("  … checks".to_string(), Color::Yellow),
+                        CheckStatus::Passed => ("  ✓ checks".to_string(), Color::Green),
+                        CheckStatus::Failed(code) => (format!("  ✗ checks ({})", code), Color::Red),
+                    });
+                let detail_label = session_details
+                    .get(name)
+                    .map(detail_label)
+                    .filter(|label| !label.is_empty());
+                let pin_label = session_details
+                    .get(name)
+                    .is_some_and(|d| d.pinned)
+                    .then_some("  pinned");
+                let workflow_label = session_details
+                    .get(name)
+                    .and_then(|d| d.workflow)
+                    .map(|workflow| format!("  via {}", workflow));
+                let indicator_width = if has_indicator { 2 } else { 0 }
+                    + pin_label.map_or(0, str::len)
+                    + workflow_label.as_ref().map_or(0, String::len)
+                    + slot_label.as_ref().map(|l| l.len()).unwrap_or(0)
+                    + time_label.as_ref().map(|l| l.len()).unwrap_or(0)
+                    + sync_label.as_ref().map(|l| l.chars().count()).unwrap_or(0)
+                    + conflict_label
+                        .as_ref()
+                        .map(|l| l.chars().count())
+                        .unwrap_or(0)
+                    + check_label
+                        .as_ref()
+                        .map(|(l, _)| l.chars().count())
+                        .unwrap_or(0)
+                    + detail_label
+                        .as_ref()
+                        .map(|l| l.chars().count())
+                        .unwrap_or(0);
                 let path_width = available_width
                     .saturating_sub(name.len() + 3)
                     .saturating_sub(indicator_width);
@@ -275,15 +461,17 @@ impl SessionSelector {
                     .saturating_sub(indicator_width);
 
                 // Active session: green, recent: dark gray, normal live: white
+                let from_history =
+                    matches!(kind, SelectorItemKind::Recent | SelectorItemKind::OtherRepo);
                 let name_style = if is_active {
                     Style::default().fg(Color::Green)
-                } else if kind == SelectorItemKind::Recent {
+                } else if from_history {
                     Style::default().fg(Color::DarkGray)
                 } else {
                     Style::default().fg(Color::White)
                 };
 
-                let path_style = if kind == SelectorItemKind::Recent {
+                let path_style = if from_history {
                     Style::default().fg(Color::DarkGray)
                 } else {
                     Style::default().fg(Color::Gray)
@@ -291,29 +479,102 @@ impl SessionSelector {
 
                 // Build spans with status indicator for live sessions
                 let mut spans = Vec::new();
+                spans.extend(mark_span);
+                if let Some(label) = slot_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::DarkGray)));
+                }
                 if has_indicator {
-                    let indicator_color = match activity {
-                        Some(SessionActivity::Stopped) => Color::Yellow, // Needs attention
-                        Some(SessionActivity::RunningTool(_)) => Color::Cyan, // Running a tool
-                        _ => Color::Magenta,                             // Active/default
+                    let (indicator, indicator_color) = match activity {
+                        Some(SessionActivity::Stopped) => ("● ", Color::Yellow), // Needs attention
+                        Some(SessionActivity::RunningTool(_)) => ("● ", Color::Cyan), // Running a tool
+                        Some(SessionActivity::Paused) => ("‖ ", Color::Blue),         // Suspended
+                        Some(SessionActivity::Flagged(_)) => ("! ", Color::Red), // Watch rule matched
+                        _ => ("● ", Color::Magenta),                             // Active/default
                     };
-                    spans.push(Span::styled("● ", Style::default().fg(indicator_color)));
+                    spans.push(Span::styled(
+                        indicator,
+                        Style::default().fg(indicator_color),
+                    ));
                 }
                 spans.push(Span::styled(name.clone(), name_style));
+                if let Some(label) = pin_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::Yellow)));
+                }
+                if let Some(label) = workflow_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::Cyan)));
+                }
+                if let Some(label) = time_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::DarkGray)));
+                }
+                if let Some(label) = sync_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::Cyan)));
+                }
+                if let Some(label) = conflict_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::Red)));
+                }
+                if let Some((label, color)) = check_label {
+                    spans.push(Span::styled(label, Style::default().fg(color)));
+                }
+                if let Some(label) = detail_label {
+                    spans.push(Span::styled(label, Style::default().fg(Color::DarkGray)));
+                }
                 spans.push(Span::raw(" ".repeat(padding)));
                 spans.push(Span::styled(path_display, path_style));
 
-                Line::from(spans)
+                let line = Line::from(spans);
+                match session_details.get(name).and_then(|d| d.progress.as_ref()) {
+                    Some(progress) => {
+                        let progress: String = progress
+                            .chars()
+                            .take(available_width.saturating_sub(4))
+                            .collect();
+                        Text::from(vec![
+                            line,
+                            Line::styled(
+                                format!("    {}", progress),
+                                Style::default()
+                                    .fg(Color::DarkGray)
+                                    .add_modifier(Modifier::ITALIC),
+                            ),
+                        ])
+                    }
+                    None => Text::from(line),
+                }
             })
             .map(ListItem::new)
             .collect();
 
+        // Nothing matches: Enter creates a session named after the filter
+        let items = if items.is_empty() && !self.query.trim().is_empty() {
+            vec![ListItem::new(Line::from(vec![
+                Span::styled("Enter", Style::default().fg(Color::Magenta)),
+                Span::styled(
+                    format!(": create session '{}'", self.query.trim()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))]
+        } else {
+            items
+        };
+
+        let mut list_block = Block::default()
+            .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
+            .border_style(Style::default().fg(Color::White));
+        if !self.marked.is_empty() {
+            list_block = list_block.title_bottom(
+                Line::from(Span::styled(
+                    format!(
+                        " {} marked · ^X kill · ^B resume · ^O dashboard ",
+                        self.marked.len()
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ))
+                .centered(),
+            );
+        }
+
         let list = List::new(items)
-            .block(
-                Block::default()
-                    .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
-                    .border_style(Style::default().fg(Color::White)),
-            )
+            .block(list_block)
             .highlight_style(
                 Style::default()
                     .bg(Color::Magenta)
@@ -325,6 +586,24 @@ impl SessionSelector {
     }
 }
 
+/// "  2m ago · fix the login redirect", with the excerpt shortened to fit a row
+fn detail_label(detail: &SessionDetail) -> String {
+    let mut label = String::new();
+    if let Some(ago) = detail.last_active {
+        label.push_str(&format!("  {} ago", format_duration(ago)));
+    }
+    if let Some(ref excerpt) = detail.excerpt {
+        let excerpt = if excerpt.chars().count() > MAX_EXCERPT_WIDTH {
+            let short: String = excerpt.chars().take(MAX_EXCERPT_WIDTH - 1).collect();
+            format!("{}…", short)
+        } else {
+            excerpt.clone()
+        };
+        label.push_str(&format!("  · {}", excerpt));
+    }
+    label
+}
+
 impl Default for SessionSelector {
     fn default() -> Self {
         Self::new()
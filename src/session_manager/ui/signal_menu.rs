@@ -0,0 +1,164 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+/// A signal offered by the menu: shortcut key, name, signal number and what it usually does
+pub struct SignalChoice {
+    pub key: u8,
+    pub name: &'static str,
+    pub signal: i32,
+    pub description: &'static str,
+}
+
+pub const SIGNALS: &[SignalChoice] = &[
+    SignalChoice {
+        key: b'i',
+        name: "INT",
+        signal: libc::SIGINT,
+        description: "interrupt",
+    },
+    SignalChoice {
+        key: b't',
+        name: "TERM",
+        signal: libc::SIGTERM,
+        description: "terminate",
+    },
+    SignalChoice {
+        key: b'h',
+        name: "HUP",
+        signal: libc::SIGHUP,
+        description: "hang up / reload",
+    },
+    SignalChoice {
+        key: b'u',
+        name: "USR1",
+        signal: libc::SIGUSR1,
+        description: "user defined",
+    },
+    SignalChoice {
+        key: b'k',
+        name: "KILL",
+        signal: libc::SIGKILL,
+        description: "kill (cannot be trapped)",
+    },
+];
+
+/// Popup for sending a signal to the foreground process of the active session
+pub struct SignalMenu {
+    state: ListState,
+}
+
+impl SignalMenu {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { state }
+    }
+
+    /// Reset the selection for a fresh view.
+    pub fn reset(&mut self) {
+        self.state.select(Some(0));
+    }
+
+    /// Move selection up, wrapping to the bottom.
+    pub fn move_up(&mut self) {
+        let current = self.state.selected().unwrap_or(0);
+        let next = if current == 0 {
+            SIGNALS.len() - 1
+        } else {
+            current - 1
+        };
+        self.state.select(Some(next));
+    }
+
+    /// Move selection down, wrapping to the top.
+    pub fn move_down(&mut self) {
+        let current = self.state.selected().unwrap_or(0);
+        self.state.select(Some((current + 1) % SIGNALS.len()));
+    }
+
+    /// The highlighted signal.
+    pub fn selected(&self) -> &'static SignalChoice {
+        &SIGNALS[self.state.selected().unwrap_or(0).min(SIGNALS.len() - 1)]
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, target: &str) {
+        let popup_width = 44.min(area.width.saturating_sub(4));
+        let popup_height = (SIGNALS.len() as u16 + 2 + 2).min(area.height.saturating_sub(2));
+
+        let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+        let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+        let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" Send signal to {} ", target))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        let list_area = Rect::new(
+            inner.x,
+            inner.y,
+            inner.width,
+            inner.height.saturating_sub(2),
+        );
+        let items: Vec<ListItem> = SIGNALS
+            .iter()
+            .map(|choice| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}  ", choice.key as char),
+                        Style::default()
+                            .fg(Color::Magenta)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("SIG{:<6}", choice.name),
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(choice.description, Style::default().fg(Color::DarkGray)),
+                ])
+            })
+            .map(ListItem::new)
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+
+        let footer_area = Rect::new(
+            inner.x,
+            inner.y + inner.height.saturating_sub(1),
+            inner.width,
+            1,
+        );
+        let key_style = Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+        let footer = Paragraph::new(Line::from(vec![
+            Span::styled("Enter", key_style),
+            Span::raw(": send  "),
+            Span::styled("Esc", key_style),
+            Span::raw(": cancel"),
+        ]));
+        frame.render_widget(footer, footer_area);
+    }
+}
+
+impl Default for SignalMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,17 +1,31 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
 
+pub use crate::config::StatusLevel;
+use crate::config::{Config, StatusTimeouts};
+use crate::error::ShepardError;
+
+use super::event_log::{EventLog, event_log_path};
+
 use ratatui::{
     style::{Color, Modifier, Style},
     text::{Line, Span},
 };
 
-const MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum StatusLevel {
-    Info,
-    Err,
+/// Minimum time a message stays up before a queued one replaces it
+const MIN_DISPLAY: Duration = Duration::from_secs(2);
+/// Messages waiting behind the current one; older ones are dropped past this
+const MAX_QUEUED: usize = 5;
+
+fn level_color(level: StatusLevel) -> Color {
+    match level {
+        StatusLevel::Info => Color::Cyan,
+        StatusLevel::Success => Color::Green,
+        StatusLevel::Warn => Color::Yellow,
+        StatusLevel::Err => Color::Red,
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -19,6 +33,14 @@ pub struct StatusMessage {
     pub level: StatusLevel,
     pub display_message: String,
     pub log_message: String,
+    /// How long to show the message; defaults to the configured timeout for its level
+    pub duration: Option<Duration>,
+}
+
+impl From<ShepardError> for StatusMessage {
+    fn from(error: ShepardError) -> Self {
+        StatusMessage::err(error.summary(), error.to_string())
+    }
 }
 
 impl StatusMessage {
@@ -31,13 +53,28 @@ impl StatusMessage {
             level,
             display_message: display_message.into(),
             log_message: log_message.into(),
+            duration: None,
         }
     }
 
+    /// Show the message for `duration` instead of its level's timeout
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
     pub fn info(display: impl Into<String>, log: impl Into<String>) -> Self {
         Self::new(StatusLevel::Info, display, log)
     }
 
+    pub fn success(display: impl Into<String>, log: impl Into<String>) -> Self {
+        Self::new(StatusLevel::Success, display, log)
+    }
+
+    pub fn warn(display: impl Into<String>, log: impl Into<String>) -> Self {
+        Self::new(StatusLevel::Warn, display, log)
+    }
+
     pub fn err(display: impl Into<String>, log: impl Into<String>) -> Self {
         Self::new(StatusLevel::Err, display, log)
     }
@@ -51,41 +88,79 @@ struct ActiveMessage {
 pub struct StatusBar {
     rx: Receiver<StatusMessage>,
     current: Option<ActiveMessage>,
+    /// Messages received while another was still within its minimum display time
+    queue: VecDeque<StatusMessage>,
+    timeouts: StatusTimeouts,
     event_log: EventLog,
 }
 
 impl StatusBar {
-    pub fn new() -> (Self, Sender<StatusMessage>) {
+    pub fn new(config: &Config) -> (Self, Sender<StatusMessage>) {
+        Self::with_event_log(config, event_log_path())
+    }
+
+    /// A status bar logging its messages to `path` rather than the default event log
+    pub fn with_event_log(config: &Config, path: Option<PathBuf>) -> (Self, Sender<StatusMessage>) {
         let (tx, rx) = mpsc::channel();
-        let event_log = EventLog::new();
+        let event_log = EventLog::at(path, config.event_log.clone());
         (
             Self {
                 rx,
                 current: None,
+                queue: VecDeque::new(),
+                timeouts: config.status_timeouts,
                 event_log,
             },
             tx,
         )
     }
 
-    pub fn update(&mut self) {
-        // Check for new messages
+    /// Receive new messages, advance the queue and clear expired ones. Returns true if
+    /// the display changed.
+    pub fn update(&mut self) -> bool {
         while let Ok(msg) = self.rx.try_recv() {
             self.event_log.append(&msg);
-            self.current = Some(ActiveMessage {
-                message: msg,
-                received_at: Instant::now(),
-            });
+            self.queue.push_back(msg);
+            if self.queue.len() > MAX_QUEUED {
+                self.queue.pop_front();
+            }
         }
 
-        // Clear expired messages
-        if let Some(ref active) = self.current
-            && active.received_at.elapsed() >= MESSAGE_TIMEOUT
-        {
-            self.current = None;
+        let shown = self.current.as_ref().map(|active| {
+            let timeout = active
+                .message
+                .duration
+                .unwrap_or_else(|| self.timeouts.for_level(active.message.level));
+            (active.received_at.elapsed(), timeout)
+        });
+        match shown {
+            // Nothing shown: show the next message, if any
+            None => self.advance(),
+            // Let queued messages through once the current one has had its minimum time
+            Some((elapsed, _)) if elapsed >= MIN_DISPLAY && !self.queue.is_empty() => {
+                self.advance()
+            }
+            Some((elapsed, timeout)) if elapsed >= timeout => self.advance(),
+            Some(_) => false,
         }
     }
 
+    /// Dismiss the current message, showing the next queued one. Returns true if the
+    /// display changed.
+    pub fn dismiss(&mut self) -> bool {
+        self.current.is_some() && self.advance()
+    }
+
+    /// Replace the current message with the next queued one (or nothing)
+    fn advance(&mut self) -> bool {
+        let had_current = self.current.is_some();
+        self.current = self.queue.pop_front().map(|message| ActiveMessage {
+            message,
+            received_at: Instant::now(),
+        });
+        had_current || self.current.is_some()
+    }
+
     pub fn render_bottom_left(&self) -> Line<'static> {
         Line::from(vec![
             Span::raw(" "),
@@ -101,75 +176,28 @@ impl StatusBar {
 
     pub fn render_bottom_center(&self) -> Option<Line<'static>> {
         self.current.as_ref().map(|active| {
-            let color = match active.message.level {
-                StatusLevel::Info => Color::Cyan,
-                StatusLevel::Err => Color::Red,
-            };
-            let style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+            let style = Style::default()
+                .fg(level_color(active.message.level))
+                .add_modifier(Modifier::BOLD);
 
-            Line::from(vec![
+            let mut spans = vec![
                 Span::raw(" "),
                 Span::styled(active.message.display_message.clone(), style),
                 Span::raw(" "),
-            ])
+            ];
+            if !self.queue.is_empty() {
+                spans.push(Span::styled(
+                    format!("(+{}) ", self.queue.len()),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
         })
     }
 }
 
 impl Default for StatusBar {
     fn default() -> Self {
-        Self::new().0
-    }
-}
-
-const MAX_LOG_LINES: usize = 1000;
-
-struct EventLog {
-    path: Option<std::path::PathBuf>,
-}
-
-impl EventLog {
-    fn new() -> Self {
-        let path = dirs::home_dir().map(|h| h.join(".shepherd").join("events.log"));
-        Self { path }
-    }
-
-    fn append(&mut self, msg: &StatusMessage) {
-        let Some(ref path) = self.path else {
-            return;
-        };
-
-        // Ensure directory exists
-        if let Some(parent) = path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-
-        // Read existing entries
-        let mut entries: Vec<String> = if path.exists() {
-            std::fs::read_to_string(path)
-                .unwrap_or_default()
-                .lines()
-                .map(String::from)
-                .collect()
-        } else {
-            Vec::new()
-        };
-
-        // Create new entry with timestamp and level
-        let level_str = match msg.level {
-            StatusLevel::Info => "INFO",
-            StatusLevel::Err => "ERR",
-        };
-        let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-        let entry = format!("[{}] [{}] {}", timestamp, level_str, msg.log_message);
-        entries.push(entry);
-
-        // Keep only the most recent entries
-        if entries.len() > MAX_LOG_LINES {
-            entries = entries.split_off(entries.len() - MAX_LOG_LINES);
-        }
-
-        // Write back
-        let _ = std::fs::write(path, entries.join("\n") + "\n");
+        Self::new(&Config::default()).0
     }
 }
@@ -8,6 +8,8 @@ use ratatui::{
 use crate::pty_widget::PtyWidget;
 use crate::session::AttachedSession;
 
+use super::render_throttled_badge;
+
 /// Terminal multiplexer managing multiple shell panes
 pub struct TerminalMultiplexer {
     panes: Vec<AttachedSession>,
@@ -53,16 +55,37 @@ impl TerminalMultiplexer {
         self.active_pane = (self.active_pane + 1) % self.panes.len();
     }
 
+    /// Get the active pane
+    pub fn active_pane(&self) -> Option<&AttachedSession> {
+        self.panes.get(self.active_pane)
+    }
+
     /// Get mutable reference to the active pane for input
     pub fn active_pane_mut(&mut self) -> Option<&mut AttachedSession> {
         self.panes.get_mut(self.active_pane)
     }
 
+    pub fn panes(&self) -> impl Iterator<Item = &AttachedSession> {
+        self.panes.iter()
+    }
+
     /// Check if the multiplexer is empty
     pub fn is_empty(&self) -> bool {
         self.panes.is_empty()
     }
 
+    /// Apply the current shared size to every pane's PTY
+    pub fn sync_sizes(&self) {
+        for pane in &self.panes {
+            pane.sync_size();
+        }
+    }
+
+    /// Check if any pane has output that hasn't been rendered yet
+    pub fn is_dirty(&self) -> bool {
+        self.panes.iter().any(|p| p.is_dirty())
+    }
+
     /// Remove dead panes and return them for cleanup
     pub fn remove_dead_panes(&mut self) -> Vec<AttachedSession> {
         let mut dead = Vec::new();
@@ -117,11 +140,13 @@ impl TerminalMultiplexer {
         // Single pane: no dividers needed
         if self.panes.len() == 1 {
             let pane = &self.panes[0];
-            let screen = pane.get_screen();
-            let (cursor_row, cursor_col) = screen.cursor_position();
-
-            let widget = PtyWidget::new(&screen);
-            frame.render_widget(widget, area);
+            let (cursor_row, cursor_col) = pane.with_screen(0, |screen| {
+                frame.render_widget(PtyWidget::new(screen), area);
+                screen.cursor_position()
+            });
+            if pane.is_throttled() {
+                render_throttled_badge(frame, area);
+            }
 
             let cursor_x = area.x + cursor_col;
             let cursor_y = area.y + cursor_row;
@@ -157,11 +182,13 @@ impl TerminalMultiplexer {
             let pane_area = chunks[i * 2];
 
             // Render the terminal content
-            let screen = pane.get_screen();
-            let (cursor_row, cursor_col) = screen.cursor_position();
-
-            let widget = PtyWidget::new(&screen).dimmed(!is_active);
-            frame.render_widget(widget, pane_area);
+            let (cursor_row, cursor_col) = pane.with_screen(0, |screen| {
+                frame.render_widget(PtyWidget::new(screen).dimmed(!is_active), pane_area);
+                screen.cursor_position()
+            });
+            if pane.is_throttled() {
+                render_throttled_badge(frame, pane_area);
+            }
 
             // Position the cursor in the active pane
             if is_active {
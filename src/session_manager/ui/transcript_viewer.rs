@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::transcript::{EntryKind, TranscriptEntry};
+
+/// Scrollable view of a Claude transcript. Each message is one collapsible row; user and
+/// assistant messages start expanded, tool calls and results collapsed.
+pub struct TranscriptViewer {
+    title: String,
+    entries: Vec<TranscriptEntry>,
+    expanded: HashSet<usize>,
+    selected: usize,
+    /// First visible line, kept so the selection stays in view
+    scroll: usize,
+}
+
+impl TranscriptViewer {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            entries: Vec::new(),
+            expanded: HashSet::new(),
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Show `entries`, starting at the end of the conversation
+    pub fn open(&mut self, title: String, entries: Vec<TranscriptEntry>) {
+        self.expanded = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e.kind, EntryKind::User | EntryKind::Assistant))
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = entries.len().saturating_sub(1);
+        self.scroll = usize::MAX;
+        self.title = title;
+        self.entries = entries;
+    }
+
+    pub fn move_up(&mut self, n: usize) {
+        self.selected = self.selected.saturating_sub(n);
+    }
+
+    pub fn move_down(&mut self, n: usize) {
+        self.selected = (self.selected + n).min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    pub fn select_last(&mut self) {
+        self.selected = self.entries.len().saturating_sub(1);
+    }
+
+    /// Expand or collapse the highlighted message
+    pub fn toggle(&mut self) {
+        if !self.expanded.remove(&self.selected) {
+            self.expanded.insert(self.selected);
+        }
+    }
+
+    /// Expand everything, or collapse everything when all is already expanded
+    pub fn toggle_all(&mut self) {
+        if self.expanded.len() == self.entries.len() {
+            self.expanded.clear();
+        } else {
+            self.expanded = (0..self.entries.len()).collect();
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let popup_area = Rect::new(
+            area.x + 2,
+            area.y + 1,
+            area.width.saturating_sub(4),
+            area.height.saturating_sub(2),
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(format!(" Transcript: {} ", self.title))
+            .title_bottom(" up/down: move  enter: expand  a: expand all  esc: close ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::White))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.entries.is_empty() {
+            let empty = Paragraph::new("The transcript is empty")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, inner);
+            return;
+        }
+
+        let width = inner.width.saturating_sub(4).max(1) as usize;
+        let mut lines: Vec<Line> = Vec::new();
+        let mut selected_span = (0, 0);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let start = lines.len();
+            let expanded = self.expanded.contains(&i);
+            let (label, color) = match &entry.kind {
+                EntryKind::User => ("You".to_string(), Color::Green),
+                EntryKind::Assistant => ("Claude".to_string(), Color::Magenta),
+                EntryKind::ToolUse(name) => (format!("Tool {}", name), Color::Cyan),
+                EntryKind::ToolResult => ("Result".to_string(), Color::DarkGray),
+            };
+            let marker = if expanded { "▾ " } else { "▸ " };
+            let mut header_style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+            if i == self.selected {
+                header_style = header_style.bg(Color::DarkGray);
+            }
+            let mut header = vec![Span::styled(format!("{}{}", marker, label), header_style)];
+            if !expanded {
+                let first: String = entry.text.lines().next().unwrap_or_default().into();
+                let first: String = first
+                    .chars()
+                    .take(width.saturating_sub(label.len()))
+                    .collect();
+                header.push(Span::styled(
+                    format!("  {}", first),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            lines.push(Line::from(header));
+            if expanded {
+                for text_line in entry.text.lines() {
+                    for chunk in wrap(text_line, width) {
+                        lines.push(Line::from(Span::raw(format!("  {}", chunk))));
+                    }
+                }
+                lines.push(Line::default());
+            }
+            if i == self.selected {
+                selected_span = (start, lines.len());
+            }
+        }
+
+        // Scroll just enough to show the selected message's header and as much of it as fits
+        let height = inner.height as usize;
+        let max_scroll = lines.len().saturating_sub(height);
+        let (start, end) = selected_span;
+        if start < self.scroll {
+            self.scroll = start;
+        } else if end > self.scroll + height {
+            self.scroll = end.saturating_sub(height).min(start);
+        }
+        self.scroll = self.scroll.min(max_scroll);
+
+        let visible: Vec<Line> = lines.into_iter().skip(self.scroll).take(height).collect();
+        frame.render_widget(Paragraph::new(visible), inner);
+    }
+}
+
+impl Default for TranscriptViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split `line` into chunks of at most `width` characters
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![String::new()];
+    }
+    chars.chunks(width).map(|c| c.iter().collect()).collect()
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeSet;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::session_manager::checkpoints::{ChangedFile, Checkpoint};
+
+/// Files changed in the active session's worktree since its latest checkpoint, to pick
+/// which ones to put back
+pub struct UndoChangesDialog {
+    session: String,
+    checkpoint: Option<Checkpoint>,
+    files: Vec<ChangedFile>,
+    state: ListState,
+    /// Indices of the files marked for reverting
+    marked: BTreeSet<usize>,
+    /// Waiting for y/n on reverting
+    confirm: bool,
+}
+
+impl UndoChangesDialog {
+    pub fn new() -> Self {
+        Self {
+            session: String::new(),
+            checkpoint: None,
+            files: Vec::new(),
+            state: ListState::default(),
+            marked: BTreeSet::new(),
+            confirm: false,
+        }
+    }
+
+    pub fn open(&mut self, session: String, checkpoint: Checkpoint, files: Vec<ChangedFile>) {
+        self.session = session;
+        self.checkpoint = Some(checkpoint);
+        self.files = files;
+        self.state.select(Some(0));
+        self.marked.clear();
+        self.confirm = false;
+    }
+
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    pub fn move_up(&mut self) {
+        let i = self.state.selected().unwrap_or(0).saturating_sub(1);
+        self.state.select(Some(i));
+    }
+
+    pub fn move_down(&mut self) {
+        let last = self.files.len().saturating_sub(1);
+        let i = (self.state.selected().unwrap_or(0) + 1).min(last);
+        self.state.select(Some(i));
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(i) = self.state.selected() else {
+            return;
+        };
+        if !self.marked.remove(&i) {
+            self.marked.insert(i);
+        }
+    }
+
+    /// Mark every file, or clear the marks if all are marked already
+    pub fn toggle_all(&mut self) {
+        if self.marked.len() == self.files.len() {
+            self.marked.clear();
+        } else {
+            self.marked = (0..self.files.len()).collect();
+        }
+    }
+
+    /// Files to revert: the marked ones, or the highlighted one if none are marked
+    pub fn to_revert(&self) -> Vec<ChangedFile> {
+        if self.marked.is_empty() {
+            return self
+                .state
+                .selected()
+                .and_then(|i| self.files.get(i))
+                .cloned()
+                .into_iter()
+                .collect();
+        }
+        self.marked.iter().map(|&i| self.files[i].clone()).collect()
+    }
+
+    pub fn confirming(&self) -> bool {
+        self.confirm
+    }
+
+    pub fn set_confirm(&mut self, confirm: bool) {
+        self.confirm = confirm;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let height = (self.files.len() as u16 + 2).clamp(5, area.height.saturating_sub(2));
+        let width = area.width.saturating_sub(4).min(80);
+        let popup_area = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        );
+        frame.render_widget(Clear, popup_area);
+
+        let footer = if self.confirm {
+            format!(" revert {} file(s)?  y/n ", self.to_revert().len())
+        } else {
+            " space: mark  a: all  enter: revert  esc: close ".to_string()
+        };
+        let since = self
+            .checkpoint
+            .as_ref()
+            .map(|c| format!(" #{}", c.number))
+            .unwrap_or_default();
+        let block = Block::default()
+            .title(format!(
+                " Undo changes since checkpoint{}: {} ",
+                since, self.session
+            ))
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if self.confirm {
+                Color::Yellow
+            } else {
+                Color::White
+            }))
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        frame.render_widget(block, popup_area);
+
+        if self.files.is_empty() {
+            let empty = Paragraph::new("No changes since the checkpoint")
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(empty, inner);
+            return;
+        }
+
+        let items: Vec<ListItem> = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| {
+                let checkbox = if self.marked.contains(&i) {
+                    Span::styled("[x] ", Style::default().fg(Color::Green))
+                } else {
+                    Span::styled("[ ] ", Style::default().fg(Color::Gray))
+                };
+                let color = match file.status {
+                    'A' => Color::Green,
+                    'D' => Color::Red,
+                    _ => Color::Yellow,
+                };
+                ListItem::new(Line::from(vec![
+                    checkbox,
+                    Span::styled(format!("{} ", file.status), Style::default().fg(color)),
+                    Span::styled(file.path.clone(), Style::default().fg(Color::White)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, inner, &mut self.state);
+    }
+}
+
+impl Default for UndoChangesDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
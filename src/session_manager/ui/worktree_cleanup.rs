@@ -7,11 +7,24 @@ use ratatui::{
 };
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::history::format_duration;
+use crate::session_manager::git_status::BranchInfo;
+
+/// A worktree offered for cleanup, with what its branch holds
+#[derive(Debug)]
+pub struct WorktreeEntry {
+    pub path: PathBuf,
+    pub info: BranchInfo,
+    /// Pid of another shepherd with a session in this worktree
+    pub owner: Option<u32>,
+}
 
 /// A dialog for selecting and deleting worktrees.
 pub struct WorktreeCleanupDialog {
-    /// List of worktree paths
-    worktrees: Vec<PathBuf>,
+    /// Worktrees, safest to delete first
+    worktrees: Vec<WorktreeEntry>,
     /// Selection state for the list
     state: ListState,
     /// Set of selected indices (multi-select)
@@ -22,6 +35,8 @@ pub struct WorktreeCleanupDialog {
     filtered_indices: Vec<usize>,
     /// Paths that have active sessions
     active_paths: HashSet<PathBuf>,
+    /// Shown instead of the list while worktrees are being read or deleted
+    pending: Option<String>,
 }
 
 impl WorktreeCleanupDialog {
@@ -35,6 +50,7 @@ impl WorktreeCleanupDialog {
             query: String::new(),
             filtered_indices: Vec::new(),
             active_paths: HashSet::new(),
+            pending: None,
         }
     }
 
@@ -45,17 +61,26 @@ impl WorktreeCleanupDialog {
         self.query.clear();
         self.filtered_indices.clear();
         self.active_paths.clear();
+        self.pending = None;
         self.state.select(Some(0));
     }
 
-    /// Set the list of worktrees to display with active session info.
+    /// Show `message` until the next `set_worktrees_with_active`
+    pub fn set_pending(&mut self, message: impl Into<String>) {
+        self.pending = Some(message.into());
+    }
+
+    /// Set the list of worktrees to display with active session info. Merged branches
+    /// come first, then unmerged ones; each group oldest last commit first.
     pub fn set_worktrees_with_active(
         &mut self,
-        worktrees: Vec<PathBuf>,
+        mut worktrees: Vec<WorktreeEntry>,
         active_paths: HashSet<PathBuf>,
     ) {
+        worktrees.sort_by_key(|w| (!w.info.merged, w.info.last_commit.unwrap_or(i64::MAX)));
         self.worktrees = worktrees;
         self.active_paths = active_paths;
+        self.pending = None;
         self.selected.clear();
         self.update_filter();
     }
@@ -78,11 +103,20 @@ impl WorktreeCleanupDialog {
             .worktrees
             .iter()
             .enumerate()
-            .filter(|(_, path)| {
+            .filter(|(_, entry)| {
                 if query_lower.is_empty() {
                     true
                 } else {
-                    path.to_string_lossy().to_lowercase().contains(&query_lower)
+                    entry
+                        .path
+                        .to_string_lossy()
+                        .to_lowercase()
+                        .contains(&query_lower)
+                        || entry
+                            .info
+                            .branch
+                            .as_ref()
+                            .is_some_and(|b| b.to_lowercase().contains(&query_lower))
                 }
             })
             .map(|(i, _)| i)
@@ -149,7 +183,7 @@ impl WorktreeCleanupDialog {
     pub fn get_selected_worktrees(&self) -> Vec<PathBuf> {
         self.selected
             .iter()
-            .filter_map(|&idx| self.worktrees.get(idx).cloned())
+            .filter_map(|&idx| self.worktrees.get(idx).map(|w| w.path.clone()))
             .collect()
     }
 
@@ -157,12 +191,7 @@ impl WorktreeCleanupDialog {
     pub fn get_current_worktree(&self) -> Option<PathBuf> {
         let filtered_idx = self.state.selected()?;
         let original_idx = self.filtered_indices.get(filtered_idx)?;
-        self.worktrees.get(*original_idx).cloned()
-    }
-
-    /// Check if there are any worktrees to display.
-    pub fn is_empty(&self) -> bool {
-        self.worktrees.is_empty()
+        self.worktrees.get(*original_idx).map(|w| w.path.clone())
     }
 
     /// Render the worktree cleanup dialog.
@@ -171,12 +200,12 @@ impl WorktreeCleanupDialog {
         let max_path_len = self
             .worktrees
             .iter()
-            .map(|p| p.to_string_lossy().len())
+            .map(|w| w.path.to_string_lossy().len())
             .max()
             .unwrap_or(20);
 
-        // Width: checkbox (4) + path + padding + borders
-        let content_width = 4 + max_path_len + 4;
+        // Width: checkbox (4) + path + branch details + padding + borders
+        let content_width = 4 + max_path_len + DETAILS_WIDTH + 4;
         let popup_width = content_width.max(50).min(area.width as usize - 4) as u16;
 
         // Height: warning (2) + input (3) + list items + footer (2) + borders
@@ -232,7 +261,11 @@ impl WorktreeCleanupDialog {
         let list_area_height = inner.height.saturating_sub(2 + 3 + 2);
         let list_area = Rect::new(inner.x, inner.y + 5, inner.width, list_area_height);
 
-        if self.worktrees.is_empty() {
+        if let Some(ref pending) = self.pending {
+            let pending_msg =
+                Paragraph::new(pending.as_str()).style(Style::default().fg(Color::Yellow));
+            frame.render_widget(pending_msg, list_area);
+        } else if self.worktrees.is_empty() {
             let empty_msg =
                 Paragraph::new("No worktrees found").style(Style::default().fg(Color::DarkGray));
             frame.render_widget(empty_msg, list_area);
@@ -242,12 +275,19 @@ impl WorktreeCleanupDialog {
                 .filtered_indices
                 .iter()
                 .map(|&i| {
-                    let path = &self.worktrees[i];
+                    let WorktreeEntry { path, info, owner } = &self.worktrees[i];
                     let is_selected = self.selected.contains(&i);
                     let is_active = self.active_paths.contains(path);
                     let active_marker = if is_active { " [ACTIVE]" } else { "" };
-                    let available_width =
-                        (popup_width as usize).saturating_sub(8 + active_marker.len()); // borders + checkbox + marker
+                    let owner_marker = owner.map(|pid| format!(" [pid {}]", pid));
+                    let details = branch_details(info, path);
+                    let details_width: usize = details.iter().map(|s| s.width()).sum();
+                    // borders + checkbox + details + markers
+                    let available_width = (popup_width as usize).saturating_sub(
+                        8 + details_width
+                            + active_marker.len()
+                            + owner_marker.as_ref().map_or(0, String::len),
+                    );
 
                     let path_str = path.to_string_lossy();
                     let path_display = if path_str.len() > available_width {
@@ -269,6 +309,7 @@ impl WorktreeCleanupDialog {
                         checkbox,
                         Span::styled(path_display, Style::default().fg(Color::White)),
                     ];
+                    spans.extend(details);
                     if is_active {
                         spans.push(Span::styled(
                             " [ACTIVE]",
@@ -277,6 +318,13 @@ impl WorktreeCleanupDialog {
                                 .add_modifier(Modifier::BOLD),
                         ));
                     }
+                    // In use by another shepherd, which keeps it from being deleted here
+                    if let Some(owner_marker) = owner_marker {
+                        spans.push(Span::styled(
+                            owner_marker,
+                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        ));
+                    }
 
                     Line::from(spans)
                 })
@@ -323,6 +371,47 @@ impl WorktreeCleanupDialog {
     }
 }
 
+/// Room reserved for a row's branch details
+const DETAILS_WIDTH: usize = 36;
+
+/// "  feat/x  merged  3d ago": the branch (when it differs from the directory name),
+/// whether it is merged or has uncommitted work, and the age of its last commit
+fn branch_details(info: &BranchInfo, path: &std::path::Path) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let dir_name = path.file_name().map(|n| n.to_string_lossy());
+    match info.branch {
+        Some(ref branch) if dir_name.as_deref() != Some(branch.as_str()) => {
+            spans.push(Span::styled(
+                format!("  {}", branch),
+                Style::default().fg(Color::Gray),
+            ));
+        }
+        Some(_) => {}
+        None => spans.push(Span::styled(
+            "  (detached)",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+
+    let (label, color) = if info.dirty {
+        ("  uncommitted", Color::Red)
+    } else if info.merged {
+        ("  merged", Color::Green)
+    } else {
+        ("  unmerged", Color::Yellow)
+    };
+    spans.push(Span::styled(label, Style::default().fg(color)));
+
+    if let Some(committed) = info.last_commit {
+        let age = (chrono::Utc::now().timestamp() - committed).max(0) as u64;
+        spans.push(Span::styled(
+            format!("  {} ago", format_duration(Duration::from_secs(age))),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    spans
+}
+
 impl Default for WorktreeCleanupDialog {
     fn default() -> Self {
         Self::new()
@@ -1,6 +1,13 @@
-use std::io::{BufRead, BufReader};
-use std::os::unix::net::UnixListener;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a connected client may take to send its line before it is dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Events received from Claude hooks via the status socket
 #[derive(Debug, Clone)]
@@ -17,8 +24,79 @@ pub enum EventKind {
     ToolStart(String),
     /// Claude finished running a tool
     ToolEnd,
-    /// Generic notification
-    Notification,
+    /// Claude is blocked on a permission prompt, with the message it gave
+    PermissionPrompt(String),
+    /// Any other notification, e.g. the idle "waiting for your input" reminder
+    Notification(String),
+    /// Short description of what the agent is doing, e.g. "running tests". Empty clears it.
+    Progress(String),
+}
+
+/// Commands from scripts and editor plugins driving shepherd, one JSON object per
+/// connection, e.g. `{"command":"send_prompt","session":"dev","prompt":"run the tests"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Create a session in the background, submitting `prompt` once claude is up
+    Create {
+        session: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prompt: Option<String>,
+    },
+    Kill {
+        session: String,
+    },
+    SendPrompt {
+        session: String,
+        prompt: String,
+    },
+    /// Live sessions with their worktrees and state
+    List,
+    /// Open a file in the configured editor. Relative paths are taken from the active
+    /// session's worktree.
+    Open {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line: Option<u32>,
+    },
+}
+
+/// A command with the connection its reply goes back on
+#[derive(Debug)]
+pub struct CommandRequest {
+    pub command: Command,
+    stream: UnixStream,
+}
+
+impl CommandRequest {
+    /// Answer with one JSON line: `{"ok":true,...}` or `{"ok":false,"error":...}`
+    pub fn reply(mut self, reply: Result<Value, String>) {
+        let line = match reply {
+            Ok(Value::Object(mut fields)) => {
+                fields.insert("ok".to_string(), Value::Bool(true));
+                Value::Object(fields)
+            }
+            Ok(_) => serde_json::json!({ "ok": true }),
+            Err(error) => serde_json::json!({ "ok": false, "error": error }),
+        };
+        let _ = writeln!(self.stream, "{}", line);
+    }
+}
+
+#[cfg(test)]
+impl CommandRequest {
+    /// `command` as if it came over the socket, with the client's end of the connection
+    pub fn pair(command: Command) -> (Self, UnixStream) {
+        let (stream, client) = UnixStream::pair().unwrap();
+        (Self { command, stream }, client)
+    }
+}
+
+/// What arrived on the socket
+#[derive(Debug)]
+pub enum SocketMessage {
+    Status(StatusEvent),
+    Command(CommandRequest),
 }
 
 /// Unix socket listener for receiving status events from Claude hooks
@@ -28,12 +106,12 @@ pub struct StatusSocket {
 }
 
 impl StatusSocket {
-    /// Create a new status socket at ~/.shepherd/status.sock
+    /// Create a new status socket at ~/.shepherd/status-<pid>.sock, one per instance
     pub fn new() -> std::io::Result<Self> {
         let socket_path = dirs::home_dir()
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No home directory"))?
             .join(".shepherd")
-            .join("status.sock");
+            .join(format!("status-{}.sock", std::process::id()));
 
         // Ensure directory exists
         if let Some(parent) = socket_path.parent() {
@@ -59,20 +137,32 @@ impl StatusSocket {
         &self.socket_path
     }
 
-    /// Poll for incoming events (non-blocking)
-    /// Returns a Vec of events received since last poll
-    pub fn poll(&self) -> Vec<StatusEvent> {
-        let mut events = Vec::new();
+    /// Poll for incoming messages (non-blocking)
+    /// Returns a Vec of messages received since last poll
+    pub fn poll(&self) -> Vec<SocketMessage> {
+        let mut messages = Vec::new();
 
         loop {
             match self.listener.accept() {
                 Ok((stream, _)) => {
-                    // Read the JSON message from the client
-                    let reader = BufReader::new(stream);
-                    for line in reader.lines().map_while(Result::ok) {
+                    // A client that never sends must not stall the event thread
+                    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+                    // Hooks send events until they close; a command is the only line on
+                    // its connection and the client waits for the reply
+                    let mut reader = BufReader::new(stream);
+                    let mut line = String::new();
+                    while matches!(reader.read_line(&mut line), Ok(n) if n > 0) {
+                        if let Some(command) = Self::parse_command(&line) {
+                            messages.push(SocketMessage::Command(CommandRequest {
+                                command,
+                                stream: reader.into_inner(),
+                            }));
+                            break;
+                        }
                         if let Some(event) = Self::parse_event(&line) {
-                            events.push(event);
+                            messages.push(SocketMessage::Status(event));
                         }
+                        line.clear();
                     }
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -86,54 +176,70 @@ impl StatusSocket {
             }
         }
 
-        events
+        messages
     }
 
-    /// Parse a JSON event message
-    fn parse_event(line: &str) -> Option<StatusEvent> {
-        // Simple JSON parsing without serde
-        // Expected format: {"session":"name","event":"stop"|"tool_start"|"tool_end"|"notification","tool":"ToolName"}
-        let line = line.trim();
-        if !line.starts_with('{') || !line.ends_with('}') {
-            return None;
-        }
-
-        let inner = &line[1..line.len() - 1];
-
-        let mut session = None;
-        let mut event_str = None;
-        let mut tool = None;
+    fn parse_command(line: &str) -> Option<Command> {
+        let value: Value = serde_json::from_str(line.trim()).ok()?;
+        value.get("command")?;
+        serde_json::from_value(value).ok()
+    }
 
-        for part in inner.split(',') {
-            let part = part.trim();
-            if let Some((key, value)) = part.split_once(':') {
-                let key = key.trim().trim_matches('"');
-                let value = value.trim().trim_matches('"');
+    /// Parse a JSON event message
+    /// Expected format: {"session":"name","event":"stop"|"tool_start"|"tool_end"|"notification"|"progress","tool":"ToolName","message":"running tests"}
+    /// Notifications may carry claude's `notification_type` alongside its `message`
+    pub(crate) fn parse_event(line: &str) -> Option<StatusEvent> {
+        let value: Value = serde_json::from_str(line.trim()).ok()?;
+        let session = value["session"].as_str()?.to_string();
 
-                match key {
-                    "session" => session = Some(value.to_string()),
-                    "event" => event_str = Some(value.to_string()),
-                    "tool" => tool = Some(value.to_string()),
-                    _ => {}
+        let event = match value["event"].as_str()? {
+            "stop" => EventKind::Stop,
+            "tool_start" => {
+                let tool_name = value["tool"].as_str().unwrap_or("unknown");
+                EventKind::ToolStart(tool_name.to_string())
+            }
+            "tool_end" => EventKind::ToolEnd,
+            "notification" => {
+                let message = value["message"].as_str().unwrap_or_default().to_string();
+                // Newer claude versions say which kind of notification it is; older ones
+                // only word permission prompts as asking for permission
+                let permission = match value["notification_type"].as_str() {
+                    Some(kind) => kind == "permission_prompt",
+                    None => message.contains("permission"),
+                };
+                if permission {
+                    EventKind::PermissionPrompt(message)
+                } else {
+                    EventKind::Notification(message)
                 }
             }
-        }
-
-        let event = match event_str.as_deref() {
-            Some("stop") => Some(EventKind::Stop),
-            Some("tool_start") => {
-                let tool_name = tool.unwrap_or_else(|| "unknown".to_string());
-                Some(EventKind::ToolStart(tool_name))
+            "progress" => {
+                let message = value["message"].as_str().unwrap_or_default();
+                EventKind::Progress(message.trim().to_string())
             }
-            Some("tool_end") => Some(EventKind::ToolEnd),
-            Some("notification") => Some(EventKind::Notification),
-            _ => None,
+            _ => return None,
         };
 
-        match (session, event) {
-            (Some(session), Some(event)) => Some(StatusEvent { session, event }),
-            _ => None,
-        }
+        Some(StatusEvent { session, event })
+    }
+}
+
+/// Send `command` to the shepherd listening at `socket_path` and wait for its reply
+pub fn send_command(socket_path: &Path, command: &Command) -> anyhow::Result<Value> {
+    let mut stream = UnixStream::connect(socket_path)?;
+    writeln!(stream, "{}", serde_json::to_string(command)?)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    let reply: Value = serde_json::from_str(&reply)?;
+    if reply["ok"] != Value::Bool(true) {
+        anyhow::bail!("{}", reply["error"].as_str().unwrap_or("command failed"));
+    }
+    Ok(reply)
+}
+
+impl AsFd for StatusSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.listener.as_fd()
     }
 }
 
@@ -163,7 +269,23 @@ mod tests {
         assert!(event.is_some());
         let event = event.unwrap();
         assert_eq!(event.session, "my-feature");
-        assert_eq!(event.event, EventKind::Notification);
+        assert_eq!(event.event, EventKind::Notification(String::new()));
+
+        let idle = StatusSocket::parse_event(
+            r#"{"session":"s","event":"notification","message":"Claude is waiting for your input"}"#,
+        );
+        assert!(matches!(idle.unwrap().event, EventKind::Notification(_)));
+        let prompt = StatusSocket::parse_event(
+            r#"{"session":"s","event":"notification","message":"Claude needs your permission to use Bash"}"#,
+        );
+        assert_eq!(
+            prompt.unwrap().event,
+            EventKind::PermissionPrompt("Claude needs your permission to use Bash".to_string())
+        );
+        let typed = StatusSocket::parse_event(
+            r#"{"session":"s","event":"notification","notification_type":"idle_prompt","message":"permission"}"#,
+        );
+        assert!(matches!(typed.unwrap().event, EventKind::Notification(_)));
     }
 
     #[test]
@@ -194,6 +316,45 @@ mod tests {
         assert_eq!(event.event, EventKind::ToolEnd);
     }
 
+    #[test]
+    fn test_parse_event_progress() {
+        let event = StatusSocket::parse_event(
+            r#"{"session":"dev","event":"progress","message":"editing auth.rs, then tests"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            event.event,
+            EventKind::Progress("editing auth.rs, then tests".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            StatusSocket::parse_command(r#"{"command":"create","session":"fix-ci"}"#),
+            Some(Command::Create {
+                session: "fix-ci".to_string(),
+                prompt: None
+            })
+        );
+        assert_eq!(
+            StatusSocket::parse_command(
+                r#"{"command":"send_prompt","session":"dev","prompt":"run the tests"}"#
+            ),
+            Some(Command::SendPrompt {
+                session: "dev".to_string(),
+                prompt: "run the tests".to_string()
+            })
+        );
+        assert_eq!(
+            StatusSocket::parse_command(r#"{"command":"list"}"#),
+            Some(Command::List)
+        );
+        // Hook events and unknown commands are not commands
+        assert!(StatusSocket::parse_command(r#"{"session":"dev","event":"stop"}"#).is_none());
+        assert!(StatusSocket::parse_command(r#"{"command":"reboot"}"#).is_none());
+    }
+
     #[test]
     fn test_parse_event_invalid() {
         assert!(StatusSocket::parse_event("not json").is_none());
@@ -0,0 +1,209 @@
+use std::io::{PipeReader, Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use portable_pty::{Child, ChildKiller, ExitStatus, MasterPty, PtySize};
+
+use crate::config::SessionLimits;
+use crate::error::ShepardError;
+use crate::session::{AttachedSession, ExitNotifier, SharedSize};
+
+/// How often a fake child's output checks whether the child has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// State shared by a fake child, its PTY and the test playing the child
+#[derive(Debug)]
+struct Shared {
+    /// Everything written to the child: keystrokes and answers to terminal queries
+    input: Mutex<Vec<u8>>,
+    /// Set once the child has exited or been killed
+    exit: Mutex<Option<ExitStatus>>,
+    size: Mutex<PtySize>,
+}
+
+/// A session whose child is played by the test. Output fed with [`TestSession::feed`]
+/// goes through the session's real PTY reader and vt100 parser, and input sent to
+/// the session is recorded instead of reaching a process.
+#[derive(Clone)]
+pub struct TestSession {
+    output: Sender<Vec<u8>>,
+    shared: Arc<Shared>,
+}
+
+impl TestSession {
+    /// Start a session on a fake child, returning the handle that plays the child
+    pub fn spawn(
+        on_exit: Option<ExitNotifier>,
+        size: SharedSize,
+        limits: &SessionLimits,
+    ) -> Result<(Self, AttachedSession), ShepardError> {
+        let (rows, cols) = size.get();
+        let shared = Arc::new(Shared {
+            input: Mutex::default(),
+            exit: Mutex::default(),
+            size: Mutex::new(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            }),
+        });
+        let (output, rx) = unbounded();
+        let (reader, writer) =
+            std::io::pipe().map_err(|e| ShepardError::pty("open a fake pty", e))?;
+        pump_output(rx, writer, shared.clone());
+        let master = FakeMaster {
+            fd: reader.as_raw_fd(),
+            output: Mutex::new(Some(reader)),
+            shared: shared.clone(),
+        };
+        let child = FakeChild {
+            shared: shared.clone(),
+        };
+        let session =
+            AttachedSession::start(Box::new(master), Box::new(child), on_exit, size, limits)?;
+        Ok((Self { output, shared }, session))
+    }
+
+    /// Print `bytes` from the child
+    pub fn feed(&self, bytes: impl AsRef<[u8]>) {
+        let _ = self.output.send(bytes.as_ref().to_vec());
+    }
+
+    /// Everything written to the child so far
+    pub fn input(&self) -> Vec<u8> {
+        self.shared.input.lock().unwrap().clone()
+    }
+
+    /// Exit with `code` once the output fed so far has been read
+    pub fn exit(&self, code: u32) {
+        let mut exit = self.shared.exit.lock().unwrap();
+        exit.get_or_insert_with(|| ExitStatus::with_exit_code(code));
+    }
+
+    /// Whether the child has exited or been killed
+    pub fn exited(&self) -> bool {
+        self.shared.exit.lock().unwrap().is_some()
+    }
+
+    /// Size the session last gave the PTY, as (rows, cols)
+    pub fn size(&self) -> (u16, u16) {
+        let size = self.shared.size.lock().unwrap();
+        (size.rows, size.cols)
+    }
+}
+
+/// Copy fed output into the pipe the session reads, on a thread of its own so feeding
+/// never blocks the test on a full pipe
+fn pump_output(rx: Receiver<Vec<u8>>, mut pipe: impl Write + Send + 'static, shared: Arc<Shared>) {
+    std::thread::spawn(move || {
+        loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(bytes) => {
+                    if pipe.write_all(&bytes).is_err() {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) if shared.exit.lock().unwrap().is_none() => {}
+                // Exited, or nothing can feed it anymore: closing the pipe gives EOF, as
+                // a closed PTY does
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+/// The PTY side the session writes input to
+struct FakeInput(Arc<Shared>);
+
+impl Write for FakeInput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.input.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct FakeMaster {
+    /// Read end of the output pipe, polled by the reader pool
+    fd: RawFd,
+    output: Mutex<Option<PipeReader>>,
+    shared: Arc<Shared>,
+}
+
+impl MasterPty for FakeMaster {
+    fn resize(&self, size: PtySize) -> anyhow::Result<()> {
+        *self.shared.size.lock().unwrap() = size;
+        Ok(())
+    }
+
+    fn get_size(&self) -> anyhow::Result<PtySize> {
+        Ok(*self.shared.size.lock().unwrap())
+    }
+
+    fn try_clone_reader(&self) -> anyhow::Result<Box<dyn Read + Send>> {
+        let output = self.output.lock().unwrap().take();
+        let output = output.ok_or_else(|| anyhow::anyhow!("output already taken"))?;
+        Ok(Box::new(output))
+    }
+
+    fn take_writer(&self) -> anyhow::Result<Box<dyn Write + Send>> {
+        Ok(Box::new(FakeInput(self.shared.clone())))
+    }
+
+    fn process_group_leader(&self) -> Option<libc::pid_t> {
+        None
+    }
+
+    fn as_raw_fd(&self) -> Option<RawFd> {
+        Some(self.fd)
+    }
+
+    fn tty_name(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+/// A child without a process; killing it just records the exit
+#[derive(Debug)]
+struct FakeChild {
+    shared: Arc<Shared>,
+}
+
+impl ChildKiller for FakeChild {
+    fn kill(&mut self) -> std::io::Result<()> {
+        let mut exit = self.shared.exit.lock().unwrap();
+        exit.get_or_insert_with(|| ExitStatus::with_signal("Hangup"));
+        Ok(())
+    }
+
+    fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+        Box::new(FakeChild {
+            shared: self.shared.clone(),
+        })
+    }
+}
+
+impl Child for FakeChild {
+    fn try_wait(&mut self) -> std::io::Result<Option<ExitStatus>> {
+        Ok(self.shared.exit.lock().unwrap().clone())
+    }
+
+    fn wait(&mut self) -> std::io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding the trace filter, in `RUST_LOG` syntax, e.g.
+/// `shepherd::session=trace,shepherd::workflows=debug`
+pub const TRACE_ENV: &str = "SHEPHERD_LOG";
+/// Daily trace files kept before the oldest is deleted
+const MAX_TRACE_FILES: usize = 7;
+
+/// `~/.shepherd/logs`, where traces are written as `shepherd.log.<date>`
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".shepherd").join("logs"))
+}
+
+/// Write traces matching `$SHEPHERD_LOG` to a daily file in `~/.shepherd/logs`. Nothing
+/// is traced when it is unset. Traces are written on a background thread until the
+/// returned guard is dropped.
+pub fn init() -> anyhow::Result<Option<WorkerGuard>> {
+    let Ok(filter) = std::env::var(TRACE_ENV) else {
+        return Ok(None);
+    };
+    let filter = EnvFilter::try_new(&filter)
+        .map_err(|e| anyhow::anyhow!("invalid {} '{}': {}", TRACE_ENV, filter, e))?;
+    let dir = log_dir().ok_or_else(|| anyhow::anyhow!("no home directory for traces"))?;
+    std::fs::create_dir_all(&dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("shepherd.log")
+        .max_log_files(MAX_TRACE_FILES)
+        .build(&dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_thread_names(true)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!("failed to start tracing: {}", e))?;
+    Ok(Some(guard))
+}
@@ -0,0 +1,189 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Who a transcript entry came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryKind {
+    User,
+    Assistant,
+    /// A tool call, with the tool's name
+    ToolUse(String),
+    ToolResult,
+}
+
+/// One message of a Claude conversation, flattened to text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub kind: EntryKind,
+    pub text: String,
+}
+
+/// Directory Claude keeps transcripts for sessions started in `cwd`: every character
+/// that isn't alphanumeric becomes '-', e.g. /home/me/repo -> ~/.claude/projects/-home-me-repo
+pub fn project_dir(cwd: &Path) -> Option<PathBuf> {
+    let encoded: String = cwd
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    Some(
+        dirs::home_dir()?
+            .join(".claude")
+            .join("projects")
+            .join(encoded),
+    )
+}
+
+/// The most recently written transcript for sessions started in `cwd`
+pub fn locate(cwd: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(project_dir(cwd)?)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Read a JSONL transcript. Lines that aren't conversation messages are skipped.
+pub fn load(path: &Path) -> anyhow::Result<Vec<TranscriptEntry>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents.lines().flat_map(parse_line).collect())
+}
+
+/// The conversation as markdown, a heading per entry with tool calls and results fenced
+pub fn to_markdown(entries: &[TranscriptEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let (heading, fenced) = match &entry.kind {
+            EntryKind::User => ("You".to_string(), false),
+            EntryKind::Assistant => ("Claude".to_string(), false),
+            EntryKind::ToolUse(name) => (format!("Tool: {}", name), true),
+            EntryKind::ToolResult => ("Result".to_string(), true),
+        };
+        if fenced {
+            out.push_str(&format!("## {}\n\n```\n{}\n```\n\n", heading, entry.text));
+        } else {
+            out.push_str(&format!("## {}\n\n{}\n\n", heading, entry.text));
+        }
+    }
+    out
+}
+
+fn parse_line(line: &str) -> Vec<TranscriptEntry> {
+    let Ok(value) = serde_json::from_str::<Value>(line) else {
+        return Vec::new();
+    };
+    let kind = match value["type"].as_str() {
+        Some("user") => EntryKind::User,
+        Some("assistant") => EntryKind::Assistant,
+        _ => return Vec::new(),
+    };
+    // Injected context (command output, reminders) rather than something typed
+    if value["isMeta"].as_bool() == Some(true) {
+        return Vec::new();
+    }
+
+    let content = &value["message"]["content"];
+    if let Some(text) = content.as_str() {
+        return vec![TranscriptEntry {
+            kind,
+            text: text.trim().to_string(),
+        }];
+    }
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| {
+            let (kind, text) = match block["type"].as_str()? {
+                "text" => (kind.clone(), block["text"].as_str()?.trim().to_string()),
+                "tool_use" => (
+                    EntryKind::ToolUse(block["name"].as_str().unwrap_or("tool").to_string()),
+                    tool_input(&block["input"]),
+                ),
+                "tool_result" => (EntryKind::ToolResult, block_text(&block["content"])),
+                _ => return None,
+            };
+            (!text.is_empty()).then_some(TranscriptEntry { kind, text })
+        })
+        .collect()
+}
+
+/// Tool arguments, one `key: value` per line
+fn tool_input(input: &Value) -> String {
+    match input.as_object() {
+        Some(fields) => fields
+            .iter()
+            .map(|(key, value)| match value.as_str() {
+                Some(s) => format!("{}: {}", key, s),
+                None => format!("{}: {}", key, value),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => input.to_string(),
+    }
+}
+
+/// Text of a tool result, which is either a string or a list of text blocks
+fn block_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.trim().to_string(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let user = r#"{"type":"user","message":{"role":"user","content":"fix the build"}}"#;
+        assert_eq!(
+            parse_line(user),
+            vec![TranscriptEntry {
+                kind: EntryKind::User,
+                text: "fix the build".to_string()
+            }]
+        );
+
+        let assistant = r#"{"type":"assistant","message":{"content":[
+            {"type":"text","text":"Running it."},
+            {"type":"tool_use","name":"Bash","input":{"command":"cargo build"}}]}}"#;
+        let entries = parse_line(&assistant.replace('\n', ""));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].kind, EntryKind::ToolUse("Bash".to_string()));
+        assert_eq!(entries[1].text, "command: cargo build");
+
+        let result = r#"{"type":"user","message":{"content":[{"type":"tool_result","content":[{"type":"text","text":"ok"}]}]}}"#;
+        assert_eq!(parse_line(result)[0].kind, EntryKind::ToolResult);
+        assert!(parse_line(r#"{"type":"summary","summary":"x"}"#).is_empty());
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let entries = [
+            TranscriptEntry {
+                kind: EntryKind::User,
+                text: "fix the build".to_string(),
+            },
+            TranscriptEntry {
+                kind: EntryKind::ToolUse("Bash".to_string()),
+                text: "command: cargo build".to_string(),
+            },
+        ];
+        assert_eq!(
+            to_markdown(&entries),
+            "## You\n\nfix the build\n\n## Tool: Bash\n\n```\ncommand: cargo build\n```\n\n"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A worktree moved to the trash instead of being deleted. Stored as `<id>.json` next to
+/// the moved checkout `<id>/`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// `<timestamp>-<name>`, also the name of the checkout's directory in the trash
+    pub id: String,
+    /// Where the worktree lived before it was trashed
+    pub original_path: PathBuf,
+    /// Repository the worktree belongs to; git commands run from here
+    pub repo_root: PathBuf,
+    pub trashed_at: DateTime<Utc>,
+}
+
+impl TrashEntry {
+    /// Worktree directory name, e.g. the session name
+    pub fn name(&self) -> &str {
+        self.original_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&self.id)
+    }
+}
+
+/// Trash directory (~/.shepherd/trash) holding deleted worktrees until they are restored
+/// or purged
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn open() -> anyhow::Result<Self> {
+        let home =
+            dirs::home_dir().ok_or_else(|| anyhow::anyhow!("could not find home directory"))?;
+        Ok(Self::at(home.join(".shepherd").join("trash")))
+    }
+
+    fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Where the checkout of `entry` currently lives
+    pub fn path(&self, entry: &TrashEntry) -> PathBuf {
+        self.dir.join(&entry.id)
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Everything in the trash, most recently trashed first
+    pub fn list(&self) -> Vec<TrashEntry> {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<TrashEntry> = dir
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+            .filter_map(|contents| serde_json::from_str(&contents).ok())
+            .collect();
+        entries.sort_by_key(|e: &TrashEntry| std::cmp::Reverse(e.trashed_at));
+        entries
+    }
+
+    /// Move `worktree` into the trash with `git worktree move`, so git keeps tracking it and
+    /// it can be moved back
+    pub fn trash_worktree(&self, repo_root: &Path, worktree: &Path) -> anyhow::Result<TrashEntry> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let name = worktree
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid path"))?;
+        let now = Utc::now();
+        let stamp = now.format("%Y%m%d-%H%M%S");
+        let mut id = format!("{}-{}", stamp, name);
+        for n in 2.. {
+            if !self.dir.join(&id).exists() && !self.manifest_path(&id).exists() {
+                break;
+            }
+            id = format!("{}-{}-{}", stamp, name, n);
+        }
+
+        let entry = TrashEntry {
+            id,
+            original_path: worktree.to_path_buf(),
+            repo_root: repo_root.to_path_buf(),
+            trashed_at: now,
+        };
+        git_worktree_move(repo_root, worktree, &self.path(&entry))?;
+        std::fs::write(
+            self.manifest_path(&entry.id),
+            serde_json::to_string_pretty(&entry)?,
+        )?;
+        Ok(entry)
+    }
+
+    /// Move a trashed worktree back to where it was
+    pub fn restore(&self, entry: &TrashEntry) -> anyhow::Result<()> {
+        if entry.original_path.exists() {
+            anyhow::bail!("{} already exists", entry.original_path.display());
+        }
+        git_worktree_move(&entry.repo_root, &self.path(entry), &entry.original_path)?;
+        let _ = std::fs::remove_file(self.manifest_path(&entry.id));
+        Ok(())
+    }
+
+    /// Delete a trashed worktree for good
+    pub fn purge(&self, entry: &TrashEntry) -> anyhow::Result<()> {
+        let path = self.path(entry);
+        // Unregister from git first; fall back to removing the directory if the repo is gone
+        let _ = Command::new("git")
+            .args(["worktree", "remove", "--force"])
+            .arg(&path)
+            .current_dir(&entry.repo_root)
+            .output();
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+        std::fs::remove_file(self.manifest_path(&entry.id))?;
+        Ok(())
+    }
+
+    /// Purge entries trashed more than `days` days ago. Returns how many were purged.
+    pub fn purge_older_than(&self, days: u64) -> usize {
+        let cutoff = Utc::now() - chrono::TimeDelta::days(days as i64);
+        self.list()
+            .iter()
+            .filter(|e| e.trashed_at < cutoff)
+            .filter(|e| self.purge(e).is_ok())
+            .count()
+    }
+}
+
+fn git_worktree_move(repo_root: &Path, from: &Path, to: &Path) -> anyhow::Result<()> {
+    let output = Command::new("git")
+        .args(["worktree", "move"])
+        .arg(from)
+        .arg(to)
+        .current_dir(repo_root)
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git worktree move failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{TempDir, git};
+
+    #[test]
+    fn test_trash_and_restore() {
+        let root = TempDir::new("trash");
+        let repo = root.repo("repo");
+        let worktree = root.join("feature");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(worktree.join("notes.txt"), "uncommitted").unwrap();
+
+        let trash = Trash::at(root.join("trash"));
+        let entry = trash.trash_worktree(&repo, &worktree).unwrap();
+        assert!(!worktree.exists());
+        assert!(trash.path(&entry).join("notes.txt").exists());
+        assert_eq!(trash.list(), vec![entry.clone()]);
+        assert_eq!(entry.name(), "feature");
+
+        trash.restore(&entry).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("notes.txt")).unwrap(),
+            "uncommitted"
+        );
+        assert!(trash.list().is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+/// Characters git never allows in a ref name (see git-check-ref-format)
+pub const FORBIDDEN_CHARS: &[char] = &['~', '^', ':', '?', '*', '[', '\\'];
+
+/// Check a name against git's ref naming rules. Returns the first problem found.
+pub fn validate_branch_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("name is empty".to_string());
+    }
+    if name == "@" {
+        return Err("'@' is not a valid branch name".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_whitespace() || c.is_control() || FORBIDDEN_CHARS.contains(c))
+    {
+        return Err(match c {
+            ' ' => "contains a space".to_string(),
+            c if c.is_control() || c.is_whitespace() => "contains a control character".to_string(),
+            c => format!("contains '{}'", c),
+        });
+    }
+    if name.starts_with('-') {
+        return Err("starts with '-'".to_string());
+    }
+    if name.contains("..") {
+        return Err("contains '..'".to_string());
+    }
+    if name.contains("@{") {
+        return Err("contains '@{'".to_string());
+    }
+    if name.ends_with('.') || name.ends_with('/') {
+        return Err("ends with '.' or '/'".to_string());
+    }
+    for component in name.split('/') {
+        if component.is_empty() {
+            return Err("contains an empty path component".to_string());
+        }
+        if component.starts_with('.') {
+            return Err("a path component starts with '.'".to_string());
+        }
+        if component.ends_with(".lock") {
+            return Err("a path component ends with '.lock'".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Turn arbitrary text into a valid branch name: lowercase ASCII letters, digits,
+/// '.', '_' and '/' survive; everything else collapses into single dashes.
+/// Returns an empty string if nothing usable is left.
+pub fn slugify_branch_name(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '/') {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    // Clean up each path component so none starts with '.' or '-' or ends with '.lock'
+    let components: Vec<String> = slug
+        .split('/')
+        .map(|component| {
+            let mut component = component
+                .trim_matches(|c| c == '-' || c == '.')
+                .replace("..", ".");
+            while let Some(stripped) = component.strip_suffix(".lock") {
+                component = stripped.trim_end_matches(['-', '.']).to_string();
+            }
+            component
+        })
+        .filter(|component| !component.is_empty())
+        .collect();
+    components.join("/")
+}
+
+/// A typed name as a branch name: kept as is when valid, slugified otherwise. Fails with
+/// the validation problem if nothing usable is left.
+pub fn resolve_branch_name(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    match validate_branch_name(input) {
+        Ok(()) => Ok(input.to_string()),
+        Err(reason) => {
+            let slug = slugify_branch_name(input);
+            if slug.is_empty() {
+                Err(reason)
+            } else {
+                Ok(slug)
+            }
+        }
+    }
+}
+
+/// Words that carry no meaning in a branch name derived from a prompt
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "to", "for", "of", "in", "on", "and", "or", "with", "is", "it", "this",
+    "that", "please", "can", "could", "you", "we", "i", "me", "my", "our", "let's", "lets",
+];
+/// Meaningful words kept from a prompt when deriving a branch name
+const PROMPT_WORDS: usize = 4;
+
+/// Derive a short branch name from a task prompt: the first few meaningful words,
+/// slugified. Returns an empty string if the prompt has no usable words.
+pub fn branch_name_from_prompt(prompt: &str) -> String {
+    let words: Vec<String> = prompt
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(&w.as_str()))
+        .take(PROMPT_WORDS)
+        .collect();
+    slugify_branch_name(&words.join(" ").replace('/', " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_branch_name() {
+        assert!(validate_branch_name("feature/login-fix").is_ok());
+        assert!(validate_branch_name("my feature!").is_err());
+        assert!(validate_branch_name("-x").is_err());
+        assert!(validate_branch_name("a..b").is_err());
+        assert!(validate_branch_name("wip.lock").is_err());
+        assert!(validate_branch_name("foo/.hidden").is_err());
+        assert!(validate_branch_name("what?").is_err());
+    }
+
+    #[test]
+    fn test_branch_name_from_prompt() {
+        assert_eq!(
+            branch_name_from_prompt("Please fix the flaky login test in CI, it times out"),
+            "fix-flaky-login-test"
+        );
+        assert_eq!(
+            branch_name_from_prompt("Add /metrics endpoint"),
+            "add-metrics-endpoint"
+        );
+        assert_eq!(branch_name_from_prompt("the a an"), "");
+    }
+
+    #[test]
+    fn test_slugify_branch_name() {
+        assert_eq!(slugify_branch_name("my feature!"), "my-feature");
+        assert_eq!(slugify_branch_name("  Fix: Login  Bug "), "fix-login-bug");
+        assert_eq!(slugify_branch_name("feat//.x..y.lock"), "feat/x.y");
+        assert_eq!(slugify_branch_name("!!!"), "");
+        for input in ["my feature!", "-lead", "a..b", "x.lock", "über cool"] {
+            let slug = slugify_branch_name(input);
+            assert!(validate_branch_name(&slug).is_ok(), "{input:?} -> {slug:?}");
+        }
+    }
+}
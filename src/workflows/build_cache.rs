@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use crate::config::Config;
+
+use super::RepoLayout;
+
+/// Env vars pointing build tools at caches shared by all of a repo's worktrees, so each
+/// new worktree doesn't rebuild from scratch. Vars already set in shepherd's own
+/// environment are left alone.
+///
+/// - cargo: `CARGO_TARGET_DIR` is shared per repo (detected from `Cargo.toml`)
+/// - pnpm: the package store is shared and kept next to the worktrees, so packages are
+///   hard-linked rather than copied (detected from `pnpm-lock.yaml`)
+pub fn build_cache_env(
+    config: &Config,
+    repo: &RepoLayout,
+    worktree_path: &Path,
+) -> Vec<(&'static str, String)> {
+    let settings = config.build_cache.for_repo(repo.name());
+    let cache_dir = config
+        .build_cache
+        .dir
+        .clone()
+        .unwrap_or_else(|| config.workflows_path.join(".cache"));
+
+    let mut env = Vec::new();
+    let cargo = settings
+        .cargo
+        .unwrap_or_else(|| worktree_path.join("Cargo.toml").exists());
+    if cargo && std::env::var_os("CARGO_TARGET_DIR").is_none() {
+        let repo_dir = repo.worktrees_dir(&cache_dir.join("cargo-target"));
+        env.push(("CARGO_TARGET_DIR", repo_dir.to_string_lossy().to_string()));
+    }
+
+    let pnpm = settings
+        .pnpm
+        .unwrap_or_else(|| worktree_path.join("pnpm-lock.yaml").exists());
+    if pnpm && std::env::var_os("npm_config_store_dir").is_none() {
+        let store = cache_dir.join("pnpm-store");
+        env.push(("npm_config_store_dir", store.to_string_lossy().to_string()));
+    }
+
+    env
+}
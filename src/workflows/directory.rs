@@ -0,0 +1,56 @@
+use crate::config::Config;
+use crate::error::ShepardError;
+use std::path::Path;
+
+use super::{PlanOverrides, Progress, SessionMetadata, SessionPlan, Workflow};
+
+/// Workflow for directories outside git: every session runs in the directory itself
+pub struct DirectoryWorkflow;
+
+impl Workflow for DirectoryWorkflow {
+    fn name(&self) -> &'static str {
+        "directory"
+    }
+
+    fn plan(
+        &self,
+        _session_name: &str,
+        _config: &Config,
+        startup_path: &Path,
+        overrides: &PlanOverrides,
+    ) -> Result<SessionPlan, ShepardError> {
+        Ok(SessionPlan {
+            path: Some(
+                overrides
+                    .path
+                    .as_deref()
+                    .unwrap_or(startup_path)
+                    .to_path_buf(),
+            ),
+            base: None,
+            operations: Vec::new(),
+        })
+    }
+
+    fn pre_session_hook(
+        &self,
+        _session_name: &str,
+        _config: &Config,
+        startup_path: &Path,
+        overrides: &PlanOverrides,
+        _progress: &Progress,
+    ) -> Result<SessionMetadata, ShepardError> {
+        let path = overrides.path.as_deref().unwrap_or(startup_path);
+        if !path.is_dir() {
+            return Err(ShepardError::workflow(
+                self.name(),
+                format!("{} is not a directory", path.display()),
+            ));
+        }
+        Ok(SessionMetadata {
+            path: path.to_path_buf(),
+            base: None,
+            setup: Vec::new(),
+        })
+    }
+}
@@ -1,28 +1,265 @@
+mod branch_name;
+mod build_cache;
+mod directory;
+mod repo_layout;
+mod script;
+mod shared;
 mod worktree;
 
+pub use branch_name::{
+    FORBIDDEN_CHARS, branch_name_from_prompt, resolve_branch_name, slugify_branch_name,
+    validate_branch_name,
+};
+pub use build_cache::build_cache_env;
+pub use directory::DirectoryWorkflow;
+pub use repo_layout::RepoLayout;
+pub use script::ScriptWorkflow;
+pub use shared::{SHARED_WORKFLOWS_FILE, SharedWorkflows, expand_pattern, merge_shared_workflows};
 pub use worktree::WorktreeWorkflow;
 
 use crate::config::Config;
-use crate::session_manager::StatusMessage;
+use crate::error::ShepardError;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 /// Metadata returned by a workflow's pre-session hook
 #[derive(Debug, Clone)]
 pub struct SessionMetadata {
     pub path: PathBuf,
+    /// What the session's branch was created from, for reporting (e.g. "origin/main")
+    pub base: Option<String>,
+    /// Steps to run in the new session's directory once it has started
+    pub setup: Vec<SetupStep>,
 }
 
-/// A workflow defines how sessions are created and configured
+/// What a workflow would do to create a session, shown for confirmation before it does
+#[derive(Debug, Clone, Default)]
+pub struct SessionPlan {
+    /// Directory the session will run in; unset when the workflow decides as it goes
+    pub path: Option<PathBuf>,
+    /// What the session's branch will start from, when it gets one
+    pub base: Option<String>,
+    /// Each step, in order, e.g. "git fetch origin main"
+    pub operations: Vec<String>,
+}
+
+/// Changes made to a session's plan before creating it
+#[derive(Debug, Clone, Default)]
+pub struct PlanOverrides {
+    pub path: Option<PathBuf>,
+    /// Start point used as is, without fetching
+    pub base: Option<String>,
+}
+
+/// Where a workflow running on a worker thread reports the step it is on, and learns
+/// that the user stopped waiting
+#[derive(Clone)]
+pub struct Progress {
+    report: Arc<dyn Fn(&str) + Send + Sync>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Progress {
+    pub fn new(report: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            report: Arc::new(report),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Progress nobody watches, for callers that wait on the hook
+    pub fn none() -> Self {
+        Self::new(|_| {})
+    }
+
+    /// Report starting `label`, e.g. "Fetching origin/main"
+    pub fn step(&self, label: &str) {
+        tracing::info!(step = label, "workflow step");
+        (self.report)(label);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// Wait for `child` like `wait_with_output`, but kill it if `progress` is cancelled first,
+/// returning None
+pub fn wait_or_cancel(mut child: Child, progress: &Progress) -> std::io::Result<Option<Output>> {
+    fn drain(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    }
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if progress.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let collect = |pipe: Option<JoinHandle<Vec<u8>>>| {
+        pipe.and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    Ok(Some(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    }))
+}
+
+/// Something run in a new session's directory, e.g. submodule initialization
+#[derive(Debug, Clone)]
+pub struct SetupStep {
+    /// Progress label shown in the status bar, e.g. "Initializing submodules"
+    pub label: String,
+    pub action: SetupAction,
+}
+
+#[derive(Debug, Clone)]
+pub enum SetupAction {
+    Git(Vec<&'static str>),
+    /// A command run through `sh -c`
+    Shell(String),
+    /// Copy files matching the patterns from `from` (the main checkout)
+    CopyFiles {
+        from: PathBuf,
+        patterns: Vec<String>,
+    },
+}
+
+impl SetupAction {
+    /// Run in `dir`, returning what went wrong on failure
+    pub fn run(&self, dir: &Path) -> Result<(), String> {
+        let output = match self {
+            SetupAction::Git(args) => Command::new("git").args(args).current_dir(dir).output(),
+            SetupAction::Shell(command) => Command::new("sh")
+                .args(["-c", command])
+                .current_dir(dir)
+                .output(),
+            SetupAction::CopyFiles { from, patterns } => {
+                return copy_files(from, dir, patterns);
+            }
+        };
+        match output {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Copy the files under `from` matching `patterns` to the same place under `to`, skipping
+/// ones that already exist there (checked-out files)
+fn copy_files(from: &Path, to: &Path, patterns: &[String]) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for file in patterns.iter().flat_map(|p| expand_pattern(from, p)) {
+        let target = to.join(&file);
+        if target.exists() {
+            continue;
+        }
+        let copied = target
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::copy(from.join(&file), &target));
+        if let Err(e) = copied {
+            errors.push(format!("{}: {}", file.display(), e));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+/// A session as the hooks after `pre_session_hook` see it
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub name: String,
+    /// Directory the session runs in
+    pub path: PathBuf,
+    /// Repository root, or the startup directory outside git
+    pub dir: PathBuf,
+}
+
+/// A workflow defines how sessions are created and configured. Hooks other than
+/// `pre_session_hook` default to doing nothing.
 pub trait Workflow: Send + Sync {
-    /// Name of this workflow for error messages
-    #[allow(dead_code)]
+    /// Name of this workflow, as chosen in config and the new session dialog
     fn name(&self) -> &'static str;
 
-    /// Called before a session is created. Returns metadata for the session.
+    /// What `pre_session_hook` would do with `overrides`, without doing any of it
+    fn plan(
+        &self,
+        session_name: &str,
+        config: &Config,
+        startup_path: &Path,
+        overrides: &PlanOverrides,
+    ) -> Result<SessionPlan, ShepardError>;
+
+    /// Called before a session is created, possibly on a worker thread reporting to
+    /// `progress`. Returns metadata for the session.
     fn pre_session_hook(
         &self,
         session_name: &str,
         config: &Config,
         startup_path: &Path,
-    ) -> Result<SessionMetadata, StatusMessage>;
+        overrides: &PlanOverrides,
+        progress: &Progress,
+    ) -> Result<SessionMetadata, ShepardError>;
+
+    /// Called once the session's claude process has started, off the UI thread
+    fn post_session_start(
+        &self,
+        _session: &SessionInfo,
+        _config: &Config,
+    ) -> Result<(), ShepardError> {
+        Ok(())
+    }
+
+    /// Called when the session's claude process has exited or been killed, off the UI thread
+    fn on_session_exit(
+        &self,
+        _session: &SessionInfo,
+        _config: &Config,
+    ) -> Result<(), ShepardError> {
+        Ok(())
+    }
+
+    /// Called before the session's worktree is deleted. An error keeps the worktree.
+    fn pre_worktree_delete(
+        &self,
+        _session: &SessionInfo,
+        _config: &Config,
+    ) -> Result<(), ShepardError> {
+        Ok(())
+    }
+
+    /// Called after the session's worktree was deleted, off the UI thread
+    fn post_worktree_delete(
+        &self,
+        _session: &SessionInfo,
+        _config: &Config,
+    ) -> Result<(), ShepardError> {
+        Ok(())
+    }
 }
@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Context;
+
+use crate::git_backend;
+
+/// Where a repository's worktrees live under `workflows_path`.
+///
+/// Worktrees are stored at `<workflows_path>/<reponame>-<hash>/<session>`, where the hash
+/// is taken from the repository's root path so two repos with the same name never share
+/// a directory. Older versions used `<workflows_path>/<reponame>/<session>`.
+#[derive(Debug, Clone)]
+pub struct RepoLayout {
+    name: String,
+    root: PathBuf,
+    common_dir: PathBuf,
+}
+
+impl RepoLayout {
+    /// Detect the repository containing `dir`. Uses the main repo (via its common git dir)
+    /// so the layout is the same whether launched from the main checkout or a worktree.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        let common_dir = git_backend::get().common_dir(dir)?;
+        let common_dir = common_dir.canonicalize().unwrap_or(common_dir);
+
+        // A bare repository is its own common dir, e.g. "/path/to/repo.git"
+        let root = if common_dir.file_name()? == ".git" {
+            common_dir.parent()?.to_path_buf()
+        } else {
+            common_dir.clone()
+        };
+        let dir_name = root.file_name()?.to_str()?;
+        let name = dir_name
+            .strip_suffix(".git")
+            .unwrap_or(dir_name)
+            .to_string();
+
+        Some(Self {
+            name,
+            root,
+            common_dir,
+        })
+    }
+
+    /// Repositories directly inside `dir`, for a workspace folder that holds several repos
+    /// but is not one itself. Sorted by name.
+    pub fn detect_children(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut repos: Vec<Self> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|path| path.join(".git").exists())
+            .filter_map(|path| Self::detect(&path))
+            .collect();
+        repos.sort_by(|a, b| a.name.cmp(&b.name));
+        // A worktree next to its main checkout resolves to the same repository
+        repos.dedup_by(|a, b| a.root == b.root);
+        repos
+    }
+
+    /// Repository name (the main checkout's directory name)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Root of the main checkout (or the bare repository itself)
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `<reponame>-<hash>`, naming this repository's directory in folders shared with
+    /// other repos
+    pub fn dir_name(&self) -> String {
+        layout_dir_name(&self.name, &self.root)
+    }
+
+    /// Directory holding this repository's worktrees
+    pub fn worktrees_dir(&self, workflows_path: &Path) -> PathBuf {
+        workflows_path.join(self.dir_name())
+    }
+
+    /// Path of the worktree for `session_name`
+    pub fn worktree_path(&self, workflows_path: &Path, session_name: &str) -> PathBuf {
+        self.worktrees_dir(workflows_path).join(session_name)
+    }
+
+    /// Move this repo's worktrees out of the legacy `<workflows_path>/<reponame>` directory.
+    /// Worktrees there that belong to another repo with the same name are left alone.
+    /// Claude keys its conversations by working directory, so each worktree's
+    /// `~/.claude/projects` directory moves with it. Returns the number of worktrees moved.
+    pub fn migrate_legacy(&self, workflows_path: &Path) -> anyhow::Result<usize> {
+        let legacy_dir = workflows_path.join(&self.name);
+        if !legacy_dir.is_dir() {
+            return Ok(0);
+        }
+
+        let target_dir = self.worktrees_dir(workflows_path);
+        let mut moved = Vec::new();
+        for entry in std::fs::read_dir(&legacy_dir)?.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_dir() || !self.owns_worktree(&path) {
+                continue;
+            }
+            let target = target_dir.join(entry.file_name());
+            if target.exists() {
+                continue;
+            }
+            std::fs::create_dir_all(&target_dir)?;
+            std::fs::rename(&path, &target)
+                .with_context(|| format!("failed to move {}", path.display()))?;
+            move_claude_project(&path, &target);
+            moved.push(target);
+        }
+
+        if moved.is_empty() {
+            return Ok(0);
+        }
+
+        // Point git's worktree metadata at the new locations
+        let output = Command::new("git")
+            .arg("worktree")
+            .arg("repair")
+            .args(&moved)
+            .current_dir(&self.root)
+            .output()
+            .context("failed to run git worktree repair")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git worktree repair failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        // Only succeeds once no other repo's worktrees are left behind
+        let _ = std::fs::remove_dir(&legacy_dir);
+        Ok(moved.len())
+    }
+
+    /// Whether `path` is a linked worktree of this repository
+    fn owns_worktree(&self, path: &Path) -> bool {
+        let Ok(contents) = std::fs::read_to_string(path.join(".git")) else {
+            return false;
+        };
+        let Some(gitdir) = contents.trim().strip_prefix("gitdir:") else {
+            return false;
+        };
+        let gitdir = path.join(gitdir.trim());
+        gitdir
+            .canonicalize()
+            .is_ok_and(|gitdir| gitdir.starts_with(&self.common_dir))
+    }
+}
+
+/// Move Claude's conversations for `from` to where it looks for them in `to`, so sessions
+/// still resume after their worktree moved
+fn move_claude_project(from: &Path, to: &Path) {
+    let (Some(from), Some(to)) = (
+        crate::transcript::project_dir(from),
+        crate::transcript::project_dir(to),
+    ) else {
+        return;
+    };
+    if !from.is_dir() || to.exists() {
+        return;
+    }
+    if let Err(error) = std::fs::rename(&from, &to) {
+        tracing::warn!(from = %from.display(), %error, "failed to move claude project dir");
+    }
+}
+
+/// `<name>-<hash>`, with an 8 hex digit hash of the repository root
+fn layout_dir_name(name: &str, root: &Path) -> String {
+    format!(
+        "{}-{:08x}",
+        name,
+        fnv1a(root.to_string_lossy().as_bytes()) as u32
+    )
+}
+
+/// FNV-1a: stable across builds, unlike std's `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_dir_name() {
+        let a = layout_dir_name("api", Path::new("/home/me/work/api"));
+        let b = layout_dir_name("api", Path::new("/home/me/personal/api"));
+        assert!(a.starts_with("api-") && a.len() == "api-".len() + 8);
+        assert_ne!(a, b);
+        assert_eq!(a, layout_dir_name("api", Path::new("/home/me/work/api")));
+    }
+}
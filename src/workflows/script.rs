@@ -0,0 +1,293 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::{Config, ScriptWorkflowConfig};
+use crate::error::ShepardError;
+
+use super::{
+    PlanOverrides, Progress, SessionInfo, SessionMetadata, SessionPlan, SetupAction, SetupStep,
+    Workflow, wait_or_cancel,
+};
+
+/// Workflow implemented by user-provided executables. Each hook runs its script in the
+/// repo (or startup) directory with a JSON request on stdin and reads a JSON reply from
+/// stdout; a non-zero exit fails the hook with the script's stderr.
+pub struct ScriptWorkflow {
+    config: ScriptWorkflowConfig,
+}
+
+/// What every hook script is sent on stdin
+#[derive(Debug, Serialize)]
+struct HookRequest<'a> {
+    hook: &'a str,
+    session: &'a str,
+    /// Repository root, or the startup directory outside git
+    dir: &'a Path,
+    workflows_path: &'a Path,
+    /// The session's directory, for every hook but `pre_session`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a Path>,
+}
+
+/// Reply to `pre_session`: where the session runs and what to do there once it started
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PreSessionReply {
+    /// Relative paths are taken from the request's `dir`
+    path: PathBuf,
+    #[serde(default)]
+    base: Option<String>,
+    /// Shell commands run in `path` after the session starts
+    #[serde(default)]
+    setup: Vec<String>,
+}
+
+impl ScriptWorkflow {
+    const NAME: &'static str = "script";
+
+    pub fn new(config: ScriptWorkflowConfig) -> Self {
+        Self { config }
+    }
+
+    fn error(message: impl Display) -> ShepardError {
+        ShepardError::workflow(Self::NAME, message)
+    }
+
+    /// Run `script` in `dir` with `request` on stdin and parse what it prints
+    fn call<T: DeserializeOwned>(
+        script: &Path,
+        dir: &Path,
+        request: &HookRequest,
+        progress: &Progress,
+    ) -> Result<T, ShepardError> {
+        let stdout = Self::run(script, dir, request, progress)?;
+        serde_json::from_slice(&stdout).map_err(|e| {
+            Self::error(format!(
+                "{} {} printed invalid JSON: {}",
+                request.hook,
+                script.display(),
+                e
+            ))
+        })
+    }
+
+    /// Run `script` in `dir` with `request` on stdin, returning its stdout. Cancelling
+    /// `progress` kills it.
+    fn run(
+        script: &Path,
+        dir: &Path,
+        request: &HookRequest,
+        progress: &Progress,
+    ) -> Result<Vec<u8>, ShepardError> {
+        let program = if script.is_relative() && script.components().count() > 1 {
+            dir.join(script)
+        } else {
+            script.to_path_buf()
+        };
+        let mut child = Command::new(&program)
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Self::error(format!("Failed to run {}: {}", program.display(), e)))?;
+        let input = serde_json::to_vec(request).map_err(Self::error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A script that doesn't read its request closes the pipe early; that's fine
+            let _ = stdin.write_all(&input);
+        }
+        let output = wait_or_cancel(child, progress)
+            .map_err(|e| Self::error(format!("{}: {}", program.display(), e)))?
+            .ok_or_else(|| Self::error("cancelled"))?;
+        if !output.status.success() {
+            return Err(Self::error(format!(
+                "{} {} exited with {}: {}",
+                request.hook,
+                program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Run a hook that only reports success, if it has a script configured. Whatever the
+    /// script prints is ignored.
+    fn notify_hook(
+        script: Option<&Path>,
+        hook: &str,
+        session: &SessionInfo,
+        config: &Config,
+    ) -> Result<(), ShepardError> {
+        let Some(script) = script else {
+            return Ok(());
+        };
+        let request = HookRequest {
+            hook,
+            session: &session.name,
+            dir: &session.dir,
+            workflows_path: &config.workflows_path,
+            path: Some(&session.path),
+        };
+        Self::run(script, &session.dir, &request, &Progress::none()).map(|_| ())
+    }
+}
+
+impl Workflow for ScriptWorkflow {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// The script picks the path and base itself, so only it can be listed
+    fn plan(
+        &self,
+        _session_name: &str,
+        _config: &Config,
+        _startup_path: &Path,
+        _overrides: &PlanOverrides,
+    ) -> Result<SessionPlan, ShepardError> {
+        Ok(SessionPlan {
+            path: None,
+            base: None,
+            operations: vec![
+                format!("{} (pre_session)", self.config.pre_session.display()),
+                "setup commands the script returns".to_string(),
+            ],
+        })
+    }
+
+    fn pre_session_hook(
+        &self,
+        session_name: &str,
+        config: &Config,
+        startup_path: &Path,
+        _overrides: &PlanOverrides,
+        progress: &Progress,
+    ) -> Result<SessionMetadata, ShepardError> {
+        let request = HookRequest {
+            hook: "pre_session",
+            session: session_name,
+            dir: startup_path,
+            workflows_path: &config.workflows_path,
+            path: None,
+        };
+        progress.step(&format!("Running {}", self.config.pre_session.display()));
+        let reply: PreSessionReply =
+            Self::call(&self.config.pre_session, startup_path, &request, progress)?;
+        let path = startup_path.join(reply.path);
+        if !path.is_dir() {
+            return Err(Self::error(format!(
+                "pre_session returned {}, which is not a directory",
+                path.display()
+            )));
+        }
+        Ok(SessionMetadata {
+            path,
+            base: reply.base,
+            setup: reply
+                .setup
+                .into_iter()
+                .map(|command| SetupStep {
+                    label: format!("Running `{}`", command),
+                    action: SetupAction::Shell(command),
+                })
+                .collect(),
+        })
+    }
+
+    fn post_session_start(
+        &self,
+        session: &SessionInfo,
+        config: &Config,
+    ) -> Result<(), ShepardError> {
+        let script = self.config.post_session_start.as_deref();
+        Self::notify_hook(script, "post_session_start", session, config)
+    }
+
+    fn on_session_exit(&self, session: &SessionInfo, config: &Config) -> Result<(), ShepardError> {
+        let script = self.config.on_session_exit.as_deref();
+        Self::notify_hook(script, "on_session_exit", session, config)
+    }
+
+    fn pre_worktree_delete(
+        &self,
+        session: &SessionInfo,
+        config: &Config,
+    ) -> Result<(), ShepardError> {
+        let script = self.config.pre_worktree_delete.as_deref();
+        Self::notify_hook(script, "pre_worktree_delete", session, config)
+    }
+
+    fn post_worktree_delete(
+        &self,
+        session: &SessionInfo,
+        config: &Config,
+    ) -> Result<(), ShepardError> {
+        let script = self.config.post_worktree_delete.as_deref();
+        Self::notify_hook(script, "post_worktree_delete", session, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_pre_session_script() {
+        let dir = TempDir::new("script");
+        std::fs::create_dir_all(dir.join("sessions")).unwrap();
+        let script = dir.join("provision.sh");
+        std::fs::write(
+            &script,
+            r#"#!/bin/sh
+request=$(cat)
+case "$request" in
+  *'"session":"broken"'*) echo "no capacity" >&2; exit 3 ;;
+esac
+mkdir -p sessions/feature
+echo '{"path": "sessions/feature", "base": "seed-db", "setup": ["make seed"]}'
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let workflow = ScriptWorkflow::new(ScriptWorkflowConfig {
+            pre_session: "./provision.sh".into(),
+            pre_worktree_delete: Some("./provision.sh".into()),
+            ..Default::default()
+        });
+        let config = Config::default();
+        let overrides = PlanOverrides::default();
+        let progress = Progress::none();
+        let metadata = workflow
+            .pre_session_hook("feature", &config, &dir, &overrides, &progress)
+            .unwrap();
+        assert_eq!(metadata.path, dir.join("sessions/feature"));
+        assert_eq!(metadata.base.as_deref(), Some("seed-db"));
+        assert_eq!(metadata.setup[0].label, "Running `make seed`");
+
+        let error = workflow
+            .pre_session_hook("broken", &config, &dir, &overrides, &progress)
+            .unwrap_err();
+        assert!(
+            matches!(error, ShepardError::Workflow { workflow: "script", ref message } if message.contains("no capacity")),
+            "{:?}",
+            error
+        );
+
+        // Hooks without a script do nothing; a failing one reports the script's stderr
+        let session = SessionInfo {
+            name: "broken".to_string(),
+            path: dir.join("sessions/feature"),
+            dir: dir.to_path_buf(),
+        };
+        assert!(workflow.on_session_exit(&session, &config).is_ok());
+        assert!(workflow.pre_worktree_delete(&session, &config).is_err());
+    }
+}
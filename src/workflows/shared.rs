@@ -0,0 +1,156 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, RepoSetup, wildcard_match};
+use crate::error::ShepardError;
+
+use super::RepoLayout;
+
+/// Session provisioning committed to a repository, so everyone working in it gets the
+/// same setup. Personal config for the same repo takes precedence.
+pub const SHARED_WORKFLOWS_FILE: &str = "shepard/workflows.toml";
+
+/// Contents of `shepard/workflows.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SharedWorkflows {
+    /// Shell commands run in each new worktree after checkout, in order
+    pub setup: Vec<String>,
+    /// Checks command (Alt+c)
+    pub checks: Option<String>,
+    /// Files copied from the main checkout into new worktrees, e.g. untracked `.env`
+    /// files. `*` matches within one path component.
+    pub copy_files: Vec<String>,
+    /// Arguments given to claude before the personal `claude_args`
+    pub claude_args: Vec<String>,
+}
+
+impl SharedWorkflows {
+    /// Read the file from `root`, if the repository has one
+    pub fn load(root: &Path) -> Result<Option<Self>, ShepardError> {
+        let path = root.join(SHARED_WORKFLOWS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            std::fs::read_to_string(&path).map_err(|e| ShepardError::config(&path, e))?;
+        toml::from_str(&contents).map_err(|e| ShepardError::config(&path, e))
+    }
+
+    /// Fill in `config`'s settings for `repo` from this file, leaving anything already
+    /// configured for the repo alone
+    pub fn merge_into(self, config: &mut Config, repo: &str) {
+        if let Some(checks) = self.checks {
+            config
+                .checks
+                .repos
+                .entry(repo.to_string())
+                .or_insert(checks);
+        }
+        if !self.claude_args.is_empty() {
+            config
+                .repo_claude_args
+                .entry(repo.to_string())
+                .or_insert(self.claude_args);
+        }
+        if !self.setup.is_empty() || !self.copy_files.is_empty() {
+            config
+                .worktree_setup
+                .repos
+                .entry(repo.to_string())
+                .or_insert(RepoSetup {
+                    commands: self.setup,
+                    copy_files: self.copy_files,
+                });
+        }
+    }
+}
+
+/// Load `repo`'s shared workflows into `config`. Returns whether the repo has the file.
+pub fn merge_shared_workflows(
+    config: &mut Config,
+    repo: &RepoLayout,
+) -> Result<bool, ShepardError> {
+    let Some(shared) = SharedWorkflows::load(repo.root())? else {
+        return Ok(false);
+    };
+    shared.merge_into(config, repo.name());
+    Ok(true)
+}
+
+/// Files under `root` matching `pattern`, relative to `root`
+pub fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::new()];
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        matches = matches
+            .into_iter()
+            .flat_map(|dir| {
+                if !component.contains('*') {
+                    return vec![dir.join(component)];
+                }
+                let Ok(entries) = std::fs::read_dir(root.join(&dir)) else {
+                    return Vec::new();
+                };
+                let mut names: Vec<String> = entries
+                    .filter_map(|e| e.ok()?.file_name().into_string().ok())
+                    .filter(|name| wildcard_match(component, name))
+                    .collect();
+                names.sort();
+                names.into_iter().map(|name| dir.join(name)).collect()
+            })
+            .collect();
+    }
+    matches.retain(|path| root.join(path).is_file());
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::TempDir;
+
+    #[test]
+    fn test_load_and_merge() {
+        let root = TempDir::new("shared");
+        std::fs::create_dir_all(root.join("shepard")).unwrap();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(
+            root.join(SHARED_WORKFLOWS_FILE),
+            r#"
+setup = ["pnpm install"]
+checks = "pnpm test"
+copy_files = [".env", "config/*.local.json"]
+claude_args = ["--permission-mode", "acceptEdits"]
+"#,
+        )
+        .unwrap();
+        for file in [".env", "config/app.local.json", "config/app.json"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let shared = SharedWorkflows::load(&root).unwrap().unwrap();
+        assert_eq!(
+            expand_pattern(&root, "config/*.local.json"),
+            vec![PathBuf::from("config/app.local.json")]
+        );
+        assert_eq!(expand_pattern(&root, ".env"), vec![PathBuf::from(".env")]);
+        assert!(expand_pattern(&root, "missing/*").is_empty());
+
+        // Personal config for the repo wins over the shared file
+        let mut config = Config::default();
+        config
+            .checks
+            .repos
+            .insert("web".to_string(), "make test".to_string());
+        shared.merge_into(&mut config, "web");
+        assert_eq!(config.checks.command_for("web"), Some("make test"));
+        assert_eq!(
+            config.claude_args_for("web")[..2],
+            ["--permission-mode", "acceptEdits"]
+        );
+        assert_eq!(
+            config.worktree_setup.repos["web"].commands,
+            vec!["pnpm install"]
+        );
+    }
+}
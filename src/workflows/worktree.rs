@@ -1,8 +1,13 @@
-use crate::config::Config;
-use crate::session_manager::StatusMessage;
-use std::process::Command;
+use crate::config::{Config, RepoSetup, WorktreeSetup};
+use crate::error::ShepardError;
+use std::fmt::Display;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
 
-use super::{SessionMetadata, Workflow};
+use super::{
+    PlanOverrides, Progress, RepoLayout, SessionMetadata, SessionPlan, SetupAction, SetupStep,
+    Workflow, wait_or_cancel,
+};
 
 /// Workflow that creates git worktrees for each session
 pub struct WorktreeWorkflow;
@@ -10,62 +15,183 @@ pub struct WorktreeWorkflow;
 impl WorktreeWorkflow {
     const NAME: &'static str = "worktree";
 
-    fn error(log_message: impl Into<String>) -> StatusMessage {
-        StatusMessage::err(format!("Workflow {} failed", Self::NAME), log_message)
+    fn error(message: impl Display) -> ShepardError {
+        ShepardError::workflow(Self::NAME, message)
     }
 
-    /// Get the repository name from the current directory
-    fn get_repo_name() -> Result<String, StatusMessage> {
-        let output = Command::new("git")
-            .args(["rev-parse", "--show-toplevel"])
+    /// Run git in `dir`, mapping a spawn failure to a git error
+    fn git(dir: &Path, args: &[&str]) -> Result<Output, ShepardError> {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
             .output()
-            .map_err(|e| Self::error(format!("failed to run git rev-parse: {}", e)))?;
+            .map_err(|e| ShepardError::git(format!("git {}", args[0]), e))
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(Self::error(format!(
-                "not in a git repository: {}",
-                stderr.trim()
-            )));
-        }
+    /// Run a slow git command in `dir`, killing it if `progress` is cancelled
+    fn git_cancellable(
+        dir: &Path,
+        args: &[&str],
+        progress: &Progress,
+    ) -> Result<Output, ShepardError> {
+        let spawn_error = |e: std::io::Error| ShepardError::git(format!("git {}", args[0]), e);
+        let child = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        wait_or_cancel(child, progress)
+            .map_err(spawn_error)?
+            .ok_or_else(|| Self::error("cancelled"))
+    }
 
-        let repo_path = String::from_utf8(output.stdout)
-            .map_err(|e| Self::error(format!("invalid utf8 in git output: {}", e)))?
-            .trim()
-            .to_string();
+    /// Whether `rev` resolves to a commit
+    fn rev_exists(dir: &Path, rev: &str) -> Result<bool, ShepardError> {
+        let rev = format!("{}^{{commit}}", rev);
+        Ok(Self::git(dir, &["rev-parse", "--verify", "--quiet", &rev])?
+            .status
+            .success())
+    }
 
-        let repo_name = std::path::Path::new(&repo_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| Self::error("could not determine repository name from path"))?
-            .to_string();
+    /// Whether the repository has an `origin` remote
+    fn has_origin(dir: &Path) -> Result<bool, ShepardError> {
+        let output = Self::git(dir, &["remote"])?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|remote| remote == "origin"))
+    }
 
-        Ok(repo_name)
+    /// Get the main branch name (main or master), if either exists
+    fn get_main_branch(dir: &Path) -> Result<Option<String>, ShepardError> {
+        for branch in ["main", "master"] {
+            if Self::rev_exists(dir, branch)? {
+                return Ok(Some(branch.to_string()));
+            }
+        }
+        Ok(None)
     }
 
-    /// Get the main branch name (main or master)
-    fn get_main_branch() -> Result<String, StatusMessage> {
-        // Check if 'main' exists
-        let output = Command::new("git")
-            .args(["rev-parse", "--verify", "main"])
-            .output()
-            .map_err(|e| Self::error(format!("failed to run git rev-parse: {}", e)))?;
+    /// Post-checkout steps for a new worktree. Unset config entries are auto-detected:
+    /// submodules from `.gitmodules`, LFS from `filter=lfs` in `.gitattributes` (when
+    /// git-lfs is installed). The repo's own files to copy and commands come last.
+    fn setup_steps(
+        worktree_path: &Path,
+        setup: &WorktreeSetup,
+        layout: &RepoLayout,
+    ) -> Vec<SetupStep> {
+        let mut steps = Vec::new();
 
-        if output.status.success() {
-            return Ok("main".to_string());
+        let submodules = setup
+            .submodules
+            .unwrap_or_else(|| worktree_path.join(".gitmodules").exists());
+        if submodules {
+            steps.push(SetupStep {
+                label: "Initializing submodules".to_string(),
+                action: SetupAction::Git(vec!["submodule", "update", "--init", "--recursive"]),
+            });
         }
 
-        // Fall back to 'master'
-        let output = Command::new("git")
-            .args(["rev-parse", "--verify", "master"])
-            .output()
-            .map_err(|e| Self::error(format!("failed to run git rev-parse: {}", e)))?;
+        let lfs = setup.lfs.unwrap_or_else(|| {
+            std::fs::read_to_string(worktree_path.join(".gitattributes"))
+                .is_ok_and(|attrs| attrs.contains("filter=lfs"))
+                && Self::git(worktree_path, &["lfs", "version"]).is_ok_and(|o| o.status.success())
+        });
+        if lfs {
+            steps.push(SetupStep {
+                label: "Pulling LFS objects".to_string(),
+                action: SetupAction::Git(vec!["lfs", "pull"]),
+            });
+        }
 
-        if output.status.success() {
-            return Ok("master".to_string());
+        let repo = setup.repos.get(layout.name()).cloned().unwrap_or_default();
+        let RepoSetup {
+            commands,
+            copy_files,
+        } = repo;
+        if !copy_files.is_empty() {
+            steps.push(SetupStep {
+                label: "Copying files".to_string(),
+                action: SetupAction::CopyFiles {
+                    from: layout.root().to_path_buf(),
+                    patterns: copy_files,
+                },
+            });
         }
+        steps.extend(commands.into_iter().map(|command| SetupStep {
+            label: format!("Running `{}`", command),
+            action: SetupAction::Shell(command),
+        }));
+
+        steps
+    }
+
+    /// The start point `resolve_base` tries first, with the fetch it runs for it
+    fn planned_base(dir: &Path, config: &Config) -> Result<(String, Option<String>), ShepardError> {
+        let branch = match &config.base_branch {
+            Some(branch) => Some(branch.clone()),
+            None => Self::get_main_branch(dir)?,
+        };
+        let Some(branch) = branch else {
+            return Ok(("HEAD".to_string(), None));
+        };
+        if Self::has_origin(dir)? {
+            let fetch = format!("git fetch origin {}", branch);
+            Ok((format!("origin/{}", branch), Some(fetch)))
+        } else {
+            Ok((branch, None))
+        }
+    }
+
+    /// Pick the commit new worktrees start from: `base_branch` from config, else main/master,
+    /// taken from origin when there is one. Falls back to the local branch when there is no
+    /// remote (or the fetch fails), and to HEAD when there is no main or master branch.
+    /// Returns the start point and a description of what was used.
+    fn resolve_base(
+        dir: &Path,
+        config: &Config,
+        progress: &Progress,
+    ) -> Result<(String, String), ShepardError> {
+        let branch = match &config.base_branch {
+            Some(branch) => Some(branch.clone()),
+            None => Self::get_main_branch(dir)?,
+        };
 
-        Err(Self::error("could not find main or master branch"))
+        let Some(branch) = branch else {
+            if Self::rev_exists(dir, "HEAD")? {
+                return Ok((
+                    "HEAD".to_string(),
+                    "HEAD (no main or master branch)".to_string(),
+                ));
+            }
+            return Err(Self::error("repository has no commits yet"));
+        };
+
+        let fallback_reason = if Self::has_origin(dir)? {
+            // Fetch latest from origin
+            progress.step(&format!("Fetching origin/{}", branch));
+            let output = Self::git_cancellable(dir, &["fetch", "origin", &branch], progress)?;
+            let remote_ref = format!("origin/{}", branch);
+            if output.status.success() && Self::rev_exists(dir, &remote_ref)? {
+                return Ok((remote_ref.clone(), remote_ref));
+            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            format!("git fetch origin {} failed: {}", branch, stderr.trim())
+        } else {
+            "no origin remote".to_string()
+        };
+
+        if Self::rev_exists(dir, &branch)? {
+            let description = format!("{} ({})", branch, fallback_reason);
+            Ok((branch, description))
+        } else {
+            Err(Self::error(format!(
+                "base branch '{}' not found ({})",
+                branch, fallback_reason
+            )))
+        }
     }
 }
 
@@ -74,60 +200,116 @@ impl Workflow for WorktreeWorkflow {
         Self::NAME
     }
 
-    fn pre_session_hook(
+    fn plan(
         &self,
         session_name: &str,
         config: &Config,
-        _startup_path: &std::path::Path,
-    ) -> Result<SessionMetadata, StatusMessage> {
-        let repo_name = Self::get_repo_name()?;
-        let main_branch = Self::get_main_branch()?;
-
-        // Build worktree path: <workflows_path>/<reponame>/<sessionname>
-        let worktree_path = config.workflows_path.join(&repo_name).join(session_name);
+        startup_path: &Path,
+        overrides: &PlanOverrides,
+    ) -> Result<SessionPlan, ShepardError> {
+        let layout = RepoLayout::detect(startup_path)
+            .ok_or_else(|| Self::error("not in a git repository"))?;
+        let path = overrides
+            .path
+            .clone()
+            .unwrap_or_else(|| layout.worktree_path(&config.workflows_path, session_name));
+        let mut operations = Vec::new();
+        let base = match &overrides.base {
+            Some(base) => base.clone(),
+            None => {
+                let (base, fetch) = Self::planned_base(startup_path, config)?;
+                operations.extend(fetch);
+                base
+            }
+        };
+        operations.push(format!(
+            "git worktree add -b {} {} {}",
+            session_name,
+            path.display(),
+            base
+        ));
+        // The checkout isn't there yet; the main one has the same .gitmodules/.gitattributes
+        let setup = Self::setup_steps(layout.root(), &config.worktree_setup, &layout);
+        operations.extend(setup.into_iter().map(|step| step.label));
+        Ok(SessionPlan {
+            path: Some(path),
+            base: Some(base),
+            operations,
+        })
+    }
 
-        // Fetch latest from origin
-        let output = Command::new("git")
-            .args(["fetch", "origin", &main_branch])
-            .output()
-            .map_err(|e| Self::error(format!("failed to run git fetch: {}", e)))?;
+    fn pre_session_hook(
+        &self,
+        session_name: &str,
+        config: &Config,
+        startup_path: &Path,
+        overrides: &PlanOverrides,
+        progress: &Progress,
+    ) -> Result<SessionMetadata, ShepardError> {
+        let layout = RepoLayout::detect(startup_path)
+            .ok_or_else(|| Self::error("not in a git repository"))?;
+        let (start_point, base) = match &overrides.base {
+            Some(base) if Self::rev_exists(startup_path, base)? => (base.clone(), base.clone()),
+            Some(base) => return Err(Self::error(format!("base '{}' not found", base))),
+            None => Self::resolve_base(startup_path, config, progress)?,
+        };
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(Self::error(format!(
-                "git fetch origin {} failed: {}",
-                main_branch,
-                stderr.trim()
-            )));
-        }
+        // Build worktree path: <workflows_path>/<reponame>-<hash>/<sessionname>
+        let worktree_path = overrides
+            .path
+            .clone()
+            .unwrap_or_else(|| layout.worktree_path(&config.workflows_path, session_name));
 
-        // Create the worktree with a new branch based on origin/main
+        // Create the worktree with a new branch based on the resolved start point
         let worktree_path_str = worktree_path
             .to_str()
             .ok_or_else(|| Self::error("worktree path contains invalid UTF-8"))?;
 
-        let output = Command::new("git")
-            .args([
+        if progress.is_cancelled() {
+            return Err(Self::error("cancelled"));
+        }
+        progress.step(&format!("Adding worktree at {}", worktree_path.display()));
+        let had_path = worktree_path.exists();
+        let had_branch = Self::rev_exists(startup_path, &format!("refs/heads/{}", session_name))?;
+        let output = Self::git_cancellable(
+            startup_path,
+            &[
                 "worktree",
                 "add",
                 "-b",
                 session_name,
                 worktree_path_str,
-                &format!("origin/{}", main_branch),
-            ])
-            .output()
-            .map_err(|e| Self::error(format!("failed to run git worktree add: {}", e)))?;
+                &start_point,
+            ],
+            progress,
+        );
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                // Killed partway: don't leave a half-made worktree or its branch behind
+                if !had_path {
+                    let _ = Self::git(
+                        startup_path,
+                        &["worktree", "remove", "--force", worktree_path_str],
+                    );
+                }
+                if !had_branch {
+                    let _ = Self::git(startup_path, &["branch", "-D", session_name]);
+                }
+                return Err(e);
+            }
+        };
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(Self::error(format!(
-                "git worktree add failed: {}",
-                stderr.trim()
-            )));
+            return Err(ShepardError::git("git worktree add", stderr.trim()));
         }
 
+        let setup = Self::setup_steps(&worktree_path, &config.worktree_setup, &layout);
         Ok(SessionMetadata {
             path: worktree_path,
+            base: Some(base),
+            setup,
         })
     }
 }